@@ -0,0 +1,124 @@
+// --- Asset information ---
+// Describes the physical asset the shell represents, including the
+// identifiers registries and discovery services use to find it
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use serde::{Deserialize, Deserializer, Serialize};
+#[cfg(feature = "wasm")]
+use tsify::Tsify;
+
+use super::{deserialize_semantic_id, Reference};
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+pub enum AssetKind {
+    /// A product type, e.g. a motor series
+    Type,
+    /// A concrete, serial-numbered asset
+    #[default]
+    Instance,
+    NotApplicable,
+}
+
+impl AssetKind {
+    pub fn parse(kind: &str) -> Option<AssetKind> {
+        match kind {
+            "Type" => Some(AssetKind::Type),
+            "Instance" => Some(AssetKind::Instance),
+            "NotApplicable" => Some(AssetKind::NotApplicable),
+            _ => None,
+        }
+    }
+}
+
+/// A domain-specific identifier such as a serial number or manufacturer part id
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+pub struct SpecificAssetId {
+    pub name: String,
+    pub value: String,
+    /// Who issued the identifier (e.g. the manufacturer)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub external_subject_id: Option<Reference>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_semantic_id",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub semantic_id: Option<Reference>,
+}
+
+/// A file used as thumbnail, e.g. the nameplate photo the twin was generated from
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+pub struct Resource {
+    pub path: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_type: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+pub struct AssetInformation {
+    #[serde(default)]
+    pub asset_kind: AssetKind,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub global_asset_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub specific_asset_ids: Vec<SpecificAssetId>,
+    /// Manufacturer and model, e.g. "Siemens 1LE1001"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub asset_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_thumbnail: Option<Resource>,
+}
+
+impl AssetInformation {
+    /// Value of the specific asset id with the given name
+    pub fn specific_asset_id(&self, name: &str) -> Option<&str> {
+        self.specific_asset_ids
+            .iter()
+            .find(|id| id.name == name)
+            .map(|id| id.value.as_str())
+    }
+
+    /// Add a specific asset id, replacing the value of an existing one with the same name
+    pub fn set_specific_asset_id(&mut self, name: &str, value: &str) {
+        match self
+            .specific_asset_ids
+            .iter_mut()
+            .find(|id| id.name == name)
+        {
+            Some(id) => id.value = value.to_string(),
+            None => self.specific_asset_ids.push(SpecificAssetId {
+                name: name.to_string(),
+                value: value.to_string(),
+                external_subject_id: None,
+                semantic_id: None,
+            }),
+        }
+    }
+}
+
+/// Read asset information either as the full structure or, as in configurations
+/// predating it, from the bare `asset_type` string
+pub fn deserialize_asset_information<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<AssetInformation, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Repr {
+        AssetType(String),
+        Full(AssetInformation),
+    }
+
+    Ok(match Repr::deserialize(deserializer)? {
+        Repr::AssetType(asset_type) => AssetInformation {
+            asset_type: Some(asset_type),
+            ..Default::default()
+        },
+        Repr::Full(info) => info,
+    })
+}
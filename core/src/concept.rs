@@ -0,0 +1,56 @@
+// --- Concept descriptions ---
+// The dictionary entries semanticIds point at, describing what an element
+// means independent of the idShort a vendor chose for it
+
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+
+use alloc::vec::Vec;
+
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "wasm")]
+use tsify::Tsify;
+
+use super::{AdministrativeInformation, EmbeddedDataSpecification, Reference};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+pub struct ConceptDescription {
+    /// The identifier used in semanticIds, e.g. an IRDI like "0173-1#02-AAE416#004"
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id_short: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub administration: Option<AdministrativeInformation>,
+    /// External concepts this one is compatible with
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub is_case_of: Vec<Reference>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub embedded_data_specifications: Vec<EmbeddedDataSpecification>,
+}
+
+/// Concept descriptions keyed by id
+pub type ConceptDictionary = BTreeMap<String, ConceptDescription>;
+
+/// (De)serialize a `ConceptDictionary` as a plain array of concept descriptions
+pub mod as_list {
+    use alloc::vec::Vec;
+
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    use super::{ConceptDescription, ConceptDictionary};
+
+    pub fn serialize<S: Serializer>(
+        map: &ConceptDictionary,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(map.values())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<ConceptDictionary, D::Error> {
+        let list = Vec::<ConceptDescription>::deserialize(deserializer)?;
+        Ok(list.into_iter().map(|cd| (cd.id.clone(), cd)).collect())
+    }
+}
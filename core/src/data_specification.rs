@@ -0,0 +1,114 @@
+// --- Embedded data specifications ---
+// Template metadata (HasDataSpecification), in practice almost always the
+// IEC 61360 content that carries preferred names, units and definitions
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "wasm")]
+use tsify::Tsify;
+
+use super::{select_lang, LangString, Reference};
+
+/// Value types of IEC 61360 (distinct from the XSD types of property values)
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum DataTypeIec61360 {
+    Date,
+    String,
+    StringTranslatable,
+    IntegerMeasure,
+    IntegerCount,
+    IntegerCurrency,
+    RealMeasure,
+    RealCount,
+    RealCurrency,
+    Boolean,
+    Iri,
+    Irdi,
+    Rational,
+    RationalMeasure,
+    Time,
+    Timestamp,
+    File,
+    Html,
+    Blob,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+pub struct DataSpecificationIec61360 {
+    #[serde(default)]
+    pub preferred_name: Vec<LangString>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub short_name: Vec<LangString>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unit: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unit_id: Option<Reference>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub symbol: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data_type: Option<DataTypeIec61360>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub definition: Vec<LangString>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value_format: Option<String>,
+}
+
+impl DataSpecificationIec61360 {
+    pub fn preferred_name(&self, locale: &str) -> Option<&str> {
+        select_lang(&self.preferred_name, locale).map(|s| s.text.as_str())
+    }
+
+    pub fn definition(&self, locale: &str) -> Option<&str> {
+        select_lang(&self.definition, locale).map(|s| s.text.as_str())
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+pub struct EmbeddedDataSpecification {
+    /// Names the template, e.g. the IEC 61360 data specification IRI
+    pub data_specification: Reference,
+    pub data_specification_content: DataSpecificationIec61360,
+}
+
+/// The IEC 61360 content among a set of embedded data specifications
+pub fn find_iec61360(specs: &[EmbeddedDataSpecification]) -> Option<&DataSpecificationIec61360> {
+    specs.first().map(|s| &s.data_specification_content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_iec61360_round_trip() {
+        let json = r#"{
+            "data_specification": {"type": "ExternalReference", "keys": [{"type": "GlobalReference",
+                "value": "https://admin-shell.io/DataSpecificationTemplates/DataSpecificationIec61360/3/0"}]},
+            "data_specification_content": {
+                "preferred_name": [{"language": "en", "text": "Rated voltage"},
+                                   {"language": "de", "text": "Bemessungsspannung"}],
+                "unit": "V",
+                "data_type": "REAL_MEASURE",
+                "definition": [{"language": "en", "text": "Voltage the motor is designed for"}]
+            }
+        }"#;
+        let spec: EmbeddedDataSpecification = serde_json::from_str(json).unwrap();
+        let content = &spec.data_specification_content;
+        assert_eq!(content.data_type, Some(DataTypeIec61360::RealMeasure));
+        assert_eq!(content.preferred_name("de-DE"), Some("Bemessungsspannung"));
+        assert_eq!(
+            content.definition("fr"),
+            Some("Voltage the motor is designed for")
+        );
+
+        let back: EmbeddedDataSpecification =
+            serde_json::from_str(&serde_json::to_string(&spec).unwrap()).unwrap();
+        assert_eq!(back, spec);
+    }
+}
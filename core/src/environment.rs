@@ -0,0 +1,217 @@
+// --- Environment ---
+// The spec's top-level container, as exported by e.g. AASX Package Explorer.
+// Shells there only reference their submodels; the twin works on a single
+// shell with its submodels embedded, so the environment is converted on load.
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use alloc::{format, vec};
+
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "wasm")]
+use tsify::Tsify;
+
+use super::{
+    deserialize_asset_information, AdministrativeInformation, AssetAdministrationShell,
+    AssetInformation, ConceptDescription, Extension, Key, KeyTypes, LangString, Reference,
+    ReferenceTypes, Submodel, NAMEPLATE_ID_SHORT,
+};
+
+/// Semantic id of the IDTA Digital Nameplate submodel template
+pub const NAMEPLATE_SEMANTIC_ID: &str = "https://admin-shell.io/zvei/nameplate/2/0/Nameplate";
+
+/// A shell as stored in an environment: submodels are referenced, not embedded
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+pub struct EnvironmentShell {
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id_short: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub display_name: Vec<LangString>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub description: Vec<LangString>,
+    #[serde(
+        alias = "asset_type",
+        deserialize_with = "deserialize_asset_information"
+    )]
+    pub asset_information: AssetInformation,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub administration: Option<AdministrativeInformation>,
+    #[serde(default)]
+    pub submodels: Vec<Reference>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extensions: Vec<Extension>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+pub struct Environment {
+    #[serde(default)]
+    pub asset_administration_shells: Vec<EnvironmentShell>,
+    #[serde(default)]
+    pub submodels: Vec<Submodel>,
+    #[serde(default)]
+    pub concept_descriptions: Vec<ConceptDescription>,
+}
+
+fn submodel_reference(id: &str) -> Reference {
+    Reference {
+        reference_type: ReferenceTypes::ModelReference,
+        referred_semantic_id: None,
+        keys: vec![Key {
+            key_type: KeyTypes::Submodel,
+            value: id.to_string(),
+        }],
+    }
+}
+
+impl Environment {
+    /// Wrap a twin's shell into an environment. A non-empty legacy nameplate
+    /// becomes a proper "Nameplate" submodel.
+    pub fn from_shell(shell: &AssetAdministrationShell) -> Environment {
+        let mut submodels = Vec::new();
+        if !shell.nameplate.is_empty() {
+            submodels.push(Submodel {
+                id: format!("{}/submodels/nameplate", shell.id),
+                id_short: NAMEPLATE_ID_SHORT.to_string(),
+                display_name: Vec::new(),
+                description: Vec::new(),
+                administration: None,
+                semantic_id: Some(Reference::global(NAMEPLATE_SEMANTIC_ID)),
+                qualifiers: Vec::new(),
+                extensions: Vec::new(),
+                kind: Default::default(),
+                submodel_elements: shell.nameplate.clone(),
+            });
+        }
+        submodels.extend(shell.submodels.iter().cloned());
+
+        Environment {
+            asset_administration_shells: vec![EnvironmentShell {
+                id: shell.id.clone(),
+                id_short: None,
+                display_name: shell.display_name.clone(),
+                description: shell.description.clone(),
+                asset_information: shell.asset_information.clone(),
+                administration: shell.administration.clone(),
+                submodels: submodels
+                    .iter()
+                    .map(|sm| submodel_reference(&sm.id))
+                    .collect(),
+                extensions: shell.extensions.clone(),
+            }],
+            submodels,
+            concept_descriptions: shell.concept_descriptions.values().cloned().collect(),
+        }
+    }
+
+    /// Select a shell (by id, or the first one) and embed the submodels it
+    /// references. All concept descriptions of the environment are kept.
+    pub fn into_shell(self, shell_id: Option<&str>) -> Result<AssetAdministrationShell, String> {
+        let Environment {
+            asset_administration_shells,
+            mut submodels,
+            concept_descriptions,
+        } = self;
+
+        let shell = match shell_id {
+            Some(id) => asset_administration_shells
+                .into_iter()
+                .find(|s| s.id == id)
+                .ok_or_else(|| format!("Shell '{}' not found in environment", id))?,
+            None => asset_administration_shells
+                .into_iter()
+                .next()
+                .ok_or("Environment contains no asset administration shell")?,
+        };
+
+        let mut embedded = Vec::with_capacity(shell.submodels.len());
+        for reference in &shell.submodels {
+            let id = match reference.keys.as_slice() {
+                [key] if key.key_type == KeyTypes::Submodel => &key.value,
+                _ => return Err(format!("Unsupported submodel reference {}", reference)),
+            };
+            let index = submodels
+                .iter()
+                .position(|sm| &sm.id == id)
+                .ok_or_else(|| format!("Submodel '{}' referenced by shell is missing", id))?;
+            embedded.push(submodels.swap_remove(index));
+        }
+
+        Ok(AssetAdministrationShell {
+            id: shell.id,
+            display_name: shell.display_name,
+            description: shell.description,
+            asset_information: shell.asset_information,
+            administration: shell.administration,
+            nameplate: Vec::new(),
+            submodels: embedded,
+            extensions: shell.extensions,
+            concept_descriptions: concept_descriptions
+                .into_iter()
+                .map(|cd| (cd.id.clone(), cd))
+                .collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ENVIRONMENT: &str = r#"{
+        "asset_administration_shells": [
+            {"id": "urn:example:aas:pump", "asset_information": {"asset_kind": "Instance"},
+             "submodels": []},
+            {"id": "urn:example:aas:motor",
+             "asset_information": {"asset_kind": "Instance", "asset_type": "Siemens 1LE1"},
+             "submodels": [{"type": "ModelReference",
+                            "keys": [{"type": "Submodel", "value": "urn:example:sm:td"}]}]}
+        ],
+        "submodels": [
+            {"id": "urn:example:sm:td", "id_short": "TechnicalData",
+             "submodel_elements": [{"id_short": "MaxTorque", "value": "49", "unit": "Nm"}]},
+            {"id": "urn:example:sm:other", "id_short": "Unrelated"}
+        ],
+        "concept_descriptions": [{"id": "0173-1#02-AAE416#004"}]
+    }"#;
+
+    #[test]
+    fn test_select_shell_from_environment() {
+        let env: Environment = serde_json::from_str(ENVIRONMENT).unwrap();
+        let shell = env
+            .clone()
+            .into_shell(Some("urn:example:aas:motor"))
+            .unwrap();
+        assert_eq!(shell.submodels.len(), 1);
+        assert_eq!(shell.submodels[0].id_short, "TechnicalData");
+        assert!(shell
+            .concept_descriptions
+            .contains_key("0173-1#02-AAE416#004"));
+
+        let first = env.clone().into_shell(None).unwrap();
+        assert_eq!(first.id, "urn:example:aas:pump");
+        assert!(env.into_shell(Some("urn:example:aas:nope")).is_err());
+    }
+
+    #[test]
+    fn test_shell_to_environment_round_trip() {
+        let shell: AssetAdministrationShell = serde_json::from_str(
+            r#"{"id": "MOTOR-1", "asset_type": "Siemens 1LE1",
+                "nameplate": [{"id_short": "Voltage", "value": "400", "unit": "V"}]}"#,
+        )
+        .unwrap();
+
+        let env = Environment::from_shell(&shell);
+        assert_eq!(env.submodels[0].id, "MOTOR-1/submodels/nameplate");
+        assert_eq!(
+            env.asset_administration_shells[0].submodels[0].key_value(),
+            Some("MOTOR-1/submodels/nameplate")
+        );
+
+        let back = env.into_shell(None).unwrap();
+        assert_eq!(back.submodels[0].id_short, NAMEPLATE_ID_SHORT);
+        assert_eq!(back.submodels[0].submodel_elements[0].id_short, "Voltage");
+    }
+}
@@ -0,0 +1,35 @@
+// --- Extensions ---
+// Vendor-specific name/value annotations (HasExtensions); the twin does not
+// interpret them but keeps them so they survive an export
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "wasm")]
+use tsify::Tsify;
+
+use super::{deserialize_semantic_id, DataTypeDefXsd, Reference};
+
+/// Name of the extension carrying a Property or Range unit in the spec
+/// serializations, where the AAS 3.0 metamodel has no unit attribute
+pub const UNIT_EXTENSION: &str = "unit";
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+pub struct Extension {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value_type: Option<DataTypeDefXsd>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+    /// Model elements the extension is about
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub refers_to: Vec<Reference>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_semantic_id",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub semantic_id: Option<Reference>,
+}
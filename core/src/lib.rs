@@ -0,0 +1,1642 @@
+// --- Minimal AAS V3.0 Data Model ---
+// This follows the Asset Administration Shell specification for Industry 4.0
+// https://www.plattform-i40.de/IP/Redaktion/EN/Standardartikel/specification-administrationshell.html
+//
+// The structs and their serde (de)serialization only need `alloc`; without
+// the default `std` feature the crate is `no_std`, so gateways that host twins
+// next to the PLC can read and write the same model as the WebAssembly kernel.
+
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use base64::Engine;
+use serde::de::{self, Deserializer};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "wasm")]
+use tsify::Tsify;
+
+mod asset;
+mod concept;
+mod data_specification;
+mod environment;
+mod extension;
+mod path;
+mod qualifier;
+mod reference;
+
+pub use asset::*;
+pub use concept::{ConceptDescription, ConceptDictionary};
+pub use data_specification::*;
+pub use environment::*;
+pub use extension::*;
+pub use path::{IdShortPath, PathSegment};
+pub use qualifier::*;
+pub use reference::*;
+
+/// idShort under which the legacy top-level `nameplate` is addressed
+pub const NAMEPLATE_ID_SHORT: &str = "Nameplate";
+
+/// XML Schema datatypes a property value can be declared as (AAS `DataTypeDefXsd`)
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+pub enum DataTypeDefXsd {
+    #[serde(rename = "xs:int")]
+    Int,
+    #[serde(rename = "xs:double")]
+    Double,
+    #[serde(rename = "xs:boolean")]
+    Boolean,
+    #[serde(rename = "xs:dateTime")]
+    DateTime,
+    #[serde(rename = "xs:string")]
+    String,
+}
+
+impl DataTypeDefXsd {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DataTypeDefXsd::Int => "xs:int",
+            DataTypeDefXsd::Double => "xs:double",
+            DataTypeDefXsd::Boolean => "xs:boolean",
+            DataTypeDefXsd::DateTime => "xs:dateTime",
+            DataTypeDefXsd::String => "xs:string",
+        }
+    }
+
+    /// The value as a JSON scalar: numbers and booleans when it parses as one,
+    /// text otherwise
+    pub fn to_json(ty: Option<DataTypeDefXsd>, raw: &str) -> serde_json::Value {
+        let typed = match ty {
+            Some(DataTypeDefXsd::Int) => raw.trim().parse::<i64>().ok().map(Into::into),
+            Some(DataTypeDefXsd::Double) => raw.trim().parse::<f64>().ok().map(Into::into),
+            Some(DataTypeDefXsd::Boolean) => match raw.trim() {
+                "true" | "1" => Some(true.into()),
+                "false" | "0" => Some(false.into()),
+                _ => None,
+            },
+            _ => None,
+        };
+        typed.unwrap_or_else(|| raw.into())
+    }
+
+    /// Check that `value` is a valid lexical form of this type
+    pub fn check(&self, value: &str) -> Result<(), String> {
+        let valid = match self {
+            DataTypeDefXsd::Int => value.trim().parse::<i32>().is_ok(),
+            DataTypeDefXsd::Double => {
+                matches!(value.trim(), "INF" | "-INF" | "NaN")
+                    || value.trim().parse::<f64>().is_ok()
+            }
+            DataTypeDefXsd::Boolean => matches!(value.trim(), "true" | "false" | "1" | "0"),
+            DataTypeDefXsd::DateTime => is_date_time(value.trim()),
+            DataTypeDefXsd::String => true,
+        };
+        if valid {
+            Ok(())
+        } else {
+            Err(format!("'{}' is not a valid {}", value, self.as_str()))
+        }
+    }
+}
+
+/// `YYYY-MM-DDThh:mm:ss`, optionally with fractional seconds and a timezone
+fn is_date_time(value: &str) -> bool {
+    let bytes = value.as_bytes();
+    let digits = |range: core::ops::Range<usize>| {
+        bytes
+            .get(range)
+            .is_some_and(|d| d.iter().all(u8::is_ascii_digit))
+    };
+    let fixed = bytes.len() >= 19
+        && digits(0..4)
+        && bytes[4] == b'-'
+        && digits(5..7)
+        && bytes[7] == b'-'
+        && digits(8..10)
+        && bytes[10] == b'T'
+        && digits(11..13)
+        && bytes[13] == b':'
+        && digits(14..16)
+        && bytes[16] == b':'
+        && digits(17..19);
+    if !fixed {
+        return false;
+    }
+    let mut rest = &value[19..];
+    if let Some(fraction) = rest.strip_prefix('.') {
+        let len = fraction.bytes().take_while(u8::is_ascii_digit).count();
+        if len == 0 {
+            return false;
+        }
+        rest = &fraction[len..];
+    }
+    match rest.as_bytes() {
+        [] | [b'Z'] => true,
+        [b'+' | b'-', h1, h2, b':', m1, m2] => [h1, h2, m1, m2].iter().all(|d| d.is_ascii_digit()),
+        _ => false,
+    }
+}
+
+/// A string tagged with its language (BCP 47, e.g. "en", "de-DE")
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+pub struct LangString {
+    pub language: String,
+    pub text: String,
+}
+
+/// Pick the best match for `locale` from a set of language-tagged strings.
+///
+/// Exact tags win, then a shared primary language ("de" ~ "de-AT"), then English,
+/// then whatever comes first.
+pub fn select_lang<'a>(strings: &'a [LangString], locale: &str) -> Option<&'a LangString> {
+    let primary = |tag: &str| tag.split('-').next().unwrap_or("").to_ascii_lowercase();
+    strings
+        .iter()
+        .find(|s| s.language.eq_ignore_ascii_case(locale))
+        .or_else(|| {
+            strings
+                .iter()
+                .find(|s| primary(&s.language) == primary(locale))
+        })
+        .or_else(|| strings.iter().find(|s| primary(&s.language) == "en"))
+        .or_else(|| strings.first())
+}
+
+/// A single-valued data element
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[cfg_attr(feature = "wasm", tsify(missing_as_null))]
+pub struct Property {
+    pub value: String,
+    pub unit: Option<String>,
+    /// Declared datatype of `value`; untyped values are treated as strings
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value_type: Option<DataTypeDefXsd>,
+}
+
+/// A data element whose value is given in several languages
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+pub struct MultiLanguageProperty {
+    #[serde(default)]
+    pub value: Vec<LangString>,
+}
+
+/// An interval of values, e.g. a permitted ambient temperature range.
+///
+/// Either bound may be omitted to model an open interval.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[cfg_attr(feature = "wasm", tsify(missing_as_null))]
+pub struct Range {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value_type: Option<DataTypeDefXsd>,
+    #[serde(default)]
+    pub min: Option<String>,
+    #[serde(default)]
+    pub max: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unit: Option<String>,
+}
+
+impl Range {
+    /// Typed lower bound, if any
+    pub fn min_f64(&self) -> Result<Option<f64>, String> {
+        self.bound(self.min.as_deref())
+    }
+
+    /// Typed upper bound, if any
+    pub fn max_f64(&self) -> Result<Option<f64>, String> {
+        self.bound(self.max.as_deref())
+    }
+
+    /// Whether `value` lies within the (inclusive) bounds
+    pub fn contains(&self, value: f64) -> Result<bool, String> {
+        let above_min = self.min_f64()?.is_none_or(|min| value >= min);
+        let below_max = self.max_f64()?.is_none_or(|max| value <= max);
+        Ok(above_min && below_max)
+    }
+
+    fn bound(&self, raw: Option<&str>) -> Result<Option<f64>, String> {
+        if let Some(t) = self.value_type {
+            if !matches!(t, DataTypeDefXsd::Double | DataTypeDefXsd::Int) {
+                return Err(format!("Range has non-numeric valueType {}", t.as_str()));
+            }
+        }
+        raw.map(|v| {
+            v.trim()
+                .parse::<f64>()
+                .map_err(|e| format!("Range bound '{}' is not a number: {}", v, e))
+        })
+        .transpose()
+    }
+}
+
+/// A reference to a file, either inside the AAS package or at an external URL
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[cfg_attr(feature = "wasm", tsify(missing_as_null))]
+pub struct File {
+    /// Package-relative path (e.g. "/aasx/files/datasheet.pdf") or absolute URL
+    #[serde(default)]
+    pub value: Option<String>,
+    /// MIME type of the referenced file, e.g. "application/pdf"
+    #[serde(default)]
+    pub content_type: Option<String>,
+}
+
+impl File {
+    /// Whether the value points outside the package (has a URL scheme)
+    pub fn is_external(&self) -> bool {
+        self.value.as_deref().is_some_and(|v| {
+            v.split_once("://").is_some_and(|(scheme, _)| {
+                !scheme.is_empty()
+                    && scheme
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || "+-.".contains(c))
+            })
+        })
+    }
+
+    /// Last path segment, without query string or fragment
+    pub fn file_name(&self) -> Option<&str> {
+        let value = self.value.as_deref()?;
+        let path = value.split(['?', '#']).next().unwrap_or(value);
+        path.rsplit('/').next().filter(|name| !name.is_empty())
+    }
+}
+
+/// Binary content embedded directly in the model, base64-encoded in JSON
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[cfg_attr(feature = "wasm", tsify(missing_as_null))]
+pub struct Blob {
+    #[serde(default)]
+    pub value: Option<String>,
+    #[serde(default)]
+    pub content_type: Option<String>,
+}
+
+impl Blob {
+    /// Encode raw bytes into a Blob
+    pub fn from_bytes(bytes: &[u8], content_type: Option<String>) -> Blob {
+        Blob {
+            value: Some(base64::engine::general_purpose::STANDARD.encode(bytes)),
+            content_type,
+        }
+    }
+
+    /// Decode the payload; an absent value decodes to no bytes
+    pub fn decode(&self) -> Result<Vec<u8>, String> {
+        let Some(value) = self.value.as_deref() else {
+            return Ok(Vec::new());
+        };
+        // Tolerate line-wrapped base64 as produced by many encoders
+        let compact: String = value.chars().filter(|c| !c.is_ascii_whitespace()).collect();
+        base64::engine::general_purpose::STANDARD
+            .decode(compact)
+            .map_err(|e| format!("Invalid base64 payload: {}", e))
+    }
+}
+
+/// An element whose value is a reference to another element or external entity
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[cfg_attr(feature = "wasm", tsify(missing_as_null))]
+pub struct ReferenceElement {
+    #[serde(default)]
+    pub value: Option<Reference>,
+}
+
+/// A directed relationship between two elements, e.g. "motor drives pump"
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[cfg_attr(feature = "wasm", tsify(missing_as_null))]
+pub struct RelationshipElement {
+    #[serde(default)]
+    pub first: Option<Reference>,
+    #[serde(default)]
+    pub second: Option<Reference>,
+}
+
+/// A relationship carrying additional data elements describing it
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[cfg_attr(feature = "wasm", tsify(missing_as_null))]
+pub struct AnnotatedRelationshipElement {
+    #[serde(default)]
+    pub first: Option<Reference>,
+    #[serde(default)]
+    pub second: Option<Reference>,
+    #[serde(default)]
+    pub annotations: Vec<SubmodelElement>,
+}
+
+/// Whether an entity has its own administration shell
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+pub enum EntityType {
+    /// Component described only within this shell (e.g. a bolt)
+    CoManagedEntity,
+    /// Component with an administration shell of its own (e.g. a bought-in motor)
+    SelfManagedEntity,
+}
+
+/// A component of the asset, used to model bills of material
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[cfg_attr(feature = "wasm", tsify(missing_as_null))]
+pub struct Entity {
+    pub entity_type: EntityType,
+    #[serde(default)]
+    pub global_asset_id: Option<String>,
+    /// Elements describing the entity, including nested sub-components
+    #[serde(default)]
+    pub statements: Vec<SubmodelElement>,
+}
+
+/// An argument or result slot of an Operation; the wrapped element describes
+/// its idShort, type and (for inputs) default value
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+pub struct OperationVariable {
+    pub value: SubmodelElement,
+}
+
+/// Callable behavior exposed by the twin
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+pub struct Operation {
+    #[serde(default)]
+    pub input_variables: Vec<OperationVariable>,
+    #[serde(default)]
+    pub output_variables: Vec<OperationVariable>,
+    #[serde(default)]
+    pub inoutput_variables: Vec<OperationVariable>,
+}
+
+/// Something the asset claims it can do, identified by its semanticId
+/// (e.g. a reference into a capability catalogue for "Drilling")
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+pub struct Capability {}
+
+/// Whether an event element reports changes of the twin or receives them
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+    Input,
+    Output,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[serde(rename_all = "lowercase")]
+pub enum StateOfEvent {
+    On,
+    Off,
+}
+
+/// Declares that events about the `observed` element are sent (or received),
+/// e.g. on an MQTT topic
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+pub struct BasicEventElement {
+    /// The element, submodel or shell the events are about
+    pub observed: Reference,
+    pub direction: Direction,
+    pub state: StateOfEvent,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message_topic: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message_broker: Option<Reference>,
+    /// Timestamp (xs:dateTime) of the last event sent or received
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_update: Option<String>,
+    /// Minimum time between two events (xs:duration)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_interval: Option<String>,
+    /// Maximum time between two events (xs:duration)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_interval: Option<String>,
+}
+
+/// A set of elements grouped under one idShort (e.g. Nameplate "AddressInformation")
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+pub struct SubmodelElementCollection {
+    #[serde(default)]
+    pub value: Vec<SubmodelElement>,
+}
+
+/// Element types a SubmodelElementList can be declared to hold
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+pub enum AasSubmodelElements {
+    AnnotatedRelationshipElement,
+    BasicEventElement,
+    Blob,
+    Capability,
+    DataElement,
+    Entity,
+    EventElement,
+    File,
+    MultiLanguageProperty,
+    Operation,
+    Property,
+    Range,
+    ReferenceElement,
+    RelationshipElement,
+    SubmodelElement,
+    SubmodelElementCollection,
+    SubmodelElementList,
+}
+
+impl AasSubmodelElements {
+    /// Whether an element of `kind` satisfies this declared type
+    pub fn matches(&self, kind: &ElementKind) -> bool {
+        let model_type = kind.model_type();
+        match self {
+            AasSubmodelElements::SubmodelElement => true,
+            AasSubmodelElements::DataElement => matches!(
+                model_type,
+                "Property"
+                    | "MultiLanguageProperty"
+                    | "Range"
+                    | "File"
+                    | "Blob"
+                    | "ReferenceElement"
+            ),
+            AasSubmodelElements::EventElement => model_type == "BasicEventElement",
+            AasSubmodelElements::RelationshipElement => matches!(
+                model_type,
+                "RelationshipElement" | "AnnotatedRelationshipElement"
+            ),
+            other => format!("{:?}", other) == model_type,
+        }
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// An ordered list of elements of the same type, addressed by index
+/// (e.g. several "Markings" of a nameplate). List items carry no idShort.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+pub struct SubmodelElementList {
+    /// If false the list is a bag and item positions carry no meaning
+    #[serde(default = "default_true")]
+    pub order_relevant: bool,
+    pub type_value_list_element: AasSubmodelElements,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub semantic_id_list_element: Option<Reference>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value_type_list_element: Option<DataTypeDefXsd>,
+    #[serde(default)]
+    pub value: Vec<SubmodelElement>,
+}
+
+impl SubmodelElementList {
+    /// Check that every item has the declared element type and valueType
+    pub fn check_items(&self) -> Result<(), String> {
+        for (i, item) in self.value.iter().enumerate() {
+            if let (Some(expected), Some(actual)) =
+                (&self.semantic_id_list_element, &item.semantic_id)
+            {
+                if expected != actual {
+                    return Err(format!(
+                        "item {} has semanticId {}, expected {}",
+                        i, actual, expected
+                    ));
+                }
+            }
+            if !self.type_value_list_element.matches(&item.kind) {
+                return Err(format!(
+                    "item {} is a {}, expected {:?}",
+                    i,
+                    item.kind.model_type(),
+                    self.type_value_list_element
+                ));
+            }
+            if let (Some(expected), Some(actual)) =
+                (self.value_type_list_element, item.value_type())
+            {
+                if expected != actual {
+                    return Err(format!(
+                        "item {} has valueType {}, expected {}",
+                        i,
+                        actual.as_str(),
+                        expected.as_str()
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The concrete element type, discriminated by `model_type` in JSON.
+///
+/// The spec's `modelType` is accepted as well, both as a plain string and in
+/// the V2.0 `{"name": "Property"}` form. Elements without a discriminator are
+/// read as plain properties so that configurations written before it existed
+/// keep loading.
+#[derive(Serialize, Clone, Debug)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[serde(tag = "model_type")]
+pub enum ElementKind {
+    Property(Property),
+    MultiLanguageProperty(MultiLanguageProperty),
+    Range(Range),
+    File(File),
+    Blob(Blob),
+    ReferenceElement(ReferenceElement),
+    RelationshipElement(RelationshipElement),
+    AnnotatedRelationshipElement(AnnotatedRelationshipElement),
+    Entity(Entity),
+    Operation(Operation),
+    Capability(Capability),
+    BasicEventElement(BasicEventElement),
+    SubmodelElementCollection(SubmodelElementCollection),
+    SubmodelElementList(SubmodelElementList),
+}
+
+impl ElementKind {
+    /// All `model_type` discriminators, in declaration order
+    pub const MODEL_TYPES: &'static [&'static str] = &[
+        "Property",
+        "MultiLanguageProperty",
+        "Range",
+        "File",
+        "Blob",
+        "ReferenceElement",
+        "RelationshipElement",
+        "AnnotatedRelationshipElement",
+        "Entity",
+        "Operation",
+        "Capability",
+        "BasicEventElement",
+        "SubmodelElementCollection",
+        "SubmodelElementList",
+    ];
+
+    pub fn model_type(&self) -> &'static str {
+        match self {
+            ElementKind::Property(_) => "Property",
+            ElementKind::MultiLanguageProperty(_) => "MultiLanguageProperty",
+            ElementKind::Range(_) => "Range",
+            ElementKind::File(_) => "File",
+            ElementKind::Blob(_) => "Blob",
+            ElementKind::ReferenceElement(_) => "ReferenceElement",
+            ElementKind::RelationshipElement(_) => "RelationshipElement",
+            ElementKind::AnnotatedRelationshipElement(_) => "AnnotatedRelationshipElement",
+            ElementKind::Entity(_) => "Entity",
+            ElementKind::Operation(_) => "Operation",
+            ElementKind::Capability(_) => "Capability",
+            ElementKind::BasicEventElement(_) => "BasicEventElement",
+            ElementKind::SubmodelElementCollection(_) => "SubmodelElementCollection",
+            ElementKind::SubmodelElementList(_) => "SubmodelElementList",
+        }
+    }
+
+    /// Both ends of a (possibly annotated) relationship element
+    pub fn relationship_ends(&self) -> Option<(Option<&Reference>, Option<&Reference>)> {
+        match self {
+            ElementKind::RelationshipElement(r) => Some((r.first.as_ref(), r.second.as_ref())),
+            ElementKind::AnnotatedRelationshipElement(r) => {
+                Some((r.first.as_ref(), r.second.as_ref()))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for ElementKind {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut fields = serde_json::Map::deserialize(deserializer)?;
+        let legacy = fields.remove("model_type").map(discriminator).transpose();
+        let spec = fields.remove("modelType").map(discriminator).transpose();
+        let model_type = match (
+            legacy.map_err(de::Error::custom)?,
+            spec.map_err(de::Error::custom)?,
+        ) {
+            (Some(a), Some(b)) if a != b => {
+                return Err(de::Error::custom(format!(
+                    "conflicting model_type '{}' and modelType '{}'",
+                    a, b
+                )))
+            }
+            (Some(t), _) | (None, Some(t)) => t,
+            (None, None) => "Property".to_string(),
+        };
+        let fields = serde_json::Value::Object(fields);
+        let kind = match model_type.as_str() {
+            "Property" => serde_json::from_value(fields).map(ElementKind::Property),
+            "MultiLanguageProperty" => {
+                serde_json::from_value(fields).map(ElementKind::MultiLanguageProperty)
+            }
+            "Range" => serde_json::from_value(fields).map(ElementKind::Range),
+            "File" => serde_json::from_value(fields).map(ElementKind::File),
+            "Blob" => serde_json::from_value(fields).map(ElementKind::Blob),
+            "ReferenceElement" => serde_json::from_value(fields).map(ElementKind::ReferenceElement),
+            "RelationshipElement" => {
+                serde_json::from_value(fields).map(ElementKind::RelationshipElement)
+            }
+            "AnnotatedRelationshipElement" => {
+                serde_json::from_value(fields).map(ElementKind::AnnotatedRelationshipElement)
+            }
+            "Entity" => serde_json::from_value(fields).map(ElementKind::Entity),
+            "Operation" => serde_json::from_value(fields).map(ElementKind::Operation),
+            "Capability" => serde_json::from_value(fields).map(ElementKind::Capability),
+            "BasicEventElement" => {
+                serde_json::from_value(fields).map(ElementKind::BasicEventElement)
+            }
+            "SubmodelElementCollection" => {
+                serde_json::from_value(fields).map(ElementKind::SubmodelElementCollection)
+            }
+            "SubmodelElementList" => {
+                serde_json::from_value(fields).map(ElementKind::SubmodelElementList)
+            }
+            other => return Err(de::Error::unknown_variant(other, Self::MODEL_TYPES)),
+        };
+        kind.map_err(|e| de::Error::custom(format!("invalid {}: {}", model_type, e)))
+    }
+}
+
+/// Read a type discriminator: a string, or a V2.0 `{"name": ...}` object
+fn discriminator(value: serde_json::Value) -> Result<String, String> {
+    match value {
+        serde_json::Value::String(s) => Ok(s),
+        serde_json::Value::Object(mut o) => match o.remove("name") {
+            Some(serde_json::Value::String(s)) => Ok(s),
+            _ => Err("modelType object must have a string name".to_string()),
+        },
+        other => Err(format!("modelType must be a string, got {}", other)),
+    }
+}
+
+/// How an element's value behaves over the asset's life
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum Category {
+    /// Fixed by the manufacturer, e.g. the serial number
+    Constant,
+    /// Configured on commissioning, e.g. a setpoint
+    Parameter,
+    /// Changes during operation, e.g. the current speed
+    Variable,
+}
+
+impl Category {
+    pub fn parse(category: &str) -> Option<Category> {
+        match category.to_ascii_uppercase().as_str() {
+            "CONSTANT" => Some(Category::Constant),
+            "PARAMETER" => Some(Category::Parameter),
+            "VARIABLE" => Some(Category::Variable),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+pub struct SubmodelElement {
+    /// Required everywhere except for the items of a SubmodelElementList
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub id_short: String,
+    /// Human-readable name, preferred over the idShort in frontends
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub display_name: Vec<LangString>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub description: Vec<LangString>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub category: Option<Category>,
+    /// What the element means, e.g. the ECLASS IRDI for rated voltage
+    #[serde(
+        default,
+        deserialize_with = "deserialize_semantic_id",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub semantic_id: Option<Reference>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub qualifiers: Vec<Qualifier>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extensions: Vec<Extension>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub embedded_data_specifications: Vec<EmbeddedDataSpecification>,
+    #[serde(flatten)]
+    #[cfg_attr(feature = "wasm", tsify(type = "ElementKind"))]
+    pub kind: ElementKind,
+}
+
+impl SubmodelElement {
+    /// An element with only an idShort and its type-specific attributes
+    pub fn new(id_short: &str, kind: ElementKind) -> SubmodelElement {
+        SubmodelElement {
+            id_short: id_short.to_string(),
+            display_name: Vec::new(),
+            description: Vec::new(),
+            category: None,
+            semantic_id: None,
+            qualifiers: Vec::new(),
+            extensions: Vec::new(),
+            embedded_data_specifications: Vec::new(),
+            kind,
+        }
+    }
+
+    /// Whether this element only exists to group others
+    pub fn is_container(&self) -> bool {
+        matches!(
+            self.kind,
+            ElementKind::SubmodelElementCollection(_) | ElementKind::SubmodelElementList(_)
+        )
+    }
+
+    /// Elements nested directly below this one (addressable by idShortPath)
+    pub fn children(&self) -> &[SubmodelElement] {
+        match &self.kind {
+            ElementKind::Entity(e) => &e.statements,
+            ElementKind::AnnotatedRelationshipElement(r) => &r.annotations,
+            ElementKind::SubmodelElementCollection(c) => &c.value,
+            ElementKind::SubmodelElementList(l) => &l.value,
+            _ => &[],
+        }
+    }
+
+    pub fn children_mut(&mut self) -> &mut [SubmodelElement] {
+        match &mut self.kind {
+            ElementKind::Entity(e) => &mut e.statements,
+            ElementKind::AnnotatedRelationshipElement(r) => &mut r.annotations,
+            ElementKind::SubmodelElementCollection(c) => &mut c.value,
+            ElementKind::SubmodelElementList(l) => &mut l.value,
+            _ => &mut [],
+        }
+    }
+
+    /// The element's list of children, if it is a container
+    pub fn children_vec_mut(&mut self) -> Option<&mut Vec<SubmodelElement>> {
+        match &mut self.kind {
+            ElementKind::Entity(e) => Some(&mut e.statements),
+            ElementKind::AnnotatedRelationshipElement(r) => Some(&mut r.annotations),
+            ElementKind::SubmodelElementCollection(c) => Some(&mut c.value),
+            ElementKind::SubmodelElementList(l) => Some(&mut l.value),
+            _ => None,
+        }
+    }
+
+    /// Declared valueType of a Property or Range
+    pub fn value_type(&self) -> Option<DataTypeDefXsd> {
+        match &self.kind {
+            ElementKind::Property(p) => p.value_type,
+            ElementKind::Range(r) => r.value_type,
+            _ => None,
+        }
+    }
+
+    /// Unit of a Property or Range
+    pub fn unit(&self) -> Option<&str> {
+        match &self.kind {
+            ElementKind::Property(p) => p.unit.as_deref(),
+            ElementKind::Range(r) => r.unit.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// Extensions as written to the spec serializations: the unit, if any,
+    /// travels as an extension named [`UNIT_EXTENSION`]
+    pub fn spec_extensions(&self) -> Vec<Extension> {
+        let unit = self.unit().map(|u| Extension {
+            name: UNIT_EXTENSION.to_string(),
+            value_type: Some(DataTypeDefXsd::String),
+            value: Some(u.to_string()),
+            refers_to: Vec::new(),
+            semantic_id: None,
+        });
+        unit.into_iter()
+            .chain(self.extensions.iter().cloned())
+            .collect()
+    }
+
+    /// Human-readable value, as shown by `DigitalTwin::get_property`
+    pub fn display_value(&self) -> String {
+        match &self.kind {
+            ElementKind::Property(p) => {
+                format!("{} {}", p.value, p.unit.as_deref().unwrap_or(""))
+            }
+            ElementKind::MultiLanguageProperty(mlp) => select_lang(&mlp.value, "en")
+                .map(|s| s.text.clone())
+                .unwrap_or_default(),
+            ElementKind::Range(r) => format!(
+                "{} .. {} {}",
+                r.min.as_deref().unwrap_or(""),
+                r.max.as_deref().unwrap_or(""),
+                r.unit.as_deref().unwrap_or("")
+            ),
+            ElementKind::File(f) => f.value.clone().unwrap_or_default(),
+            ElementKind::Blob(b) => b.content_type.clone().unwrap_or_default(),
+            ElementKind::ReferenceElement(r) => {
+                r.value.as_ref().map(|r| r.to_string()).unwrap_or_default()
+            }
+            kind @ (ElementKind::RelationshipElement(_)
+            | ElementKind::AnnotatedRelationshipElement(_)) => {
+                let (first, second) = kind.relationship_ends().unwrap_or_default();
+                let end = |r: Option<&Reference>| r.map(|r| r.to_string()).unwrap_or_default();
+                format!("{} -> {}", end(first), end(second))
+            }
+            ElementKind::Entity(e) => e.global_asset_id.clone().unwrap_or_default(),
+            ElementKind::Operation(op) => {
+                let names = |vars: &[OperationVariable]| {
+                    vars.iter()
+                        .map(|v| v.value.id_short.as_str())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                };
+                format!(
+                    "({}) -> ({})",
+                    names(&[op.input_variables.as_slice(), &op.inoutput_variables].concat()),
+                    names(&[op.output_variables.as_slice(), &op.inoutput_variables].concat())
+                )
+            }
+            ElementKind::Capability(_) => self
+                .semantic_id
+                .as_ref()
+                .and_then(|r| r.key_value())
+                .unwrap_or_default()
+                .to_string(),
+            ElementKind::BasicEventElement(e) => e.observed.to_string(),
+            ElementKind::SubmodelElementCollection(c) => format!("{} elements", c.value.len()),
+            ElementKind::SubmodelElementList(l) => format!("{} items", l.value.len()),
+        }
+    }
+
+    /// The value as JSON: typed for properties, `{min, max}` for ranges and the
+    /// display text for everything else
+    pub fn json_value(&self) -> serde_json::Value {
+        match &self.kind {
+            ElementKind::Property(p) => DataTypeDefXsd::to_json(p.value_type, &p.value),
+            ElementKind::Range(r) => {
+                let bound = |b: &Option<String>| match b {
+                    Some(b) => DataTypeDefXsd::to_json(r.value_type, b),
+                    None => serde_json::Value::Null,
+                };
+                serde_json::json!({"min": bound(&r.min), "max": bound(&r.max)})
+            }
+            _ => self.display_value().into(),
+        }
+    }
+
+    /// Value in the requested locale; plain properties ignore the locale
+    pub fn localized_value(&self, locale: &str) -> Option<String> {
+        match &self.kind {
+            ElementKind::Property(p) => Some(p.value.clone()),
+            ElementKind::MultiLanguageProperty(mlp) => {
+                select_lang(&mlp.value, locale).map(|s| s.text.clone())
+            }
+            _ => Some(self.display_value()),
+        }
+    }
+
+    /// Parse the value as a number (accepted for xs:double, xs:int and untyped values)
+    pub fn value_as_f64(&self) -> Result<f64, String> {
+        let p = self.typed_property(&[DataTypeDefXsd::Double, DataTypeDefXsd::Int])?;
+        p.value.trim().parse::<f64>().map_err(|e| {
+            format!(
+                "Property '{}' value '{}' is not a number: {}",
+                self.id_short, p.value, e
+            )
+        })
+    }
+
+    /// Parse the value as a 32-bit integer (accepted for xs:int and untyped values)
+    pub fn value_as_i32(&self) -> Result<i32, String> {
+        let p = self.typed_property(&[DataTypeDefXsd::Int])?;
+        p.value.trim().parse::<i32>().map_err(|e| {
+            format!(
+                "Property '{}' value '{}' is not an integer: {}",
+                self.id_short, p.value, e
+            )
+        })
+    }
+
+    /// Parse the value as xs:boolean ("true", "false", "1" or "0")
+    pub fn value_as_bool(&self) -> Result<bool, String> {
+        let p = self.typed_property(&[DataTypeDefXsd::Boolean])?;
+        match p.value.trim() {
+            "true" | "1" => Ok(true),
+            "false" | "0" => Ok(false),
+            other => Err(format!(
+                "Property '{}' value '{}' is not a boolean",
+                self.id_short, other
+            )),
+        }
+    }
+
+    /// Replace a Property's value, checking it against the declared valueType
+    pub fn set_value(&mut self, value: &str) -> Result<(), String> {
+        let id_short = &self.id_short;
+        match &mut self.kind {
+            ElementKind::Property(p) => {
+                if let Some(t) = p.value_type {
+                    t.check(value)
+                        .map_err(|e| format!("Property '{}': {}", id_short, e))?;
+                }
+                p.value = value.to_string();
+                Ok(())
+            }
+            other => Err(format!(
+                "Element '{}' is a {}, not a Property",
+                id_short,
+                other.model_type()
+            )),
+        }
+    }
+
+    /// Check `value` against a Range element's bounds
+    pub fn range_contains(&self, value: f64) -> Result<bool, String> {
+        match &self.kind {
+            ElementKind::Range(r) => r
+                .contains(value)
+                .map_err(|e| format!("Range '{}': {}", self.id_short, e)),
+            other => Err(format!(
+                "Element '{}' is a {}, not a Range",
+                self.id_short,
+                other.model_type()
+            )),
+        }
+    }
+
+    /// Decode a Blob element's payload
+    pub fn blob_bytes(&self) -> Result<Vec<u8>, String> {
+        match &self.kind {
+            ElementKind::Blob(b) => b
+                .decode()
+                .map_err(|e| format!("Blob '{}': {}", self.id_short, e)),
+            other => Err(format!(
+                "Element '{}' is a {}, not a Blob",
+                self.id_short,
+                other.model_type()
+            )),
+        }
+    }
+
+    fn typed_property(&self, allowed: &[DataTypeDefXsd]) -> Result<&Property, String> {
+        let p = match &self.kind {
+            ElementKind::Property(p) => p,
+            other => {
+                return Err(format!(
+                    "Element '{}' is a {}, not a Property",
+                    self.id_short,
+                    other.model_type()
+                ))
+            }
+        };
+        match p.value_type {
+            Some(t) if !allowed.contains(&t) => Err(format!(
+                "Property '{}' has valueType {}",
+                self.id_short,
+                t.as_str()
+            )),
+            _ => Ok(p),
+        }
+    }
+}
+
+/// Lifecycle information of a shell or submodel
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+pub struct AdministrativeInformation {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revision: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub creator: Option<Reference>,
+    /// Id of the template this element was instantiated from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template_id: Option<String>,
+}
+
+impl AdministrativeInformation {
+    /// Increment the revision (starting from 0) and return the new value.
+    /// A revision requires a version, so a missing version is set to "1".
+    pub fn bump_revision(&mut self) -> Result<String, String> {
+        let current = match self.revision.as_deref() {
+            Some(r) => r
+                .trim()
+                .parse::<u32>()
+                .map_err(|_| format!("Revision '{}' is not numeric", r))?,
+            None => 0,
+        };
+        let next = (current + 1).to_string();
+        self.version.get_or_insert_with(|| "1".to_string());
+        self.revision = Some(next.clone());
+        Ok(next)
+    }
+}
+
+/// Whether a submodel describes a template or a concrete asset instance
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+pub enum ModellingKind {
+    Template,
+    #[default]
+    Instance,
+}
+
+/// A submodel groups related elements (e.g., TechnicalData, Documentation)
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[cfg_attr(feature = "wasm", tsify(missing_as_null))]
+pub struct Submodel {
+    pub id: String,
+    pub id_short: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub display_name: Vec<LangString>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub description: Vec<LangString>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub administration: Option<AdministrativeInformation>,
+    #[serde(default, deserialize_with = "deserialize_semantic_id")]
+    pub semantic_id: Option<Reference>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub qualifiers: Vec<Qualifier>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extensions: Vec<Extension>,
+    #[serde(default)]
+    pub kind: ModellingKind,
+    #[serde(default)]
+    pub submodel_elements: Vec<SubmodelElement>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+pub struct AssetAdministrationShell {
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub display_name: Vec<LangString>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub description: Vec<LangString>,
+    /// Also accepted as a plain `asset_type` string, as written by older generators
+    #[serde(
+        alias = "asset_type",
+        deserialize_with = "deserialize_asset_information"
+    )]
+    pub asset_information: AssetInformation,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub administration: Option<AdministrativeInformation>,
+    pub nameplate: Vec<SubmodelElement>,
+    /// Additional submodels living alongside the nameplate
+    #[serde(default)]
+    pub submodels: Vec<Submodel>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extensions: Vec<Extension>,
+    /// Definitions the semanticIds of this shell's elements point at
+    #[serde(
+        default,
+        with = "concept::as_list",
+        skip_serializing_if = "ConceptDictionary::is_empty"
+    )]
+    #[cfg_attr(feature = "wasm", tsify(type = "ConceptDescription[]"))]
+    pub concept_descriptions: ConceptDictionary,
+}
+
+/// Join an element path prefix and an idShort ("Motor" + "Bearing" -> "Motor.Bearing")
+pub fn join_path(prefix: &str, id_short: &str) -> String {
+    if prefix.is_empty() {
+        id_short.to_string()
+    } else {
+        format!("{}.{}", prefix, id_short)
+    }
+}
+
+/// Depth-first list of `elements` and all their descendants with their paths
+pub fn walk_elements<'a>(
+    prefix: &str,
+    elements: &'a [SubmodelElement],
+    out: &mut Vec<(String, &'a SubmodelElement)>,
+) {
+    for element in elements {
+        let path = join_path(prefix, &element.id_short);
+        out.push((path.clone(), element));
+        if let ElementKind::SubmodelElementList(list) = &element.kind {
+            for (i, item) in list.value.iter().enumerate() {
+                let item_path = format!("{}[{}]", path, i);
+                out.push((item_path.clone(), item));
+                walk_elements(&item_path, item.children(), out);
+            }
+        } else {
+            walk_elements(&path, element.children(), out);
+        }
+    }
+}
+
+/// Follow the segments of a parsed idShortPath down the element tree: an
+/// idShort selects a child, an index an item of the enclosing list
+pub fn find_element_in<'a>(
+    elements: &'a [SubmodelElement],
+    segments: &[PathSegment],
+) -> Option<&'a SubmodelElement> {
+    let (first, rest) = segments.split_first()?;
+    let PathSegment::IdShort(id_short) = first else {
+        return None;
+    };
+    let mut element = elements.iter().find(|e| &e.id_short == id_short)?;
+    for segment in rest {
+        element = match segment {
+            PathSegment::IdShort(id_short) => element
+                .children()
+                .iter()
+                .find(|e| &e.id_short == id_short)?,
+            PathSegment::Index(index) => match &element.kind {
+                ElementKind::SubmodelElementList(list) => list.value.get(*index)?,
+                _ => return None,
+            },
+        };
+    }
+    Some(element)
+}
+
+/// Mutable counterpart of [`find_element_in`]
+pub fn find_element_in_mut<'a>(
+    elements: &'a mut [SubmodelElement],
+    segments: &[PathSegment],
+) -> Option<&'a mut SubmodelElement> {
+    let (first, rest) = segments.split_first()?;
+    let PathSegment::IdShort(id_short) = first else {
+        return None;
+    };
+    let mut element = elements.iter_mut().find(|e| &e.id_short == id_short)?;
+    for segment in rest {
+        element = match segment {
+            PathSegment::IdShort(id_short) => element
+                .children_mut()
+                .iter_mut()
+                .find(|e| &e.id_short == id_short)?,
+            PathSegment::Index(index) => match &mut element.kind {
+                ElementKind::SubmodelElementList(list) => list.value.get_mut(*index)?,
+                _ => return None,
+            },
+        };
+    }
+    Some(element)
+}
+
+/// The target of a successfully resolved model reference
+#[derive(Debug)]
+pub enum ResolvedReference<'a> {
+    Shell(&'a AssetAdministrationShell),
+    Submodel {
+        id_short: &'a str,
+        elements: &'a [SubmodelElement],
+    },
+    Element {
+        /// Element path as accepted by the `DigitalTwin` getters
+        path: String,
+        element: &'a SubmodelElement,
+    },
+}
+
+impl AssetAdministrationShell {
+    /// Look up a submodel by its globally unique id.
+    ///
+    /// The legacy nameplate has no id of its own and answers to `NAMEPLATE_ID_SHORT`.
+    pub fn submodel_by_id(&self, id: &str) -> Option<(&str, &[SubmodelElement])> {
+        if id == NAMEPLATE_ID_SHORT {
+            return Some((NAMEPLATE_ID_SHORT, &self.nameplate));
+        }
+        self.submodels
+            .iter()
+            .find(|sm| sm.id == id)
+            .map(|sm| (sm.id_short.as_str(), sm.submodel_elements.as_slice()))
+    }
+
+    /// Follow a ModelReference to the shell, a submodel or an element.
+    ///
+    /// External references and dangling keys resolve to `None`.
+    pub fn resolve(&self, reference: &Reference) -> Option<ResolvedReference<'_>> {
+        if reference.reference_type != ReferenceTypes::ModelReference {
+            return None;
+        }
+        let (first, rest) = reference.keys.split_first()?;
+        let (id_short, elements) = match first.key_type {
+            KeyTypes::AssetAdministrationShell if first.value == self.id && rest.is_empty() => {
+                return Some(ResolvedReference::Shell(self));
+            }
+            KeyTypes::Submodel => self.submodel_by_id(&first.value)?,
+            _ => return None,
+        };
+        if rest.is_empty() {
+            return Some(ResolvedReference::Submodel { id_short, elements });
+        }
+        if !rest.iter().all(|k| k.key_type.is_submodel_element()) {
+            return None;
+        }
+        // Keys read as an idShortPath: a number below a list selects an item
+        let keys: Vec<&str> = rest.iter().map(|k| k.value.as_str()).collect();
+        let element = find_element_in(
+            elements,
+            IdShortPath::parse(&keys.join(".")).ok()?.segments(),
+        )?;
+        let mut path = if id_short == NAMEPLATE_ID_SHORT {
+            String::new()
+        } else {
+            id_short.to_string()
+        };
+        for key in rest {
+            path = join_path(&path, &key.value);
+        }
+        Some(ResolvedReference::Element { path, element })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn find_at<'a>(elements: &'a [SubmodelElement], path: &str) -> Option<&'a SubmodelElement> {
+        find_element_in(elements, IdShortPath::parse(path).unwrap().segments())
+    }
+
+    #[test]
+    fn test_element_kind_defaults_to_property() {
+        let elem: SubmodelElement =
+            serde_json::from_str(r#"{"id_short": "Voltage", "value": "400", "unit": "V"}"#)
+                .unwrap();
+        assert_eq!(elem.kind.model_type(), "Property");
+
+        let json = serde_json::to_string(&elem).unwrap();
+        assert!(json.contains(r#""model_type":"Property""#));
+        let again: SubmodelElement = serde_json::from_str(&json).unwrap();
+        assert_eq!(again.display_value(), "400 V");
+    }
+
+    #[test]
+    fn test_spec_model_type_discriminator() {
+        let elements: Vec<SubmodelElement> = serde_json::from_str(
+            r#"[
+                {"id_short": "Voltage", "modelType": "Property", "value": "400"},
+                {"id_short": "Name", "modelType": "MultiLanguageProperty",
+                 "value": [{"language": "en", "text": "Motor"}]},
+                {"id_short": "Manual", "modelType": {"name": "File"}, "value": "/docs/manual.pdf"},
+                {"id_short": "Limits", "modelType": "Range", "min": "0", "max": "80"}
+            ]"#,
+        )
+        .unwrap();
+        let types: Vec<_> = elements.iter().map(|e| e.kind.model_type()).collect();
+        assert_eq!(
+            types,
+            ["Property", "MultiLanguageProperty", "File", "Range"]
+        );
+
+        let conflicting = r#"{"id_short": "X", "model_type": "File", "modelType": "Blob"}"#;
+        let err = serde_json::from_str::<SubmodelElement>(conflicting).unwrap_err();
+        assert!(err.to_string().contains("conflicting"));
+        let unknown = r#"{"id_short": "X", "modelType": "Gizmo"}"#;
+        assert!(serde_json::from_str::<SubmodelElement>(unknown).is_err());
+    }
+
+    #[test]
+    fn test_multi_language_property() {
+        let elem: SubmodelElement = serde_json::from_str(
+            r#"{
+                "id_short": "ProductDesignation",
+                "model_type": "MultiLanguageProperty",
+                "value": [
+                    {"language": "en", "text": "Low-voltage motor"},
+                    {"language": "de-DE", "text": "Niederspannungsmotor"}
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(elem.localized_value("de").unwrap(), "Niederspannungsmotor");
+        assert_eq!(
+            elem.localized_value("DE-de").unwrap(),
+            "Niederspannungsmotor"
+        );
+        assert_eq!(elem.localized_value("fr").unwrap(), "Low-voltage motor");
+        assert!(elem
+            .value_as_f64()
+            .unwrap_err()
+            .contains("MultiLanguageProperty"));
+
+        let unknown = serde_json::from_str::<SubmodelElement>(
+            r#"{"id_short": "X", "model_type": "Hologram"}"#,
+        );
+        assert!(unknown.unwrap_err().to_string().contains("Hologram"));
+    }
+
+    #[test]
+    fn test_range_bounds() {
+        let elem: SubmodelElement = serde_json::from_str(
+            r#"{
+                "id_short": "AmbientTemperature",
+                "model_type": "Range",
+                "value_type": "xs:double",
+                "min": "-20",
+                "max": "40",
+                "unit": "°C"
+            }"#,
+        )
+        .unwrap();
+
+        assert!(elem.range_contains(25.0).unwrap());
+        assert!(elem.range_contains(40.0).unwrap());
+        assert!(!elem.range_contains(-25.0).unwrap());
+        assert_eq!(elem.display_value(), "-20 .. 40 °C");
+
+        let open: Range = serde_json::from_str(r#"{"max": "1500"}"#).unwrap();
+        assert!(open.contains(-1.0e9).unwrap());
+        let bad: Range = serde_json::from_str(r#"{"min": "low"}"#).unwrap();
+        assert!(bad.contains(0.0).is_err());
+    }
+
+    #[test]
+    fn test_file_paths() {
+        let datasheet: File = serde_json::from_str(
+            r#"{"value": "/aasx/files/1LE1 datasheet.pdf", "content_type": "application/pdf"}"#,
+        )
+        .unwrap();
+        assert!(!datasheet.is_external());
+        assert_eq!(datasheet.file_name(), Some("1LE1 datasheet.pdf"));
+
+        let cad: File = serde_json::from_str(
+            r#"{"value": "https://example.com/cad/motor.step?rev=3", "content_type": null}"#,
+        )
+        .unwrap();
+        assert!(cad.is_external());
+        assert_eq!(cad.file_name(), Some("motor.step"));
+
+        let empty: File = serde_json::from_str("{}").unwrap();
+        assert_eq!(empty.file_name(), None);
+    }
+
+    #[test]
+    fn test_blob_round_trip() {
+        let manifest = br#"{"firmware":"2.4.1"}"#;
+        let blob = Blob::from_bytes(manifest, Some("application/json".to_string()));
+        assert_eq!(blob.value.as_deref(), Some("eyJmaXJtd2FyZSI6IjIuNC4xIn0="));
+        assert_eq!(blob.decode().unwrap(), manifest);
+
+        let wrapped = Blob {
+            value: Some("eyJmaXJtd2Fy\nZSI6IjIuNC4xIn0=".to_string()),
+            content_type: None,
+        };
+        assert_eq!(wrapped.decode().unwrap(), manifest);
+
+        let broken = Blob {
+            value: Some("not base64!".to_string()),
+            content_type: None,
+        };
+        assert!(broken.decode().is_err());
+    }
+
+    #[test]
+    fn test_resolve_model_reference() {
+        let aas: AssetAdministrationShell = serde_json::from_str(
+            r#"{
+                "id": "urn:example:aas:motor",
+                "asset_type": "Siemens 1LE1",
+                "nameplate": [{"id_short": "Voltage", "value": "400", "unit": "V"}],
+                "submodels": [{
+                    "id": "urn:example:sm:technical-data",
+                    "id_short": "TechnicalData",
+                    "submodel_elements": [
+                        {"id_short": "MaxTorque", "value": "49", "unit": "Nm"},
+                        {"id_short": "VoltageRef", "model_type": "ReferenceElement", "value": {
+                            "type": "ModelReference",
+                            "keys": [
+                                {"type": "Submodel", "value": "Nameplate"},
+                                {"type": "Property", "value": "Voltage"}
+                            ]
+                        }}
+                    ]
+                }]
+            }"#,
+        )
+        .unwrap();
+
+        let ElementKind::ReferenceElement(re) = &aas.submodels[0].submodel_elements[1].kind else {
+            panic!("expected a ReferenceElement");
+        };
+        let target = re.value.as_ref().unwrap();
+        assert_eq!(
+            target.to_string(),
+            "[ModelRef](Submodel)Nameplate, (Property)Voltage"
+        );
+        match aas.resolve(target) {
+            Some(ResolvedReference::Element { path, element }) => {
+                assert_eq!(path, "Voltage");
+                assert_eq!(element.display_value(), "400 V");
+            }
+            other => panic!("unexpected resolution: {:?}", other),
+        }
+
+        let mut torque = target.clone();
+        torque.keys[0].value = "urn:example:sm:technical-data".to_string();
+        torque.keys[1].value = "MaxTorque".to_string();
+        assert!(matches!(
+            aas.resolve(&torque),
+            Some(ResolvedReference::Element { path, .. }) if path == "TechnicalData.MaxTorque"
+        ));
+
+        torque.keys[1].value = "Missing".to_string();
+        assert!(aas.resolve(&torque).is_none());
+        assert!(aas
+            .resolve(&Reference::global("0173-1#02-AAE416#004"))
+            .is_none());
+    }
+
+    #[test]
+    fn test_nested_collections() {
+        let elements: Vec<SubmodelElement> = serde_json::from_str(
+            r#"[{
+                "id_short": "AddressInformation",
+                "model_type": "SubmodelElementCollection",
+                "value": [
+                    {"id_short": "Street", "value": "Werner-von-Siemens-Str. 1", "unit": null},
+                    {"id_short": "Phone", "model_type": "SubmodelElementCollection", "value": [
+                        {"id_short": "TelephoneNumber", "value": "+49 9131 7-0", "unit": null}
+                    ]}
+                ]
+            }]"#,
+        )
+        .unwrap();
+
+        let mut paths = Vec::new();
+        walk_elements("Nameplate", &elements, &mut paths);
+        let paths: Vec<_> = paths.into_iter().map(|(p, _)| p).collect();
+        assert_eq!(
+            paths,
+            [
+                "Nameplate.AddressInformation",
+                "Nameplate.AddressInformation.Street",
+                "Nameplate.AddressInformation.Phone",
+                "Nameplate.AddressInformation.Phone.TelephoneNumber",
+            ]
+        );
+
+        let phone = find_at(&elements, "AddressInformation.Phone.TelephoneNumber");
+        assert_eq!(phone.unwrap().display_value(), "+49 9131 7-0 ");
+        assert!(elements[0].is_container());
+        assert!(find_at(&elements, "AddressInformation.Fax").is_none());
+    }
+
+    #[test]
+    fn test_submodel_element_list() {
+        let elements: Vec<SubmodelElement> = serde_json::from_str(
+            r#"[{
+                "id_short": "Markings",
+                "model_type": "SubmodelElementList",
+                "type_value_list_element": "SubmodelElementCollection",
+                "value": [
+                    {"model_type": "SubmodelElementCollection", "value": [
+                        {"id_short": "MarkingName", "value": "CE", "unit": null}
+                    ]},
+                    {"model_type": "SubmodelElementCollection", "value": [
+                        {"id_short": "MarkingName", "value": "UKCA", "unit": null}
+                    ]}
+                ]
+            }]"#,
+        )
+        .unwrap();
+
+        let ElementKind::SubmodelElementList(list) = &elements[0].kind else {
+            panic!("expected a list");
+        };
+        assert!(list.order_relevant);
+        assert!(list.check_items().is_ok());
+
+        let ukca = find_at(&elements, "Markings[1].MarkingName").unwrap();
+        assert_eq!(ukca.display_value(), "UKCA ");
+        let by_key = find_at(&elements, "Markings.0.MarkingName").unwrap();
+        assert_eq!(by_key.display_value(), "CE ");
+        assert!(find_at(&elements, "Markings[2]").is_none());
+        assert!(find_at(&elements, "Markings[0][0]").is_none());
+
+        let mut paths = Vec::new();
+        walk_elements("", &elements, &mut paths);
+        assert_eq!(paths[3].0, "Markings[1]");
+        assert_eq!(paths[4].0, "Markings[1].MarkingName");
+
+        let typed: SubmodelElementList = serde_json::from_str(
+            r#"{"type_value_list_element": "Property", "value_type_list_element": "xs:int",
+                "value": [{"value": "1", "unit": null, "value_type": "xs:double"}]}"#,
+        )
+        .unwrap();
+        assert!(typed.check_items().unwrap_err().contains("xs:double"));
+    }
+
+    #[test]
+    fn test_semantic_id_forms() {
+        let elements: Vec<SubmodelElement> = serde_json::from_str(
+            r#"[
+                {"id_short": "Voltage", "value": "400", "unit": "V",
+                 "semantic_id": "0173-1#02-AAE416#004"},
+                {"id_short": "Current", "value": "15.2", "unit": "A", "semantic_id": {
+                    "type": "ExternalReference",
+                    "keys": [{"type": "GlobalReference", "value": "0173-1#02-AAE415#004"}]
+                }},
+                {"id_short": "Frequency", "value": "50", "unit": "Hz"}
+            ]"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            elements[0].semantic_id,
+            Some(Reference::global("0173-1#02-AAE416#004"))
+        );
+        assert!(elements[1]
+            .semantic_id
+            .as_ref()
+            .unwrap()
+            .refers_to("0173-1#02-AAE415#004"));
+        assert!(elements[2].semantic_id.is_none());
+
+        let json = serde_json::to_string(&elements).unwrap();
+        assert_eq!(json.matches("GlobalReference").count(), 2);
+        let again: Vec<SubmodelElement> = serde_json::from_str(&json).unwrap();
+        assert_eq!(again[0].semantic_id, elements[0].semantic_id);
+    }
+
+    #[test]
+    fn test_extensions_round_trip() {
+        let json = r#"{
+            "id": "MOTOR-12345",
+            "asset_type": "Siemens 1LE1",
+            "nameplate": [{
+                "id_short": "Voltage", "value": "400", "unit": "V",
+                "extensions": [{"name": "vendor:plcTag", "value_type": "xs:string", "value": "DB10.DBD4"}]
+            }],
+            "submodels": [{
+                "id": "urn:example:sm:1",
+                "id_short": "TechnicalData",
+                "extensions": [{"name": "vendor:source", "value": "photo"}]
+            }],
+            "extensions": [{"name": "vendor:scannedBy", "refers_to": [{
+                "type": "ModelReference", "keys": [{"type": "Submodel", "value": "urn:example:sm:1"}]
+            }]}]
+        }"#;
+
+        let aas: AssetAdministrationShell = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            aas.nameplate[0].extensions[0].value.as_deref(),
+            Some("DB10.DBD4")
+        );
+        assert_eq!(aas.submodels[0].extensions[0].name, "vendor:source");
+
+        let again: AssetAdministrationShell =
+            serde_json::from_str(&serde_json::to_string(&aas).unwrap()).unwrap();
+        assert_eq!(again.extensions, aas.extensions);
+        assert_eq!(again.nameplate[0].extensions, aas.nameplate[0].extensions);
+        assert_eq!(again.submodels[0].extensions, aas.submodels[0].extensions);
+    }
+
+    #[test]
+    fn test_bump_revision() {
+        let mut admin = AdministrativeInformation::default();
+        assert_eq!(admin.bump_revision().unwrap(), "1");
+        assert_eq!(admin.version.as_deref(), Some("1"));
+
+        let mut admin: AdministrativeInformation = serde_json::from_str(
+            r#"{"version": "2", "revision": "7", "template_id": "https://admin-shell.io/idta/nameplate/3/0/Nameplate"}"#,
+        )
+        .unwrap();
+        assert_eq!(admin.bump_revision().unwrap(), "8");
+        assert_eq!(admin.version.as_deref(), Some("2"));
+
+        admin.revision = Some("beta".to_string());
+        assert!(admin.bump_revision().is_err());
+    }
+
+    #[test]
+    fn test_legacy_asset_type() {
+        let legacy: AssetAdministrationShell = serde_json::from_str(
+            r#"{"id": "MOTOR-1", "asset_type": "Siemens 1LE1", "nameplate": []}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            legacy.asset_information.asset_type.as_deref(),
+            Some("Siemens 1LE1")
+        );
+        assert_eq!(legacy.asset_information.asset_kind, AssetKind::Instance);
+
+        let json = serde_json::to_string(&legacy).unwrap();
+        assert!(json.contains(
+            r#""asset_information":{"asset_kind":"Instance","asset_type":"Siemens 1LE1"}"#
+        ));
+        let again: AssetAdministrationShell = serde_json::from_str(&json).unwrap();
+        assert_eq!(again.asset_information, legacy.asset_information);
+    }
+}
@@ -0,0 +1,121 @@
+// --- idShortPaths ---
+// Element addresses as in the AAS REST API: idShorts joined by dots, list
+// items selected by a bracketed index ("Markings[2].MarkingFile"). A bare
+// number directly below a list selects an item as well, as in model
+// reference keys.
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+use core::str::FromStr;
+
+/// One step of an idShortPath
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PathSegment {
+    IdShort(String),
+    /// Position in the enclosing SubmodelElementList
+    Index(usize),
+}
+
+/// A parsed, syntactically valid idShortPath
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IdShortPath(Vec<PathSegment>);
+
+impl IdShortPath {
+    pub fn parse(path: &str) -> Result<IdShortPath, String> {
+        let invalid = |reason: &str| format!("Invalid idShortPath '{}': {}", path, reason);
+        let mut segments = Vec::new();
+        for part in path.split('.') {
+            let (id_short, mut rest) = part.split_at(part.find('[').unwrap_or(part.len()));
+            match id_short.parse::<usize>() {
+                Ok(index) if !segments.is_empty() => segments.push(PathSegment::Index(index)),
+                Ok(_) => return Err(invalid("it starts with an index")),
+                Err(_) if id_short.is_empty() => return Err(invalid("empty idShort")),
+                Err(_) => segments.push(PathSegment::IdShort(id_short.to_string())),
+            }
+            while !rest.is_empty() {
+                let close = rest.find(']').ok_or_else(|| invalid("unclosed '['"))?;
+                let index = rest[1..close]
+                    .parse()
+                    .map_err(|_| invalid(&format!("'{}' is not an index", &rest[1..close])))?;
+                segments.push(PathSegment::Index(index));
+                rest = &rest[close + 1..];
+                if !rest.is_empty() && !rest.starts_with('[') {
+                    return Err(invalid("text after ']'"));
+                }
+            }
+        }
+        Ok(IdShortPath(segments))
+    }
+
+    pub fn segments(&self) -> &[PathSegment] {
+        &self.0
+    }
+
+    /// The path without its last segment and that segment
+    pub fn split_last(&self) -> (IdShortPath, &PathSegment) {
+        let (last, parent) = self.0.split_last().expect("idShortPaths are never empty");
+        (IdShortPath(parent.to_vec()), last)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl FromStr for IdShortPath {
+    type Err = String;
+
+    fn from_str(path: &str) -> Result<IdShortPath, String> {
+        IdShortPath::parse(path)
+    }
+}
+
+/// The canonical form: dots between idShorts, brackets for indices
+impl fmt::Display for IdShortPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, segment) in self.0.iter().enumerate() {
+            match segment {
+                PathSegment::IdShort(id_short) if i == 0 => f.write_str(id_short)?,
+                PathSegment::IdShort(id_short) => write!(f, ".{}", id_short)?,
+                PathSegment::Index(index) => write!(f, "[{}]", index)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_id_short_path() {
+        let path = IdShortPath::parse("TechnicalData.Markings[2][0].File").unwrap();
+        assert_eq!(
+            path.segments(),
+            [
+                PathSegment::IdShort("TechnicalData".to_string()),
+                PathSegment::IdShort("Markings".to_string()),
+                PathSegment::Index(2),
+                PathSegment::Index(0),
+                PathSegment::IdShort("File".to_string()),
+            ]
+        );
+        assert_eq!(path.to_string(), "TechnicalData.Markings[2][0].File");
+        assert_eq!(
+            IdShortPath::parse("Markings.1.MarkingName")
+                .unwrap()
+                .to_string(),
+            "Markings[1].MarkingName"
+        );
+        let (parent, last) = path.split_last();
+        assert_eq!(parent.to_string(), "TechnicalData.Markings[2][0]");
+        assert_eq!(last, &PathSegment::IdShort("File".to_string()));
+
+        for invalid in ["", "A..B", "A[x]", "A[1", "A[1]B", "[0]", "0.A"] {
+            assert!(IdShortPath::parse(invalid).is_err(), "{}", invalid);
+        }
+    }
+}
@@ -0,0 +1,80 @@
+// --- Qualifiers ---
+// Additional constraints or metadata attached to an element, such as
+// SMT/Cardinality from the IDTA submodel templates
+
+use alloc::string::String;
+
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "wasm")]
+use tsify::Tsify;
+
+use super::{deserialize_semantic_id, DataTypeDefXsd, Reference};
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+pub enum QualifierKind {
+    /// Qualifies the value of the element; may change with it
+    ValueQualifier,
+    /// Qualifies the semantic definition of the element
+    #[default]
+    ConceptQualifier,
+    /// Only meaningful while the element is part of a template
+    TemplateQualifier,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[cfg_attr(feature = "wasm", tsify(missing_as_null))]
+pub struct Qualifier {
+    /// Qualifier type, e.g. "SMT/Cardinality"
+    #[serde(rename = "type")]
+    pub qualifier_type: String,
+    pub value_type: DataTypeDefXsd,
+    #[serde(default)]
+    pub value: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value_id: Option<Reference>,
+    #[serde(default)]
+    pub kind: QualifierKind,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_semantic_id",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub semantic_id: Option<Reference>,
+}
+
+/// First qualifier of the given type, e.g. `find_qualifier(&q, "SMT/Cardinality")`
+pub fn find_qualifier<'a>(
+    qualifiers: &'a [Qualifier],
+    qualifier_type: &str,
+) -> Option<&'a Qualifier> {
+    qualifiers
+        .iter()
+        .find(|q| q.qualifier_type == qualifier_type)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_qualifier_defaults() {
+        let qualifiers: Vec<Qualifier> = serde_json::from_str(
+            r#"[
+                {"type": "SMT/Cardinality", "value_type": "xs:string", "value": "ZeroToMany",
+                 "kind": "TemplateQualifier"},
+                {"type": "Multiplicity", "value_type": "xs:string", "value": "One"}
+            ]"#,
+        )
+        .unwrap();
+
+        let cardinality = find_qualifier(&qualifiers, "SMT/Cardinality").unwrap();
+        assert_eq!(cardinality.kind, QualifierKind::TemplateQualifier);
+        assert_eq!(qualifiers[1].kind, QualifierKind::ConceptQualifier);
+        assert!(find_qualifier(&qualifiers, "ExampleValue").is_none());
+
+        let json = serde_json::to_string(&qualifiers[0]).unwrap();
+        assert!(json.starts_with(r#"{"type":"SMT/Cardinality""#));
+    }
+}
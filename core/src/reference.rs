@@ -0,0 +1,146 @@
+// --- References between model elements ---
+// A Reference is a chain of keys, either pointing into the loaded model
+// (ModelReference) or at something outside of it (ExternalReference)
+
+use alloc::boxed::Box;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+
+use serde::{Deserialize, Deserializer, Serialize};
+#[cfg(feature = "wasm")]
+use tsify::Tsify;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+pub enum ReferenceTypes {
+    ExternalReference,
+    ModelReference,
+}
+
+/// What a single key in a reference chain points at
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+pub enum KeyTypes {
+    AnnotatedRelationshipElement,
+    AssetAdministrationShell,
+    BasicEventElement,
+    Blob,
+    Capability,
+    ConceptDescription,
+    DataElement,
+    Entity,
+    EventElement,
+    File,
+    FragmentReference,
+    GlobalReference,
+    Identifiable,
+    MultiLanguageProperty,
+    Operation,
+    Property,
+    Range,
+    Referable,
+    ReferenceElement,
+    RelationshipElement,
+    Submodel,
+    SubmodelElement,
+    SubmodelElementCollection,
+    SubmodelElementList,
+}
+
+impl KeyTypes {
+    /// Keys addressing an element inside a submodel (by idShort)
+    pub fn is_submodel_element(&self) -> bool {
+        !matches!(
+            self,
+            KeyTypes::AssetAdministrationShell
+                | KeyTypes::ConceptDescription
+                | KeyTypes::FragmentReference
+                | KeyTypes::GlobalReference
+                | KeyTypes::Identifiable
+                | KeyTypes::Submodel
+        )
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+pub struct Key {
+    #[serde(rename = "type")]
+    pub key_type: KeyTypes,
+    pub value: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+pub struct Reference {
+    #[serde(rename = "type")]
+    pub reference_type: ReferenceTypes,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub referred_semantic_id: Option<Box<Reference>>,
+    #[serde(default)]
+    pub keys: Vec<Key>,
+}
+
+impl Reference {
+    /// External reference to a single global identifier (e.g. an IRI or IRDI)
+    pub fn global(value: &str) -> Reference {
+        Reference {
+            reference_type: ReferenceTypes::ExternalReference,
+            referred_semantic_id: None,
+            keys: vec![Key {
+                key_type: KeyTypes::GlobalReference,
+                value: value.to_string(),
+            }],
+        }
+    }
+
+    /// Value of the last key, i.e. the identifier a semanticId stands for
+    pub fn key_value(&self) -> Option<&str> {
+        self.keys.last().map(|k| k.value.as_str())
+    }
+
+    /// Whether this (semantic) reference stands for the identifier `iri`
+    pub fn refers_to(&self, iri: &str) -> bool {
+        self.key_value() == Some(iri)
+    }
+}
+
+/// Read an optional semanticId given either as a full Reference or, as in
+/// older configurations, as a bare identifier string
+pub fn deserialize_semantic_id<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<Reference>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum SemanticId {
+        Iri(String),
+        Reference(Reference),
+    }
+
+    Ok(match Option::<SemanticId>::deserialize(deserializer)? {
+        Some(SemanticId::Iri(iri)) => Some(Reference::global(&iri)),
+        Some(SemanticId::Reference(r)) => Some(r),
+        None => None,
+    })
+}
+
+/// Text serialization from the AAS spec, e.g.
+/// `[ModelRef](Submodel)urn:example:sm:1, (Property)MaxTorque`
+impl fmt::Display for Reference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let prefix = match self.reference_type {
+            ReferenceTypes::ExternalReference => "[ExternalRef]",
+            ReferenceTypes::ModelReference => "[ModelRef]",
+        };
+        f.write_str(prefix)?;
+        for (i, key) in self.keys.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "({:?}){}", key.key_type, key.value)?;
+        }
+        Ok(())
+    }
+}
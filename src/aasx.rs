@@ -0,0 +1,472 @@
+// --- AASX packages ---
+// An .aasx file is an OPC (ZIP) container: `_rels/.rels` points at the
+// aasx-origin part, whose relationships point at the serialized environment
+// ("aas-spec"), whose relationships in turn list supplementary files such as
+// manuals and images ("aas-suppl"). Export writes the same layout back.
+
+use std::collections::BTreeMap;
+use std::io::{Cursor, Read, Write};
+
+use quick_xml::escape::escape;
+use quick_xml::events::Event;
+use quick_xml::{Reader, XmlVersion};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+use zip::write::SimpleFileOptions;
+use zip::{ZipArchive, ZipWriter};
+
+use crate::{
+    environment_from_json, environment_from_xml, environment_to_spec_json, select_shell,
+    DigitalTwin, ElementKind, Environment, TwinError,
+};
+
+pub const REL_AASX_ORIGIN: &str = "http://admin-shell.io/aasx/relationships/aasx-origin";
+pub const REL_AAS_SPEC: &str = "http://admin-shell.io/aasx/relationships/aas-spec";
+pub const REL_AAS_SUPPL: &str = "http://admin-shell.io/aasx/relationships/aas-suppl";
+pub const REL_THUMBNAIL: &str =
+    "http://schemas.openxmlformats.org/package/2006/relationships/metadata/thumbnail";
+
+/// Part names used when writing a package
+const ORIGIN_PART: &str = "/aasx/aasx-origin";
+const ENVIRONMENT_PART: &str = "/aasx/environment.aas.json";
+
+/// The content of an AASX package
+pub struct AasxPackage {
+    pub environment: Environment,
+    /// Supplementary files and the thumbnail, keyed by absolute part name
+    /// (e.g. "/aasx/files/manual.pdf", as used in File element values)
+    pub files: BTreeMap<String, Vec<u8>>,
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl DigitalTwin {
+    /// Hydrate the twin from an .aasx package (the first shell of its environment);
+    /// embedded files become available through `get_supplementary_file`
+    pub fn from_aasx(bytes: &[u8]) -> Result<DigitalTwin, TwinError> {
+        let package = read_aasx(bytes).map_err(TwinError::parse)?;
+        let mut twin = DigitalTwin::from_shell(select_shell(package.environment, None)?)?;
+        twin.supplementary_files = package.files;
+        Ok(twin)
+    }
+
+    /// Export the twin as an .aasx package (a `Uint8Array` in JS) that AASX
+    /// Package Explorer can open, including all supplementary files
+    pub fn to_aasx(&self) -> Result<Vec<u8>, TwinError> {
+        let package = AasxPackage {
+            environment: Environment::from_shell(&self.data),
+            files: self.supplementary_files.clone(),
+        };
+        let content_types: BTreeMap<String, String> = self
+            .file_entries()
+            .into_iter()
+            .filter_map(|f| Some((normalize_part_name(&f.value?), f.content_type?)))
+            .collect();
+        let thumbnail = self
+            .data
+            .asset_information
+            .default_thumbnail
+            .as_ref()
+            .map(|r| normalize_part_name(&r.path));
+        write_aasx(&package, &content_types, thumbnail.as_deref())
+            .map_err(|e| TwinError::failed(None, e))
+    }
+
+    /// List the part names of the files that came with the AASX package as a JSON array
+    pub fn list_supplementary_files(&self) -> String {
+        serde_json::to_string(&self.supplementary_files.keys().collect::<Vec<_>>())
+            .unwrap_or_else(|_| "[]".to_string())
+    }
+
+    /// Get a file from the AASX package by part name (e.g. "/aasx/files/manual.pdf")
+    pub fn get_supplementary_file(&self, part_name: &str) -> Option<Vec<u8>> {
+        self.supplementary_files
+            .get(&normalize_part_name(part_name))
+            .cloned()
+    }
+
+    /// Get the packaged content of the File element at `path`, or `undefined`
+    /// if it points outside the package
+    pub fn get_file_content(&self, path: &str) -> Result<Option<Vec<u8>>, TwinError> {
+        match &self.find_element(path)?.kind {
+            ElementKind::File(f) => Ok(f
+                .value
+                .as_deref()
+                .and_then(|v| self.get_supplementary_file(v))),
+            other => Err(TwinError::type_mismatch(
+                path,
+                format!("Element '{}' is a {}, not a File", path, other.model_type()),
+            )),
+        }
+    }
+}
+
+/// A relationship from a `.rels` part
+struct Relationship {
+    rel_type: String,
+    target: String,
+}
+
+/// Unpack an AASX package and parse its environment
+pub fn read_aasx(bytes: &[u8]) -> Result<AasxPackage, String> {
+    let mut archive =
+        ZipArchive::new(Cursor::new(bytes)).map_err(|e| format!("Invalid AASX package: {}", e))?;
+    let mut read_part = |name: &str| -> Result<Option<Vec<u8>>, String> {
+        let mut file = match archive.by_name(name.trim_start_matches('/')) {
+            Ok(file) => file,
+            Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+            Err(e) => return Err(format!("Cannot read '{}': {}", name, e)),
+        };
+        let mut buf = Vec::new();
+        file.read_to_end(&mut buf)
+            .map_err(|e| format!("Cannot read '{}': {}", name, e))?;
+        Ok(Some(buf))
+    };
+
+    let root_rels = read_relationships(&mut read_part, "/")?;
+    let origin = root_rels
+        .iter()
+        .find(|r| r.rel_type == REL_AASX_ORIGIN)
+        .map(|r| resolve_target("/", &r.target))
+        .ok_or("AASX package has no aasx-origin relationship")?;
+    let specs: Vec<String> = read_relationships(&mut read_part, &origin)?
+        .iter()
+        .filter(|r| r.rel_type == REL_AAS_SPEC)
+        .map(|r| resolve_target(&origin, &r.target))
+        .collect();
+    let spec = specs
+        .first()
+        .ok_or("AASX origin does not reference an environment (aas-spec)")?;
+
+    let content =
+        read_part(spec)?.ok_or_else(|| format!("Environment part '{}' is missing", spec))?;
+    let environment = if spec.ends_with(".xml") {
+        let xml = String::from_utf8(content)
+            .map_err(|e| format!("Invalid AAS environment XML in '{}': {}", spec, e))?;
+        environment_from_xml(&xml)
+            .map_err(|e| format!("Invalid AAS environment XML in '{}': {}", spec, e))?
+    } else {
+        environment_from_json(&content).map_err(|e| format!("{} in '{}'", e, spec))?
+    };
+
+    let mut files = BTreeMap::new();
+    let suppl = read_relationships(&mut read_part, spec)?
+        .into_iter()
+        .filter(|r| r.rel_type == REL_AAS_SUPPL)
+        .map(|r| resolve_target(spec, &r.target));
+    let thumbnail = root_rels
+        .iter()
+        .filter(|r| r.rel_type == REL_THUMBNAIL)
+        .map(|r| resolve_target("/", &r.target));
+    for name in suppl.chain(thumbnail).collect::<Vec<_>>() {
+        if let Some(content) = read_part(&name)? {
+            files.insert(name, content);
+        }
+    }
+
+    Ok(AasxPackage { environment, files })
+}
+
+/// Pack an environment and its files into an AASX package. `content_types`
+/// maps part names to MIME types; other files are typed by extension.
+/// `thumbnail` names the file (if packaged) to register as the package thumbnail.
+pub fn write_aasx(
+    package: &AasxPackage,
+    content_types: &BTreeMap<String, String>,
+    thumbnail: Option<&str>,
+) -> Result<Vec<u8>, String> {
+    let environment = serde_json::to_vec_pretty(&environment_to_spec_json(&package.environment))
+        .map_err(|e| format!("Cannot serialize environment: {}", e))?;
+    let thumbnail = thumbnail.filter(|t| package.files.contains_key(*t));
+    let suppl: Vec<&String> = package
+        .files
+        .keys()
+        .filter(|name| Some(name.as_str()) != thumbnail)
+        .collect();
+
+    let mut overrides = vec![(ORIGIN_PART.to_string(), "text/plain".to_string())];
+    for name in package.files.keys() {
+        let content_type = content_types
+            .get(name)
+            .cloned()
+            .unwrap_or_else(|| content_type_for(name).to_string());
+        overrides.push((name.clone(), content_type));
+    }
+    let content_types_xml = format!(
+        concat!(
+            r#"<?xml version="1.0" encoding="utf-8"?>"#,
+            r#"<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types">"#,
+            r#"<Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/>"#,
+            r#"<Default Extension="json" ContentType="application/json"/>"#,
+            "{}</Types>"
+        ),
+        overrides
+            .iter()
+            .map(|(name, t)| format!(
+                r#"<Override PartName="{}" ContentType="{}"/>"#,
+                escape(name.as_str()),
+                escape(t.as_str())
+            ))
+            .collect::<String>()
+    );
+
+    let mut root_rels = vec![(REL_AASX_ORIGIN, ORIGIN_PART)];
+    root_rels.extend(thumbnail.map(|t| (REL_THUMBNAIL, t)));
+    let suppl_rels: Vec<_> = suppl.iter().map(|n| (REL_AAS_SUPPL, n.as_str())).collect();
+    let parts: Vec<(String, Vec<u8>)> = vec![
+        (
+            "/[Content_Types].xml".to_string(),
+            content_types_xml.into_bytes(),
+        ),
+        (
+            relationships_part("/"),
+            relationships_xml(&root_rels).into_bytes(),
+        ),
+        (ORIGIN_PART.to_string(), b"Intentionally empty.".to_vec()),
+        (
+            relationships_part(ORIGIN_PART),
+            relationships_xml(&[(REL_AAS_SPEC, ENVIRONMENT_PART)]).into_bytes(),
+        ),
+        (ENVIRONMENT_PART.to_string(), environment),
+        (
+            relationships_part(ENVIRONMENT_PART),
+            relationships_xml(&suppl_rels).into_bytes(),
+        ),
+    ];
+
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    let files = package.files.iter().map(|(n, c)| (n, c.as_slice()));
+    for (name, content) in parts.iter().map(|(n, c)| (n, c.as_slice())).chain(files) {
+        zip.start_file(name.trim_start_matches('/'), SimpleFileOptions::default())
+            .and_then(|_| zip.write_all(content).map_err(Into::into))
+            .map_err(|e| format!("Cannot write '{}': {}", name, e))?;
+    }
+    let cursor = zip
+        .finish()
+        .map_err(|e| format!("Cannot write AASX package: {}", e))?;
+    Ok(cursor.into_inner())
+}
+
+fn relationships_xml(relationships: &[(&str, &str)]) -> String {
+    let body: String = relationships
+        .iter()
+        .enumerate()
+        .map(|(i, (rel_type, target))| {
+            format!(
+                r#"<Relationship Type="{}" Target="{}" Id="R{}"/>"#,
+                escape(*rel_type),
+                escape(*target),
+                i
+            )
+        })
+        .collect();
+    format!(
+        concat!(
+            r#"<?xml version="1.0" encoding="utf-8"?>"#,
+            r#"<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">"#,
+            "{}</Relationships>"
+        ),
+        body
+    )
+}
+
+/// MIME type of a supplementary file without a declared contentType
+fn content_type_for(name: &str) -> &'static str {
+    let extension = name.rsplit_once('.').map(|(_, e)| e.to_ascii_lowercase());
+    match extension.as_deref() {
+        Some("pdf") => "application/pdf",
+        Some("png") => "image/png",
+        Some("jpg" | "jpeg") => "image/jpeg",
+        Some("svg") => "image/svg+xml",
+        Some("txt") => "text/plain",
+        Some("json") => "application/json",
+        Some("xml") => "application/xml",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Part name of the relationships of `part` ("/aasx/x.json" -> "/aasx/_rels/x.json.rels")
+pub fn relationships_part(part: &str) -> String {
+    let (dir, file) = part.rsplit_once('/').unwrap_or(("", part));
+    format!("{}/_rels/{}.rels", dir, file)
+}
+
+fn read_relationships(
+    read_part: &mut impl FnMut(&str) -> Result<Option<Vec<u8>>, String>,
+    part: &str,
+) -> Result<Vec<Relationship>, String> {
+    let name = relationships_part(part);
+    let Some(content) = read_part(&name)? else {
+        return Ok(Vec::new());
+    };
+    let xml = String::from_utf8(content).map_err(|e| format!("Invalid '{}': {}", name, e))?;
+    let mut reader = Reader::from_str(&xml);
+    let mut relationships = Vec::new();
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e) | Event::Empty(e)) if e.local_name().as_ref() == "Relationship" => {
+                let (mut rel_type, mut target) = (None, None);
+                for attr in e.attributes().flatten() {
+                    let value = attr
+                        .normalized_value(XmlVersion::Implicit1_0)
+                        .map_err(|e| format!("Invalid '{}': {}", name, e))?
+                        .into_owned();
+                    match attr.key.as_ref() {
+                        "Type" => rel_type = Some(value),
+                        "Target" => target = Some(value),
+                        _ => {}
+                    }
+                }
+                if let (Some(rel_type), Some(target)) = (rel_type, target) {
+                    relationships.push(Relationship { rel_type, target });
+                }
+            }
+            Ok(Event::Eof) => break,
+            Ok(_) => {}
+            Err(e) => return Err(format!("Invalid '{}': {}", name, e)),
+        }
+    }
+    Ok(relationships)
+}
+
+/// Resolve a relationship target against the part the relationship belongs to
+fn resolve_target(source: &str, target: &str) -> String {
+    if target.starts_with('/') {
+        return normalize_part_name(target);
+    }
+    let dir = source.rsplit_once('/').map(|(d, _)| d).unwrap_or("");
+    normalize_part_name(&format!("{}/{}", dir, target))
+}
+
+/// Absolute part name without "." and ".." segments
+fn normalize_part_name(name: &str) -> String {
+    let mut segments: Vec<&str> = Vec::new();
+    for segment in name.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop();
+            }
+            s => segments.push(s),
+        }
+    }
+    format!("/{}", segments.join("/"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn package(parts: &[(&str, &str)]) -> Vec<u8> {
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, content) in parts {
+            zip.start_file(*name, SimpleFileOptions::default()).unwrap();
+            zip.write_all(content.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn test_read_aasx() {
+        let env = r#"{
+            "asset_administration_shells": [{"id": "urn:example:aas:motor",
+                "asset_information": {"asset_kind": "Instance"},
+                "submodels": [{"type": "ModelReference",
+                               "keys": [{"type": "Submodel", "value": "urn:example:sm:doc"}]}]}],
+            "submodels": [{"id": "urn:example:sm:doc", "id_short": "Documentation",
+                "submodel_elements": [{"id_short": "Manual", "model_type": "File",
+                                       "value": "/aasx/files/manual.pdf",
+                                       "content_type": "application/pdf"}]}]
+        }"#;
+        let bytes = package(&[
+            (
+                "_rels/.rels",
+                &relationships_xml(&[(REL_AASX_ORIGIN, "/aasx/aasx-origin")]),
+            ),
+            ("aasx/aasx-origin", ""),
+            (
+                "aasx/_rels/aasx-origin.rels",
+                &relationships_xml(&[(REL_AAS_SPEC, "motor/motor.aas.json")]),
+            ),
+            ("aasx/motor/motor.aas.json", env),
+            (
+                "aasx/motor/_rels/motor.aas.json.rels",
+                &relationships_xml(&[(REL_AAS_SUPPL, "../files/manual.pdf")]),
+            ),
+            ("aasx/files/manual.pdf", "%PDF-1.7"),
+        ]);
+
+        let twin = DigitalTwin::from_aasx(&bytes).unwrap();
+        assert_eq!(twin.get_id(), "urn:example:aas:motor");
+        assert_eq!(
+            twin.list_supplementary_files(),
+            r#"["/aasx/files/manual.pdf"]"#
+        );
+        assert_eq!(
+            twin.get_file_content("Documentation.Manual")
+                .unwrap()
+                .unwrap(),
+            b"%PDF-1.7"
+        );
+    }
+
+    #[test]
+    fn test_aasx_round_trip() {
+        let json = r#"{
+            "id": "MOTOR-12345",
+            "asset_information": {"asset_kind": "Instance", "asset_type": "Siemens 1LE1",
+                                  "default_thumbnail": {"path": "/aasx/thumbnail.png"}},
+            "nameplate": [{"id_short": "Voltage", "value": "400", "unit": "V"}],
+            "submodels": [{"id": "urn:example:sm:doc", "id_short": "Documentation",
+                "submodel_elements": [{"id_short": "Manual", "model_type": "File",
+                                       "value": "/aasx/files/manual.pdf",
+                                       "content_type": "application/pdf"}]}]
+        }"#;
+        let mut twin = DigitalTwin::new(json).unwrap();
+        twin.supplementary_files
+            .insert("/aasx/files/manual.pdf".to_string(), b"%PDF-1.7".to_vec());
+        twin.supplementary_files
+            .insert("/aasx/thumbnail.png".to_string(), b"PNG".to_vec());
+
+        let bytes = twin.to_aasx().unwrap();
+        let mut archive = ZipArchive::new(Cursor::new(bytes.as_slice())).unwrap();
+        let mut content_types = String::new();
+        archive
+            .by_name("[Content_Types].xml")
+            .unwrap()
+            .read_to_string(&mut content_types)
+            .unwrap();
+        assert!(content_types
+            .contains(r#"PartName="/aasx/files/manual.pdf" ContentType="application/pdf""#));
+
+        let back = DigitalTwin::from_aasx(&bytes).unwrap();
+        assert_eq!(back.get_property_display("Nameplate.Voltage"), "400 V");
+        assert_eq!(
+            back.list_supplementary_files(),
+            r#"["/aasx/files/manual.pdf","/aasx/thumbnail.png"]"#
+        );
+        assert_eq!(
+            back.get_file_content("Documentation.Manual")
+                .unwrap()
+                .unwrap(),
+            b"%PDF-1.7"
+        );
+    }
+
+    #[test]
+    fn test_missing_origin() {
+        let bytes = package(&[("aasx/env.json", "{}")]);
+        let err = read_aasx(&bytes).err().unwrap();
+        assert!(err.contains("aasx-origin"));
+        assert!(read_aasx(b"not a zip").is_err());
+    }
+
+    #[test]
+    fn test_part_names() {
+        assert_eq!(resolve_target("/aasx/a/b.json", "../c.pdf"), "/aasx/c.pdf");
+        assert_eq!(
+            relationships_part("/aasx/aasx-origin"),
+            "/aasx/_rels/aasx-origin.rels"
+        );
+        assert_eq!(relationships_part("/"), "/_rels/.rels");
+    }
+}
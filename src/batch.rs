@@ -0,0 +1,175 @@
+// --- Batch updates ---
+// Telemetry feeds update hundreds of properties per second; crossing the WASM
+// boundary once per value is the bottleneck. A batch carries many values in
+// one call and is all-or-nothing: if any update is rejected, the values set
+// before it are put back and the report lists every rejected update.
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+#[cfg(feature = "wasm")]
+use tsify::Tsify;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{DigitalTwin, ElementKind, TwinError};
+
+/// Outcome of a batch of property updates
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+pub struct BatchReport {
+    /// Whether the batch was applied; when false the twin is unchanged
+    pub applied: bool,
+    /// Number of updates in the batch
+    pub count: usize,
+    pub failures: Vec<BatchFailure>,
+}
+
+/// One rejected update of a batch
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+pub struct BatchFailure {
+    pub path: String,
+    /// The `TwinError` code, e.g. "TYPE_MISMATCH"
+    pub code: String,
+    pub message: String,
+}
+
+impl BatchFailure {
+    pub(crate) fn new(path: &str, error: TwinError) -> BatchFailure {
+        BatchFailure {
+            path: path.to_string(),
+            code: error.code().to_string(),
+            message: error.message().to_string(),
+        }
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl DigitalTwin {
+    /// Set many property values at once from a JSON object of paths to
+    /// values (`{"Voltage": 230, "Nameplate.Running": true}`); returns the
+    /// `BatchReport` as JSON. Nothing is changed unless every update succeeds.
+    pub fn set_properties(&mut self, updates_json: &str) -> Result<String, TwinError> {
+        let updates: Map<String, Value> = serde_json::from_str(updates_json)
+            .map_err(|e| TwinError::parse(format!("Invalid property updates: {}", e)))?;
+        let report = self.update_properties(&updates);
+        Ok(serde_json::to_string(&report).unwrap_or_else(|_| "{}".to_string()))
+    }
+}
+
+impl DigitalTwin {
+    /// Rust-side `set_properties`; numbers and booleans are set in their
+    /// textual form
+    pub fn update_properties(&mut self, updates: &Map<String, Value>) -> BatchReport {
+        self.tracked(|twin| twin.apply_updates(updates))
+    }
+
+    fn apply_updates(&mut self, updates: &Map<String, Value>) -> BatchReport {
+        let mut previous = Vec::new();
+        let mut failures = Vec::new();
+        for (path, value) in updates {
+            let value = match value {
+                Value::String(s) => s.clone(),
+                Value::Number(_) | Value::Bool(_) => value.to_string(),
+                other => {
+                    failures.push(BatchFailure::new(
+                        path,
+                        TwinError::invalid_argument(format!(
+                            "Value for '{}' must be a string, number or boolean, got {}",
+                            path, other
+                        )),
+                    ));
+                    continue;
+                }
+            };
+            let element = match self.find_element_mut(path) {
+                Ok(element) => element,
+                Err(e) => {
+                    failures.push(BatchFailure::new(path, e));
+                    continue;
+                }
+            };
+            let old = match &element.kind {
+                ElementKind::Property(p) => p.value.clone(),
+                _ => String::new(),
+            };
+            match element.set_value(&value) {
+                Ok(()) => previous.push((path, old)),
+                Err(e) => failures.push(BatchFailure::new(path, TwinError::type_mismatch(path, e))),
+            }
+        }
+
+        let applied = failures.is_empty();
+        if !applied {
+            for (path, old) in previous.into_iter().rev() {
+                if let Ok(element) = self.find_element_mut(path) {
+                    if let ElementKind::Property(p) = &mut element.kind {
+                        p.value = old;
+                    }
+                }
+            }
+        }
+        BatchReport {
+            applied,
+            count: updates.len(),
+            failures,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const CONFIG: &str = r#"{
+        "id": "urn:example:aas:motor",
+        "asset_type": "Siemens 1LE1",
+        "nameplate": [
+            {"id_short": "Voltage", "value": "400", "unit": "V", "value_type": "xs:double"},
+            {"id_short": "Running", "value": "false", "value_type": "xs:boolean"},
+            {"id_short": "Maker", "value": "Siemens"}
+        ]
+    }"#;
+
+    #[test]
+    fn test_batch_update_applies_all() {
+        let mut twin = DigitalTwin::new(CONFIG).unwrap();
+        let report: Value = serde_json::from_str(
+            &twin
+                .set_properties(r#"{"Voltage": 230, "Running": true, "Maker": "ABB"}"#)
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(report, json!({"applied": true, "count": 3, "failures": []}));
+        assert_eq!(twin.get_property_display("Voltage"), "230 V");
+        assert_eq!(twin.get_property_display("Running"), "true ");
+        assert_eq!(twin.get_property_display("Maker"), "ABB ");
+    }
+
+    #[test]
+    fn test_batch_update_is_all_or_nothing() {
+        let mut twin = DigitalTwin::new(CONFIG).unwrap();
+        let updates = json!({"Maker": "ABB", "Missing": 1, "Running": "maybe", "Voltage": [1]});
+        let report = twin.update_properties(updates.as_object().unwrap());
+        assert!(!report.applied);
+        assert_eq!(report.count, 4);
+        let failed: Vec<(&str, &str)> = report
+            .failures
+            .iter()
+            .map(|f| (f.path.as_str(), f.code.as_str()))
+            .collect();
+        assert_eq!(
+            failed,
+            [
+                ("Missing", "NOT_FOUND"),
+                ("Running", "TYPE_MISMATCH"),
+                ("Voltage", "INVALID_ARGUMENT")
+            ]
+        );
+        assert_eq!(twin.get_property_display("Maker"), "Siemens ");
+        assert!(twin
+            .set_properties("[1]")
+            .is_err_and(|e| e.code() == "PARSE_ERROR"));
+    }
+}
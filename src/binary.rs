@@ -0,0 +1,203 @@
+// --- Binary encodings ---
+// Compact alternatives to the JSON strings for shipping twin state between
+// workers and to edge gateways. They carry the shell (in the legacy layout)
+// together with the simulation state, so a twin restored from them resumes
+// where the original left off. Model and tick outputs can be requested in
+// any of the wire formats per call.
+
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::simulation::MOTOR_RPM;
+use crate::{AssetAdministrationShell, DigitalTwin, SimulationValues, TwinError};
+
+/// Everything needed to recreate a twin elsewhere
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TwinState {
+    pub shell: AssetAdministrationShell,
+    pub rpm: f64,
+    pub tick_count: u32,
+    /// Outputs of all simulation models; `rpm` repeats the motor speed for
+    /// readers of earlier states
+    #[serde(default)]
+    pub simulation: SimulationValues,
+}
+
+impl DigitalTwin {
+    /// Snapshot of the model and simulation state
+    pub fn state(&self) -> TwinState {
+        TwinState {
+            shell: self.data.clone(),
+            rpm: self
+                .simulation_values()
+                .get(MOTOR_RPM)
+                .copied()
+                .unwrap_or(0.0),
+            tick_count: self.tick_count,
+            simulation: self.simulation_values().clone(),
+        }
+    }
+
+    /// Recreate a twin from a snapshot; handlers and listeners are not part of it
+    pub fn from_state(state: TwinState) -> Result<DigitalTwin, TwinError> {
+        let mut twin = DigitalTwin::from_shell(state.shell)?;
+        let mut values = state.simulation;
+        values.entry(MOTOR_RPM.to_string()).or_insert(state.rpm);
+        twin.resume_models(&values, state.tick_count as f64);
+        twin.tick_count = state.tick_count;
+        Ok(twin)
+    }
+}
+
+/// Output of one simulation tick
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct TickSample {
+    pub tick: u32,
+    pub rpm: f64,
+}
+
+/// Encoding of binary-capable outputs
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WireFormat {
+    /// UTF-8 JSON, e.g. for logging
+    Json,
+    /// MessagePack with field names, so maps keep their keys
+    MessagePack,
+    Cbor,
+}
+
+/// Serialize a value in the given wire format
+pub fn encode<T: Serialize>(value: &T, format: WireFormat) -> Result<Vec<u8>, String> {
+    match format {
+        WireFormat::Json => {
+            serde_json::to_vec(value).map_err(|e| format!("Cannot encode JSON: {}", e))
+        }
+        WireFormat::MessagePack => {
+            rmp_serde::to_vec_named(value).map_err(|e| format!("Cannot encode MessagePack: {}", e))
+        }
+        WireFormat::Cbor => {
+            let mut out = Vec::new();
+            ciborium::into_writer(value, &mut out)
+                .map_err(|e| format!("Cannot encode CBOR: {}", e))?;
+            Ok(out)
+        }
+    }
+}
+
+/// Deserialize a value written by `encode` with the same format
+pub fn decode<T: for<'de> Deserialize<'de>>(bytes: &[u8], format: WireFormat) -> Result<T, String> {
+    match format {
+        WireFormat::Json => {
+            serde_json::from_slice(bytes).map_err(|e| format!("Invalid JSON: {}", e))
+        }
+        WireFormat::MessagePack => {
+            rmp_serde::from_slice(bytes).map_err(|e| format!("Invalid MessagePack: {}", e))
+        }
+        WireFormat::Cbor => {
+            ciborium::from_reader(bytes).map_err(|e| format!("Invalid CBOR: {}", e))
+        }
+    }
+}
+
+/// Encode twin state as CBOR (RFC 8949)
+pub fn state_to_cbor(state: &TwinState) -> Result<Vec<u8>, String> {
+    encode(state, WireFormat::Cbor)
+}
+
+/// Decode twin state written by `state_to_cbor`
+pub fn state_from_cbor(bytes: &[u8]) -> Result<TwinState, String> {
+    decode(bytes, WireFormat::Cbor).map_err(|e| format!("Invalid twin state: {}", e))
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl DigitalTwin {
+    /// Encode the twin (model and simulation state) as CBOR
+    pub fn to_cbor(&self) -> Result<Vec<u8>, TwinError> {
+        state_to_cbor(&self.state()).map_err(|e| TwinError::failed(None, e))
+    }
+
+    /// Recreate a twin from `to_cbor` output
+    pub fn from_cbor(bytes: &[u8]) -> Result<DigitalTwin, TwinError> {
+        DigitalTwin::from_state(state_from_cbor(bytes).map_err(TwinError::parse)?)
+    }
+
+    /// Encode the twin (model and simulation state) as MessagePack
+    pub fn to_msgpack(&self) -> Result<Vec<u8>, TwinError> {
+        encode(&self.state(), WireFormat::MessagePack).map_err(|e| TwinError::failed(None, e))
+    }
+
+    /// Recreate a twin from `to_msgpack` output
+    pub fn from_msgpack(bytes: &[u8]) -> Result<DigitalTwin, TwinError> {
+        let state = decode(bytes, WireFormat::MessagePack)
+            .map_err(|e| TwinError::parse(format!("Invalid twin state: {}", e)))?;
+        DigitalTwin::from_state(state)
+    }
+
+    /// The shell as `get_aas_json` returns it, in the given wire format
+    pub fn get_aas_encoded(&self, format: WireFormat) -> Result<Vec<u8>, TwinError> {
+        encode(&self.data, format).map_err(|e| TwinError::failed(None, e))
+    }
+
+    /// Advance the simulation like `tick_simulation` and return the sample
+    /// (`{tick, rpm}`) in the given wire format
+    pub fn tick_simulation_encoded(&mut self, format: WireFormat) -> Result<Vec<u8>, TwinError> {
+        encode(&self.advance_simulation(), format).map_err(|e| TwinError::failed(None, e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cbor_round_trip() {
+        let json = r#"{
+            "id": "urn:example:aas:motor",
+            "asset_type": "Siemens 1LE1",
+            "nameplate": [
+                {"id_short": "Voltage", "value": "400", "unit": "V", "value_type": "xs:double"},
+                {"id_short": "Firmware", "model_type": "Blob", "content_type": "application/octet-stream", "value": "AAEC"}
+            ]
+        }"#;
+        let mut twin = DigitalTwin::new(json).unwrap();
+        twin.tick_simulation();
+
+        let bytes = state_to_cbor(&twin.state()).unwrap();
+        assert!(bytes.len() < serde_json::to_vec(&twin.state()).unwrap().len());
+
+        let restored = DigitalTwin::from_state(state_from_cbor(&bytes).unwrap()).unwrap();
+        assert_eq!(restored.get_property_display("Voltage"), "400 V");
+        assert_eq!(restored.get_aas_json(), twin.get_aas_json());
+        assert_eq!(restored.tick_count, 1);
+        assert_eq!(restored.simulation_values(), twin.simulation_values());
+        assert!(state_from_cbor(&[0xff]).is_err());
+    }
+
+    #[test]
+    fn test_wire_formats() {
+        let json = r#"{"id": "M-1", "asset_type": "Pump", "nameplate": [
+            {"id_short": "Voltage", "value": "400", "unit": "V"}
+        ]}"#;
+        let mut twin = DigitalTwin::new(json).unwrap();
+
+        let packed = encode(&twin.data, WireFormat::MessagePack).unwrap();
+        assert!(packed.len() < twin.get_aas_json().len());
+        let shell: AssetAdministrationShell = decode(&packed, WireFormat::MessagePack).unwrap();
+        assert_eq!(shell.nameplate[0].display_value(), "400 V");
+
+        let state: TwinState = decode(
+            &encode(&twin.state(), WireFormat::MessagePack).unwrap(),
+            WireFormat::MessagePack,
+        )
+        .unwrap();
+        assert_eq!(state.shell.id, "M-1");
+
+        let sample = twin.advance_simulation();
+        for format in [WireFormat::Json, WireFormat::MessagePack, WireFormat::Cbor] {
+            let bytes = encode(&sample, format).unwrap();
+            assert_eq!(decode::<TickSample>(&bytes, format).unwrap(), sample);
+        }
+    }
+}
@@ -0,0 +1,190 @@
+// --- Programmatic construction ---
+// A chainable builder for twins, so demos and tests do not need hand-written
+// JSON. Properties go to the nameplate until `submodel` switches the target.
+// Mistakes (an unknown valueType, a duplicate idShort) are collected and
+// reported by `build`, which keeps the chain free of error handling.
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    AssetAdministrationShell, AssetInformation, DataTypeDefXsd, DigitalTwin, ElementKind, Property,
+    Submodel, SubmodelElement, TwinError,
+};
+
+/// Builder for a `DigitalTwin`:
+/// `TwinBuilder::new(id).asset_type("...").property("Voltage", "400", "V").build()`
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub struct TwinBuilder {
+    shell: AssetAdministrationShell,
+    /// Index of the submodel receiving properties; `None` for the nameplate
+    target: Option<usize>,
+    error: Option<String>,
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl TwinBuilder {
+    #[cfg_attr(feature = "wasm", wasm_bindgen(constructor))]
+    pub fn new(id: &str) -> TwinBuilder {
+        TwinBuilder {
+            shell: AssetAdministrationShell {
+                id: id.to_string(),
+                display_name: Vec::new(),
+                description: Vec::new(),
+                asset_information: AssetInformation::default(),
+                administration: None,
+                nameplate: Vec::new(),
+                submodels: Vec::new(),
+                extensions: Vec::new(),
+                concept_descriptions: Default::default(),
+            },
+            target: None,
+            error: None,
+        }
+    }
+
+    /// Manufacturer and model, e.g. "Siemens 1LE1"
+    pub fn asset_type(mut self, asset_type: &str) -> TwinBuilder {
+        self.shell.asset_information.asset_type = Some(asset_type.to_string());
+        self
+    }
+
+    pub fn global_asset_id(mut self, global_asset_id: &str) -> TwinBuilder {
+        self.shell.asset_information.global_asset_id = Some(global_asset_id.to_string());
+        self
+    }
+
+    /// Add a property to the current target; an empty unit means none
+    pub fn property(self, id_short: &str, value: &str, unit: &str) -> TwinBuilder {
+        let property = Property {
+            value: value.to_string(),
+            unit: (!unit.is_empty()).then(|| unit.to_string()),
+            value_type: None,
+        };
+        self.element(SubmodelElement::new(
+            id_short,
+            ElementKind::Property(property),
+        ))
+    }
+
+    /// Declare the valueType (e.g. "xs:double") of the property added last
+    pub fn value_type(mut self, value_type: &str) -> TwinBuilder {
+        let parsed: Result<DataTypeDefXsd, _> =
+            serde_json::from_value(serde_json::Value::String(value_type.to_string()));
+        let Ok(parsed) = parsed else {
+            return self.fail(format!("Unknown valueType '{}'", value_type));
+        };
+        match self.elements().last_mut().map(|e| &mut e.kind) {
+            Some(ElementKind::Property(p)) => p.value_type = Some(parsed),
+            _ => return self.fail(format!("valueType '{}' without a property", value_type)),
+        }
+        self
+    }
+
+    /// Start a submodel (id defaults to `{shell id}/submodels/{idShort}`);
+    /// the following properties go into it
+    pub fn submodel(mut self, id_short: &str, id: Option<String>) -> TwinBuilder {
+        if self.shell.submodels.iter().any(|s| s.id_short == id_short) {
+            return self.fail(format!("Duplicate submodel '{}'", id_short));
+        }
+        self.shell.submodels.push(Submodel {
+            id: id.unwrap_or_else(|| format!("{}/submodels/{}", self.shell.id, id_short)),
+            id_short: id_short.to_string(),
+            display_name: Vec::new(),
+            description: Vec::new(),
+            administration: None,
+            semantic_id: None,
+            qualifiers: Vec::new(),
+            extensions: Vec::new(),
+            kind: Default::default(),
+            submodel_elements: Vec::new(),
+        });
+        self.target = Some(self.shell.submodels.len() - 1);
+        self
+    }
+
+    /// Send the following properties to the nameplate again
+    pub fn nameplate(mut self) -> TwinBuilder {
+        self.target = None;
+        self
+    }
+
+    /// The twin, or the first mistake made while building it
+    pub fn build(self) -> Result<DigitalTwin, TwinError> {
+        if let Some(error) = self.error {
+            return Err(TwinError::invalid_argument(error));
+        }
+        DigitalTwin::from_shell(self.shell)
+    }
+}
+
+impl TwinBuilder {
+    /// Add any element to the current target
+    pub fn element(mut self, element: SubmodelElement) -> TwinBuilder {
+        if self
+            .elements()
+            .iter()
+            .any(|e| e.id_short == element.id_short)
+        {
+            return self.fail(format!("Duplicate idShort '{}'", element.id_short));
+        }
+        self.elements().push(element);
+        self
+    }
+
+    fn elements(&mut self) -> &mut Vec<SubmodelElement> {
+        match self.target {
+            Some(i) => &mut self.shell.submodels[i].submodel_elements,
+            None => &mut self.shell.nameplate,
+        }
+    }
+
+    fn fail(mut self, error: String) -> TwinBuilder {
+        self.error.get_or_insert(error);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builder() {
+        let twin = TwinBuilder::new("MOTOR-12345")
+            .asset_type("Siemens 1LE1")
+            .property("Voltage", "400", "V")
+            .value_type("xs:double")
+            .submodel("OperationalData", None)
+            .property("Speed", "1450", "rpm")
+            .nameplate()
+            .property("SerialNumber", "XYZ-001", "")
+            .build()
+            .unwrap();
+
+        assert_eq!(twin.get_asset_type(), "Siemens 1LE1");
+        assert_eq!(twin.get_property_f64("Voltage").unwrap(), 400.0);
+        assert_eq!(
+            twin.get_property_display("OperationalData.Speed"),
+            "1450 rpm"
+        );
+        assert_eq!(twin.get_property_display("SerialNumber"), "XYZ-001 ");
+        assert_eq!(
+            twin.data.submodels[0].id,
+            "MOTOR-12345/submodels/OperationalData"
+        );
+    }
+
+    #[test]
+    fn test_builder_reports_the_first_mistake() {
+        let err = TwinBuilder::new("M")
+            .property("Voltage", "400", "V")
+            .value_type("xs:float")
+            .property("Voltage", "230", "V")
+            .build()
+            .err()
+            .unwrap();
+        assert_eq!(err.code(), "INVALID_ARGUMENT");
+        assert_eq!(err.message(), "Unknown valueType 'xs:float'");
+    }
+}
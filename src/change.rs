@@ -0,0 +1,237 @@
+// --- Change notifications ---
+// Listeners subscribe to property values by path pattern and are told about
+// every value that set_property, batch updates, patches, restores and
+// simulation ticks change. Changes are found by comparing the matching values
+// before and after an edit, so no edit path can forget to report one; without
+// listeners nothing is compared.
+
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+#[cfg(feature = "wasm")]
+use tsify::Tsify;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{DigitalTwin, ElementKind};
+
+/// Path under which simulation ticks report the simulated speed; it is not
+/// an element of the model. Outputs of other simulation models are reported
+/// as "Simulation.<model>.<output>" alike.
+pub const SIMULATION_RPM_PATH: &str = "Simulation.motor.rpm";
+
+/// One changed property value
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[cfg_attr(feature = "wasm", tsify(missing_as_null))]
+pub struct PropertyChange {
+    /// Path as listed by `list_properties`
+    pub path: String,
+    /// None when the property was added
+    pub old_value: Option<String>,
+    /// None when the property was removed
+    pub new_value: Option<String>,
+}
+
+/// Receives the changes to the values matching its pattern
+pub type ChangeListener = Box<dyn Fn(&PropertyChange)>;
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl DigitalTwin {
+    /// Register a JavaScript function called with a `{path, old_value,
+    /// new_value}` object for every change of a property matching
+    /// `path_pattern`. In patterns `*` stands for any part of one path
+    /// segment and `**` for any number of segments ("TechnicalData.*",
+    /// "**.Temperature"). The callback must not modify the twin.
+    #[cfg(feature = "wasm")]
+    pub fn on_change(
+        &mut self,
+        path_pattern: &str,
+        #[wasm_bindgen(unchecked_param_type = "(change: PropertyChange) => void")]
+        callback: js_sys::Function,
+    ) {
+        self.add_change_listener(
+            path_pattern,
+            Box::new(move |change| {
+                let _ = callback.call1(&JsValue::NULL, &crate::to_js(change));
+            }),
+        );
+    }
+}
+
+impl DigitalTwin {
+    pub fn add_change_listener(&mut self, path_pattern: &str, listener: ChangeListener) {
+        self.change_listeners
+            .push((path_pattern.to_string(), listener));
+    }
+
+    /// Run an edit and notify the listeners of the values it changed
+    pub(crate) fn tracked<R>(&mut self, edit: impl FnOnce(&mut DigitalTwin) -> R) -> R {
+        if self.change_listeners.is_empty() {
+            let result = edit(self);
+            self.update_computed();
+            return result;
+        }
+        let before = self.watched_values();
+        // Edits made of other tracked edits report their changes once
+        let listeners = std::mem::take(&mut self.change_listeners);
+        let result = edit(self);
+        self.update_computed();
+        self.change_listeners = listeners;
+        let mut after = self.watched_values();
+        let mut changes = Vec::new();
+        for (path, old_value) in before {
+            let new_value = after.remove(&path);
+            if new_value.as_ref() != Some(&old_value) {
+                changes.push(PropertyChange {
+                    path,
+                    old_value: Some(old_value),
+                    new_value,
+                });
+            }
+        }
+        changes.extend(after.into_iter().map(|(path, value)| PropertyChange {
+            path,
+            old_value: None,
+            new_value: Some(value),
+        }));
+        changes.sort_by(|a, b| a.path.cmp(&b.path));
+        changes.iter().for_each(|change| self.notify_change(change));
+        result
+    }
+
+    pub(crate) fn notify_change(&self, change: &PropertyChange) {
+        for (pattern, listener) in &self.change_listeners {
+            if matches_pattern(pattern, &change.path) {
+                listener(change);
+            }
+        }
+    }
+
+    /// Values of the properties some listener watches
+    fn watched_values(&self) -> BTreeMap<String, String> {
+        self.elements()
+            .filter_map(|(path, element)| match &element.kind {
+                ElementKind::Property(p) => Some((path, p.value.clone())),
+                _ => None,
+            })
+            .filter(|(path, _)| {
+                self.change_listeners
+                    .iter()
+                    .any(|(pattern, _)| matches_pattern(pattern, path))
+            })
+            .collect()
+    }
+}
+
+/// Whether `path` matches a pattern in which `*` matches within a segment and
+/// `**` across segments
+pub fn matches_pattern(pattern: &str, path: &str) -> bool {
+    fn matches(pattern: &[u8], path: &[u8]) -> bool {
+        match pattern {
+            [] => path.is_empty(),
+            [b'*', b'*', b'.', rest @ ..] => {
+                matches(rest, path)
+                    || (0..path.len()).any(|i| path[i] == b'.' && matches(rest, &path[i + 1..]))
+            }
+            [b'*', b'*', rest @ ..] => (0..=path.len()).any(|i| matches(rest, &path[i..])),
+            [b'*', rest @ ..] => (0..=path.len())
+                .take_while(|&i| i == 0 || path[i - 1] != b'.')
+                .any(|i| matches(rest, &path[i..])),
+            [c, rest @ ..] => path.first() == Some(c) && matches(rest, &path[1..]),
+        }
+    }
+    matches(pattern.as_bytes(), path.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use super::*;
+
+    const CONFIG: &str = r#"{
+        "id": "urn:example:aas:motor",
+        "asset_type": "Siemens 1LE1",
+        "nameplate": [
+            {"id_short": "Voltage", "value": "400", "unit": "V", "value_type": "xs:double"},
+            {"id_short": "Maker", "value": "Siemens"}
+        ],
+        "submodels": [{
+            "id": "urn:example:sm:td",
+            "id_short": "TechnicalData",
+            "submodel_elements": [
+                {"id_short": "MaxTorque", "value": "250", "unit": "Nm"},
+                {"id_short": "Cooling", "model_type": "SubmodelElementCollection", "value": [
+                    {"id_short": "Temperature", "value": "40", "unit": "degC"}
+                ]}
+            ]
+        }]
+    }"#;
+
+    #[test]
+    fn test_path_patterns() {
+        assert!(matches_pattern("Voltage", "Voltage"));
+        assert!(!matches_pattern("Voltage", "VoltageMax"));
+        assert!(matches_pattern(
+            "TechnicalData.*",
+            "TechnicalData.MaxTorque"
+        ));
+        assert!(!matches_pattern(
+            "TechnicalData.*",
+            "TechnicalData.Cooling.Temperature"
+        ));
+        assert!(matches_pattern(
+            "TechnicalData.**",
+            "TechnicalData.Cooling.Temperature"
+        ));
+        assert!(matches_pattern(
+            "**.Temperature",
+            "TechnicalData.Cooling.Temperature"
+        ));
+        assert!(matches_pattern("**.Temperature", "Temperature"));
+        assert!(matches_pattern("Markings[*]", "Markings[2]"));
+        assert!(matches_pattern("**", "Voltage"));
+    }
+
+    #[test]
+    fn test_change_listeners() {
+        let mut twin = DigitalTwin::new(CONFIG).unwrap();
+        let seen = Rc::new(RefCell::new(Vec::new()));
+        let sink = seen.clone();
+        twin.add_change_listener(
+            "TechnicalData.**",
+            Box::new(move |change| sink.borrow_mut().push(change.clone())),
+        );
+        let rpm = Rc::new(RefCell::new(Vec::new()));
+        let sink = rpm.clone();
+        twin.add_change_listener(
+            SIMULATION_RPM_PATH,
+            Box::new(move |change| sink.borrow_mut().push(change.new_value.clone())),
+        );
+
+        twin.set_property("Voltage", "230").unwrap();
+        twin.set_property("TechnicalData.MaxTorque", "260").unwrap();
+        twin.merge(r#"{"TechnicalData": {"Cooling": {"Temperature": "45"}, "MaxTorque": null}}"#)
+            .unwrap();
+        twin.set_property("TechnicalData.MaxTorque", "x")
+            .unwrap_err();
+        twin.advance_simulation();
+
+        let change = |path: &str, old: Option<&str>, new: Option<&str>| PropertyChange {
+            path: path.to_string(),
+            old_value: old.map(str::to_string),
+            new_value: new.map(str::to_string),
+        };
+        assert_eq!(
+            *seen.borrow(),
+            [
+                change("TechnicalData.MaxTorque", Some("250"), Some("260")),
+                change("TechnicalData.Cooling.Temperature", Some("40"), Some("45")),
+                change("TechnicalData.MaxTorque", Some("260"), None),
+            ]
+        );
+        assert_eq!(rpm.borrow().len(), 1);
+    }
+}
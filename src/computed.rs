@@ -0,0 +1,409 @@
+// --- Computed properties ---
+// Properties whose value follows from others through an arithmetic
+// expression, e.g. `Voltage * Current * 1.732 * cosPhi / 1000` for the
+// electrical power, so common KPIs need no JavaScript glue. They come from
+// the `computed` section of a configuration or are defined at runtime, and
+// are evaluated in definition order whenever an edit changes the twin; a
+// computed property can use the ones defined before it. Expressions know
+// `+ - * / ^`, parentheses, numbers, idShortPaths of numeric properties and
+// the functions sqrt, abs, min and max. While an input is missing or not a
+// number, the computed value is left as it was.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+#[cfg(feature = "wasm")]
+use tsify::Tsify;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+#[cfg(feature = "wasm")]
+use crate::to_js;
+use crate::{
+    DataTypeDefXsd, DigitalTwin, ElementKind, Property, SubmodelElement, TwinError,
+    NAMEPLATE_ID_SHORT,
+};
+
+/// Key of the computed properties in a configuration
+pub const COMPUTED_SECTION: &str = "computed";
+
+/// A property computed from an expression
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[cfg_attr(feature = "wasm", tsify(missing_as_null))]
+#[serde(deny_unknown_fields)]
+pub struct ComputedProperty {
+    /// idShortPath of the property, added to the nameplate or its parent
+    /// when missing
+    pub path: String,
+    pub expression: String,
+    /// Unit of a property that is added
+    #[serde(default)]
+    pub unit: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Expr {
+    Number(f64),
+    Path(String),
+    Negate(Box<Expr>),
+    Binary(char, Box<Expr>, Box<Expr>),
+    Call(String, Vec<Expr>),
+}
+
+impl Expr {
+    /// Parse an expression, reporting the position of the first error
+    pub(crate) fn parse(source: &str) -> Result<Expr, String> {
+        let mut parser = Parser {
+            chars: source.chars().collect(),
+            pos: 0,
+        };
+        let expr = parser.sum()?;
+        parser.skip_space();
+        match parser.peek() {
+            None => Ok(expr),
+            Some(c) => Err(format!("Unexpected '{}' at {}", c, parser.pos)),
+        }
+    }
+
+    /// Every path the expression reads
+    pub(crate) fn paths(&self) -> Vec<&str> {
+        match self {
+            Expr::Number(_) => Vec::new(),
+            Expr::Path(path) => vec![path.as_str()],
+            Expr::Negate(inner) => inner.paths(),
+            Expr::Binary(_, left, right) => [left.paths(), right.paths()].concat(),
+            Expr::Call(_, args) => args.iter().flat_map(Expr::paths).collect(),
+        }
+    }
+
+    /// The value with `lookup` resolving paths; None when a path does not
+    /// resolve
+    pub(crate) fn eval(&self, lookup: &dyn Fn(&str) -> Option<f64>) -> Option<f64> {
+        Some(match self {
+            Expr::Number(n) => *n,
+            Expr::Path(path) => lookup(path)?,
+            Expr::Negate(inner) => -inner.eval(lookup)?,
+            Expr::Binary(op, left, right) => {
+                let (a, b) = (left.eval(lookup)?, right.eval(lookup)?);
+                match op {
+                    '+' => a + b,
+                    '-' => a - b,
+                    '*' => a * b,
+                    '/' => a / b,
+                    _ => a.powf(b),
+                }
+            }
+            Expr::Call(name, args) => {
+                let args = args
+                    .iter()
+                    .map(|arg| arg.eval(lookup))
+                    .collect::<Option<Vec<f64>>>()?;
+                match (name.as_str(), args.as_slice()) {
+                    ("sqrt", [x]) => x.sqrt(),
+                    ("abs", [x]) => x.abs(),
+                    ("min", [x, rest @ ..]) => rest.iter().fold(*x, |m, v| m.min(*v)),
+                    ("max", [x, rest @ ..]) => rest.iter().fold(*x, |m, v| m.max(*v)),
+                    _ => return None,
+                }
+            }
+        })
+    }
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn skip_space(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.pos += 1;
+        }
+    }
+
+    /// Consume `c` if it comes next
+    fn eat(&mut self, c: char) -> bool {
+        self.skip_space();
+        let found = self.peek() == Some(c);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn sum(&mut self) -> Result<Expr, String> {
+        let mut expr = self.product()?;
+        loop {
+            let op = match () {
+                _ if self.eat('+') => '+',
+                _ if self.eat('-') => '-',
+                _ => return Ok(expr),
+            };
+            expr = Expr::Binary(op, Box::new(expr), Box::new(self.product()?));
+        }
+    }
+
+    fn product(&mut self) -> Result<Expr, String> {
+        let mut expr = self.unary()?;
+        loop {
+            let op = match () {
+                _ if self.eat('*') => '*',
+                _ if self.eat('/') => '/',
+                _ => return Ok(expr),
+            };
+            expr = Expr::Binary(op, Box::new(expr), Box::new(self.unary()?));
+        }
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        if self.eat('-') {
+            return Ok(Expr::Negate(Box::new(self.unary()?)));
+        }
+        let base = self.atom()?;
+        if self.eat('^') {
+            // Right-associative and binding tighter than a leading minus
+            return Ok(Expr::Binary('^', Box::new(base), Box::new(self.unary()?)));
+        }
+        Ok(base)
+    }
+
+    fn atom(&mut self) -> Result<Expr, String> {
+        if self.eat('(') {
+            let expr = self.sum()?;
+            return match self.eat(')') {
+                true => Ok(expr),
+                false => Err(format!("Expected ')' at {}", self.pos)),
+            };
+        }
+        self.skip_space();
+        let start = self.pos;
+        match self.peek() {
+            Some(c) if c.is_ascii_digit() || c == '.' => {
+                while let Some(c) = self.peek() {
+                    let exponent = matches!(c, 'e' | 'E');
+                    if !(c.is_ascii_digit() || c == '.' || exponent) {
+                        break;
+                    }
+                    self.pos += 1;
+                    // The exponent may be signed, as in 1e-3
+                    if exponent && matches!(self.peek(), Some('+' | '-')) {
+                        self.pos += 1;
+                    }
+                }
+                let text: String = self.chars[start..self.pos].iter().collect();
+                text.parse()
+                    .map(Expr::Number)
+                    .map_err(|_| format!("Invalid number '{}' at {}", text, start))
+            }
+            Some(c) if c.is_alphabetic() || c == '_' => {
+                while self
+                    .peek()
+                    .is_some_and(|c| c.is_alphanumeric() || "_.[]".contains(c))
+                {
+                    self.pos += 1;
+                }
+                let name: String = self.chars[start..self.pos].iter().collect();
+                if !self.eat('(') {
+                    return Ok(Expr::Path(name));
+                }
+                let mut args = vec![self.sum()?];
+                while self.eat(',') {
+                    args.push(self.sum()?);
+                }
+                if !self.eat(')') {
+                    return Err(format!("Expected ')' at {}", self.pos));
+                }
+                match (name.as_str(), args.len()) {
+                    ("sqrt" | "abs", 1) | ("min" | "max", _) => Ok(Expr::Call(name, args)),
+                    _ => Err(format!("Unknown function {}/{}", name, args.len())),
+                }
+            }
+            Some(c) => Err(format!("Unexpected '{}' at {}", c, start)),
+            None => Err("Unexpected end of expression".to_string()),
+        }
+    }
+}
+
+/// The computed properties in a configuration, none if it has no section
+pub(crate) fn computed_from_config(config: &Value) -> Result<Vec<ComputedProperty>, TwinError> {
+    match config.get(COMPUTED_SECTION) {
+        Some(section) => serde_json::from_value(section.clone())
+            .map_err(|e| TwinError::parse(format!("Invalid computed properties: {}", e))),
+        None => Ok(Vec::new()),
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl DigitalTwin {
+    /// Compute the property at `path` from `expression` from now on,
+    /// replacing an earlier definition for it; a missing property is added
+    /// as xs:double with `unit`
+    pub fn define_computed(
+        &mut self,
+        path: &str,
+        expression: &str,
+        unit: Option<String>,
+    ) -> Result<(), TwinError> {
+        self.add_computed(ComputedProperty {
+            path: path.to_string(),
+            expression: expression.to_string(),
+            unit,
+        })
+    }
+
+    /// Stop computing a property; it keeps its last value
+    pub fn remove_computed(&mut self, path: &str) -> Result<(), TwinError> {
+        let index = self
+            .computed
+            .iter()
+            .position(|(c, _)| c.path == path)
+            .ok_or_else(|| TwinError::not_found(path, format!("'{}' is not computed", path)))?;
+        self.computed.remove(index);
+        Ok(())
+    }
+
+    /// The definitions as a `ComputedProperty` array
+    #[cfg(feature = "wasm")]
+    #[wasm_bindgen(unchecked_return_type = "ComputedProperty[]")]
+    pub fn get_computed(&self) -> JsValue {
+        to_js(&self.computed_properties())
+    }
+}
+
+impl DigitalTwin {
+    /// Rust-side `define_computed`
+    pub fn add_computed(&mut self, computed: ComputedProperty) -> Result<(), TwinError> {
+        let path = computed.path.as_str();
+        let expr = Expr::parse(&computed.expression)
+            .map_err(|e| TwinError::parse(format!("Expression of '{}': {}", path, e)))?;
+        if expr.paths().contains(&path) {
+            return Err(TwinError::validation(
+                Some(path),
+                format!("'{}' cannot be computed from itself", path),
+            ));
+        }
+        if let Some(input) = expr
+            .paths()
+            .into_iter()
+            .find(|p| self.find_element(p).is_err())
+        {
+            return Err(TwinError::not_found(
+                input,
+                format!("Expression of '{}' reads the missing '{}'", path, input),
+            ));
+        }
+        match self.find_element(path).map(|e| &e.kind) {
+            Ok(ElementKind::Property(_)) => {}
+            Ok(other) => {
+                return Err(TwinError::type_mismatch(
+                    path,
+                    format!("'{}' is a {}, not a Property", path, other.model_type()),
+                ))
+            }
+            Err(_) => {
+                let (parent, id_short) =
+                    path.rsplit_once('.').unwrap_or((NAMEPLATE_ID_SHORT, path));
+                let property = Property {
+                    value: String::new(),
+                    unit: computed.unit.clone(),
+                    value_type: Some(DataTypeDefXsd::Double),
+                };
+                self.insert_element(
+                    parent,
+                    SubmodelElement::new(id_short, ElementKind::Property(property)),
+                )?;
+            }
+        }
+        self.computed.retain(|(c, _)| c.path != computed.path);
+        self.computed.push((computed, expr));
+        self.tracked(|_| ());
+        Ok(())
+    }
+
+    /// Rust-side `get_computed`
+    pub fn computed_properties(&self) -> Vec<ComputedProperty> {
+        self.computed.iter().map(|(c, _)| c.clone()).collect()
+    }
+
+    /// Evaluate every computed property in definition order
+    pub(crate) fn update_computed(&mut self) {
+        for i in 0..self.computed.len() {
+            let lookup = |path: &str| self.get_property_f64(path).ok();
+            let Some(value) = self.computed[i].1.eval(&lookup).filter(|v| v.is_finite()) else {
+                continue;
+            };
+            let path = self.computed[i].0.path.clone();
+            if let Ok(element) = self.find_element_mut(&path) {
+                if let ElementKind::Property(p) = &mut element.kind {
+                    p.value = value.to_string();
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expressions() {
+        let lookup = |path: &str| match path {
+            "Voltage" => Some(400.0),
+            "TechnicalData.Current" => Some(10.0),
+            _ => None,
+        };
+        let eval = |source: &str| Expr::parse(source).unwrap().eval(&lookup);
+        assert_eq!(eval("1 + 2 * 3 - 4 / 2"), Some(5.0));
+        assert_eq!(eval("-2 ^ 2 + (1 + 1) ^ 3 ^ 0"), Some(-2.0));
+        assert_eq!(eval("Voltage * TechnicalData.Current / 1e3"), Some(4.0));
+        assert_eq!(eval("Voltage * TechnicalData.Current * 1e-3"), Some(4.0));
+        assert_eq!(eval("2.5E+2 - 5e-1"), Some(249.5));
+        assert_eq!(eval("max(1, sqrt(16), abs(-3)) + min(2, 0.5)"), Some(4.5));
+        assert_eq!(eval("Speed * 2"), None);
+        assert!(Expr::parse("1 +").is_err());
+        assert!(Expr::parse("(1 + 2").is_err());
+        assert!(Expr::parse("log(2)").is_err());
+        assert!(Expr::parse("2 $ 3").is_err());
+    }
+
+    #[test]
+    fn test_computed_properties_follow_inputs() {
+        let mut twin = DigitalTwin::new(
+            r#"{"id": "M-1", "asset_type": "Motor", "nameplate": [
+                {"id_short": "Voltage", "value": "400", "unit": "V"},
+                {"id_short": "Current", "value": "10", "unit": "A"},
+                {"id_short": "cosPhi", "value": "0.85"}
+            ], "computed": [
+                {"path": "Power", "expression": "Voltage * Current * 1.732 * cosPhi / 1000", "unit": "kW"},
+                {"path": "PowerW", "expression": "Power * 1000"}
+            ]}"#,
+        )
+        .unwrap();
+        assert!((twin.get_property_f64("Power").unwrap() - 5.8888).abs() < 1e-9);
+        assert_eq!(twin.find_element("Power").unwrap().unit(), Some("kW"));
+        twin.set_property("Current", "20").unwrap();
+        assert!((twin.get_property_f64("PowerW").unwrap() - 11777.6).abs() < 1e-6);
+
+        // A non-numeric input leaves the value as it was
+        twin.set_property("cosPhi", "n/a").unwrap();
+        assert!((twin.get_property_f64("PowerW").unwrap() - 11777.6).abs() < 1e-6);
+
+        let mut error = |path: &str, expression: &str| {
+            twin.define_computed(path, expression, None)
+                .unwrap_err()
+                .code()
+        };
+        assert_eq!(error("Load", "Current / 0 +"), "PARSE_ERROR");
+        assert_eq!(error("Load", "Torque * 2"), "NOT_FOUND");
+        assert_eq!(error("Current", "Current * 2"), "VALIDATION_FAILED");
+        twin.remove_computed("Power").unwrap();
+        assert_eq!(twin.computed_properties().len(), 1);
+        assert!(twin.remove_computed("Power").is_err());
+    }
+}
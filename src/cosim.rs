@@ -0,0 +1,140 @@
+// --- Co-simulation ---
+// An FMI-style slave interface, so a co-simulation master can run the twin
+// next to FMUs: between communication points the master sets inputs, asks the
+// twin to step from the current communication point and reads its outputs.
+// Inputs are the simulation inputs the models consume ("load", "load_torque",
+// "supply_voltage", "ambient_temperature", "energized" and those the
+// profile's signals depend on) and outputs the model signals ("motor.rpm").
+// The same inputs let a scenario player or another twin drive the simulation
+// without a master.
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    DigitalTwin, TwinError, AMBIENT_TEMPERATURE_INPUT, ENERGIZED_INPUT, LOAD_INPUT,
+    LOAD_TORQUE_INPUT, SUPPLY_VOLTAGE_INPUT,
+};
+
+/// Largest difference between the master's and the twin's time still
+/// taken as the same communication point, relative to the time
+const TIME_TOLERANCE: f64 = 1e-9;
+
+/// Inputs of the built-in models a master may set
+const MODEL_INPUTS: [&str; 5] = [
+    LOAD_INPUT,
+    LOAD_TORQUE_INPUT,
+    SUPPLY_VOLTAGE_INPUT,
+    AMBIENT_TEMPERATURE_INPUT,
+    ENERGIZED_INPUT,
+];
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl DigitalTwin {
+    /// Advance from the communication point `current_time`, which must be
+    /// the twin's simulation time, by `step_size` seconds
+    pub fn do_step(&mut self, current_time: f64, step_size: f64) -> Result<(), TwinError> {
+        if !step_size.is_finite() || step_size <= 0.0 {
+            return Err(TwinError::invalid_argument(format!(
+                "Step size must be a positive number of seconds, got {}",
+                step_size
+            )));
+        }
+        let time = self.simulation_time();
+        if !current_time.is_finite()
+            || (current_time - time).abs() > TIME_TOLERANCE * time.abs().max(1.0)
+        {
+            return Err(TwinError::failed(
+                None,
+                format!(
+                    "Communication point {} does not match the simulation time {}",
+                    current_time, time
+                ),
+            ));
+        }
+        self.step_models(step_size);
+        Ok(())
+    }
+
+    /// Set a simulation input ("load", "load_torque", "supply_voltage",
+    /// "ambient_temperature", "energized" or one the profile's signals depend
+    /// on) that the models consume from the next tick on
+    pub fn set_input(&mut self, name: &str, value: f64) -> Result<(), TwinError> {
+        if !MODEL_INPUTS.contains(&name) && !self.profile.inputs().any(|input| input == name) {
+            return Err(TwinError::invalid_argument(format!(
+                "No simulation input '{}'",
+                name
+            )));
+        }
+        if !value.is_finite() {
+            return Err(TwinError::invalid_argument(format!(
+                "Input '{}' needs a finite value, got {}",
+                name, value
+            )));
+        }
+        self.set_simulation_input(name, value);
+        Ok(())
+    }
+
+    /// The latest value of a model output ("motor.rpm")
+    pub fn get_output(&self, name: &str) -> Result<f64, TwinError> {
+        self.simulation_values()
+            .get(name)
+            .copied()
+            .ok_or_else(|| TwinError::not_found(name, format!("No simulation output '{}'", name)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_co_simulation_steps() {
+        let config = r#"{"id": "M-1", "asset_type": "Motor", "nameplate": []}"#;
+        let mut twin = DigitalTwin::new(config).unwrap();
+        let mut reference = DigitalTwin::new(config).unwrap();
+        twin.set_input("load", 0.5).unwrap();
+        reference.set_load(0.5).unwrap();
+        let mut time = 0.0;
+        for _ in 0..30 {
+            twin.do_step(time, 0.1).unwrap();
+            reference.advance_by(0.1).unwrap();
+            time += 0.1;
+        }
+        assert_eq!(
+            twin.get_output("motor.rpm").unwrap(),
+            reference.simulation_values()["motor.rpm"]
+        );
+
+        assert!(twin
+            .do_step(0.0, 0.1)
+            .is_err_and(|e| e.code() == "OPERATION_FAILED"));
+        assert!(twin.do_step(time, 0.0).is_err());
+        assert!(twin.set_input("load", f64::INFINITY).is_err());
+        assert!(twin
+            .set_input("motor.rpm", 100.0)
+            .is_err_and(|e| e.code() == "INVALID_ARGUMENT"));
+        assert!(twin.set_input("", 1.0).is_err());
+        assert!(twin
+            .get_output("motor.torque_ripple")
+            .is_err_and(|e| e.code() == "NOT_FOUND"));
+    }
+
+    #[test]
+    fn test_profile_inputs() {
+        let mut twin = DigitalTwin::new(
+            r#"{"id": "P-1", "asset_type": "Pump", "nameplate": [], "simulation": {
+                "builtin": false, "models": [{"name": "pump", "signals": [
+                    {"name": "flow", "depends_on": {"valve": 10.0}}
+                ]}]}}"#,
+        )
+        .unwrap();
+        twin.set_input("valve", 0.5).unwrap();
+        twin.do_step(0.0, 0.1).unwrap();
+        assert_eq!(twin.get_output("pump.flow").unwrap(), 5.0);
+        assert!(twin
+            .set_input("throttle", 0.5)
+            .is_err_and(|e| e.code() == "INVALID_ARGUMENT"));
+    }
+}
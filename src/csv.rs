@@ -0,0 +1,180 @@
+// --- CSV export ---
+// Flat idShortPath/value/unit/semanticId rows of a submodel for
+// spreadsheets. Containers are not written themselves, only their leaves.
+// The signal history is exported with a time column and a column per
+// signal, one row per point in time; cells of signals without a value at
+// that time stay empty.
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    walk_elements, DigitalTwin, ElementKind, SubmodelElement, TwinError, NAMEPLATE_ID_SHORT,
+};
+
+/// Header row of `elements_to_csv`
+pub const CSV_COLUMNS: [&str; 4] = ["idShortPath", "value", "unit", "semanticId"];
+
+/// First column of `export_history_csv`, the simulated time in seconds
+pub const HISTORY_TIME_COLUMN: &str = "time";
+
+/// One row per leaf element, paths relative to the submodel (RFC 4180, CRLF line ends)
+pub fn elements_to_csv(elements: &[SubmodelElement]) -> String {
+    let mut paths = Vec::new();
+    walk_elements("", elements, &mut paths);
+
+    let mut out = CSV_COLUMNS.join(",") + "\r\n";
+    for (path, element) in paths.iter().filter(|(_, e)| !e.is_container()) {
+        let value = match &element.kind {
+            ElementKind::Property(p) => p.value.clone(),
+            ElementKind::Range(r) => format!(
+                "{}..{}",
+                r.min.as_deref().unwrap_or(""),
+                r.max.as_deref().unwrap_or("")
+            ),
+            _ => element.display_value(),
+        };
+        let semantic_id = element.semantic_id.as_ref().and_then(|r| r.key_value());
+        let row = [
+            path.as_str(),
+            value.as_str(),
+            element.unit().unwrap_or(""),
+            semantic_id.unwrap_or(""),
+        ];
+        out.push_str(&row.map(field).join(","));
+        out.push_str("\r\n");
+    }
+    out
+}
+
+/// Quote a field if it contains a separator, quote or line break
+fn field(value: &str) -> String {
+    if value.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl DigitalTwin {
+    /// Export the leaf elements of a submodel (by idShort; "Nameplate" for the
+    /// nameplate) as CSV with idShortPath, value, unit and semanticId columns
+    pub fn export_csv(&self, submodel: &str) -> Result<String, TwinError> {
+        let elements = match self
+            .data
+            .submodels
+            .iter()
+            .find(|sm| sm.id_short == submodel)
+        {
+            Some(sm) => &sm.submodel_elements,
+            None if submodel == NAMEPLATE_ID_SHORT => &self.data.nameplate,
+            None => {
+                return Err(TwinError::not_found(
+                    submodel,
+                    format!("Submodel '{}' not found", submodel),
+                ))
+            }
+        };
+        Ok(elements_to_csv(elements))
+    }
+
+    /// Export the history of `names` (every recorded signal if empty) from
+    /// `from` to `to` seconds of simulated time, both inclusive and open when
+    /// omitted, as CSV with a `time` column and a column per signal
+    pub fn export_history_csv(
+        &self,
+        names: Vec<String>,
+        from: Option<f64>,
+        to: Option<f64>,
+    ) -> Result<String, TwinError> {
+        let table = self.history_range(&names, from, to)?;
+        let mut out = std::iter::once(HISTORY_TIME_COLUMN)
+            .chain(table.keys.iter().map(String::as_str))
+            .map(field)
+            .collect::<Vec<_>>()
+            .join(",")
+            + "\r\n";
+        for (time, values) in &table.rows {
+            let cells = values
+                .iter()
+                .map(|v| v.map(|v| v.to_string()).unwrap_or_default());
+            let row: Vec<String> = std::iter::once(time.to_string()).chain(cells).collect();
+            out.push_str(&row.join(","));
+            out.push_str("\r\n");
+        }
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_export_csv() {
+        let json = r#"{
+            "id": "M-1",
+            "asset_type": "Pump",
+            "nameplate": [
+                {"id_short": "Voltage", "value": "400", "unit": "V", "semantic_id": "0173-1#02-AAE416#004"},
+                {"id_short": "Note", "value": "dry, \"cool\" place"}
+            ],
+            "submodels": [{
+                "id": "urn:example:sm:td",
+                "id_short": "TechnicalData",
+                "submodel_elements": [{
+                    "id_short": "Ambient",
+                    "model_type": "SubmodelElementCollection",
+                    "value": [
+                        {"id_short": "Temperature", "model_type": "Range", "min": "-20", "max": "40", "unit": "degC"}
+                    ]
+                }]
+            }]
+        }"#;
+        let twin = DigitalTwin::new(json).unwrap();
+
+        assert_eq!(
+            twin.export_csv("Nameplate").unwrap(),
+            "idShortPath,value,unit,semanticId\r\n\
+             Voltage,400,V,0173-1#02-AAE416#004\r\n\
+             Note,\"dry, \"\"cool\"\" place\",,\r\n"
+        );
+        assert_eq!(
+            twin.export_csv("TechnicalData").unwrap(),
+            "idShortPath,value,unit,semanticId\r\nAmbient.Temperature,-20..40,degC,\r\n"
+        );
+    }
+
+    #[test]
+    fn test_export_history_csv() {
+        let mut twin =
+            DigitalTwin::new(r#"{"id": "M-1", "asset_type": "Motor", "nameplate": []}"#).unwrap();
+        for _ in 0..4 {
+            twin.advance_by(0.5).unwrap();
+        }
+        let names = vec![
+            "motor.rpm".to_string(),
+            "thermal.overtemperature".to_string(),
+        ];
+        let csv = twin
+            .export_history_csv(names.clone(), Some(1.0), Some(1.5))
+            .unwrap();
+        let lines: Vec<&str> = csv.split_terminator("\r\n").collect();
+        assert_eq!(lines[0], "time,motor.rpm,thermal.overtemperature");
+        assert_eq!(lines.len(), 3);
+        let last: Vec<&str> = lines[2].split(',').collect();
+        assert_eq!(last[0], "1.5");
+        assert_eq!(last[2], "0");
+
+        let all = twin.export_history_csv(Vec::new(), None, None).unwrap();
+        assert_eq!(all.lines().count(), 5);
+        assert!(all.lines().next().unwrap().contains("vibration.rms"));
+        assert!(twin
+            .export_history_csv(names, Some(2.0), Some(1.0))
+            .is_err_and(|e| e.code() == "INVALID_ARGUMENT"));
+        assert!(twin
+            .export_history_csv(vec!["motor.torque_x".to_string()], None, None)
+            .is_err_and(|e| e.code() == "NOT_FOUND"));
+    }
+}
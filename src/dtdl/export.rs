@@ -0,0 +1,214 @@
+// Mapping of the model onto DTDL: properties become Properties (Telemetry
+// for the VARIABLE category, writable for PARAMETER), containers become
+// Object and Array schemas, operations become Commands
+
+use serde_json::{json, Value};
+
+use super::{dtdl_name, DTDL_CONTEXT};
+use crate::{
+    select_lang, AssetAdministrationShell, Category, DataTypeDefXsd, ElementKind, LangString,
+    OperationVariable, SubmodelElement, NAMEPLATE_ID_SHORT,
+};
+
+/// DTDL interfaces for a shell: the shell interface first, then one per submodel
+pub fn shell_to_dtdl(shell: &AssetAdministrationShell, dtmi_prefix: &str) -> Value {
+    let root_name = dtdl_name(
+        shell
+            .asset_information
+            .asset_type
+            .as_deref()
+            .unwrap_or("AssetAdministrationShell"),
+    );
+    let root_id = format!("{}:{};1", dtmi_prefix, root_name);
+
+    let mut submodels = Vec::new();
+    if !shell.nameplate.is_empty() {
+        submodels.push((NAMEPLATE_ID_SHORT, &[][..], &shell.nameplate[..]));
+    }
+    for sm in &shell.submodels {
+        submodels.push((
+            sm.id_short.as_str(),
+            &sm.display_name[..],
+            &sm.submodel_elements[..],
+        ));
+    }
+
+    let mut components = Vec::new();
+    let mut interfaces = Vec::new();
+    for (id_short, display_name, elements) in submodels {
+        let name = dtdl_name(id_short);
+        let id = format!("{}:{}:{};1", dtmi_prefix, root_name, name);
+        let mut component = json!({"@type": "Component", "name": name, "schema": id});
+        labels(&mut component, display_name, &[]);
+        components.push(component);
+        interfaces.push(json!({
+            "@context": DTDL_CONTEXT,
+            "@id": id,
+            "@type": "Interface",
+            "displayName": id_short,
+            "contents": elements.iter().map(content).collect::<Vec<_>>(),
+        }));
+    }
+
+    let mut root = json!({
+        "@context": DTDL_CONTEXT,
+        "@id": root_id,
+        "@type": "Interface",
+        "displayName": text(&shell.display_name).unwrap_or_else(|| shell.id.clone()),
+        "contents": components,
+    });
+    labels(&mut root, &[], &shell.description);
+    interfaces.insert(0, root);
+    Value::Array(interfaces)
+}
+
+/// Add `displayName` and `description` if the element has them
+fn labels(target: &mut Value, display_name: &[LangString], description: &[LangString]) {
+    if let Some(map) = target.as_object_mut() {
+        if let Some(name) = text(display_name) {
+            map.insert("displayName".to_string(), json!(name));
+        }
+        if let Some(description) = text(description) {
+            map.insert("description".to_string(), json!(description));
+        }
+    }
+}
+
+fn text(strings: &[LangString]) -> Option<String> {
+    select_lang(strings, "en").map(|s| s.text.clone())
+}
+
+/// Interface content for an element
+fn content(element: &SubmodelElement) -> Value {
+    let name = dtdl_name(&element.id_short);
+    let mut out = match &element.kind {
+        ElementKind::Operation(op) => {
+            let mut command = json!({"@type": "Command", "name": name});
+            let inputs = [op.input_variables.as_slice(), &op.inoutput_variables].concat();
+            let outputs = [op.output_variables.as_slice(), &op.inoutput_variables].concat();
+            if !inputs.is_empty() {
+                command["request"] = json!({"name": "input", "schema": variables(&inputs)});
+            }
+            if !outputs.is_empty() {
+                command["response"] = json!({"name": "output", "schema": variables(&outputs)});
+            }
+            command
+        }
+        ElementKind::BasicEventElement(_) => {
+            json!({"@type": "Telemetry", "name": name, "schema": "string"})
+        }
+        _ => {
+            let kind = match element.category {
+                Some(Category::Variable) => "Telemetry",
+                _ => "Property",
+            };
+            let mut property = json!({"@type": kind, "name": name, "schema": schema(element)});
+            if element.category == Some(Category::Parameter) {
+                property["writable"] = json!(true);
+            }
+            property
+        }
+    };
+    if let Some(unit) = element.unit() {
+        out["comment"] = json!(format!("unit: {}", unit));
+    }
+    labels(&mut out, &element.display_name, &element.description);
+    out
+}
+
+fn variables(vars: &[OperationVariable]) -> Value {
+    let elements: Vec<SubmodelElement> = vars.iter().map(|v| v.value.clone()).collect();
+    object_schema(&elements)
+}
+
+fn object_schema(elements: &[SubmodelElement]) -> Value {
+    let fields: Vec<Value> = elements
+        .iter()
+        .map(|e| {
+            let mut field = json!({"name": dtdl_name(&e.id_short), "schema": schema(e)});
+            labels(&mut field, &e.display_name, &e.description);
+            field
+        })
+        .collect();
+    json!({"@type": "Object", "fields": fields})
+}
+
+/// DTDL schema of an element's value
+fn schema(element: &SubmodelElement) -> Value {
+    match &element.kind {
+        ElementKind::Property(p) => json!(match p.value_type {
+            Some(DataTypeDefXsd::Int) => "integer",
+            Some(DataTypeDefXsd::Double) => "double",
+            Some(DataTypeDefXsd::Boolean) => "boolean",
+            Some(DataTypeDefXsd::DateTime) => "dateTime",
+            Some(DataTypeDefXsd::String) | None => "string",
+        }),
+        ElementKind::Range(_) => json!({
+            "@type": "Object",
+            "fields": [
+                {"name": "min", "schema": "double"},
+                {"name": "max", "schema": "double"},
+            ],
+        }),
+        ElementKind::SubmodelElementCollection(c) => object_schema(&c.value),
+        ElementKind::Entity(e) => object_schema(&e.statements),
+        ElementKind::SubmodelElementList(l) => json!({
+            "@type": "Array",
+            "elementSchema": l.value.first().map(schema).unwrap_or(json!("string")),
+        }),
+        _ => json!("string"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::DigitalTwin;
+
+    #[test]
+    fn test_dtdl_export() {
+        let json = r#"{
+            "id": "urn:example:aas:motor",
+            "asset_type": "Siemens 1LE1",
+            "nameplate": [
+                {"id_short": "Voltage", "value": "400", "unit": "V", "value_type": "xs:double"}
+            ],
+            "submodels": [{
+                "id": "urn:example:sm:op",
+                "id_short": "Operational-Data",
+                "submodel_elements": [
+                    {"id_short": "Speed", "value": "1450", "value_type": "xs:int", "category": "VARIABLE"},
+                    {"id_short": "SetPoint", "value": "1500", "value_type": "xs:int", "category": "PARAMETER"},
+                    {"id_short": "Bearing", "model_type": "SubmodelElementCollection", "value": [
+                        {"id_short": "Temperature", "value": "40", "value_type": "xs:double"}
+                    ]},
+                    {"id_short": "Start", "model_type": "Operation", "input_variables": [
+                        {"value": {"id_short": "Rpm", "value": "0", "value_type": "xs:int"}}
+                    ]}
+                ]
+            }]
+        }"#;
+        let dtdl: serde_json::Value =
+            serde_json::from_str(&DigitalTwin::new(json).unwrap().get_dtdl(None)).unwrap();
+
+        let root = &dtdl[0];
+        assert_eq!(root["@id"], "dtmi:snaptotwin:Siemens_1LE1;1");
+        assert_eq!(root["@context"], "dtmi:dtdl:context;3");
+        assert_eq!(root["contents"][1]["name"], "Operational_Data");
+        assert_eq!(
+            root["contents"][1]["schema"],
+            "dtmi:snaptotwin:Siemens_1LE1:Operational_Data;1"
+        );
+
+        let nameplate = &dtdl[1]["contents"][0];
+        assert_eq!(nameplate["@type"], "Property");
+        assert_eq!(nameplate["schema"], "double");
+        assert_eq!(nameplate["comment"], "unit: V");
+
+        let op = &dtdl[2]["contents"];
+        assert_eq!(op[0]["@type"], "Telemetry");
+        assert_eq!(op[1]["writable"], true);
+        assert_eq!(op[2]["schema"]["fields"][0]["name"], "Temperature");
+        assert_eq!(op[3]["@type"], "Command");
+        assert_eq!(op[3]["request"]["schema"]["fields"][0]["schema"], "integer");
+    }
+}
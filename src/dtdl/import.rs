@@ -0,0 +1,400 @@
+// Building a shell from DTDL interfaces and an Azure Digital Twins twin
+// instance: components become submodels (a "Nameplate" component, like the
+// interface's own contents, fills the nameplate), Properties and Telemetry
+// become properties with the twin's values, Object/Map/Array schemas
+// become collections and lists, Commands become operations. Schemas must be
+// inline; `extends` and Relationships are not followed.
+
+use std::collections::BTreeMap;
+
+use serde_json::Value;
+
+use super::dtdl_name;
+use crate::{
+    AasSubmodelElements, AssetAdministrationShell, AssetInformation, Category, DataTypeDefXsd,
+    ElementKind, LangString, MultiLanguageProperty, Operation, OperationVariable, Property,
+    Submodel, SubmodelElement, SubmodelElementCollection, SubmodelElementList, NAMEPLATE_ID_SHORT,
+};
+
+/// Map DTDL interfaces (one interface or an array) and an optional twin
+/// instance onto a shell. The root interface is the twin's `$metadata.$model`,
+/// or else the first interface.
+pub fn shell_from_dtdl(
+    interfaces: &Value,
+    twin: Option<&Value>,
+) -> Result<AssetAdministrationShell, String> {
+    let list: Vec<&Value> = match interfaces {
+        Value::Array(items) => items.iter().collect(),
+        single => vec![single],
+    };
+    let by_id: BTreeMap<&str, &Value> = list
+        .iter()
+        .filter_map(|i| Some((i.get("@id")?.as_str()?, *i)))
+        .collect();
+    let model = twin
+        .and_then(|t| t.get("$metadata"))
+        .and_then(|m| m.get("$model"))
+        .and_then(Value::as_str);
+    let root = match model {
+        Some(id) => *by_id
+            .get(id)
+            .ok_or_else(|| format!("DTDL interface '{}' of the twin is missing", id))?,
+        None => *list.first().ok_or("No DTDL interface given")?,
+    };
+    if root.get("@type").and_then(Value::as_str) != Some("Interface") {
+        return Err("DTDL root must be an Interface".to_string());
+    }
+    let root_id = root.get("@id").and_then(Value::as_str).unwrap_or("");
+    let empty = Value::Null;
+    let twin = twin.unwrap_or(&empty);
+
+    let mut nameplate = Vec::new();
+    let mut submodels = Vec::new();
+    for content in contents(root) {
+        let name = str_field(content, "name")?;
+        if content.get("@type").and_then(Value::as_str) != Some("Component") {
+            nameplate.push(element(content, twin.get(name))?);
+            continue;
+        }
+        let schema = match content.get("schema") {
+            Some(Value::String(id)) => *by_id.get(id.as_str()).ok_or_else(|| {
+                format!("DTDL interface '{}' of component '{}' is missing", id, name)
+            })?,
+            Some(inline @ Value::Object(_)) => inline,
+            _ => return Err(format!("DTDL component '{}' has no schema", name)),
+        };
+        let values = twin.get(name);
+        let elements = contents(schema)
+            .map(|c| element(c, values.and_then(|v| v.get(str_field(c, "name").ok()?))))
+            .collect::<Result<Vec<_>, _>>()?;
+        if name == NAMEPLATE_ID_SHORT {
+            nameplate.extend(elements);
+            continue;
+        }
+        submodels.push(Submodel {
+            id: format!("{}/submodels/{}", shell_id(root_id, twin), name),
+            id_short: dtdl_name(name),
+            display_name: lang_strings(content.get("displayName")),
+            description: lang_strings(content.get("description")),
+            administration: None,
+            semantic_id: None,
+            qualifiers: Vec::new(),
+            extensions: Vec::new(),
+            kind: Default::default(),
+            submodel_elements: elements,
+        });
+    }
+
+    let asset_type = match lang_strings(root.get("displayName")).first() {
+        Some(s) => s.text.clone(),
+        None => dtmi_name(root_id).to_string(),
+    };
+    Ok(AssetAdministrationShell {
+        id: shell_id(root_id, twin),
+        display_name: Vec::new(),
+        description: lang_strings(root.get("description")),
+        asset_information: AssetInformation {
+            asset_type: Some(asset_type),
+            ..Default::default()
+        },
+        administration: None,
+        nameplate,
+        submodels,
+        extensions: Vec::new(),
+        concept_descriptions: Default::default(),
+    })
+}
+
+/// The twin's `$dtId`, or the interface DTMI
+fn shell_id(root_id: &str, twin: &Value) -> String {
+    twin.get("$dtId")
+        .and_then(Value::as_str)
+        .unwrap_or(root_id)
+        .to_string()
+}
+
+/// Last DTMI segment without version ("dtmi:com:example:Motor;1" -> "Motor")
+fn dtmi_name(dtmi: &str) -> &str {
+    let path = dtmi.split(';').next().unwrap_or(dtmi);
+    path.rsplit(':').next().unwrap_or(path)
+}
+
+fn contents(interface: &Value) -> impl Iterator<Item = &Value> {
+    interface
+        .get("contents")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+}
+
+fn str_field<'a>(value: &'a Value, field: &str) -> Result<&'a str, String> {
+    value
+        .get(field)
+        .and_then(Value::as_str)
+        .ok_or_else(|| format!("DTDL content is missing '{}'", field))
+}
+
+/// DTDL strings are plain or a map of language codes to text
+fn lang_strings(value: Option<&Value>) -> Vec<LangString> {
+    let lang = |language: &str, text: &Value| {
+        text.as_str().map(|t| LangString {
+            language: language.to_string(),
+            text: t.to_string(),
+        })
+    };
+    match value {
+        Some(Value::Object(map)) => map.iter().filter_map(|(l, t)| lang(l, t)).collect(),
+        Some(text) => lang("en", text).into_iter().collect(),
+        None => Vec::new(),
+    }
+}
+
+/// An element for a Property, Telemetry or Command with its twin value
+fn element(content: &Value, value: Option<&Value>) -> Result<SubmodelElement, String> {
+    let name = str_field(content, "name")?;
+    let types: Vec<&str> = match content.get("@type") {
+        Some(Value::String(t)) => vec![t.as_str()],
+        Some(Value::Array(ts)) => ts.iter().filter_map(Value::as_str).collect(),
+        _ => Vec::new(),
+    };
+    let mut element = if types.contains(&"Command") {
+        let variables = |field: &str| -> Result<Vec<OperationVariable>, String> {
+            let Some(payload) = content.get(field) else {
+                return Ok(Vec::new());
+            };
+            let payload_name = str_field(payload, "name")?;
+            let vars = match payload.get("schema") {
+                Some(schema @ Value::Object(_)) if schema["@type"] == "Object" => fields(schema)
+                    .map(|f| schema_element(f, None))
+                    .collect::<Result<Vec<_>, _>>()?,
+                schema => vec![schema_element(
+                    &serde_json::json!({"name": payload_name, "schema": schema}),
+                    None,
+                )?],
+            };
+            Ok(vars
+                .into_iter()
+                .map(|value| OperationVariable { value })
+                .collect())
+        };
+        SubmodelElement::new(
+            &dtdl_name(name),
+            ElementKind::Operation(Operation {
+                input_variables: variables("request")?,
+                output_variables: variables("response")?,
+                inoutput_variables: Vec::new(),
+            }),
+        )
+    } else {
+        let mut element = schema_element(content, value)?;
+        if types.contains(&"Telemetry") {
+            element.category = Some(Category::Variable);
+        } else if content.get("writable") == Some(&Value::Bool(true)) {
+            element.category = Some(Category::Parameter);
+        }
+        element
+    };
+    element.display_name = lang_strings(content.get("displayName"));
+    element.description = lang_strings(content.get("description"));
+    Ok(element)
+}
+
+fn fields(schema: &Value) -> impl Iterator<Item = &Value> {
+    schema
+        .get("fields")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+}
+
+/// An element for anything with a `name` and a `schema`
+fn schema_element(content: &Value, value: Option<&Value>) -> Result<SubmodelElement, String> {
+    let id_short = dtdl_name(str_field(content, "name")?);
+    let schema = content.get("schema").unwrap_or(&Value::Null);
+    let unit = content
+        .get("unit")
+        .and_then(Value::as_str)
+        .or_else(|| {
+            content
+                .get("comment")
+                .and_then(Value::as_str)
+                .and_then(|c| c.strip_prefix("unit: "))
+        })
+        .map(str::to_string);
+
+    let kind = match schema {
+        Value::Object(s) => match s.get("@type").and_then(Value::as_str) {
+            Some("Object") => ElementKind::SubmodelElementCollection(SubmodelElementCollection {
+                value: fields(schema)
+                    .map(|f| schema_element(f, value.and_then(|v| v.get(f.get("name")?.as_str()?))))
+                    .collect::<Result<_, _>>()?,
+            }),
+            Some("Map") => {
+                let value_schema = s
+                    .get("mapValue")
+                    .and_then(|m| m.get("schema"))
+                    .cloned()
+                    .unwrap_or(Value::Null);
+                let entries = value.and_then(Value::as_object).into_iter().flatten();
+                ElementKind::SubmodelElementCollection(SubmodelElementCollection {
+                    value: entries
+                        .map(|(key, v)| {
+                            schema_element(
+                                &serde_json::json!({"name": key, "schema": value_schema}),
+                                Some(v),
+                            )
+                        })
+                        .collect::<Result<_, _>>()?,
+                })
+            }
+            Some("Array") => {
+                let item_schema = s.get("elementSchema").cloned().unwrap_or(Value::Null);
+                let items: Vec<SubmodelElement> = value
+                    .and_then(Value::as_array)
+                    .into_iter()
+                    .flatten()
+                    .map(|v| {
+                        let mut item = schema_element(
+                            &serde_json::json!({"name": "item", "schema": item_schema}),
+                            Some(v),
+                        )?;
+                        item.id_short.clear();
+                        Ok::<_, String>(item)
+                    })
+                    .collect::<Result<_, _>>()?;
+                let (type_value_list_element, value_type_list_element) =
+                    match primitive(&item_schema) {
+                        Some(t) => (AasSubmodelElements::Property, Some(t)),
+                        None => (AasSubmodelElements::SubmodelElementCollection, None),
+                    };
+                ElementKind::SubmodelElementList(SubmodelElementList {
+                    order_relevant: true,
+                    type_value_list_element,
+                    semantic_id_list_element: None,
+                    value_type_list_element,
+                    value: items,
+                })
+            }
+            Some("Enum") => property(value, s.get("valueSchema").and_then(primitive), unit),
+            _ => property(value, Some(DataTypeDefXsd::String), unit),
+        },
+        primitive_schema => match primitive_schema.as_str() {
+            Some("string") if value.is_some_and(Value::is_object) => {
+                ElementKind::MultiLanguageProperty(MultiLanguageProperty {
+                    value: lang_strings(value),
+                })
+            }
+            _ => property(value, primitive(primitive_schema), unit),
+        },
+    };
+    Ok(SubmodelElement::new(&id_short, kind))
+}
+
+fn property(
+    value: Option<&Value>,
+    value_type: Option<DataTypeDefXsd>,
+    unit: Option<String>,
+) -> ElementKind {
+    let value = match value {
+        None | Some(Value::Null) => String::new(),
+        Some(Value::String(s)) => s.clone(),
+        Some(other) => other.to_string(),
+    };
+    ElementKind::Property(Property {
+        value,
+        unit,
+        value_type,
+    })
+}
+
+/// XSD type for a primitive DTDL schema; other schemas have none
+fn primitive(schema: &Value) -> Option<DataTypeDefXsd> {
+    Some(match schema.as_str()? {
+        "integer" | "long" | "byte" | "short" | "unsignedInteger" | "unsignedLong"
+        | "unsignedByte" | "unsignedShort" => DataTypeDefXsd::Int,
+        "double" | "float" => DataTypeDefXsd::Double,
+        "boolean" => DataTypeDefXsd::Boolean,
+        "dateTime" => DataTypeDefXsd::DateTime,
+        _ => DataTypeDefXsd::String,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DigitalTwin;
+
+    #[test]
+    fn test_dtdl_import() {
+        let interfaces = serde_json::json!([
+            {
+                "@context": "dtmi:dtdl:context;3",
+                "@id": "dtmi:com:example:Pump;1",
+                "@type": "Interface",
+                "displayName": "Acme Pump",
+                "contents": [
+                    {"@type": "Property", "name": "serialNumber", "schema": "string"},
+                    {"@type": "Telemetry", "name": "flow", "schema": "double", "unit": "litrePerSecond"},
+                    {"@type": "Component", "name": "settings", "schema": "dtmi:com:example:Settings;1"},
+                    {"@type": "Command", "name": "flush", "request": {"name": "seconds", "schema": "integer"}}
+                ]
+            },
+            {
+                "@context": "dtmi:dtdl:context;3",
+                "@id": "dtmi:com:example:Settings;1",
+                "@type": "Interface",
+                "contents": [
+                    {"@type": "Property", "name": "targetPressure", "schema": "double", "writable": true,
+                     "displayName": {"en": "Target pressure", "de": "Solldruck"}},
+                    {"@type": "Property", "name": "limits", "schema": {"@type": "Object", "fields": [
+                        {"name": "low", "schema": "double"}, {"name": "high", "schema": "double"}
+                    ]}},
+                    {"@type": "Property", "name": "modes", "schema": {"@type": "Array", "elementSchema": "string"}}
+                ]
+            }
+        ]);
+        let twin = serde_json::json!({
+            "$dtId": "pump-17",
+            "$metadata": {"$model": "dtmi:com:example:Pump;1"},
+            "serialNumber": "SN-0042",
+            "settings": {
+                "$metadata": {},
+                "targetPressure": 2.5,
+                "limits": {"low": 1, "high": 4},
+                "modes": ["eco", "boost"]
+            }
+        });
+
+        let shell = shell_from_dtdl(&interfaces, Some(&twin)).unwrap();
+        assert_eq!(shell.id, "pump-17");
+        assert_eq!(
+            shell.asset_information.asset_type.as_deref(),
+            Some("Acme Pump")
+        );
+
+        let twin = DigitalTwin::from_shell(shell).unwrap();
+        assert_eq!(twin.get_property_display("serialNumber"), "SN-0042 ");
+        assert_eq!(twin.get_property_display("flow"), " litrePerSecond");
+        assert_eq!(twin.get_property_display("settings.targetPressure"), "2.5 ");
+        assert_eq!(twin.get_property_display("settings.limits.high"), "4 ");
+        assert_eq!(twin.get_property_display("settings.modes[1]"), "boost ");
+        let flush = twin.find_element("flush").unwrap();
+        assert!(matches!(&flush.kind, ElementKind::Operation(op)
+            if op.input_variables[0].value.id_short == "seconds"));
+    }
+
+    #[test]
+    fn test_dtdl_round_trip() {
+        let json = r#"{"id": "M-1", "asset_type": "Pump", "nameplate": [
+            {"id_short": "Voltage", "value": "400", "unit": "V", "value_type": "xs:double"}
+        ]}"#;
+        let dtdl: Value =
+            serde_json::from_str(&DigitalTwin::new(json).unwrap().get_dtdl(None)).unwrap();
+        let shell = shell_from_dtdl(&dtdl, None).unwrap();
+        let voltage = &shell.nameplate[0];
+        assert_eq!(voltage.id_short, "Voltage");
+        assert_eq!(voltage.unit(), Some("V"));
+
+        assert!(shell_from_dtdl(&serde_json::json!({"@type": "Property"}), None).is_err());
+    }
+}
@@ -0,0 +1,61 @@
+// --- DTDL (Azure Digital Twins) ---
+// The shell becomes a DTDL v3 interface with one component per submodel (the
+// nameplate included); each submodel gets an interface of its own. Element
+// names are idShorts adapted to the DTDL name rules. Interfaces and twin
+// instances from Azure Digital Twins can be read back into a shell.
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{DigitalTwin, TwinError};
+
+mod export;
+mod import;
+
+pub use export::shell_to_dtdl;
+pub use import::shell_from_dtdl;
+
+pub const DTDL_CONTEXT: &str = "dtmi:dtdl:context;3";
+
+/// DTMI prefix used when none is given
+pub const DEFAULT_DTMI_PREFIX: &str = "dtmi:snaptotwin";
+
+/// A DTDL name (also a DTMI segment): letters, digits and underscores,
+/// starting with a letter and not ending with an underscore
+pub fn dtdl_name(id_short: &str) -> String {
+    let mut name: String = id_short
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if !name.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        name.insert(0, 'x');
+    }
+    name.trim_end_matches('_').to_string()
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl DigitalTwin {
+    /// Export the twin as DTDL v3 interfaces (a JSON array) for provisioning
+    /// into Azure Digital Twins. `dtmi_prefix` defaults to "dtmi:snaptotwin".
+    pub fn get_dtdl(&self, dtmi_prefix: Option<String>) -> String {
+        let prefix = dtmi_prefix.unwrap_or_else(|| DEFAULT_DTMI_PREFIX.to_string());
+        serde_json::to_string_pretty(&shell_to_dtdl(&self.data, &prefix))
+            .unwrap_or_else(|_| "[]".to_string())
+    }
+
+    /// Build a twin from DTDL interfaces (one or an array) and an optional
+    /// Azure Digital Twins twin instance supplying the property values
+    pub fn from_dtdl(
+        interfaces_json: &str,
+        twin_json: Option<String>,
+    ) -> Result<DigitalTwin, TwinError> {
+        let interfaces: serde_json::Value = serde_json::from_str(interfaces_json)
+            .map_err(|e| TwinError::parse(format!("Invalid DTDL JSON: {}", e)))?;
+        let twin: Option<serde_json::Value> = twin_json
+            .map(|t| serde_json::from_str(&t))
+            .transpose()
+            .map_err(|e| TwinError::parse(format!("Invalid twin instance JSON: {}", e)))?;
+        let data = shell_from_dtdl(&interfaces, twin.as_ref()).map_err(TwinError::parse)?;
+        DigitalTwin::from_shell(data)
+    }
+}
@@ -0,0 +1,205 @@
+// --- Structural edits ---
+// Elements can be added below a submodel or a container element and removed
+// by path at runtime. Every edit keeps the invariants checked on load:
+// idShorts are unique among siblings and list items match the list's
+// declared element type; a rejected edit leaves the twin unchanged.
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    join_path, parse_path, DigitalTwin, ElementKind, PathSegment, SubmodelElement, TwinError,
+    NAMEPLATE_ID_SHORT,
+};
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl DigitalTwin {
+    /// Add an element (in the twin's JSON format) to a submodel ("Nameplate"
+    /// for the nameplate) or to the collection, list or entity at that path;
+    /// returns the new element's path
+    pub fn add_element(&mut self, submodel: &str, element_json: &str) -> Result<String, TwinError> {
+        let element: SubmodelElement = serde_json::from_str(element_json)
+            .map_err(|e| TwinError::parse(format!("Invalid element JSON: {}", e)))?;
+        self.insert_element(submodel, element)
+    }
+
+    /// Remove the element at `path` and return it as JSON
+    pub fn remove_element(&mut self, path: &str) -> Result<String, TwinError> {
+        let element = self.remove_element_at(path)?;
+        Ok(serde_json::to_string_pretty(&element).unwrap_or_else(|_| "{}".to_string()))
+    }
+}
+
+impl DigitalTwin {
+    /// Rust-side `add_element`
+    pub fn insert_element(
+        &mut self,
+        parent: &str,
+        element: SubmodelElement,
+    ) -> Result<String, TwinError> {
+        let (prefix, siblings, list) = self.children_at_mut(parent)?;
+        let path = if list {
+            format!("{}[{}]", prefix, siblings.len())
+        } else {
+            if element.id_short.is_empty() {
+                return Err(TwinError::validation(
+                    Some(parent),
+                    format!("Element added to '{}' has no idShort", parent),
+                ));
+            }
+            if siblings.iter().any(|s| s.id_short == element.id_short) {
+                return Err(TwinError::validation(
+                    Some(parent),
+                    format!("'{}' already has an element '{}'", parent, element.id_short),
+                ));
+            }
+            join_path(&prefix, &element.id_short)
+        };
+        siblings.push(element);
+        if let Err(e) = self.check_lists() {
+            self.children_at_mut(parent)?.1.pop();
+            return Err(TwinError::validation(Some(parent), e));
+        }
+        Ok(path)
+    }
+
+    /// Rust-side `remove_element`
+    pub fn remove_element_at(&mut self, path: &str) -> Result<SubmodelElement, TwinError> {
+        let path = self.resolve_path(path)?;
+        let parsed = parse_path(&path)?;
+        let (parent, last) = parsed.split_last();
+        let parent = parent.to_string();
+        let index = match last {
+            PathSegment::Index(index) => *index,
+            PathSegment::IdShort(id_short) => self
+                .children_at_mut(&parent)?
+                .1
+                .iter()
+                .position(|s| &s.id_short == id_short)
+                .ok_or_else(|| {
+                    TwinError::not_found(&path, format!("Property '{}' not found", path))
+                })?,
+        };
+        Ok(self.children_at_mut(&parent)?.1.remove(index))
+    }
+
+    /// Elements directly below a submodel, the nameplate or a container
+    /// element, with their path prefix and whether they are list items
+    pub(crate) fn children_at_mut(
+        &mut self,
+        parent: &str,
+    ) -> Result<(String, &mut Vec<SubmodelElement>, bool), TwinError> {
+        if let Some(i) = self
+            .data
+            .submodels
+            .iter()
+            .position(|s| s.id_short == parent)
+        {
+            let sm = &mut self.data.submodels[i];
+            return Ok((parent.to_string(), &mut sm.submodel_elements, false));
+        }
+        if parent.is_empty() || parent == NAMEPLATE_ID_SHORT {
+            return Ok((String::new(), &mut self.data.nameplate, false));
+        }
+        let path = self.resolve_path(parent)?;
+        let element = self.find_element_mut(&path)?;
+        let list = matches!(element.kind, ElementKind::SubmodelElementList(_));
+        let model_type = element.kind.model_type();
+        match element.children_vec_mut() {
+            Some(children) => Ok((path, children, list)),
+            None => Err(TwinError::type_mismatch(
+                parent,
+                format!(
+                    "Element '{}' is a {} and cannot contain elements",
+                    parent, model_type
+                ),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::DigitalTwin;
+
+    const TWIN: &str = r#"{
+        "id": "MOTOR-12345",
+        "asset_type": "Siemens 1LE1",
+        "nameplate": [
+            {"id_short": "Voltage", "value": "400", "unit": "V"},
+            {"id_short": "Markings", "model_type": "SubmodelElementList",
+             "type_value_list_element": "Property", "value": [{"value": "CE", "unit": null}]}
+        ],
+        "submodels": [{
+            "id": "urn:example:sm:maintenance",
+            "id_short": "Maintenance",
+            "submodel_elements": []
+        }]
+    }"#;
+
+    #[test]
+    fn test_add_and_remove_elements() {
+        let mut twin = DigitalTwin::new(TWIN).unwrap();
+        let record = r#"{"id_short": "Calibration", "model_type": "SubmodelElementCollection", "value": [
+            {"id_short": "Date", "value": "2024-05-01", "unit": null}
+        ]}"#;
+        assert_eq!(
+            twin.add_element("Maintenance", record).unwrap(),
+            "Maintenance.Calibration"
+        );
+        let offset = r#"{"id_short": "Offset", "value": "0.2", "unit": "K"}"#;
+        assert_eq!(
+            twin.add_element("Maintenance.Calibration", offset).unwrap(),
+            "Maintenance.Calibration.Offset"
+        );
+        assert_eq!(
+            twin.add_element("Markings", r#"{"value": "UL", "unit": null}"#)
+                .unwrap(),
+            "Markings[1]"
+        );
+        assert_eq!(
+            twin.get_property_display("Maintenance.Calibration.Offset"),
+            "0.2 K"
+        );
+        assert_eq!(twin.get_property_display("Markings[1]"), "UL ");
+        assert!(twin.get_aas_json().contains("Calibration"));
+
+        let removed = twin.remove_element("Markings[0]").unwrap();
+        assert!(removed.contains("\"CE\""));
+        assert_eq!(twin.get_property_display("Markings[0]"), "UL ");
+        twin.remove_element("Voltage").unwrap();
+        twin.remove_element_at("Maintenance.Calibration.Date")
+            .unwrap();
+        assert_eq!(
+            twin.list_properties_display(),
+            "Markings[0], Maintenance.Calibration.Offset"
+        );
+    }
+
+    #[test]
+    fn test_rejected_edits_leave_the_twin_unchanged() {
+        let mut twin = DigitalTwin::new(TWIN).unwrap();
+        let voltage =
+            serde_json::from_str(r#"{"id_short": "Voltage", "value": "230", "unit": "V"}"#)
+                .unwrap();
+        assert_eq!(
+            twin.insert_element("Nameplate", voltage)
+                .unwrap_err()
+                .message(),
+            "'Nameplate' already has an element 'Voltage'"
+        );
+        let range =
+            serde_json::from_str(r#"{"model_type": "Range", "min": "0", "max": "1"}"#).unwrap();
+        assert!(twin
+            .insert_element("Markings", range)
+            .unwrap_err()
+            .message()
+            .contains("expected Property"));
+        let child =
+            serde_json::from_str(r#"{"id_short": "X", "value": "1", "unit": null}"#).unwrap();
+        assert!(twin.insert_element("Voltage", child).is_err());
+        assert!(twin.remove_element_at("Maintenance.Missing").is_err());
+        assert_eq!(twin.get_property_display("Voltage"), "400 V");
+        assert_eq!(twin.list_properties_display(), "Voltage, Markings[0]");
+    }
+}
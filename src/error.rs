@@ -0,0 +1,176 @@
+// --- Errors ---
+// Every fallible method of the twin reports a TwinError. JavaScript receives
+// it as an `Error` whose `code` and `path` properties tell the failure modes
+// apart without parsing the message.
+
+use std::fmt;
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+/// Why a twin operation failed
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TwinError {
+    /// Malformed input: JSON, XML, AASX, binary encodings
+    ParseError { message: String },
+    /// No element, submodel or other object at `path`
+    NotFound { path: String, message: String },
+    /// The element at `path` has the wrong kind or valueType for the request
+    TypeMismatch { path: String, message: String },
+    /// The model would violate an AAS constraint
+    ValidationFailed {
+        path: Option<String>,
+        message: String,
+    },
+    /// An argument outside the accepted values (unknown category, index out
+    /// of bounds, ...)
+    InvalidArgument { message: String },
+    /// A valid request that could not be carried out (rejected event, failing
+    /// operation handler, ...)
+    OperationFailed {
+        path: Option<String>,
+        message: String,
+    },
+}
+
+impl TwinError {
+    pub fn parse(message: impl Into<String>) -> TwinError {
+        TwinError::ParseError {
+            message: message.into(),
+        }
+    }
+
+    pub fn not_found(path: &str, message: impl Into<String>) -> TwinError {
+        TwinError::NotFound {
+            path: path.to_string(),
+            message: message.into(),
+        }
+    }
+
+    pub fn type_mismatch(path: &str, message: impl Into<String>) -> TwinError {
+        TwinError::TypeMismatch {
+            path: path.to_string(),
+            message: message.into(),
+        }
+    }
+
+    pub fn validation(path: Option<&str>, message: impl Into<String>) -> TwinError {
+        TwinError::ValidationFailed {
+            path: path.map(str::to_string),
+            message: message.into(),
+        }
+    }
+
+    pub fn invalid_argument(message: impl Into<String>) -> TwinError {
+        TwinError::InvalidArgument {
+            message: message.into(),
+        }
+    }
+
+    pub fn failed(path: Option<&str>, message: impl Into<String>) -> TwinError {
+        TwinError::OperationFailed {
+            path: path.map(str::to_string),
+            message: message.into(),
+        }
+    }
+
+    /// Stable identifier of the failure mode, e.g. "NOT_FOUND"
+    pub fn code(&self) -> &'static str {
+        match self {
+            TwinError::ParseError { .. } => "PARSE_ERROR",
+            TwinError::NotFound { .. } => "NOT_FOUND",
+            TwinError::TypeMismatch { .. } => "TYPE_MISMATCH",
+            TwinError::ValidationFailed { .. } => "VALIDATION_FAILED",
+            TwinError::InvalidArgument { .. } => "INVALID_ARGUMENT",
+            TwinError::OperationFailed { .. } => "OPERATION_FAILED",
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            TwinError::ParseError { message }
+            | TwinError::NotFound { message, .. }
+            | TwinError::TypeMismatch { message, .. }
+            | TwinError::ValidationFailed { message, .. }
+            | TwinError::InvalidArgument { message }
+            | TwinError::OperationFailed { message, .. } => message,
+        }
+    }
+
+    /// Path of the element or submodel the error is about, if any
+    pub fn path(&self) -> Option<&str> {
+        match self {
+            TwinError::NotFound { path, .. } | TwinError::TypeMismatch { path, .. } => Some(path),
+            TwinError::ValidationFailed { path, .. } | TwinError::OperationFailed { path, .. } => {
+                path.as_deref()
+            }
+            TwinError::ParseError { .. } | TwinError::InvalidArgument { .. } => None,
+        }
+    }
+}
+
+impl fmt::Display for TwinError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.message())
+    }
+}
+
+impl std::error::Error for TwinError {}
+
+/// Helpers that report plain messages keep working with `?`
+impl From<TwinError> for String {
+    fn from(error: TwinError) -> String {
+        error.message().to_string()
+    }
+}
+
+/// A JavaScript `Error` named "TwinError" with `code` and `path` properties
+#[cfg(feature = "wasm")]
+impl From<TwinError> for JsValue {
+    fn from(error: TwinError) -> JsValue {
+        let js = js_sys::Error::new(error.message());
+        js.set_name("TwinError");
+        let path = error.path().map(JsValue::from).unwrap_or(JsValue::NULL);
+        let _ = js_sys::Reflect::set(&js, &"code".into(), &error.code().into());
+        let _ = js_sys::Reflect::set(&js, &"path".into(), &path);
+        js.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{DigitalTwin, TwinError};
+
+    #[test]
+    fn test_error_codes() {
+        let json = r#"{
+            "id": "MOTOR-12345",
+            "asset_type": "Siemens 1LE1",
+            "nameplate": [
+                {"id_short": "Voltage", "value": "400", "unit": "V", "value_type": "xs:double"},
+                {"id_short": "Note", "value": "spare", "unit": null, "value_type": "xs:string"}
+            ]
+        }"#;
+        let mut twin = DigitalTwin::new(json).unwrap();
+
+        let err = twin.get_property_f64("Torque").unwrap_err();
+        assert_eq!(err.code(), "NOT_FOUND");
+        assert_eq!(err.path(), Some("Torque"));
+        assert_eq!(err.message(), "Property 'Torque' not found");
+
+        let err = twin.get_property_f64("Note").unwrap_err();
+        assert_eq!(err.code(), "TYPE_MISMATCH");
+        assert_eq!(err.path(), Some("Note"));
+        let err = twin.set_property("Voltage", "high").unwrap_err();
+        assert_eq!(err.code(), "TYPE_MISMATCH");
+
+        assert_eq!(
+            twin.list_properties_by_category("HOT").unwrap_err().code(),
+            "INVALID_ARGUMENT"
+        );
+        assert!(matches!(
+            DigitalTwin::new("{not json"),
+            Err(TwinError::ParseError { .. })
+        ));
+    }
+}
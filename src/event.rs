@@ -0,0 +1,288 @@
+// --- Event emission ---
+// BasicEventElements declare which parts of the twin send events; emitting goes
+// through them so that switched-off or input-only events are rejected and the
+// element's lastUpdate stays current
+
+use serde::Serialize;
+use serde_json::Value;
+#[cfg(feature = "wasm")]
+use tsify::Tsify;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+#[cfg(feature = "wasm")]
+use crate::to_js;
+use crate::{BasicEventElement, DigitalTwin, Direction, ElementKind, StateOfEvent, TwinError};
+
+/// A BasicEventElement flattened for the frontend
+#[derive(Serialize, Clone, Debug)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[cfg_attr(feature = "wasm", tsify(missing_as_null))]
+pub struct EventEntry {
+    pub path: String,
+    /// The observed reference in its textual form
+    pub observed: String,
+    pub direction: Direction,
+    pub state: StateOfEvent,
+    pub message_topic: Option<String>,
+    pub last_update: Option<String>,
+}
+
+/// One event sent through a BasicEventElement
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[cfg_attr(feature = "wasm", tsify(missing_as_null))]
+pub struct EventMessage {
+    /// Path of the emitting BasicEventElement
+    pub source: String,
+    pub observed: String,
+    pub topic: Option<String>,
+    pub timestamp: String,
+    #[cfg_attr(feature = "wasm", tsify(type = "unknown"))]
+    pub payload: Value,
+}
+
+/// Receives every emitted event, e.g. to forward it to an MQTT client
+pub type EventListener = Box<dyn Fn(&EventMessage)>;
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl DigitalTwin {
+    /// List all BasicEventElements as an array of `{path, observed,
+    /// direction, state, message_topic, last_update}` entries
+    #[cfg(feature = "wasm")]
+    #[wasm_bindgen(unchecked_return_type = "EventEntry[]")]
+    pub fn list_events(&self) -> JsValue {
+        to_js(&self.event_entries())
+    }
+
+    /// Emit an event through the BasicEventElement at `path` with a JSON payload,
+    /// timestamped with the current time; returns the event message as JSON
+    pub fn emit_event(&mut self, path: &str, payload_json: &str) -> Result<String, TwinError> {
+        let payload: Value = serde_json::from_str(payload_json)
+            .map_err(|e| TwinError::invalid_argument(format!("Invalid event payload: {}", e)))?;
+        let message = self.emit(path, payload, &format_timestamp(now_millis()))?;
+        Ok(serde_json::to_string(&message).unwrap_or_else(|_| "{}".to_string()))
+    }
+
+    /// Register a JavaScript function called with every emitted event object
+    #[cfg(feature = "wasm")]
+    pub fn on_event(&mut self, listener: js_sys::Function) {
+        self.add_event_listener(Box::new(move |message| {
+            let json = serde_json::to_string(message).unwrap_or_else(|_| "{}".to_string());
+            if let Ok(event) = js_sys::JSON::parse(&json) {
+                let _ = listener.call1(&JsValue::NULL, &event);
+            }
+        }));
+    }
+
+    /// Return the events emitted since the last call as `EventMessage`
+    /// objects and clear the queue
+    #[cfg(feature = "wasm")]
+    #[wasm_bindgen(unchecked_return_type = "EventMessage[]")]
+    pub fn take_events(&mut self) -> JsValue {
+        to_js(&self.take_event_messages())
+    }
+}
+
+impl DigitalTwin {
+    /// Rust-side `take_events`
+    pub fn take_event_messages(&mut self) -> Vec<EventMessage> {
+        std::mem::take(&mut self.event_queue)
+    }
+
+    /// Rust-side `list_events`: BasicEventElements across the nameplate and
+    /// all submodels
+    pub fn event_entries(&self) -> Vec<EventEntry> {
+        self.elements()
+            .filter_map(|(path, elem)| match &elem.kind {
+                ElementKind::BasicEventElement(e) => Some(EventEntry {
+                    path,
+                    observed: e.observed.to_string(),
+                    direction: e.direction,
+                    state: e.state,
+                    message_topic: e.message_topic.clone(),
+                    last_update: e.last_update.clone(),
+                }),
+                _ => None,
+            })
+            .collect()
+    }
+
+    pub fn add_event_listener(&mut self, listener: EventListener) {
+        self.event_listeners.push(listener);
+    }
+
+    /// Emit an event through the output BasicEventElement at `path`: updates its
+    /// lastUpdate, notifies the listeners and queues the message
+    pub fn emit(
+        &mut self,
+        path: &str,
+        payload: Value,
+        timestamp: &str,
+    ) -> Result<EventMessage, TwinError> {
+        let event = self.event_at_mut(path)?;
+        if event.direction != Direction::Output {
+            return Err(TwinError::failed(
+                Some(path),
+                format!("Event '{}' is an input and cannot be emitted", path),
+            ));
+        }
+        if event.state != StateOfEvent::On {
+            return Err(TwinError::failed(
+                Some(path),
+                format!("Event '{}' is switched off", path),
+            ));
+        }
+        event.last_update = Some(timestamp.to_string());
+
+        let message = EventMessage {
+            source: path.to_string(),
+            observed: event.observed.to_string(),
+            topic: event.message_topic.clone(),
+            timestamp: timestamp.to_string(),
+            payload,
+        };
+        for listener in &self.event_listeners {
+            listener(&message);
+        }
+        self.event_queue.push(message.clone());
+        Ok(message)
+    }
+
+    fn event_at_mut(&mut self, path: &str) -> Result<&mut BasicEventElement, TwinError> {
+        match &mut self.find_element_mut(path)?.kind {
+            ElementKind::BasicEventElement(e) => Ok(e),
+            other => Err(TwinError::type_mismatch(
+                path,
+                format!(
+                    "Element '{}' is a {}, not a BasicEventElement",
+                    path,
+                    other.model_type()
+                ),
+            )),
+        }
+    }
+}
+
+/// Milliseconds since the Unix epoch; natively built wasm-feature tests have
+/// no JavaScript clock
+#[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+pub(crate) fn now_millis() -> u64 {
+    js_sys::Date::now() as u64
+}
+
+#[cfg(not(all(feature = "wasm", target_arch = "wasm32")))]
+pub(crate) fn now_millis() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis() as u64)
+}
+
+/// An xs:dateTime in UTC with milliseconds, as JavaScript's `toISOString`
+pub(crate) fn format_timestamp(millis: u64) -> String {
+    let (days, ms) = (millis / 86_400_000, millis % 86_400_000);
+    // Civil date from days since 1970-01-01 (proleptic Gregorian calendar)
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        year,
+        month,
+        day,
+        ms / 3_600_000,
+        ms / 60_000 % 60,
+        ms / 1000 % 60,
+        ms % 1000
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use serde_json::json;
+
+    use super::format_timestamp;
+    use crate::DigitalTwin;
+
+    const TWIN: &str = r#"{
+        "id": "MOTOR-12345",
+        "asset_type": "Siemens 1LE1",
+        "nameplate": [],
+        "submodels": [{
+            "id": "urn:example:sm:op",
+            "id_short": "Operational",
+            "submodel_elements": [
+                {"id_short": "Speed", "value": "1450"},
+                {"id_short": "SpeedChanged", "model_type": "BasicEventElement",
+                 "observed": {"type": "ModelReference", "keys": [
+                     {"type": "Submodel", "value": "urn:example:sm:op"},
+                     {"type": "Property", "value": "Speed"}]},
+                 "direction": "output", "state": "on", "message_topic": "motors/12345/speed"},
+                {"id_short": "Muted", "model_type": "BasicEventElement",
+                 "observed": {"type": "ModelReference", "keys": [
+                     {"type": "Submodel", "value": "urn:example:sm:op"}]},
+                 "direction": "output", "state": "off"}
+            ]
+        }]
+    }"#;
+
+    #[test]
+    fn test_emit_event() {
+        let mut twin = DigitalTwin::new(TWIN).unwrap();
+        let seen = Rc::new(RefCell::new(Vec::new()));
+        let sink = seen.clone();
+        twin.add_event_listener(Box::new(move |m| sink.borrow_mut().push(m.clone())));
+
+        let message = twin
+            .emit(
+                "Operational.SpeedChanged",
+                json!({"Speed": 1500}),
+                "2024-05-01T12:00:00Z",
+            )
+            .unwrap();
+        assert_eq!(message.topic.as_deref(), Some("motors/12345/speed"));
+        assert_eq!(seen.borrow().as_slice(), [message]);
+        assert_eq!(
+            twin.event_entries()[0].last_update.as_deref(),
+            Some("2024-05-01T12:00:00Z")
+        );
+        assert_eq!(
+            twin.take_event_messages()[0].topic.as_deref(),
+            Some("motors/12345/speed")
+        );
+        assert!(twin.take_event_messages().is_empty());
+    }
+
+    #[test]
+    fn test_switched_off_event_is_rejected() {
+        let mut twin = DigitalTwin::new(TWIN).unwrap();
+        let err = twin
+            .emit("Operational.Muted", json!(null), "2024-05-01T12:00:00Z")
+            .unwrap_err();
+        assert_eq!(err.code(), "OPERATION_FAILED");
+        assert!(err.message().contains("switched off"));
+        assert!(twin.emit("Operational.Speed", json!(null), "now").is_err());
+    }
+
+    #[test]
+    fn test_format_timestamp() {
+        assert_eq!(format_timestamp(0), "1970-01-01T00:00:00.000Z");
+        assert_eq!(
+            format_timestamp(951_782_400_000),
+            "2000-02-29T00:00:00.000Z"
+        );
+        assert_eq!(
+            format_timestamp(1_718_000_000_123),
+            "2024-06-10T06:13:20.123Z"
+        );
+    }
+}
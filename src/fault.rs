@@ -0,0 +1,216 @@
+// --- Fault injection ---
+// Faults are simulation inputs named "fault.<kind>" carrying their severity
+// from 0 (absent) to 1 (fully developed), which the models turn into
+// symptoms: bearing wear adds the defect lines to the vibration, phase loss
+// raises the current and weakens the torque, overload adds to the commanded
+// load and sensor drift runs the winding temperature reading away. Injecting
+// or clearing a fault raises an alarm through the OperationalData Alarm
+// event, so operators can train on the twin and anomaly detectors can be
+// tested against known faults.
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+#[cfg(feature = "wasm")]
+use tsify::Tsify;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+#[cfg(feature = "wasm")]
+use crate::to_js;
+#[cfg(test)]
+use crate::SENSOR_DRIFT_RATE;
+use crate::{enum_name, DigitalTwin, SimulationValues, TwinError};
+
+/// Event of the OperationalData submodel raising fault alarms
+pub const ALARM_ID_SHORT: &str = "Alarm";
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[serde(rename_all = "snake_case")]
+pub enum FaultKind {
+    BearingWear,
+    PhaseLoss,
+    Overload,
+    SensorDrift,
+}
+
+impl FaultKind {
+    pub const ALL: [FaultKind; 4] = [
+        FaultKind::BearingWear,
+        FaultKind::PhaseLoss,
+        FaultKind::Overload,
+        FaultKind::SensorDrift,
+    ];
+
+    /// A kind by its name, e.g. "phase_loss"
+    pub fn parse(name: &str) -> Option<FaultKind> {
+        serde_json::from_value(serde_json::Value::String(name.to_string())).ok()
+    }
+
+    /// Simulation input carrying the severity, e.g. "fault.phase_loss"
+    pub fn input(self) -> String {
+        format!("fault.{}", enum_name(&self))
+    }
+
+    /// Severity among a model's inputs, 0 when the fault is absent
+    pub fn severity(self, inputs: &SimulationValues) -> f64 {
+        inputs.get(&self.input()).copied().unwrap_or(0.0)
+    }
+
+    fn alarm_message(self) -> &'static str {
+        match self {
+            FaultKind::BearingWear => "Bearing wear: vibration at the defect frequencies",
+            FaultKind::PhaseLoss => "Phase loss: the motor runs on two phases",
+            FaultKind::Overload => "Overload of the driven machine",
+            FaultKind::SensorDrift => "Winding temperature sensor drifting",
+        }
+    }
+}
+
+/// An injected fault
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+pub struct Fault {
+    pub kind: FaultKind,
+    /// From 0 (absent) to 1 (fully developed)
+    pub severity: f64,
+}
+
+fn parse_kind(kind: &str) -> Result<FaultKind, TwinError> {
+    FaultKind::parse(kind).ok_or_else(|| {
+        TwinError::invalid_argument(format!(
+            "Unknown fault '{}', expected bearing_wear, phase_loss, overload or sensor_drift",
+            kind
+        ))
+    })
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl DigitalTwin {
+    /// Inject a fault ("bearing_wear", "phase_loss", "overload",
+    /// "sensor_drift") with a severity above 0 and up to 1, or change the
+    /// severity of an injected one; it takes effect from the next tick and
+    /// raises an alarm
+    pub fn inject_fault(&mut self, kind: &str, severity: f64) -> Result<(), TwinError> {
+        let kind = parse_kind(kind)?;
+        if !(severity > 0.0 && severity <= 1.0) {
+            return Err(TwinError::invalid_argument(format!(
+                "Fault severity must be above 0 and at most 1, got {}",
+                severity
+            )));
+        }
+        self.set_simulation_input(&kind.input(), severity);
+        self.raise_alarm(kind, severity)
+    }
+
+    /// Remove an injected fault, announcing it with an alarm; nothing
+    /// happens if it is not injected
+    pub fn clear_fault(&mut self, kind: &str) -> Result<(), TwinError> {
+        let kind = parse_kind(kind)?;
+        if self.remove_simulation_input(&kind.input()).is_none() {
+            return Ok(());
+        }
+        self.raise_alarm(kind, 0.0)
+    }
+
+    /// Remove every injected fault
+    pub fn clear_faults(&mut self) -> Result<(), TwinError> {
+        for fault in self.active_faults() {
+            self.clear_fault(&enum_name(&fault.kind))?;
+        }
+        Ok(())
+    }
+
+    /// The injected faults as a `Fault` array
+    #[cfg(feature = "wasm")]
+    #[wasm_bindgen(unchecked_return_type = "Fault[]")]
+    pub fn get_faults(&self) -> JsValue {
+        to_js(&self.active_faults())
+    }
+}
+
+impl DigitalTwin {
+    /// Rust-side `get_faults`
+    pub fn active_faults(&self) -> Vec<Fault> {
+        FaultKind::ALL
+            .into_iter()
+            .filter_map(|kind| {
+                let severity = self.simulation_input(&kind.input())?;
+                Some(Fault { kind, severity })
+            })
+            .collect()
+    }
+
+    fn raise_alarm(&mut self, kind: FaultKind, severity: f64) -> Result<(), TwinError> {
+        let payload = json!({
+            "fault": kind,
+            "severity": severity,
+            "active": severity > 0.0,
+            "message": kind.alarm_message(),
+        });
+        self.announce(ALARM_ID_SHORT, None, payload)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"{"id": "M-1", "asset_type": "Motor", "nameplate": []}"#;
+
+    fn run(twin: &mut DigitalTwin, ticks: usize) -> SimulationValues {
+        for _ in 0..ticks {
+            twin.advance_simulation();
+        }
+        twin.simulation_values().clone()
+    }
+
+    #[test]
+    fn test_faults_alter_outputs() {
+        let mut healthy = DigitalTwin::new(CONFIG).unwrap();
+        let healthy = run(&mut healthy, 20);
+        let faulty = |kind: &str| {
+            let mut twin = DigitalTwin::new(CONFIG).unwrap();
+            twin.inject_fault(kind, 1.0).unwrap();
+            run(&mut twin, 20)
+        };
+
+        assert!(faulty("bearing_wear")["vibration.rms"] > healthy["vibration.rms"] + 1.0);
+        let phase_loss = faulty("phase_loss");
+        assert!(phase_loss["motor.current"] > 1.5 * healthy["motor.current"]);
+        assert!(phase_loss["motor.slip"] > healthy["motor.slip"]);
+        assert!(faulty("overload")["motor.current"] > healthy["motor.current"]);
+        let drift = faulty("sensor_drift")["thermal.winding_temperature"]
+            - healthy["thermal.winding_temperature"];
+        assert!((drift - 20.0 * SENSOR_DRIFT_RATE).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_faults_raise_alarms() {
+        let mut twin = DigitalTwin::new(CONFIG).unwrap();
+        twin.inject_fault("phase_loss", 0.5).unwrap();
+        assert_eq!(
+            twin.active_faults(),
+            vec![Fault {
+                kind: FaultKind::PhaseLoss,
+                severity: 0.5
+            }]
+        );
+        assert_eq!(twin.status().alarms, 1);
+        twin.clear_faults().unwrap();
+        assert!(twin.active_faults().is_empty());
+        twin.clear_fault("phase_loss").unwrap();
+
+        let events: serde_json::Value = serde_json::to_value(twin.take_event_messages()).unwrap();
+        assert_eq!(events.as_array().unwrap().len(), 2);
+        assert_eq!(events[0]["source"], "OperationalData.Alarm");
+        assert_eq!(events[0]["payload"]["fault"], "phase_loss");
+        assert_eq!(events[1]["payload"]["active"], false);
+
+        assert!(twin
+            .inject_fault("corrosion", 0.5)
+            .is_err_and(|e| e.code() == "INVALID_ARGUMENT"));
+        assert!(twin.inject_fault("overload", 1.5).is_err());
+        assert!(twin.inject_fault("overload", f64::NAN).is_err());
+    }
+}
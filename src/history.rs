@@ -0,0 +1,370 @@
+// --- Signal history ---
+// The twin keeps the latest values of every simulation signal with the
+// simulated time they were computed at, so a chart can be drawn from the
+// twin alone instead of the frontend buffering every frame. Each signal has
+// a ring buffer of the same capacity; signals fed from telemetry hold the
+// ingested readings while those are live, and numeric properties updated by
+// telemetry records have one under their path. Long histories can be
+// aggregated into fixed windows (min, max or average per window) before they
+// cross to JavaScript, so a chart gets one point per bucket instead of every
+// sample. Resetting or resuming the simulation starts the history afresh.
+
+use std::collections::{BTreeMap, VecDeque};
+
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "wasm")]
+use tsify::Tsify;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+#[cfg(feature = "wasm")]
+use crate::to_js;
+use crate::{enum_name, DigitalTwin, SimulationValues, TwinError};
+
+/// Values kept per signal unless set otherwise
+pub const DEFAULT_HISTORY_CAPACITY: usize = 1000;
+
+/// A past value of a signal
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+pub struct HistoryPoint {
+    /// Simulated seconds since the last reset
+    pub time: f64,
+    pub value: f64,
+}
+
+/// How the values in a window of the history are combined
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[serde(rename_all = "snake_case")]
+pub enum Aggregation {
+    Min,
+    Max,
+    Avg,
+}
+
+impl Aggregation {
+    /// An aggregation by its name, e.g. "avg"
+    pub fn parse(name: &str) -> Option<Aggregation> {
+        serde_json::from_value(serde_json::Value::String(name.to_string())).ok()
+    }
+
+    fn apply(self, values: &[f64]) -> f64 {
+        match self {
+            Aggregation::Min => values.iter().copied().fold(f64::INFINITY, f64::min),
+            Aggregation::Max => values.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+            Aggregation::Avg => values.iter().sum::<f64>() / values.len() as f64,
+        }
+    }
+}
+
+/// Ring buffers of every signal's latest values
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct SignalHistory {
+    capacity: usize,
+    signals: BTreeMap<String, VecDeque<HistoryPoint>>,
+}
+
+impl Default for SignalHistory {
+    fn default() -> SignalHistory {
+        SignalHistory {
+            capacity: DEFAULT_HISTORY_CAPACITY,
+            signals: BTreeMap::new(),
+        }
+    }
+}
+
+impl SignalHistory {
+    /// Append the values every signal has at `time`, dropping the oldest
+    /// beyond the capacity
+    pub(crate) fn record(&mut self, values: &SimulationValues, time: f64) {
+        for (key, value) in values {
+            self.push(key, *value, time);
+        }
+    }
+
+    /// Append one value of the signal or property `key`
+    pub(crate) fn push(&mut self, key: &str, value: f64, time: f64) {
+        if self.capacity == 0 {
+            return;
+        }
+        let points = self.signals.entry(key.to_string()).or_default();
+        // Readings may arrive out of order; the history stays sorted by time
+        let index = points.partition_point(|p| p.time <= time);
+        points.insert(index, HistoryPoint { time, value });
+        if points.len() > self.capacity {
+            points.pop_front();
+        }
+    }
+
+    /// Time of the latest value of `key`
+    pub(crate) fn latest_time(&self, key: &str) -> Option<f64> {
+        Some(self.signals.get(key)?.back()?.time)
+    }
+
+    /// Keys of the signals and properties with a history
+    pub(crate) fn keys(&self) -> impl Iterator<Item = &str> {
+        self.signals.keys().map(String::as_str)
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.signals.clear();
+    }
+
+    /// Keep at most `capacity` values per signal, the latest ones
+    pub(crate) fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        for points in self.signals.values_mut() {
+            let excess = points.len().saturating_sub(capacity);
+            points.drain(..excess);
+        }
+    }
+
+    /// At most `max_points` values of a signal spread evenly over its
+    /// history from the oldest to the latest; all of them for 0
+    fn points(&self, key: &str, max_points: usize) -> Option<Vec<HistoryPoint>> {
+        let points = self.signals.get(key)?;
+        let len = points.len();
+        if max_points == 0 || len <= max_points {
+            return Some(points.iter().copied().collect());
+        }
+        // Indices counted back from the latest value
+        let mut picked: Vec<HistoryPoint> = (0..max_points)
+            .map(|i| points[len - 1 - i * (len - 1) / (max_points - 1).max(1)])
+            .collect();
+        picked.reverse();
+        Some(picked)
+    }
+}
+
+/// Histories of several signals side by side
+pub(crate) struct HistoryTable {
+    pub(crate) keys: Vec<String>,
+    /// A row per point in time at which any of the signals has a value,
+    /// oldest first, with the value of each signal at that time
+    pub(crate) rows: Vec<(f64, Vec<Option<f64>>)>,
+}
+
+/// One point per window of `window` seconds that holds values, at the
+/// window's start; windows start at multiples of `window`
+fn aggregate(points: &[HistoryPoint], window: f64, aggregation: Aggregation) -> Vec<HistoryPoint> {
+    let mut buckets: Vec<(f64, Vec<f64>)> = Vec::new();
+    for point in points {
+        let start = (point.time / window).floor() * window;
+        match buckets.last_mut() {
+            Some((bucket, values)) if *bucket == start => values.push(point.value),
+            _ => buckets.push((start, vec![point.value])),
+        }
+    }
+    buckets
+        .into_iter()
+        .map(|(time, values)| HistoryPoint {
+            time,
+            value: aggregation.apply(&values),
+        })
+        .collect()
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl DigitalTwin {
+    /// Past values of a signal ("motor.rpm") or of a property updated by
+    /// telemetry as a `HistoryPoint` array,
+    /// oldest first, thinned out evenly to at most `max_points` (0 for all)
+    #[cfg(feature = "wasm")]
+    #[wasm_bindgen(unchecked_return_type = "HistoryPoint[]")]
+    pub fn get_history(&self, name: &str, max_points: u32) -> Result<JsValue, TwinError> {
+        Ok(to_js(&self.history(name, max_points as usize)?))
+    }
+
+    /// The history of a signal combined per window of `window_seconds` by
+    /// `function` ("min", "max" or "avg") as a `HistoryPoint` array, one
+    /// point at the start of every window that holds values
+    #[cfg(feature = "wasm")]
+    #[wasm_bindgen(unchecked_return_type = "HistoryPoint[]")]
+    pub fn aggregate(
+        &self,
+        name: &str,
+        window_seconds: f64,
+        function: &str,
+    ) -> Result<JsValue, TwinError> {
+        let aggregation = Aggregation::parse(function).ok_or_else(|| {
+            TwinError::invalid_argument(format!(
+                "Unknown aggregation '{}', expected min, max or avg",
+                function
+            ))
+        })?;
+        Ok(to_js(&self.aggregate_history(
+            name,
+            window_seconds,
+            aggregation,
+        )?))
+    }
+
+    /// Keep the latest `capacity` values of every signal (0 turns the
+    /// history off), dropping older ones right away
+    pub fn set_history_capacity(&mut self, capacity: u32) {
+        self.simulation
+            .history_mut()
+            .set_capacity(capacity as usize);
+    }
+}
+
+impl DigitalTwin {
+    /// Rust-side `get_history`
+    pub fn history(&self, name: &str, max_points: usize) -> Result<Vec<HistoryPoint>, TwinError> {
+        self.simulation
+            .history()
+            .points(name, max_points)
+            .ok_or_else(|| TwinError::not_found(name, format!("No history of signal '{}'", name)))
+    }
+
+    /// The history of `keys`, of every signal with one if empty
+    pub(crate) fn history_table(&self, keys: &[String]) -> Result<HistoryTable, TwinError> {
+        let keys: Vec<String> = match keys {
+            [] => self
+                .simulation
+                .history()
+                .keys()
+                .map(str::to_string)
+                .collect(),
+            _ => keys.to_vec(),
+        };
+        let mut rows: Vec<(f64, Vec<Option<f64>>)> = Vec::new();
+        for (column, key) in keys.iter().enumerate() {
+            for point in self.history(key, 0)? {
+                let index = rows.partition_point(|(t, _)| *t < point.time);
+                if rows.get(index).is_none_or(|(t, _)| *t != point.time) {
+                    rows.insert(index, (point.time, vec![None; keys.len()]));
+                }
+                rows[index].1[column] = Some(point.value);
+            }
+        }
+        Ok(HistoryTable { keys, rows })
+    }
+
+    /// `history_table` limited to the rows from `from` to `to` seconds, both
+    /// inclusive and open when omitted
+    pub(crate) fn history_range(
+        &self,
+        keys: &[String],
+        from: Option<f64>,
+        to: Option<f64>,
+    ) -> Result<HistoryTable, TwinError> {
+        let (from, to) = (
+            from.unwrap_or(f64::NEG_INFINITY),
+            to.unwrap_or(f64::INFINITY),
+        );
+        if from.is_nan() || to.is_nan() || from > to {
+            return Err(TwinError::invalid_argument(format!(
+                "History range must run from an earlier to a later time, got {} to {}",
+                from, to
+            )));
+        }
+        let mut table = self.history_table(keys)?;
+        table.rows.retain(|(time, _)| (from..=to).contains(time));
+        Ok(table)
+    }
+
+    /// Unit of a signal's model output, or of the property `key` names
+    pub(crate) fn history_unit(&self, key: &str) -> Option<String> {
+        match self.live_values().remove(key) {
+            Some(value) => value.unit,
+            None => self.find_element(key).ok()?.unit().map(str::to_string),
+        }
+    }
+
+    /// Rust-side `aggregate`
+    pub fn aggregate_history(
+        &self,
+        name: &str,
+        window_seconds: f64,
+        aggregation: Aggregation,
+    ) -> Result<Vec<HistoryPoint>, TwinError> {
+        if !(window_seconds.is_finite() && window_seconds > 0.0) {
+            return Err(TwinError::invalid_argument(format!(
+                "Window of the {} aggregation must be a positive number of seconds, got {}",
+                enum_name(&aggregation),
+                window_seconds
+            )));
+        }
+        let points = self.history(name, 0)?;
+        Ok(aggregate(&points, window_seconds, aggregation))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_history_keeps_latest_values() {
+        let mut twin =
+            DigitalTwin::new(r#"{"id": "M-1", "asset_type": "Motor", "nameplate": []}"#).unwrap();
+        twin.set_history_capacity(50);
+        for _ in 0..80 {
+            twin.advance_by(0.1).unwrap();
+        }
+        let history = twin.history("motor.rpm", 0).unwrap();
+        assert_eq!(history.len(), 50);
+        let latest = history.last().unwrap();
+        assert!((latest.time - 8.0).abs() < 1e-9);
+        assert_eq!(latest.value, twin.simulation_values()["motor.rpm"]);
+        assert!((history[0].time - 3.1).abs() < 1e-9);
+
+        let thinned = twin.history("motor.rpm", 5).unwrap();
+        assert_eq!(thinned.len(), 5);
+        assert_eq!((thinned[0], thinned[4]), (history[0], *latest));
+        assert_eq!(twin.history("motor.rpm", 1).unwrap(), vec![*latest]);
+
+        twin.set_history_capacity(10);
+        assert_eq!(twin.history("motor.rpm", 0).unwrap()[..], history[40..]);
+        twin.reset_simulation();
+        assert_eq!(twin.history("motor.rpm", 0).unwrap().len(), 1);
+        assert!(twin
+            .history("motor.torque_x", 0)
+            .is_err_and(|e| e.code() == "NOT_FOUND"));
+    }
+
+    #[test]
+    fn test_aggregate_windows() {
+        let points: Vec<HistoryPoint> =
+            [(0.0, 4.0), (0.5, 2.0), (1.0, 6.0), (3.2, 1.0), (3.9, 3.0)]
+                .into_iter()
+                .map(|(time, value)| HistoryPoint { time, value })
+                .collect();
+        let series = |aggregation| -> Vec<(f64, f64)> {
+            aggregate(&points, 1.0, aggregation)
+                .iter()
+                .map(|p| (p.time, p.value))
+                .collect()
+        };
+        assert_eq!(
+            series(Aggregation::Min),
+            [(0.0, 2.0), (1.0, 6.0), (3.0, 1.0)]
+        );
+        assert_eq!(
+            series(Aggregation::Max),
+            [(0.0, 4.0), (1.0, 6.0), (3.0, 3.0)]
+        );
+        assert_eq!(
+            series(Aggregation::Avg),
+            [(0.0, 3.0), (1.0, 6.0), (3.0, 2.0)]
+        );
+        assert_eq!(Aggregation::parse("avg"), Some(Aggregation::Avg));
+        assert_eq!(Aggregation::parse("median"), None);
+
+        let mut twin =
+            DigitalTwin::new(r#"{"id": "M-1", "asset_type": "Motor", "nameplate": []}"#).unwrap();
+        for _ in 0..99 {
+            twin.advance_by(0.1).unwrap();
+        }
+        let buckets = twin
+            .aggregate_history("motor.rpm", 2.5, Aggregation::Max)
+            .unwrap();
+        assert_eq!(buckets.len(), 4);
+        assert!(buckets.windows(2).all(|w| w[1].time - w[0].time == 2.5));
+        assert!(twin
+            .aggregate_history("motor.rpm", 0.0, Aggregation::Avg)
+            .is_err_and(|e| e.code() == "INVALID_ARGUMENT"));
+    }
+}
@@ -1,50 +1,347 @@
+use std::collections::{BTreeMap, HashMap};
+
+use serde::Serialize;
+#[cfg(feature = "wasm")]
+use tsify::Tsify;
+#[cfg(feature = "wasm")]
 use wasm_bindgen::prelude::*;
-use serde::{Serialize, Deserialize};
 
 // --- 1. Minimal AAS V3.0 Data Model ---
-// This follows the Asset Administration Shell specification for Industry 4.0
-// https://www.plattform-i40.de/IP/Redaktion/EN/Standardartikel/specification-administrationshell.html
+// The AAS structures live in the `snap_to_twin_core` crate (`core/`), which
+// builds without std; they are re-exported here for convenience
+
+mod aasx;
+mod arrow;
+mod batch;
+mod binary;
+mod builder;
+mod change;
+mod computed;
+mod cosim;
+mod csv;
+mod dtdl;
+mod edit;
+mod error;
+mod event;
+mod fault;
+mod history;
+mod limit;
+mod migration;
+mod monte_carlo;
+mod motor;
+mod ngsi_ld;
+mod noise;
+mod ode;
+mod opcua;
+mod operation;
+mod patch;
+mod profile;
+mod protobuf;
+mod query;
+mod rdf;
+mod scenario;
+#[cfg(feature = "scripting")]
+mod script;
+mod sim_state;
+mod simulation;
+mod snapshot;
+mod spec_json;
+mod state_machine;
+mod stream;
+mod telemetry;
+mod thermal;
+mod time_series;
+mod validation;
+mod vibration;
+mod wear;
+mod wot;
+mod xml;
+mod yaml;
+
+pub use aasx::{read_aasx, AasxPackage};
+pub use arrow::ARROW_MAGIC;
+pub use batch::{BatchFailure, BatchReport};
+pub use binary::{
+    decode, encode, state_from_cbor, state_to_cbor, TickSample, TwinState, WireFormat,
+};
+pub use builder::TwinBuilder;
+pub use change::{matches_pattern, ChangeListener, PropertyChange, SIMULATION_RPM_PATH};
+use computed::{computed_from_config, Expr};
+pub use computed::{ComputedProperty, COMPUTED_SECTION};
+pub use csv::{elements_to_csv, CSV_COLUMNS, HISTORY_TIME_COLUMN};
+pub use dtdl::{dtdl_name, shell_from_dtdl, shell_to_dtdl, DEFAULT_DTMI_PREFIX, DTDL_CONTEXT};
+pub use error::TwinError;
+pub use event::{EventEntry, EventListener, EventMessage};
+pub use fault::{Fault, FaultKind, ALARM_ID_SHORT};
+pub use history::{Aggregation, HistoryPoint, DEFAULT_HISTORY_CAPACITY};
+pub use limit::{CrossingDirection, LIMIT_EVENT_ID_SHORT};
+pub use migration::{environment_from_v2_json, is_v2_json, migrate_v2, MigrationChange};
+pub use monte_carlo::{MonteCarloReport, ParameterDistribution, RunStatistics};
+pub use motor::{
+    MotorModel, DEFAULT_LOAD, ENERGIZED_INPUT, LOAD_INPUT, LOAD_TORQUE_INPUT, SUPPLY_VOLTAGE_INPUT,
+};
+pub use ngsi_ld::{shell_to_ngsi_ld, NGSI_LD_CORE_CONTEXT};
+pub use noise::NoiseModel;
+pub use ode::Rk4;
+pub use opcua::{shell_to_nodeset, NODESET_NAMESPACE};
+pub use operation::OperationHandler;
+pub use profile::{
+    ModelDefinition, ProfileModel, SignalDefinition, SimulationProfile, SIMULATION_SECTION,
+};
+pub use protobuf::{pb, state_from_protobuf, state_to_protobuf};
+pub use query::QueryMatch;
+pub use rdf::{environment_graph, to_json_ld, to_turtle, AAS_RDF_NAMESPACE};
+use scenario::ScenarioPlayer;
+pub use scenario::{
+    Scenario, ScenarioFailure, ScenarioReport, ScenarioRow, DEFAULT_SCENARIO_TOLERANCE,
+    EXPECT_COLUMN_PREFIX,
+};
+#[cfg(feature = "scripting")]
+use script::ScriptRegistry;
+pub use sim_state::SimulationState;
+pub use simulation::{
+    positive_parameter, unknown_parameter, LiveValue, SampleBlock, SimulationFrame,
+    SimulationModel, SimulationRng, SimulationSignal, SimulationValues, DEFAULT_SEED,
+    DEFAULT_STEP_SIZE, MAX_CATCH_UP_STEPS, MOTOR_MODEL, TICK_INPUT, TIME_INPUT,
+};
+use simulation::{SimulationRegistry, MOTOR_RPM};
+pub use snap_to_twin_core::*;
+pub use snapshot::TwinSnapshot;
+pub use spec_json::{
+    environment_from_json, environment_from_spec_json, environment_to_spec_json, JsonFormat,
+};
+use state_machine::StateMachine;
+pub use state_machine::{
+    OperationalState, StateMachineConfig, StateTransition, OPERATIONAL_STATE_ID_SHORT,
+    STATE_CHANGED_ID_SHORT,
+};
+pub use stream::EnvironmentStreamParser;
+pub use telemetry::{
+    SignalSourceStatus, TelemetryRecord, TelemetryReport, TelemetrySource, DEFAULT_STALE_AFTER,
+};
+pub use thermal::{
+    ThermalModel, AMBIENT_TEMPERATURE_INPUT, DEFAULT_INSULATION_CLASS, SENSOR_DRIFT_RATE,
+    THERMAL_MODEL,
+};
+pub use time_series::{TIME_SERIES_ID_SHORT, TIME_SERIES_SEMANTIC_ID};
+pub use validation::{
+    validate_aas_json_report, validation_report, Severity, ValidationIssue, ValidationReport,
+};
+pub use vibration::{BearingDefect, VibrationModel, BPFI_ORDER, BPFO_ORDER, VIBRATION_MODEL};
+pub use wear::{
+    RulEstimate, WearModel, DEFAULT_RATED_LIFETIME_HOURS, OPERATIONAL_DATA_ID_SHORT, WEAR_MODEL,
+};
+pub use wot::{shell_to_thing_description, TD_CONTEXT};
+pub use xml::{environment_from_xml, environment_to_xml, AAS_XML_NAMESPACE};
+pub use yaml::yaml_to_json;
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(typescript_custom_section)]
+const ELEMENT_VALUE_TS: &str = r#"
+/**
+ * An element value as returned to JavaScript: numbers and booleans for typed
+ * properties, `{min, max}` for ranges, text otherwise
+ */
+export type ElementValue =
+    | string
+    | number
+    | boolean
+    | { min: string | number | null; max: string | number | null };
+"#;
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
-pub struct SubmodelElement {
+/// A property value as returned to JavaScript
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[cfg_attr(feature = "wasm", tsify(missing_as_null))]
+pub struct PropertyValue {
+    pub path: String,
+    /// Numbers and booleans for typed properties, `{min, max}` for ranges,
+    /// text otherwise
+    #[cfg_attr(feature = "wasm", tsify(type = "ElementValue"))]
+    pub value: serde_json::Value,
+    pub unit: Option<String>,
+}
+
+/// A row of `list_properties`
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[cfg_attr(feature = "wasm", tsify(missing_as_null))]
+pub struct PropertyEntry {
+    pub id_short_path: String,
+    pub value_type: Option<DataTypeDefXsd>,
+    pub unit: Option<String>,
+}
+
+/// Everything known about one element, for detail views
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[cfg_attr(feature = "wasm", tsify(missing_as_null))]
+pub struct PropertyDetail {
+    pub path: String,
     pub id_short: String,
-    pub value: String,
+    pub model_type: &'static str,
+    /// As in `PropertyValue`
+    #[cfg_attr(feature = "wasm", tsify(type = "ElementValue"))]
+    pub value: serde_json::Value,
+    pub value_type: Option<DataTypeDefXsd>,
+    /// The element's own unit, else the one from its IEC 61360 data specification
     pub unit: Option<String>,
+    pub semantic_id: Option<String>,
+    pub category: Option<Category>,
+    pub qualifiers: Vec<Qualifier>,
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
-pub struct AssetAdministrationShell {
+/// Identification of the twin and the paths of its properties
+#[derive(Serialize, Clone, Debug)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+pub struct TwinSummary {
     pub id: String,
     pub asset_type: String,
-    pub nameplate: Vec<SubmodelElement>,
+    pub properties: Vec<String>,
+}
+
+/// Counters for a status card, with camelCase keys as the UI uses them
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[serde(rename_all = "camelCase")]
+pub struct TwinStatus {
+    pub id: String,
+    pub asset_type: String,
+    pub submodel_count: usize,
+    /// Non-container elements, as listed by `list_properties`
+    pub property_count: usize,
+    /// Simulation tick count
+    pub last_tick: u32,
+    /// Events emitted and not yet collected with `take_events`
+    pub alarms: usize,
+    /// Whether the simulated winding temperature exceeds its limit
+    pub overtemperature: bool,
+}
+
+/// A File element flattened for the frontend (e.g., to render a download link)
+#[derive(Serialize, Clone, Debug)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[cfg_attr(feature = "wasm", tsify(missing_as_null))]
+pub struct FileEntry {
+    /// Path of the element, as accepted by the property getters
+    pub path: String,
+    pub value: Option<String>,
+    pub content_type: Option<String>,
+    pub file_name: Option<String>,
+    /// True for absolute URLs, false for files inside the AAS package
+    pub is_external: bool,
+}
+
+/// An Entity element positioned in the bill of material
+#[derive(Serialize, Clone, Debug)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[cfg_attr(feature = "wasm", tsify(missing_as_null))]
+pub struct EntityEntry {
+    pub path: String,
+    /// Path of the closest enclosing entity, if any
+    pub parent: Option<String>,
+    pub entity_type: EntityType,
+    pub global_asset_id: Option<String>,
+    /// Paths of the entities directly contained in this one
+    pub components: Vec<String>,
+}
+
+/// A Capability element with the semanticId identifying what it stands for
+#[derive(Serialize, Clone, Debug)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[cfg_attr(feature = "wasm", tsify(missing_as_null))]
+pub struct CapabilityEntry {
+    pub path: String,
+    pub id_short: String,
+    /// Identifier of the capability, e.g. an IRI from a capability catalogue
+    pub semantic_id: Option<String>,
+}
+
+/// A relationship element with both ends resolved against the loaded model
+#[derive(Serialize, Clone, Debug)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[cfg_attr(feature = "wasm", tsify(missing_as_null))]
+pub struct RelationshipEntry {
+    pub path: String,
+    pub first: Option<Reference>,
+    pub second: Option<Reference>,
+    /// Element path (or submodel idShort / shell id) of each end, if it resolves
+    pub first_path: Option<String>,
+    pub second_path: Option<String>,
+    /// idShorts of the annotations of an AnnotatedRelationshipElement
+    pub annotations: Vec<String>,
 }
 
 // --- 2. The Active Twin Class ---
 // This is the "executable" digital twin that runs in WebAssembly
 // It combines passive data (AAS JSON) with active behavior (simulation, queries)
 
-#[wasm_bindgen]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 pub struct DigitalTwin {
     data: AssetAdministrationShell,
     // Internal state for simulation (demonstrates "live" twin behavior)
-    rpm_sim: f64,
+    simulation: SimulationRegistry,
+    // Models configured in the `simulation` section
+    profile: SimulationProfile,
     tick_count: u32,
+    // Off/Starting/Running/Fault/Maintenance and its transition table
+    state_machine: StateMachine,
+    // Rhai scripts run on every tick
+    #[cfg(feature = "scripting")]
+    scripts: ScriptRegistry,
+    // Recorded scenario being played back
+    scenario: Option<ScenarioPlayer>,
+    // Properties evaluated from expressions after every edit, in order
+    computed: Vec<(ComputedProperty, Expr)>,
+    // Behavior behind Operation elements, keyed by element path
+    operation_handlers: HashMap<String, OperationHandler>,
+    // Notified of every event emitted through a BasicEventElement
+    event_listeners: Vec<EventListener>,
+    // Notified of property value changes, each with its path pattern
+    change_listeners: Vec<(String, ChangeListener)>,
+    // Emitted events not yet collected by `take_events`
+    event_queue: Vec<EventMessage>,
+    // Files that came with an AASX package, keyed by part name
+    supplementary_files: BTreeMap<String, Vec<u8>>,
+    // Rewrites applied when the twin was loaded from V2.0 JSON
+    migration_report: Vec<MigrationChange>,
 }
 
-#[wasm_bindgen]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 impl DigitalTwin {
     /// Constructor: Hydrates the twin from an AAS JSON string
     /// This is called from JavaScript when loading twin_config.json
-    #[wasm_bindgen(constructor)]
-    pub fn new(json_config: &str) -> Result<DigitalTwin, JsValue> {
-        let data: AssetAdministrationShell = serde_json::from_str(json_config)
-            .map_err(|e| JsValue::from_str(&format!("Invalid AAS JSON: {}", e)))?;
+    /// Accepts either a single shell or a full AAS environment in legacy or spec
+    /// JSON (the first shell is used), or the same as YAML
+    #[cfg_attr(feature = "wasm", wasm_bindgen(constructor))]
+    pub fn new(json_config: &str) -> Result<DigitalTwin, TwinError> {
+        DigitalTwin::from_config(config_value(json_config).map_err(TwinError::parse)?)
+    }
 
-        Ok(DigitalTwin {
-            data,
-            rpm_sim: 0.0,
-            tick_count: 0,
-        })
+    /// Hydrate the twin from a configuration JavaScript already holds as an
+    /// object, in any of the layouts `new` accepts, without a JSON round trip
+    #[cfg(feature = "wasm")]
+    pub fn from_object(config: JsValue) -> Result<DigitalTwin, TwinError> {
+        let value: serde_json::Value = serde_wasm_bindgen::from_value(config)
+            .map_err(|e| TwinError::parse(format!("Invalid AAS object: {}", e)))?;
+        DigitalTwin::from_config(value)
+    }
+
+    /// Hydrate the twin from an AAS environment JSON (e.g., exported by AASX
+    /// Package Explorer), selecting the shell with the given id or the first one
+    pub fn from_environment(
+        json: &str,
+        shell_id: Option<String>,
+    ) -> Result<DigitalTwin, TwinError> {
+        let env = environment_from_json(json.as_bytes()).map_err(TwinError::parse)?;
+        DigitalTwin::from_shell(select_shell(env, shell_id.as_deref())?)
+    }
+
+    /// Export the twin as an AAS environment JSON (shells, submodels and concept
+    /// descriptions side by side, the nameplate as its own submodel)
+    pub fn get_environment_json(&self) -> String {
+        serde_json::to_string_pretty(&Environment::from_shell(&self.data))
+            .unwrap_or_else(|_| "{}".to_string())
     }
 
     /// Export standard AAS JSON (for interoperability with other Industry 4.0 tools)
@@ -52,13 +349,239 @@ impl DigitalTwin {
         serde_json::to_string_pretty(&self.data).unwrap_or_else(|_| "{}".to_string())
     }
 
-    /// Query a specific property from the nameplate (e.g., "Voltage", "RPM")
+    /// Query a property (e.g., "Voltage", "TechnicalData.MaxTorque") as
+    /// `{path, value, unit}`, with numbers and booleans typed per valueType
+    #[cfg(feature = "wasm")]
+    #[wasm_bindgen(unchecked_return_type = "PropertyValue")]
+    pub fn get_property(&self, name: &str) -> Result<JsValue, TwinError> {
+        Ok(to_js(&self.property_value(name)?))
+    }
+
+    /// Query value, valueType, unit, semanticId, category and qualifiers of an
+    /// element at once; throws NOT_FOUND for unknown paths
+    #[cfg(feature = "wasm")]
+    #[wasm_bindgen(unchecked_return_type = "PropertyDetail")]
+    pub fn get_property_detail(&self, name: &str) -> Result<JsValue, TwinError> {
+        Ok(to_js(&self.property_detail(name)?))
+    }
+
+    /// Query a specific property from the nameplate as "value unit"
     /// This demonstrates structured data access following AAS semantics
-    pub fn get_property(&self, name: &str) -> String {
-        if let Some(elem) = self.data.nameplate.iter().find(|e| e.id_short == name) {
-            return format!("{} {}", elem.value, elem.unit.as_deref().unwrap_or(""));
+    pub fn get_property_display(&self, name: &str) -> String {
+        match self.find_element(name) {
+            Ok(elem) => elem.display_value(),
+            Err(_) => format!("Property '{}' not found", name),
         }
-        format!("Property '{}' not found", name)
+    }
+
+    /// Query a property by its semanticId (e.g., an ECLASS IRDI or an IDTA IRI)
+    /// instead of its vendor-specific idShort
+    pub fn get_property_by_semantic_id(&self, iri: &str) -> Result<String, TwinError> {
+        self.elements()
+            .find(|(_, e)| e.semantic_id.as_ref().is_some_and(|r| r.refers_to(iri)))
+            .map(|(_, e)| e.display_value())
+            .ok_or_else(|| {
+                TwinError::not_found(iri, format!("No property with semanticId '{}'", iri))
+            })
+    }
+
+    /// Walk all elements depth-first, calling `callback(path, element)` with
+    /// each element as an object in the twin's JSON format. Returning `false`
+    /// from the callback stops the walk.
+    #[cfg(feature = "wasm")]
+    pub fn for_each_element(
+        &self,
+        #[wasm_bindgen(
+            unchecked_param_type = "(path: string, element: SubmodelElement) => boolean | void"
+        )]
+        callback: &js_sys::Function,
+    ) -> Result<(), TwinError> {
+        for (path, element) in self.elements() {
+            let result = callback
+                .call2(&JsValue::NULL, &JsValue::from_str(&path), &to_js(element))
+                .map_err(|e| {
+                    TwinError::failed(Some(&path), format!("Element callback threw: {:?}", e))
+                })?;
+            if result.as_bool() == Some(false) {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// All elements carrying the semanticId `iri`, across the nameplate and
+    /// all submodels, as an array of `get_property_detail` objects
+    #[cfg(feature = "wasm")]
+    #[wasm_bindgen(unchecked_return_type = "PropertyDetail[]")]
+    pub fn find_by_semantic_id(&self, iri: &str) -> JsValue {
+        to_js(&self.elements_by_semantic_id(iri))
+    }
+
+    /// Query a property in a specific language (e.g., "de"), falling back to English
+    /// and then to the first available language for MultiLanguageProperties
+    pub fn get_property_localized(&self, name: &str, locale: &str) -> Result<String, TwinError> {
+        self.find_element(name)?
+            .localized_value(locale)
+            .ok_or_else(|| {
+                TwinError::type_mismatch(name, format!("Property '{}' has no text", name))
+            })
+    }
+
+    /// Check a value against the limits of a Range element (e.g., "OperatingTemperature")
+    pub fn is_in_range(&self, path: &str, value: f64) -> Result<bool, TwinError> {
+        self.find_element(path)?
+            .range_contains(value)
+            .map_err(|e| TwinError::type_mismatch(path, e))
+    }
+
+    /// Read a numeric property (xs:double or xs:int), throwing if it is missing or not a number
+    pub fn get_property_f64(&self, name: &str) -> Result<f64, TwinError> {
+        self.find_element(name)?
+            .value_as_f64()
+            .map_err(|e| TwinError::type_mismatch(name, e))
+    }
+
+    /// Read an xs:int property, throwing if it is missing or not an integer
+    pub fn get_property_i32(&self, name: &str) -> Result<i32, TwinError> {
+        self.find_element(name)?
+            .value_as_i32()
+            .map_err(|e| TwinError::type_mismatch(name, e))
+    }
+
+    /// Read an xs:boolean property, throwing if it is missing or not a boolean
+    pub fn get_property_bool(&self, name: &str) -> Result<bool, TwinError> {
+        self.find_element(name)?
+            .value_as_bool()
+            .map_err(|e| TwinError::type_mismatch(name, e))
+    }
+
+    /// Write a property value at runtime (e.g., a live sensor reading into
+    /// "OperationalData.Temperature"), throwing if the value does not match the
+    /// property's valueType
+    pub fn set_property(&mut self, id_short_path: &str, value: &str) -> Result<(), TwinError> {
+        self.tracked(|twin| {
+            twin.find_element_mut(id_short_path)?
+                .set_value(value)
+                .map_err(|e| TwinError::type_mismatch(id_short_path, e))
+        })
+    }
+
+    /// Get the qualifiers (e.g., SMT/Cardinality) of the element or submodel at `path`
+    /// as a JSON array
+    pub fn get_qualifiers(&self, path: &str) -> Result<String, TwinError> {
+        let qualifiers = match self.data.submodels.iter().find(|s| s.id_short == path) {
+            Some(sm) => &sm.qualifiers,
+            None => &self.find_element(path)?.qualifiers,
+        };
+        Ok(serde_json::to_string(qualifiers).unwrap_or_else(|_| "[]".to_string()))
+    }
+
+    /// Name to show end users for the shell (empty path), a submodel or an element.
+    ///
+    /// Falls back from the displayName to the IEC 61360 preferred name to the
+    /// idShort (or the shell id).
+    pub fn get_display_name(&self, path: &str, locale: &str) -> Result<String, TwinError> {
+        let pick = |names: &[LangString]| select_lang(names, locale).map(|s| s.text.clone());
+        if path.is_empty() {
+            return Ok(pick(&self.data.display_name).unwrap_or_else(|| self.data.id.clone()));
+        }
+        if let Some(sm) = self.data.submodels.iter().find(|s| s.id_short == path) {
+            return Ok(pick(&sm.display_name).unwrap_or_else(|| sm.id_short.clone()));
+        }
+        let element = self.find_element(path)?;
+        Ok(pick(&element.display_name)
+            .or_else(|| {
+                self.iec61360_for(element)
+                    .and_then(|s| s.preferred_name(locale))
+                    .map(str::to_string)
+            })
+            .unwrap_or_else(|| element.id_short.clone()))
+    }
+
+    /// Description of the shell (empty path), a submodel or an element in the
+    /// best matching language
+    pub fn get_description(&self, path: &str, locale: &str) -> Result<Option<String>, TwinError> {
+        let description = if path.is_empty() {
+            &self.data.description
+        } else {
+            match self.data.submodels.iter().find(|s| s.id_short == path) {
+                Some(sm) => &sm.description,
+                None => &self.find_element(path)?.description,
+            }
+        };
+        Ok(select_lang(description, locale).map(|s| s.text.clone()))
+    }
+
+    /// Get the shell's administrative information (version, revision, creator,
+    /// templateId) as JSON, or `undefined` if none is recorded
+    pub fn get_administration(&self) -> Option<String> {
+        self.data
+            .administration
+            .as_ref()
+            .and_then(|a| serde_json::to_string(a).ok())
+    }
+
+    /// Increment the shell's revision after an edit and return the new revision
+    pub fn bump_revision(&mut self) -> Result<String, TwinError> {
+        self.data
+            .administration
+            .get_or_insert_with(Default::default)
+            .bump_revision()
+            .map_err(|e| TwinError::failed(None, e))
+    }
+
+    /// Increment the revision of the submodel with the given idShort
+    pub fn bump_submodel_revision(&mut self, id_short: &str) -> Result<String, TwinError> {
+        let sm = self
+            .data
+            .submodels
+            .iter_mut()
+            .find(|s| s.id_short == id_short)
+            .ok_or_else(|| {
+                TwinError::not_found(id_short, format!("Submodel '{}' not found", id_short))
+            })?;
+        sm.administration
+            .get_or_insert_with(Default::default)
+            .bump_revision()
+            .map_err(|e| TwinError::failed(Some(id_short), e))
+    }
+
+    /// Get the concept description for the element at `path` (via its semanticId),
+    /// or `undefined` if the element has no semanticId or the concept is unknown
+    pub fn get_concept_for(&self, path: &str) -> Result<Option<String>, TwinError> {
+        let element = self.find_element(path)?;
+        Ok(element
+            .semantic_id
+            .as_ref()
+            .and_then(|r| r.key_value())
+            .and_then(|id| self.get_concept_description(id)))
+    }
+
+    /// Get a concept description by id as JSON
+    pub fn get_concept_description(&self, id: &str) -> Option<String> {
+        self.data
+            .concept_descriptions
+            .get(id)
+            .and_then(|cd| serde_json::to_string(cd).ok())
+    }
+
+    /// Unit of an element: its own (Property/Range), else the one from its
+    /// IEC 61360 data specification or that of its concept description
+    pub fn get_unit(&self, path: &str) -> Result<Option<String>, TwinError> {
+        let element = self.find_element(path)?;
+        Ok(element
+            .unit()
+            .or_else(|| self.iec61360_for(element).and_then(|s| s.unit.as_deref()))
+            .map(str::to_string))
+    }
+
+    /// IEC 61360 definition of an element in the best matching language
+    pub fn get_definition(&self, path: &str, locale: &str) -> Result<Option<String>, TwinError> {
+        let element = self.find_element(path)?;
+        Ok(self
+            .iec61360_for(element)
+            .and_then(|s| s.definition(locale))
+            .map(str::to_string))
     }
 
     /// Get the asset identifier
@@ -68,57 +591,774 @@ impl DigitalTwin {
 
     /// Get the asset type (manufacturer + model)
     pub fn get_asset_type(&self) -> String {
-        self.data.asset_type.clone()
+        self.data
+            .asset_information
+            .asset_type
+            .clone()
+            .unwrap_or_default()
+    }
+
+    /// Set the asset type (manufacturer + model)
+    pub fn set_asset_type(&mut self, asset_type: &str) {
+        self.data.asset_information.asset_type = Some(asset_type.to_string());
+    }
+
+    /// Get the full asset information block (assetKind, globalAssetId,
+    /// specificAssetIds, defaultThumbnail) as JSON
+    pub fn get_asset_information(&self) -> String {
+        serde_json::to_string(&self.data.asset_information).unwrap_or_else(|_| "{}".to_string())
+    }
+
+    /// Get the global asset id, or `undefined` if none is set
+    pub fn get_global_asset_id(&self) -> Option<String> {
+        self.data.asset_information.global_asset_id.clone()
+    }
+
+    /// Set the global asset id used by registries to identify the asset
+    pub fn set_global_asset_id(&mut self, global_asset_id: &str) {
+        self.data.asset_information.global_asset_id = Some(global_asset_id.to_string());
+    }
+
+    /// Get the asset kind ("Type", "Instance" or "NotApplicable")
+    pub fn get_asset_kind(&self) -> String {
+        format!("{:?}", self.data.asset_information.asset_kind)
+    }
+
+    /// Set the asset kind ("Type", "Instance" or "NotApplicable")
+    pub fn set_asset_kind(&mut self, kind: &str) -> Result<(), TwinError> {
+        self.data.asset_information.asset_kind = AssetKind::parse(kind)
+            .ok_or_else(|| TwinError::invalid_argument(format!("Unknown asset kind '{}'", kind)))?;
+        Ok(())
+    }
+
+    /// Get a specific asset id (e.g., "SerialNumber") by name
+    pub fn get_specific_asset_id(&self, name: &str) -> Option<String> {
+        self.data
+            .asset_information
+            .specific_asset_id(name)
+            .map(str::to_string)
     }
 
-    /// List all available properties
-    pub fn list_properties(&self) -> String {
+    /// Add or update a specific asset id (e.g., "SerialNumber" = "XYZ-2024-001234")
+    pub fn set_specific_asset_id(&mut self, name: &str, value: &str) {
         self.data
-            .nameplate
+            .asset_information
+            .set_specific_asset_id(name, value);
+    }
+
+    /// List all available properties as an array of `{id_short_path,
+    /// value_type, unit}` entries, including those nested in collections and
+    /// other submodels (collections themselves are skipped)
+    #[cfg(feature = "wasm")]
+    #[wasm_bindgen(unchecked_return_type = "PropertyEntry[]")]
+    pub fn list_properties(&self) -> JsValue {
+        to_js(&self.property_entries())
+    }
+
+    /// Find properties whose idShort or a display name contains `pattern`
+    /// ("volt" finds "RatedVoltage"), ignoring case. Patterns with `*` or `?`
+    /// are globs that must match the whole name ("Max*", "Temp?").
+    #[cfg(feature = "wasm")]
+    #[wasm_bindgen(unchecked_return_type = "PropertyEntry[]")]
+    pub fn search_properties(&self, pattern: &str) -> JsValue {
+        to_js(&self.search_property_entries(pattern))
+    }
+
+    /// The property paths of `list_properties`, comma-separated
+    pub fn list_properties_display(&self) -> String {
+        self.property_paths().join(", ")
+    }
+
+    /// List the paths of all properties with the given category
+    /// ("CONSTANT", "PARAMETER" or "VARIABLE"), e.g. to separate live values
+    /// from nameplate constants
+    pub fn list_properties_by_category(&self, category: &str) -> Result<Vec<String>, TwinError> {
+        let category = Category::parse(category).ok_or_else(|| {
+            TwinError::invalid_argument(format!("Unknown category '{}'", category))
+        })?;
+        Ok(self
+            .elements()
+            .filter(|(_, e)| !e.is_container() && e.category == Some(category))
+            .map(|(path, _)| path)
+            .collect())
+    }
+
+    /// List the idShorts of all submodels carried by the twin
+    pub fn list_submodels(&self) -> Vec<String> {
+        self.data
+            .submodels
             .iter()
-            .map(|e| e.id_short.clone())
-            .collect::<Vec<_>>()
-            .join(", ")
+            .map(|s| s.id_short.clone())
+            .collect()
     }
 
-    /// Simulate "live" data (demonstrates active twin behavior)
-    /// In a real system, this could connect to sensor data or PLC interfaces
-    pub fn tick_simulation(&mut self) -> String {
-        self.tick_count += 1;
+    /// Get a single submodel as AAS JSON, or `undefined` if it does not exist
+    pub fn get_submodel(&self, id_short: &str) -> Option<String> {
+        self.data
+            .submodels
+            .iter()
+            .find(|s| s.id_short == id_short)
+            .and_then(|s| serde_json::to_string_pretty(s).ok())
+    }
 
-        // Simulate varying RPM with some realistic variation
-        self.rpm_sim += 10.5 + (self.tick_count as f64 * 0.3).sin() * 5.0;
+    /// Get the decoded payload of a Blob element (a `Uint8Array` in JS)
+    pub fn get_blob(&self, path: &str) -> Result<Vec<u8>, TwinError> {
+        self.find_element(path)?
+            .blob_bytes()
+            .map_err(|e| TwinError::type_mismatch(path, e))
+    }
 
-        format!("Live RPM: {:.2} (tick: {})", self.rpm_sim, self.tick_count)
+    /// Get the contentType of a Blob or File element, if declared
+    pub fn get_content_type(&self, path: &str) -> Result<Option<String>, TwinError> {
+        match &self.find_element(path)?.kind {
+            ElementKind::Blob(b) => Ok(b.content_type.clone()),
+            ElementKind::File(f) => Ok(f.content_type.clone()),
+            other => Err(TwinError::type_mismatch(
+                path,
+                format!(
+                    "Element '{}' is a {}, not a Blob or File",
+                    path,
+                    other.model_type()
+                ),
+            )),
+        }
+    }
+
+    /// Follow the ReferenceElement at `path` and return the path of its target
+    /// (an element path, a submodel idShort, or the shell id)
+    pub fn resolve_reference(&self, path: &str) -> Result<String, TwinError> {
+        let reference = match &self.find_element(path)?.kind {
+            ElementKind::ReferenceElement(r) => r.value.as_ref(),
+            other => {
+                return Err(TwinError::type_mismatch(
+                    path,
+                    format!(
+                        "Element '{}' is a {}, not a ReferenceElement",
+                        path,
+                        other.model_type()
+                    ),
+                ))
+            }
+        }
+        .ok_or_else(|| {
+            TwinError::failed(Some(path), format!("ReferenceElement '{}' is empty", path))
+        })?;
+
+        self.resolve_to_path(reference).ok_or_else(|| {
+            TwinError::not_found(
+                path,
+                format!("Reference {} could not be resolved", reference),
+            )
+        })
+    }
+
+    /// List all (annotated) relationship elements as an array of
+    /// `{path, first, second, first_path, second_path, annotations}` entries
+    #[cfg(feature = "wasm")]
+    #[wasm_bindgen(unchecked_return_type = "RelationshipEntry[]")]
+    pub fn list_relationships(&self) -> JsValue {
+        to_js(&self.relationship_entries())
+    }
+
+    /// List all Entity elements (the bill of material) as an array of
+    /// `{path, parent, entity_type, global_asset_id, components}` entries
+    #[cfg(feature = "wasm")]
+    #[wasm_bindgen(unchecked_return_type = "EntityEntry[]")]
+    pub fn list_entities(&self) -> JsValue {
+        to_js(&self.entity_entries())
+    }
+
+    /// Paths of the sub-components (child entities) of the Entity at `path`, as a JSON array
+    pub fn get_entity_components(&self, path: &str) -> Result<String, TwinError> {
+        let entity = match &self.find_element(path)?.kind {
+            ElementKind::Entity(e) => e,
+            other => {
+                return Err(TwinError::type_mismatch(
+                    path,
+                    format!(
+                        "Element '{}' is a {}, not an Entity",
+                        path,
+                        other.model_type()
+                    ),
+                ))
+            }
+        };
+        let components = entity_components(path, entity);
+        Ok(serde_json::to_string(&components).unwrap_or_else(|_| "[]".to_string()))
+    }
+
+    /// List what the asset can do as an array of `{path, id_short, semantic_id}` entries
+    #[cfg(feature = "wasm")]
+    #[wasm_bindgen(unchecked_return_type = "CapabilityEntry[]")]
+    pub fn list_capabilities(&self) -> JsValue {
+        to_js(&self.capability_entries())
+    }
+
+    /// Get the item at `index` of the SubmodelElementList at `path` as AAS JSON
+    pub fn get_list_item(&self, path: &str, index: usize) -> Result<String, TwinError> {
+        let list = match &self.find_element(path)?.kind {
+            ElementKind::SubmodelElementList(l) => l,
+            other => {
+                return Err(TwinError::type_mismatch(
+                    path,
+                    format!(
+                        "Element '{}' is a {}, not a SubmodelElementList",
+                        path,
+                        other.model_type()
+                    ),
+                ))
+            }
+        };
+        let item = list.value.get(index).ok_or_else(|| {
+            TwinError::invalid_argument(format!(
+                "Index {} out of bounds for list '{}' with {} items",
+                index,
+                path,
+                list.value.len()
+            ))
+        })?;
+        Ok(serde_json::to_string_pretty(item).unwrap_or_else(|_| "{}".to_string()))
+    }
+
+    /// List all File elements (datasheets, CAD links, ...) as an array of
+    /// `{path, value, content_type, file_name, is_external}` entries
+    #[cfg(feature = "wasm")]
+    #[wasm_bindgen(unchecked_return_type = "FileEntry[]")]
+    pub fn list_files(&self) -> JsValue {
+        to_js(&self.file_entries())
+    }
+
+    /// Simulate "live" data (demonstrates active twin behavior)
+    /// In a real system, this could connect to sensor data or PLC interfaces.
+    /// Steps by one second; `tick` takes the step and returns every signal.
+    pub fn tick_simulation(&mut self) -> String {
+        let sample = self.advance_simulation();
+        format!("Live RPM: {:.2} (tick: {})", sample.rpm, sample.tick)
     }
 
     /// Reset simulation state
     pub fn reset_simulation(&mut self) {
-        self.rpm_sim = 0.0;
+        self.reset_models();
+        self.reset_state();
         self.tick_count = 0;
     }
 
-    /// Get a summary of the twin
-    pub fn get_summary(&self) -> String {
+    /// Get a summary of the twin as `{id, asset_type, properties}`
+    #[cfg(feature = "wasm")]
+    #[wasm_bindgen(unchecked_return_type = "TwinSummary")]
+    pub fn get_summary(&self) -> JsValue {
+        to_js(&self.summary())
+    }
+
+    /// Get `{id, assetType, submodelCount, propertyCount, lastTick, alarms,
+    /// overtemperature}` as JSON for status cards
+    pub fn get_summary_json(&self) -> String {
+        serde_json::to_string(&self.status()).unwrap_or_else(|_| "{}".to_string())
+    }
+
+    /// Get a summary of the twin as text
+    pub fn get_summary_display(&self) -> String {
         format!(
             "Asset: {}\nType: {}\nProperties: {}",
             self.data.id,
-            self.data.asset_type,
-            self.list_properties()
+            self.get_asset_type(),
+            self.list_properties_display()
         )
     }
 }
 
+impl DigitalTwin {
+    /// Rust-side `get_property`
+    pub fn property_value(&self, name: &str) -> Result<PropertyValue, TwinError> {
+        let path = self.resolve_path(name)?;
+        let element = self.find_element(&path)?;
+        Ok(PropertyValue {
+            path,
+            value: element.json_value(),
+            unit: element.unit().map(str::to_string),
+        })
+    }
+
+    /// Rust-side `get_property_detail`
+    pub fn property_detail(&self, name: &str) -> Result<PropertyDetail, TwinError> {
+        let path = self.resolve_path(name)?;
+        let element = self.find_element(&path)?;
+        let unit = element
+            .unit()
+            .or_else(|| self.iec61360_for(element).and_then(|s| s.unit.as_deref()));
+        Ok(PropertyDetail {
+            id_short: element.id_short.clone(),
+            model_type: element.kind.model_type(),
+            value: element.json_value(),
+            value_type: element.value_type(),
+            unit: unit.map(str::to_string),
+            semantic_id: element
+                .semantic_id
+                .as_ref()
+                .and_then(|r| r.key_value())
+                .map(str::to_string),
+            category: element.category,
+            qualifiers: element.qualifiers.clone(),
+            path,
+        })
+    }
+
+    /// Rust-side `find_by_semantic_id`, in `elements` order
+    pub fn elements_by_semantic_id(&self, iri: &str) -> Vec<PropertyDetail> {
+        self.elements()
+            .filter(|(_, e)| e.semantic_id.as_ref().is_some_and(|r| r.refers_to(iri)))
+            .filter_map(|(path, _)| self.property_detail(&path).ok())
+            .collect()
+    }
+
+    /// Paths of all non-container elements, in `elements` order
+    pub fn property_paths(&self) -> Vec<String> {
+        self.elements()
+            .filter(|(_, e)| !e.is_container())
+            .map(|(path, _)| path)
+            .collect()
+    }
+
+    /// Rust-side `list_properties`
+    pub fn property_entries(&self) -> Vec<PropertyEntry> {
+        self.elements()
+            .filter(|(_, e)| !e.is_container())
+            .map(|(path, e)| PropertyEntry {
+                value_type: e.value_type(),
+                unit: e.unit().map(str::to_string),
+                id_short_path: path,
+            })
+            .collect()
+    }
+
+    /// Rust-side `search_properties`
+    pub fn search_property_entries(&self, pattern: &str) -> Vec<PropertyEntry> {
+        let pattern = pattern.to_lowercase();
+        let glob = pattern.contains(['*', '?']);
+        let matches = |name: &str| {
+            let name = name.to_lowercase();
+            if glob {
+                glob_match(&pattern, &name)
+            } else {
+                name.contains(&pattern)
+            }
+        };
+        self.elements()
+            .filter(|(_, e)| !e.is_container())
+            .filter(|(_, e)| {
+                matches(&e.id_short) || e.display_name.iter().any(|name| matches(&name.text))
+            })
+            .map(|(path, e)| PropertyEntry {
+                value_type: e.value_type(),
+                unit: e.unit().map(str::to_string),
+                id_short_path: path,
+            })
+            .collect()
+    }
+
+    /// Rust-side `get_summary`
+    pub fn summary(&self) -> TwinSummary {
+        TwinSummary {
+            id: self.data.id.clone(),
+            asset_type: self.get_asset_type(),
+            properties: self.property_paths(),
+        }
+    }
+
+    /// Rust-side `get_summary_json`
+    pub fn status(&self) -> TwinStatus {
+        TwinStatus {
+            id: self.data.id.clone(),
+            asset_type: self.get_asset_type(),
+            submodel_count: self.data.submodels.len(),
+            property_count: self.property_paths().len(),
+            last_tick: self.tick_count,
+            alarms: self.event_queue.len(),
+            overtemperature: self.is_overtemperature(),
+        }
+    }
+
+    /// Advance the simulation by one tick of one second, scaled by the
+    /// playback speed; nothing happens while paused
+    pub fn advance_simulation(&mut self) -> TickSample {
+        if !self.is_paused() {
+            self.step_models(self.playback_time(1.0));
+        }
+        TickSample {
+            tick: self.tick_count,
+            rpm: self.simulation.value(MOTOR_RPM).unwrap_or(0.0),
+        }
+    }
+
+    /// Build a twin from a parsed configuration, with the simulation profile
+    /// from its `simulation` section and the properties of its `computed`
+    /// section
+    fn from_config(config: serde_json::Value) -> Result<DigitalTwin, TwinError> {
+        let profile = SimulationProfile::from_config(&config)?;
+        let computed = computed_from_config(&config)?;
+        let (data, migration_report) = config_from_value(config, None).map_err(TwinError::parse)?;
+        let mut twin = DigitalTwin::from_shell(data)?;
+        twin.migration_report = migration_report;
+        if profile != SimulationProfile::default() {
+            twin.set_simulation_profile(profile)?;
+        }
+        for computed in computed {
+            twin.add_computed(computed)?;
+        }
+        Ok(twin)
+    }
+
+    /// Build a twin around an already parsed shell
+    pub fn from_shell(data: AssetAdministrationShell) -> Result<DigitalTwin, TwinError> {
+        let mut twin = DigitalTwin {
+            data,
+            simulation: SimulationRegistry::default(),
+            profile: SimulationProfile::default(),
+            tick_count: 0,
+            state_machine: StateMachine::default(),
+            #[cfg(feature = "scripting")]
+            scripts: ScriptRegistry::default(),
+            scenario: None,
+            computed: Vec::new(),
+            operation_handlers: HashMap::new(),
+            event_listeners: Vec::new(),
+            change_listeners: Vec::new(),
+            event_queue: Vec::new(),
+            supplementary_files: BTreeMap::new(),
+            migration_report: Vec::new(),
+        };
+        twin.check_lists()
+            .map_err(|e| TwinError::validation(None, format!("Invalid AAS JSON: {}", e)))?;
+        twin.simulation = twin.builtin_models();
+        Ok(twin)
+    }
+
+    /// IEC 61360 content for an element, embedded or via its concept description
+    fn iec61360_for<'a>(
+        &'a self,
+        element: &'a SubmodelElement,
+    ) -> Option<&'a DataSpecificationIec61360> {
+        find_iec61360(&element.embedded_data_specifications).or_else(|| {
+            let id = element.semantic_id.as_ref()?.key_value()?;
+            find_iec61360(
+                &self
+                    .data
+                    .concept_descriptions
+                    .get(id)?
+                    .embedded_data_specifications,
+            )
+        })
+    }
+
+    /// Verify that every SubmodelElementList holds items of its declared type
+    fn check_lists(&self) -> Result<(), String> {
+        for (path, elem) in self.elements() {
+            if let ElementKind::SubmodelElementList(list) = &elem.kind {
+                list.check_items()
+                    .map_err(|e| format!("SubmodelElementList '{}': {}", path, e))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// All elements with their paths, depth-first: nameplate first, then each
+    /// submodel. Containers come before their children, list items are
+    /// addressed by index ("Markings[0]").
+    pub fn elements(&self) -> impl Iterator<Item = (String, &SubmodelElement)> {
+        let mut out = Vec::new();
+        walk_elements("", &self.data.nameplate, &mut out);
+        for sm in &self.data.submodels {
+            walk_elements(&sm.id_short, &sm.submodel_elements, &mut out);
+        }
+        out.into_iter()
+    }
+
+    /// Rust-side `list_files`: File elements across the nameplate and all
+    /// submodels
+    pub fn file_entries(&self) -> Vec<FileEntry> {
+        self.elements()
+            .filter_map(|(path, elem)| match &elem.kind {
+                ElementKind::File(f) => Some(FileEntry {
+                    path,
+                    value: f.value.clone(),
+                    content_type: f.content_type.clone(),
+                    file_name: f.file_name().map(str::to_string),
+                    is_external: f.is_external(),
+                }),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Rust-side `list_entities`: Entity elements across the nameplate and
+    /// all submodels, parents before children
+    pub fn entity_entries(&self) -> Vec<EntityEntry> {
+        let entities: Vec<_> = self
+            .elements()
+            .filter_map(|(path, elem)| match &elem.kind {
+                ElementKind::Entity(e) => Some((path, e)),
+                _ => None,
+            })
+            .collect();
+        entities
+            .iter()
+            .map(|(path, entity)| EntityEntry {
+                path: path.clone(),
+                parent: entities
+                    .iter()
+                    .map(|(p, _)| p)
+                    .filter(|p| path.starts_with(p.as_str()) && path[p.len()..].starts_with('.'))
+                    .max_by_key(|p| p.len())
+                    .cloned(),
+                entity_type: entity.entity_type,
+                global_asset_id: entity.global_asset_id.clone(),
+                components: entity_components(path, entity),
+            })
+            .collect()
+    }
+
+    /// Rust-side `list_capabilities`: Capability elements across the
+    /// nameplate and all submodels
+    pub fn capability_entries(&self) -> Vec<CapabilityEntry> {
+        self.elements()
+            .filter_map(|(path, elem)| match &elem.kind {
+                ElementKind::Capability(_) => Some(CapabilityEntry {
+                    path,
+                    id_short: elem.id_short.clone(),
+                    semantic_id: elem
+                        .semantic_id
+                        .as_ref()
+                        .and_then(|r| r.key_value())
+                        .map(str::to_string),
+                }),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Rust-side `list_relationships`: relationship elements across the
+    /// nameplate and all submodels
+    pub fn relationship_entries(&self) -> Vec<RelationshipEntry> {
+        self.elements()
+            .filter_map(|(path, elem)| {
+                let (first, second) = elem.kind.relationship_ends()?;
+                let annotations = match &elem.kind {
+                    ElementKind::AnnotatedRelationshipElement(r) => {
+                        r.annotations.iter().map(|a| a.id_short.clone()).collect()
+                    }
+                    _ => Vec::new(),
+                };
+                Some(RelationshipEntry {
+                    path,
+                    first_path: first.and_then(|r| self.resolve_to_path(r)),
+                    second_path: second.and_then(|r| self.resolve_to_path(r)),
+                    first: first.cloned(),
+                    second: second.cloned(),
+                    annotations,
+                })
+            })
+            .collect()
+    }
+
+    fn resolve_to_path(&self, reference: &Reference) -> Option<String> {
+        match self.data.resolve(reference)? {
+            ResolvedReference::Shell(shell) => Some(shell.id.clone()),
+            ResolvedReference::Submodel { id_short, .. } => Some(id_short.to_string()),
+            ResolvedReference::Element { path, .. } => Some(path),
+        }
+    }
+
+    /// Resolve an element by dotted path. Paths starting with a submodel idShort
+    /// ("TechnicalData.MaxTorque") address that submodel, anything else the nameplate.
+    /// A bare idShort that is not at the top of the nameplate is searched for
+    /// depth-first through all nested elements.
+    fn find_element(&self, path: &str) -> Result<&SubmodelElement, TwinError> {
+        let parsed = parse_path(path)?;
+        self.find_element_at(&parsed)
+            .or_else(|| {
+                let [PathSegment::IdShort(id_short)] = parsed.segments() else {
+                    return None;
+                };
+                self.elements()
+                    .find(|(_, e)| &e.id_short == id_short)
+                    .map(|(_, e)| e)
+            })
+            .ok_or_else(|| TwinError::not_found(path, format!("Property '{}' not found", path)))
+    }
+
+    /// Mutable counterpart of `find_element`, with the same bare-idShort fallback
+    fn find_element_mut(&mut self, path: &str) -> Result<&mut SubmodelElement, TwinError> {
+        let path = self.resolve_path(path)?;
+        let parsed = parse_path(&path)?;
+        let (submodel, segments) = self.split_submodel(&parsed);
+        let elements = match submodel {
+            Some(i) => &mut self.data.submodels[i].submodel_elements,
+            None => &mut self.data.nameplate,
+        };
+        find_element_in_mut(elements, segments)
+            .ok_or_else(|| TwinError::not_found(&path, format!("Property '{}' not found", path)))
+    }
+
+    /// The full path of the element `find_element` would return
+    fn resolve_path(&self, path: &str) -> Result<String, TwinError> {
+        let not_found = || TwinError::not_found(path, format!("Property '{}' not found", path));
+        let parsed = parse_path(path)?;
+        if self.find_element_at(&parsed).is_some() {
+            return Ok(path.to_string());
+        }
+        match parsed.segments() {
+            [PathSegment::IdShort(id_short)] => self
+                .elements()
+                .find(|(_, e)| &e.id_short == id_short)
+                .map(|(p, _)| p)
+                .ok_or_else(not_found),
+            _ => Err(not_found()),
+        }
+    }
+
+    /// The element at exactly `path`, None for a malformed path
+    fn find_element_by_path(&self, path: &str) -> Option<&SubmodelElement> {
+        self.find_element_at(&IdShortPath::parse(path).ok()?)
+    }
+
+    fn find_element_at(&self, path: &IdShortPath) -> Option<&SubmodelElement> {
+        let (submodel, segments) = self.split_submodel(path);
+        let elements = match submodel {
+            Some(i) => &self.data.submodels[i].submodel_elements,
+            None => &self.data.nameplate,
+        };
+        find_element_in(elements, segments)
+    }
+
+    /// The submodel a path starts with (None for the nameplate) and the
+    /// segments below it; paths without a submodel are in the nameplate
+    fn split_submodel<'p>(&self, path: &'p IdShortPath) -> (Option<usize>, &'p [PathSegment]) {
+        let segments = path.segments();
+        let (Some(PathSegment::IdShort(first)), Some(PathSegment::IdShort(_))) =
+            (segments.first(), segments.get(1))
+        else {
+            return (None, segments);
+        };
+        match self
+            .data
+            .submodels
+            .iter()
+            .position(|s| &s.id_short == first)
+        {
+            Some(i) => (Some(i), &segments[1..]),
+            None if first == NAMEPLATE_ID_SHORT => (None, &segments[1..]),
+            None => (None, segments),
+        }
+    }
+}
+
+/// Parse an idShortPath; malformed paths are the caller's mistake
+pub(crate) fn parse_path(path: &str) -> Result<IdShortPath, TwinError> {
+    IdShortPath::parse(path).map_err(TwinError::invalid_argument)
+}
+
+/// Whether `name` matches a glob in which `*` stands for any text and `?`
+/// for one character
+fn glob_match(pattern: &str, name: &str) -> bool {
+    fn matches(pattern: &[char], name: &[char]) -> bool {
+        match pattern {
+            [] => name.is_empty(),
+            ['*', rest @ ..] => (0..=name.len()).any(|i| matches(rest, &name[i..])),
+            ['?', rest @ ..] => !name.is_empty() && matches(rest, &name[1..]),
+            [c, rest @ ..] => name.first() == Some(c) && matches(rest, &name[1..]),
+        }
+    }
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    matches(&pattern, &name)
+}
+
+/// Paths of the entities listed directly among an entity's statements
+fn entity_components(path: &str, entity: &Entity) -> Vec<String> {
+    entity
+        .statements
+        .iter()
+        .filter(|s| matches!(s.kind, ElementKind::Entity(_)))
+        .map(|s| join_path(path, &s.id_short))
+        .collect()
+}
+
+/// The shell to load from an environment; a missing one is reported as not found
+pub(crate) fn select_shell(
+    env: Environment,
+    shell_id: Option<&str>,
+) -> Result<AssetAdministrationShell, TwinError> {
+    env.into_shell(shell_id)
+        .map_err(|e| TwinError::not_found(shell_id.unwrap_or(""), e))
+}
+
+/// The name an enum value has in the serialization, e.g. "ModelReference"
+pub(crate) fn enum_name<T: Serialize>(value: &T) -> String {
+    match serde_json::to_value(value) {
+        Ok(serde_json::Value::String(s)) => s,
+        _ => String::new(),
+    }
+}
+
+/// A serializable value as a plain JS object or array (maps become objects)
+#[cfg(feature = "wasm")]
+pub(crate) fn to_js<T: Serialize>(value: &T) -> JsValue {
+    value
+        .serialize(&serde_wasm_bindgen::Serializer::json_compatible())
+        .unwrap_or(JsValue::NULL)
+}
+
+/// Parse a twin configuration: a bare shell, or an environment from which the
+/// shell with `shell_id` (or the first one) is taken. V2.0 environments are
+/// migrated; the rewrites applied are returned alongside the shell. YAML is
+/// accepted in place of JSON.
+pub(crate) fn parse_config(
+    json: &str,
+    shell_id: Option<&str>,
+) -> Result<(AssetAdministrationShell, Vec<MigrationChange>), String> {
+    config_from_value(config_value(json)?, shell_id)
+}
+
+/// A JSON or YAML configuration as a JSON value
+pub(crate) fn config_value(json: &str) -> Result<serde_json::Value, String> {
+    if yaml::is_yaml(json) {
+        yaml_to_json(json)
+    } else {
+        serde_json::from_str(json).map_err(|e| format!("Invalid AAS JSON: {}", e))
+    }
+}
+
+/// `parse_config` for an already parsed document
+pub(crate) fn config_from_value(
+    value: serde_json::Value,
+    shell_id: Option<&str>,
+) -> Result<(AssetAdministrationShell, Vec<MigrationChange>), String> {
+    if is_v2_json(&value) {
+        let (env, changes) = environment_from_v2_json(value)?;
+        return Ok((env.into_shell(shell_id)?, changes));
+    }
+    let shell = if spec_json::is_spec_json(&value) {
+        environment_from_spec_json(value)?.into_shell(shell_id)?
+    } else if value.get("asset_administration_shells").is_some() {
+        let env: Environment = serde_json::from_value(value)
+            .map_err(|e| format!("Invalid AAS environment JSON: {}", e))?;
+        env.into_shell(shell_id)?
+    } else {
+        serde_json::from_value(value).map_err(|e| format!("Invalid AAS JSON: {}", e))?
+    };
+    Ok((shell, Vec::new()))
+}
+
 // --- 3. Module-level functions for utilities ---
 
 /// Validate if a JSON string is a valid AAS configuration
-#[wasm_bindgen]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 pub fn validate_aas_json(json_str: &str) -> bool {
-    serde_json::from_str::<AssetAdministrationShell>(json_str).is_ok()
+    parse_config(json_str, None).is_ok_and(|(shell, _)| DigitalTwin::from_shell(shell).is_ok())
 }
 
 /// Get the library version
-#[wasm_bindgen]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 pub fn get_version() -> String {
     env!("CARGO_PKG_VERSION").to_string()
 }
@@ -155,6 +1395,845 @@ mod tests {
 
         let twin = DigitalTwin::new(json).unwrap();
         assert_eq!(twin.get_id(), "MOTOR-12345");
-        assert!(twin.get_property("Voltage").contains("400"));
+        assert!(twin.get_property_display("Voltage").contains("400"));
+    }
+
+    #[test]
+    fn test_summary_json() {
+        let json = r#"{
+            "id": "MOTOR-12345",
+            "asset_type": "Siemens 1LE1",
+            "nameplate": [{"id_short": "Voltage", "value": "400", "unit": "V"}],
+            "submodels": [{
+                "id": "urn:example:sm:td",
+                "id_short": "TechnicalData",
+                "submodel_elements": [{"id_short": "MaxTorque", "value": "250"}]
+            }]
+        }"#;
+
+        let mut twin = DigitalTwin::new(json).unwrap();
+        twin.advance_simulation();
+        let summary: serde_json::Value = serde_json::from_str(&twin.get_summary_json()).unwrap();
+        assert_eq!(
+            summary,
+            serde_json::json!({
+                "id": "MOTOR-12345",
+                "assetType": "Siemens 1LE1",
+                "submodelCount": 1,
+                "propertyCount": 2,
+                "lastTick": 1,
+                "alarms": 0,
+                "overtemperature": false
+            })
+        );
+    }
+
+    #[test]
+    fn test_property_by_semantic_id() {
+        let json = r#"{
+            "id": "MOTOR-12345",
+            "asset_type": "Siemens 1LE1",
+            "nameplate": [
+                {"id_short": "Spannung", "value": "400", "unit": "V",
+                 "semantic_id": "0173-1#02-AAE416#004"}
+            ],
+            "submodels": [{
+                "id": "urn:example:sm:td",
+                "id_short": "TechnicalData",
+                "submodel_elements": [
+                    {"id_short": "RatedVoltage", "value": "230", "unit": "V",
+                     "semantic_id": "0173-1#02-AAE416#004"},
+                    {"id_short": "MaxTorque", "value": "250", "unit": "Nm"}
+                ]
+            }]
+        }"#;
+
+        let twin = DigitalTwin::new(json).unwrap();
+        assert_eq!(
+            twin.get_property_by_semantic_id("0173-1#02-AAE416#004")
+                .unwrap(),
+            "400 V"
+        );
+        assert!(twin.get_aas_json().contains("0173-1#02-AAE416#004"));
+
+        let found = twin.elements_by_semantic_id("0173-1#02-AAE416#004");
+        let paths: Vec<_> = found.iter().map(|d| d.path.as_str()).collect();
+        assert_eq!(paths, ["Spannung", "TechnicalData.RatedVoltage"]);
+        assert_eq!(found[1].value, serde_json::json!("230"));
+        assert!(twin
+            .elements_by_semantic_id("0173-1#02-AAE417#004")
+            .is_empty());
+    }
+
+    #[test]
+    fn test_typed_property_values() {
+        let json = r#"{
+            "id": "MOTOR-12345",
+            "asset_type": "Siemens 1LE1",
+            "nameplate": [
+                {"id_short": "Power", "value": "7.5", "unit": "kW", "value_type": "xs:double"},
+                {"id_short": "Poles", "value": "4", "unit": null, "value_type": "xs:int"},
+                {"id_short": "Ex", "value": "false", "unit": null, "value_type": "xs:boolean"},
+                {"id_short": "IPRating", "value": "IP55", "unit": null, "value_type": "xs:string"}
+            ]
+        }"#;
+
+        let twin = DigitalTwin::new(json).unwrap();
+        assert_eq!(twin.get_property_f64("Power").unwrap(), 7.5);
+        assert_eq!(twin.get_property_f64("Poles").unwrap(), 4.0);
+        assert_eq!(twin.get_property_i32("Poles").unwrap(), 4);
+        assert!(!twin.get_property_bool("Ex").unwrap());
+
+        let ip = &twin.data.nameplate[3];
+        assert!(ip.value_as_f64().unwrap_err().contains("xs:string"));
+        assert!(twin.data.nameplate[0].value_as_i32().is_err());
+        assert!(twin
+            .get_aas_json()
+            .contains("\"value_type\": \"xs:double\""));
+    }
+
+    #[test]
+    fn test_structured_property_values() {
+        let json = r#"{
+            "id": "MOTOR-12345",
+            "asset_type": "Siemens 1LE1",
+            "nameplate": [
+                {"id_short": "Power", "value": "7.5", "unit": "kW", "value_type": "xs:double"},
+                {"id_short": "Poles", "value": "4", "unit": null, "value_type": "xs:int"},
+                {"id_short": "Serial, Batch", "value": "A1", "unit": null}
+            ],
+            "submodels": [{
+                "id": "urn:example:sm:td",
+                "id_short": "TechnicalData",
+                "submodel_elements": [
+                    {"id_short": "Ambient", "model_type": "Range", "value_type": "xs:int", "min": "-20", "max": "40"}
+                ]
+            }]
+        }"#;
+
+        let twin = DigitalTwin::new(json).unwrap();
+        assert_eq!(
+            twin.property_value("Power").unwrap(),
+            PropertyValue {
+                path: "Power".to_string(),
+                value: serde_json::json!(7.5),
+                unit: Some("kW".to_string()),
+            }
+        );
+        assert_eq!(twin.property_value("Poles").unwrap().value, 4);
+        let ambient = twin.property_value("Ambient").unwrap();
+        assert_eq!(ambient.path, "TechnicalData.Ambient");
+        assert_eq!(ambient.value, serde_json::json!({"min": -20, "max": 40}));
+        assert!(twin.property_value("Torque").is_err());
+
+        let entries = twin.property_entries();
+        assert_eq!(
+            entries[0],
+            PropertyEntry {
+                id_short_path: "Power".to_string(),
+                value_type: Some(DataTypeDefXsd::Double),
+                unit: Some("kW".to_string()),
+            }
+        );
+        assert_eq!(entries[2].id_short_path, "Serial, Batch");
+        assert_eq!(entries[3].value_type, Some(DataTypeDefXsd::Int));
+
+        let summary = twin.summary();
+        assert_eq!(summary.asset_type, "Siemens 1LE1");
+        assert_eq!(summary.properties[2], "Serial, Batch");
+        assert!(twin
+            .get_summary_display()
+            .starts_with("Asset: MOTOR-12345\n"));
+    }
+
+    #[test]
+    fn test_property_detail() {
+        let json = r#"{
+            "id": "MOTOR-12345",
+            "asset_type": "Siemens 1LE1",
+            "nameplate": [],
+            "submodels": [{
+                "id": "urn:example:sm:td",
+                "id_short": "TechnicalData",
+                "submodel_elements": [{
+                    "id_short": "MaxTorque", "value": "120", "unit": "Nm", "value_type": "xs:double",
+                    "category": "PARAMETER",
+                    "semantic_id": {"type": "ExternalReference", "keys": [{"type": "GlobalReference", "value": "0173-1#02-BAE376#004"}]},
+                    "qualifiers": [{"type": "SMT/Cardinality", "value_type": "xs:string", "value": "One"}]
+                }]
+            }]
+        }"#;
+
+        let twin = DigitalTwin::new(json).unwrap();
+        let detail = twin.property_detail("MaxTorque").unwrap();
+        assert_eq!(detail.path, "TechnicalData.MaxTorque");
+        assert_eq!(detail.model_type, "Property");
+        assert_eq!(detail.value, serde_json::json!(120.0));
+        assert_eq!(detail.value_type, Some(DataTypeDefXsd::Double));
+        assert_eq!(detail.unit.as_deref(), Some("Nm"));
+        assert_eq!(detail.semantic_id.as_deref(), Some("0173-1#02-BAE376#004"));
+        assert_eq!(detail.category, Some(Category::Parameter));
+        assert_eq!(detail.qualifiers[0].qualifier_type, "SMT/Cardinality");
+
+        let err = twin.property_detail("TechnicalData.MinTorque").unwrap_err();
+        assert_eq!(err.code(), "NOT_FOUND");
+    }
+
+    #[test]
+    fn test_set_property() {
+        let json = r#"{
+            "id": "MOTOR-12345",
+            "asset_type": "Siemens 1LE1",
+            "nameplate": [],
+            "submodels": [{
+                "id": "urn:example:sm:op",
+                "id_short": "OperationalData",
+                "submodel_elements": [
+                    {"id_short": "Temperature", "value": "20", "unit": "degC", "value_type": "xs:double"},
+                    {"id_short": "Running", "value": "false", "unit": null, "value_type": "xs:boolean"},
+                    {"id_short": "LastService", "value": "", "unit": null, "value_type": "xs:dateTime"}
+                ]
+            }]
+        }"#;
+
+        let mut twin = DigitalTwin::new(json).unwrap();
+        twin.set_property("OperationalData.Temperature", "41.5")
+            .unwrap();
+        twin.set_property("Running", "true").unwrap();
+        twin.set_property("LastService", "2024-05-01T08:30:00+02:00")
+            .unwrap();
+        assert_eq!(twin.get_property_f64("Temperature").unwrap(), 41.5);
+        assert_eq!(twin.get_property_display("Temperature"), "41.5 degC");
+        assert!(twin.get_property_bool("Running").unwrap());
+
+        let temperature = twin.find_element_mut("Temperature").unwrap();
+        assert_eq!(
+            temperature.set_value("warm").unwrap_err(),
+            "Property 'Temperature': 'warm' is not a valid xs:double"
+        );
+        let last_service = twin.find_element_mut("LastService").unwrap();
+        assert!(last_service.set_value("2024-05-01").is_err());
+        assert_eq!(twin.get_property_display("Temperature"), "41.5 degC");
+    }
+
+    #[test]
+    fn test_localized_nameplate() {
+        let json = r#"{
+            "id": "MOTOR-12345",
+            "asset_type": "Siemens 1LE1",
+            "nameplate": [
+                {"id_short": "ManufacturerName", "model_type": "MultiLanguageProperty", "value": [
+                    {"language": "en", "text": "Siemens AG"},
+                    {"language": "zh", "text": "西门子"}
+                ]},
+                {"id_short": "Voltage", "value": "400", "unit": "V"}
+            ]
+        }"#;
+
+        let twin = DigitalTwin::new(json).unwrap();
+        assert_eq!(
+            twin.get_property_localized("ManufacturerName", "zh")
+                .unwrap(),
+            "西门子"
+        );
+        assert_eq!(twin.get_property_localized("Voltage", "zh").unwrap(), "400");
+        assert_eq!(twin.get_property_display("ManufacturerName"), "Siemens AG");
+    }
+
+    #[test]
+    fn test_list_files() {
+        let json = r#"{
+            "id": "MOTOR-12345",
+            "asset_type": "Siemens 1LE1",
+            "nameplate": [{"id_short": "Voltage", "value": "400", "unit": "V"}],
+            "submodels": [{
+                "id": "urn:example:sm:docs",
+                "id_short": "Documentation",
+                "submodel_elements": [
+                    {"id_short": "Datasheet", "model_type": "File",
+                     "value": "/aasx/files/datasheet.pdf", "content_type": "application/pdf"},
+                    {"id_short": "CadModel", "model_type": "File",
+                     "value": "https://example.com/motor.step", "content_type": "model/step"}
+                ]
+            }]
+        }"#;
+
+        let twin = DigitalTwin::new(json).unwrap();
+        let files = twin.file_entries();
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].path, "Documentation.Datasheet");
+        assert_eq!(files[0].file_name.as_deref(), Some("datasheet.pdf"));
+        assert!(files[1].is_external);
+        assert_eq!(files[1].content_type.as_deref(), Some("model/step"));
+    }
+
+    #[test]
+    fn test_relationships_resolve_both_ends() {
+        let json = r#"{
+            "id": "urn:example:aas:line",
+            "asset_type": "Pump station",
+            "nameplate": [],
+            "submodels": [{
+                "id": "urn:example:sm:bom",
+                "id_short": "Topology",
+                "submodel_elements": [
+                    {"id_short": "Motor", "value": "1LE1", "unit": null},
+                    {"id_short": "Pump", "value": "Etanorm", "unit": null},
+                    {"id_short": "MotorDrivesPump", "model_type": "AnnotatedRelationshipElement",
+                     "first": {"type": "ModelReference", "keys": [
+                        {"type": "Submodel", "value": "urn:example:sm:bom"},
+                        {"type": "Property", "value": "Motor"}]},
+                     "second": {"type": "ModelReference", "keys": [
+                        {"type": "Submodel", "value": "urn:example:sm:bom"},
+                        {"type": "Property", "value": "Pump"}]},
+                     "annotations": [{"id_short": "Coupling", "value": "flexible", "unit": null}]},
+                    {"id_short": "SuppliedBy", "model_type": "RelationshipElement",
+                     "first": {"type": "ModelReference", "keys": [
+                        {"type": "Submodel", "value": "urn:example:sm:bom"},
+                        {"type": "Property", "value": "Pump"}]},
+                     "second": {"type": "ExternalReference", "keys": [
+                        {"type": "GlobalReference", "value": "https://example.com/ksb"}]}}
+                ]
+            }]
+        }"#;
+
+        let twin = DigitalTwin::new(json).unwrap();
+        let rels = twin.relationship_entries();
+        assert_eq!(rels.len(), 2);
+        assert_eq!(rels[0].path, "Topology.MotorDrivesPump");
+        assert_eq!(rels[0].first_path.as_deref(), Some("Topology.Motor"));
+        assert_eq!(rels[0].second_path.as_deref(), Some("Topology.Pump"));
+        assert_eq!(rels[0].annotations, vec!["Coupling"]);
+        assert_eq!(rels[1].second_path, None);
+        let supplier = rels[1].second.as_ref().unwrap();
+        assert_eq!(supplier.keys[0].value, "https://example.com/ksb");
+    }
+
+    #[test]
+    fn test_bill_of_material_entities() {
+        let json = r#"{
+            "id": "urn:example:aas:pump-unit",
+            "asset_type": "Pump unit",
+            "nameplate": [],
+            "submodels": [{
+                "id": "urn:example:sm:bom",
+                "id_short": "BillOfMaterial",
+                "submodel_elements": [{
+                    "id_short": "PumpUnit", "model_type": "Entity",
+                    "entity_type": "SelfManagedEntity",
+                    "global_asset_id": "urn:example:asset:pump-unit",
+                    "statements": [
+                        {"id_short": "Motor", "model_type": "Entity",
+                         "entity_type": "SelfManagedEntity",
+                         "global_asset_id": "urn:example:asset:motor",
+                         "statements": [
+                            {"id_short": "Bearing", "model_type": "Entity",
+                             "entity_type": "CoManagedEntity"},
+                            {"id_short": "RatedPower", "value": "7.5", "unit": "kW"}
+                         ]},
+                        {"id_short": "Coupling", "model_type": "Entity",
+                         "entity_type": "CoManagedEntity"}
+                    ]
+                }]
+            }]
+        }"#;
+
+        let twin = DigitalTwin::new(json).unwrap();
+        let entities = twin.entity_entries();
+        assert_eq!(entities.len(), 4);
+        let bearing = &entities[2];
+        assert_eq!(bearing.path, "BillOfMaterial.PumpUnit.Motor.Bearing");
+        assert_eq!(
+            bearing.parent.as_deref(),
+            Some("BillOfMaterial.PumpUnit.Motor")
+        );
+        assert_eq!(entities[0].parent, None);
+        assert_eq!(
+            twin.get_entity_components("BillOfMaterial.PumpUnit")
+                .unwrap(),
+            r#"["BillOfMaterial.PumpUnit.Motor","BillOfMaterial.PumpUnit.Coupling"]"#
+        );
+        assert_eq!(
+            twin.get_property_f64("BillOfMaterial.PumpUnit.Motor.RatedPower")
+                .unwrap(),
+            7.5
+        );
+        assert!(twin
+            .entity_entries()
+            .iter()
+            .any(|e| e.global_asset_id.as_deref() == Some("urn:example:asset:motor")));
+    }
+
+    #[test]
+    fn test_list_capabilities() {
+        let json = r#"{
+            "id": "urn:example:aas:cell",
+            "asset_type": "Robot cell",
+            "nameplate": [],
+            "submodels": [{
+                "id": "urn:example:sm:capabilities",
+                "id_short": "Capabilities",
+                "submodel_elements": [
+                    {"id_short": "Drilling", "model_type": "Capability", "semantic_id": {
+                        "type": "ExternalReference",
+                        "keys": [{"type": "GlobalReference", "value": "https://example.com/cap/Drilling"}]
+                    }},
+                    {"id_short": "Welding", "model_type": "Capability"}
+                ]
+            }]
+        }"#;
+
+        let twin = DigitalTwin::new(json).unwrap();
+        let caps = twin.capability_entries();
+        assert_eq!(caps.len(), 2);
+        assert_eq!(
+            caps[0].semantic_id.as_deref(),
+            Some("https://example.com/cap/Drilling")
+        );
+        assert_eq!(caps[1].path, "Capabilities.Welding");
+        assert_eq!(caps[1].semantic_id, None);
+        assert_eq!(caps[0].path, "Capabilities.Drilling");
+    }
+
+    #[test]
+    fn test_nested_nameplate_properties() {
+        let json = r#"{
+            "id": "MOTOR-12345",
+            "asset_type": "Siemens 1LE1",
+            "nameplate": [
+                {"id_short": "Voltage", "value": "400", "unit": "V"},
+                {"id_short": "Markings", "model_type": "SubmodelElementCollection", "value": [
+                    {"id_short": "CE", "value": "true", "unit": null, "value_type": "xs:boolean"}
+                ]}
+            ],
+            "submodels": [{
+                "id": "urn:example:sm:technical-data",
+                "id_short": "TechnicalData",
+                "submodel_elements": [
+                    {"id_short": "GeneralInformation", "model_type": "SubmodelElementCollection",
+                     "value": [{"id_short": "ManufacturerName", "value": "Siemens", "unit": null}]}
+                ]
+            }]
+        }"#;
+
+        let twin = DigitalTwin::new(json).unwrap();
+        assert_eq!(
+            twin.list_properties_display(),
+            "Voltage, Markings.CE, TechnicalData.GeneralInformation.ManufacturerName"
+        );
+        assert!(twin.get_property_bool("Markings.CE").unwrap());
+        assert_eq!(
+            twin.get_property_display("TechnicalData.GeneralInformation.ManufacturerName"),
+            "Siemens "
+        );
+        assert_eq!(twin.get_property_display("ManufacturerName"), "Siemens ");
+        assert_eq!(
+            twin.get_property_display("Markings.UL"),
+            "Property 'Markings.UL' not found"
+        );
+    }
+
+    #[test]
+    fn test_list_items_by_index() {
+        let json = r#"{
+            "id": "MOTOR-12345",
+            "asset_type": "Siemens 1LE1",
+            "nameplate": [{
+                "id_short": "Markings",
+                "model_type": "SubmodelElementList",
+                "type_value_list_element": "File",
+                "value": [
+                    {"model_type": "File", "value": "/aasx/ce.png", "content_type": "image/png"},
+                    {"model_type": "File", "value": "/aasx/ukca.png", "content_type": "image/png"}
+                ]
+            }]
+        }"#;
+
+        let twin = DigitalTwin::new(json).unwrap();
+        assert!(twin
+            .get_list_item("Markings", 1)
+            .unwrap()
+            .contains("ukca.png"));
+        assert_eq!(twin.list_properties_display(), "Markings[0], Markings[1]");
+        assert_eq!(twin.file_entries()[0].path, "Markings[0]");
+    }
+
+    #[test]
+    fn test_id_short_paths_into_nested_lists() {
+        let json = r#"{
+            "id": "MOTOR-12345",
+            "asset_type": "Siemens 1LE1",
+            "nameplate": [],
+            "submodels": [{
+                "id": "urn:example:sm:td",
+                "id_short": "TechnicalData",
+                "submodel_elements": [{
+                    "id_short": "Markings",
+                    "model_type": "SubmodelElementList",
+                    "type_value_list_element": "SubmodelElementCollection",
+                    "value": [
+                        {"model_type": "SubmodelElementCollection", "value": [
+                            {"id_short": "MarkingName", "value": "CE"}
+                        ]},
+                        {"model_type": "SubmodelElementCollection", "value": [
+                            {"id_short": "MarkingName", "value": "UKCA"}
+                        ]}
+                    ]
+                }]
+            }]
+        }"#;
+
+        let mut twin = DigitalTwin::new(json).unwrap();
+        let paths: Vec<_> = twin.elements().map(|(path, _)| path).collect();
+        assert_eq!(
+            paths,
+            [
+                "TechnicalData.Markings",
+                "TechnicalData.Markings[0]",
+                "TechnicalData.Markings[0].MarkingName",
+                "TechnicalData.Markings[1]",
+                "TechnicalData.Markings[1].MarkingName",
+            ]
+        );
+
+        let path = "TechnicalData.Markings[1].MarkingName";
+        assert_eq!(twin.get_property_display(path), "UKCA ");
+        twin.set_property(path, "UL").unwrap();
+        assert_eq!(
+            twin.get_property_display("TechnicalData.Markings.1.MarkingName"),
+            "UL "
+        );
+
+        twin.remove_element_at("TechnicalData.Markings.0").unwrap();
+        assert_eq!(
+            twin.get_property_display("TechnicalData.Markings[0].MarkingName"),
+            "UL "
+        );
+
+        let err = twin
+            .set_property("TechnicalData.Markings[x]", "CE")
+            .unwrap_err();
+        assert_eq!(err.code(), "INVALID_ARGUMENT");
+        assert!(err.message().contains("'x' is not an index"));
+        // An index only selects list items
+        assert!(twin
+            .set_property("TechnicalData.Markings[0].MarkingName[0]", "CE")
+            .is_err_and(|e| e.code() == "NOT_FOUND"));
+    }
+
+    #[cfg(feature = "wasm")]
+    #[test]
+    fn test_typescript_declarations() {
+        assert!(PropertyValue::DECL.contains("value: ElementValue;"));
+        assert!(PropertyValue::DECL.contains("unit: string | null;"));
+        // Flattened element kinds make an intersection, not an interface
+        assert!(SubmodelElement::DECL.starts_with("export type SubmodelElement = {"));
+        assert!(SubmodelElement::DECL.ends_with("} & ElementKind;"));
+        assert!(ElementKind::DECL.contains(r#"({ model_type: "Range" } & Range)"#));
+    }
+
+    #[test]
+    fn test_qualifiers_preserved() {
+        let json = r#"{
+            "id": "MOTOR-12345",
+            "asset_type": "Siemens 1LE1",
+            "nameplate": [],
+            "submodels": [{
+                "id": "urn:example:sm:contact",
+                "id_short": "Contact",
+                "qualifiers": [{"type": "SMT/Cardinality", "value_type": "xs:string", "value": "One"}],
+                "submodel_elements": [{
+                    "id_short": "Phone", "value": "+49 9131 7-0", "unit": null,
+                    "qualifiers": [{"type": "SMT/Cardinality", "value_type": "xs:string",
+                                    "value": "ZeroToOne", "kind": "TemplateQualifier"}]
+                }]
+            }]
+        }"#;
+
+        let twin = DigitalTwin::new(json).unwrap();
+        assert!(twin
+            .get_qualifiers("Contact.Phone")
+            .unwrap()
+            .contains(r#""value":"ZeroToOne","kind":"TemplateQualifier""#));
+        assert!(twin
+            .get_qualifiers("Contact")
+            .unwrap()
+            .contains(r#""value":"One""#));
+        assert!(twin.get_aas_json().contains("ZeroToOne"));
+    }
+
+    #[test]
+    fn test_bump_revisions() {
+        let json = r#"{
+            "id": "MOTOR-12345",
+            "asset_type": "Siemens 1LE1",
+            "administration": {"version": "1", "revision": "3"},
+            "nameplate": [],
+            "submodels": [{"id": "urn:example:sm:1", "id_short": "TechnicalData"}]
+        }"#;
+
+        let mut twin = DigitalTwin::new(json).unwrap();
+        assert_eq!(twin.bump_revision().unwrap(), "4");
+        assert_eq!(
+            twin.get_administration().unwrap(),
+            r#"{"version":"1","revision":"4"}"#
+        );
+        assert_eq!(twin.bump_submodel_revision("TechnicalData").unwrap(), "1");
+        assert!(twin
+            .get_submodel("TechnicalData")
+            .unwrap()
+            .contains(r#""revision": "1""#));
+    }
+
+    #[test]
+    fn test_asset_information() {
+        let json = r#"{
+            "id": "urn:example:aas:motor",
+            "asset_information": {
+                "asset_kind": "Instance",
+                "global_asset_id": "urn:example:asset:motor-12345",
+                "specific_asset_ids": [{"name": "SerialNumber", "value": "XYZ-2024-001234"}],
+                "asset_type": "Siemens 1LE1",
+                "default_thumbnail": {"path": "/aasx/nameplate.jpg", "content_type": "image/jpeg"}
+            },
+            "nameplate": []
+        }"#;
+
+        let mut twin = DigitalTwin::new(json).unwrap();
+        assert_eq!(twin.get_asset_type(), "Siemens 1LE1");
+        assert_eq!(
+            twin.get_global_asset_id().as_deref(),
+            Some("urn:example:asset:motor-12345")
+        );
+        assert_eq!(
+            twin.get_specific_asset_id("SerialNumber").as_deref(),
+            Some("XYZ-2024-001234")
+        );
+
+        twin.set_specific_asset_id("SerialNumber", "XYZ-2024-999999");
+        twin.set_specific_asset_id("ManufacturerPartId", "1LE1001-1AB43-4AA4");
+        twin.set_asset_kind("Type").unwrap();
+        assert_eq!(twin.get_asset_kind(), "Type");
+        let info = twin.get_asset_information();
+        assert!(info.contains("XYZ-2024-999999") && info.contains("ManufacturerPartId"));
+        assert!(info.contains("/aasx/nameplate.jpg"));
+    }
+
+    #[test]
+    fn test_concept_lookup() {
+        let json = r#"{
+            "id": "MOTOR-12345",
+            "asset_type": "Siemens 1LE1",
+            "nameplate": [
+                {"id_short": "Voltage", "value": "400", "unit": "V",
+                 "semantic_id": "0173-1#02-AAE416#004"},
+                {"id_short": "Colour", "value": "RAL 7030", "unit": null}
+            ],
+            "concept_descriptions": [
+                {"id": "0173-1#02-AAE416#004", "id_short": "RatedVoltage"}
+            ]
+        }"#;
+
+        let twin = DigitalTwin::new(json).unwrap();
+        let concept = twin.get_concept_for("Voltage").unwrap().unwrap();
+        assert!(concept.contains("RatedVoltage"));
+        assert_eq!(twin.get_concept_for("Colour").unwrap(), None);
+        assert!(twin.get_aas_json().contains(r#""concept_descriptions": ["#));
+    }
+
+    #[test]
+    fn test_iec61360_units_and_definitions() {
+        let iec = r#"{"type": "ExternalReference", "keys": [{"type": "GlobalReference",
+            "value": "https://admin-shell.io/DataSpecificationTemplates/DataSpecificationIec61360/3/0"}]}"#;
+        let json = format!(
+            r#"{{
+            "id": "MOTOR-12345",
+            "asset_type": "Siemens 1LE1",
+            "nameplate": [
+                {{"id_short": "Voltage", "value": "400", "semantic_id": "0173-1#02-AAE416#004"}},
+                {{"id_short": "Speed", "value": "1450",
+                 "embedded_data_specifications": [{{"data_specification": {iec},
+                     "data_specification_content": {{"preferred_name": [], "unit": "1/min"}}}}]}}
+            ],
+            "concept_descriptions": [
+                {{"id": "0173-1#02-AAE416#004",
+                 "embedded_data_specifications": [{{"data_specification": {iec},
+                     "data_specification_content": {{
+                         "preferred_name": [{{"language": "en", "text": "Rated voltage"}}],
+                         "unit": "V",
+                         "definition": [{{"language": "en", "text": "Nominal supply voltage"}}]}}}}]}}
+            ]
+        }}"#
+        );
+
+        let twin = DigitalTwin::new(&json).unwrap();
+        assert_eq!(twin.get_unit("Voltage").unwrap().as_deref(), Some("V"));
+        assert_eq!(twin.get_unit("Speed").unwrap().as_deref(), Some("1/min"));
+        assert_eq!(
+            twin.get_definition("Voltage", "de").unwrap().as_deref(),
+            Some("Nominal supply voltage")
+        );
+
+        let exported = DigitalTwin::new(&twin.get_aas_json()).unwrap();
+        assert_eq!(
+            exported.get_unit("Speed").unwrap().as_deref(),
+            Some("1/min")
+        );
+    }
+
+    #[test]
+    fn test_display_names() {
+        let json = r#"{
+            "id": "MOTOR-12345",
+            "display_name": [{"language": "en", "text": "Motor 12345"}],
+            "asset_type": "Siemens 1LE1",
+            "nameplate": [
+                {"id_short": "Voltage", "value": "400", "unit": "V",
+                 "display_name": [{"language": "en", "text": "Rated voltage"},
+                                  {"language": "de", "text": "Bemessungsspannung"}],
+                 "description": [{"language": "en", "text": "Supply voltage"}]},
+                {"id_short": "Colour", "value": "RAL 7030"}
+            ],
+            "submodels": [{"id": "urn:example:sm:td", "id_short": "TechnicalData",
+                           "display_name": [{"language": "de", "text": "Technische Daten"}]}]
+        }"#;
+
+        let twin = DigitalTwin::new(json).unwrap();
+        assert_eq!(twin.get_display_name("", "en").unwrap(), "Motor 12345");
+        assert_eq!(
+            twin.get_display_name("Voltage", "de-AT").unwrap(),
+            "Bemessungsspannung"
+        );
+        assert_eq!(
+            twin.get_display_name("Voltage", "fr").unwrap(),
+            "Rated voltage"
+        );
+        assert_eq!(twin.get_display_name("Colour", "en").unwrap(), "Colour");
+        assert_eq!(
+            twin.get_display_name("TechnicalData", "en").unwrap(),
+            "Technische Daten"
+        );
+        assert_eq!(
+            twin.get_description("Voltage", "de").unwrap().as_deref(),
+            Some("Supply voltage")
+        );
+        assert_eq!(twin.get_description("Colour", "en").unwrap(), None);
+    }
+
+    #[test]
+    fn test_search_properties() {
+        let json = r#"{
+            "id": "MOTOR-12345",
+            "asset_type": "Siemens 1LE1",
+            "nameplate": [
+                {"id_short": "RatedVoltage", "value": "400", "unit": "V"},
+                {"id_short": "MaxTorque", "value": "250", "unit": "Nm",
+                 "display_name": [{"language": "de", "text": "Maximales Drehmoment"}]},
+                {"id_short": "Temp1", "value": "40"},
+                {"id_short": "Temp12", "value": "41"}
+            ]
+        }"#;
+
+        let twin = DigitalTwin::new(json).unwrap();
+        let paths = |pattern: &str| -> Vec<String> {
+            twin.search_property_entries(pattern)
+                .into_iter()
+                .map(|e| e.id_short_path)
+                .collect()
+        };
+        assert_eq!(paths("volt"), ["RatedVoltage"]);
+        assert_eq!(paths("DREH"), ["MaxTorque"]);
+        assert_eq!(paths("max*"), ["MaxTorque"]);
+        assert_eq!(paths("temp?"), ["Temp1"]);
+        assert_eq!(paths("*t*1*").len(), 2);
+        assert!(paths("current").is_empty());
+    }
+
+    #[test]
+    fn test_list_properties_by_category() {
+        let json = r#"{
+            "id": "MOTOR-12345",
+            "asset_type": "Siemens 1LE1",
+            "nameplate": [
+                {"id_short": "SerialNumber", "value": "XYZ-1", "category": "CONSTANT"},
+                {"id_short": "Voltage", "value": "400", "unit": "V"}
+            ],
+            "submodels": [{"id": "urn:example:sm:op", "id_short": "Operational",
+                           "submodel_elements": [
+                               {"id_short": "Speed", "value": "1450", "category": "VARIABLE"},
+                               {"id_short": "Temperature", "value": "41", "category": "VARIABLE"}
+                           ]}]
+        }"#;
+
+        let twin = DigitalTwin::new(json).unwrap();
+        assert_eq!(
+            twin.list_properties_by_category("variable").unwrap(),
+            ["Operational.Speed", "Operational.Temperature"]
+        );
+        assert_eq!(
+            twin.list_properties_by_category("CONSTANT").unwrap(),
+            ["SerialNumber"]
+        );
+        assert!(twin.get_aas_json().contains(r#""category": "VARIABLE""#));
+    }
+
+    #[test]
+    fn test_constructor_accepts_environment() {
+        let env = r#"{
+            "asset_administration_shells": [{
+                "id": "urn:example:aas:motor",
+                "asset_information": {"asset_kind": "Instance", "asset_type": "Siemens 1LE1"},
+                "submodels": [{"type": "ModelReference",
+                               "keys": [{"type": "Submodel", "value": "urn:example:sm:np"}]}]
+            }],
+            "submodels": [{"id": "urn:example:sm:np", "id_short": "Nameplate",
+                           "submodel_elements": [{"id_short": "Voltage", "value": "400", "unit": "V"}]}]
+        }"#;
+
+        assert!(validate_aas_json(env));
+        let twin = DigitalTwin::new(env).unwrap();
+        assert_eq!(twin.get_id(), "urn:example:aas:motor");
+        assert_eq!(twin.get_property_display("Nameplate.Voltage"), "400 V");
+        assert_eq!(twin.get_property_display("Voltage"), "400 V");
+
+        let selected =
+            DigitalTwin::from_environment(env, Some("urn:example:aas:motor".to_string())).unwrap();
+        assert!(selected
+            .get_environment_json()
+            .contains("urn:example:sm:np"));
+    }
+
+    #[test]
+    fn test_submodels_alongside_nameplate() {
+        let json = r#"{
+            "id": "MOTOR-12345",
+            "asset_type": "Siemens 1LE1",
+            "nameplate": [],
+            "submodels": [
+                {
+                    "id": "urn:example:sm:technical-data",
+                    "id_short": "TechnicalData",
+                    "semantic_id": "https://admin-shell.io/ZVEI/TechnicalData/Submodel/1/2",
+                    "submodel_elements": [
+                        {"id_short": "MaxTorque", "value": "49", "unit": "Nm"}
+                    ]
+                },
+                {"id": "urn:example:sm:docs", "id_short": "Documentation", "kind": "Template"}
+            ]
+        }"#;
+
+        let twin = DigitalTwin::new(json).unwrap();
+        assert_eq!(twin.list_submodels(), ["TechnicalData", "Documentation"]);
+        assert!(twin
+            .get_submodel("TechnicalData")
+            .unwrap()
+            .contains("MaxTorque"));
+        assert!(twin.get_submodel("OperationalData").is_none());
+        assert_eq!(
+            twin.get_property_f64("TechnicalData.MaxTorque").unwrap(),
+            49.0
+        );
+        assert_eq!(twin.data.submodels[0].kind, ModellingKind::Instance);
+        assert_eq!(twin.data.submodels[1].kind, ModellingKind::Template);
     }
 }
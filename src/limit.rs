@@ -0,0 +1,175 @@
+// --- Limit events ---
+// Limits configured per signal in the `limits` map of the simulation profile
+// are checked on every tick: when a simulated or ingested signal crosses one
+// between two ticks, the OperationalData LimitCrossed event is emitted with
+// the signal's path, the limit, the new value and the direction. Limits
+// keyed by a property path are checked whenever telemetry updates that
+// property. The events reach `on_event` listeners and the queue
+// `take_events` empties, so the UI does not have to poll and compare.
+
+use serde::Serialize;
+use serde_json::{json, Value};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{DigitalTwin, SimulationValues, TwinError, SIMULATION_SECTION};
+
+/// Event of the OperationalData submodel announcing limit crossings
+pub const LIMIT_EVENT_ID_SHORT: &str = "LimitCrossed";
+
+/// Which way a signal crossed a limit
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CrossingDirection {
+    Rising,
+    Falling,
+}
+
+/// Check that every limit of a signal is a finite number
+pub(crate) fn validate_limits(key: &str, limits: &[f64]) -> Result<(), TwinError> {
+    match limits.iter().find(|limit| !limit.is_finite()) {
+        Some(limit) => Err(TwinError::validation(
+            Some(SIMULATION_SECTION),
+            format!(
+                "Limit {} of signal '{}' must be a finite number",
+                limit, key
+            ),
+        )),
+        None => Ok(()),
+    }
+}
+
+/// The direction in which a signal moving from `before` to `after` crossed
+/// `limit`; reaching it from below counts as crossing
+fn crossing(before: f64, after: f64, limit: f64) -> Option<CrossingDirection> {
+    if before < limit && after >= limit {
+        Some(CrossingDirection::Rising)
+    } else if before >= limit && after < limit {
+        Some(CrossingDirection::Falling)
+    } else {
+        None
+    }
+}
+
+/// LimitCrossed payloads for the `limits` crossed by a change from `old` to
+/// `new` of what `path` names
+fn limit_crossings(path: &str, limits: &[f64], old: f64, new: f64) -> Vec<Value> {
+    limits
+        .iter()
+        .filter_map(|limit| {
+            let direction = crossing(old, new, *limit)?;
+            Some(json!({
+                "path": path,
+                "limit": limit,
+                "value": new,
+                "direction": direction,
+            }))
+        })
+        .collect()
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl DigitalTwin {
+    /// Emit the LimitCrossed event whenever the signal `key`
+    /// ("motor.current") or the property at that path crosses one of
+    /// `limits`, replacing its earlier ones; no limits stop the checks
+    pub fn set_signal_limits(&mut self, key: &str, limits: Vec<f64>) -> Result<(), TwinError> {
+        if key.is_empty() {
+            return Err(TwinError::invalid_argument("Signal key must be non-empty"));
+        }
+        validate_limits(key, &limits)?;
+        if limits.is_empty() {
+            self.profile.limits.remove(key);
+        } else {
+            self.profile.limits.insert(key.to_string(), limits);
+        }
+        Ok(())
+    }
+}
+
+impl DigitalTwin {
+    /// Announce the limits crossed since the outputs were `before`
+    pub(crate) fn check_limits(&mut self, before: &SimulationValues) {
+        let mut crossings = Vec::new();
+        for (key, limits) in &self.profile.limits {
+            let (Some(old), Some(new)) = (before.get(key), self.simulation_values().get(key))
+            else {
+                continue;
+            };
+            crossings.extend(limit_crossings(
+                &format!("Simulation.{}", key),
+                limits,
+                *old,
+                *new,
+            ));
+        }
+        self.announce_crossings(crossings);
+    }
+
+    /// Announce the limits of the property at `path` crossed by a change
+    /// from `old` to `new`
+    pub(crate) fn check_property_limits(&mut self, path: &str, old: f64, new: f64) {
+        let crossings = match self.profile.limits.get(path) {
+            Some(limits) => limit_crossings(path, limits, old, new),
+            None => return,
+        };
+        self.announce_crossings(crossings);
+    }
+
+    fn announce_crossings(&mut self, crossings: Vec<Value>) {
+        for payload in crossings {
+            // Limit checks run on every tick; an OperationalData that
+            // cannot be written must not stop the simulation
+            let _ = self.announce(LIMIT_EVENT_ID_SHORT, None, payload);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_limit_crossings_emit_events() {
+        let mut twin = DigitalTwin::new(
+            r#"{"id": "M-1", "asset_type": "Motor", "nameplate": [],
+                "simulation": {"limits": {"motor.rpm": [500, 1000]}}}"#,
+        )
+        .unwrap();
+        for _ in 0..30 {
+            twin.advance_simulation();
+        }
+        twin.set_load(3.0).unwrap();
+        for _ in 0..30 {
+            twin.advance_simulation();
+        }
+        let events = twin.take_event_messages();
+        let crossings: Vec<_> = events
+            .iter()
+            .filter(|e| e.source == "OperationalData.LimitCrossed")
+            .map(|e| {
+                (
+                    e.payload["limit"].as_f64().unwrap(),
+                    e.payload["direction"].clone(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            crossings,
+            vec![
+                (500.0, json!("rising")),
+                (1000.0, json!("rising")),
+                (1000.0, json!("falling")),
+                (500.0, json!("falling")),
+            ]
+        );
+        assert_eq!(events[0].payload["path"], "Simulation.motor.rpm");
+        assert!(twin.take_event_messages().is_empty());
+
+        assert!(twin
+            .set_signal_limits("motor.rpm", vec![f64::NAN])
+            .is_err_and(|e| e.code() == "VALIDATION_FAILED"));
+        twin.set_signal_limits("motor.rpm", Vec::new()).unwrap();
+        assert!(twin.simulation_profile().limits.is_empty());
+    }
+}
@@ -0,0 +1,508 @@
+// --- AAS V2.0 migration ---
+// Environments still written against the V2.0 metamodel are rewritten into
+// V3.0 spec JSON before loading. Every rewrite is counted in a report, so
+// users can see what changed (and what was dropped) for their shells.
+//
+// The V2.0 shapes handled: `identification {id, idType}` instead of `id`,
+// `modelType {"name": ..}`, a separate `assets` list referenced by the
+// shells, keys with `local`/`idType`, `mimeType`, `langString`-wrapped
+// multi-language values, `constraints` and the `kind` of submodel elements.
+
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+#[cfg(feature = "wasm")]
+use tsify::Tsify;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{environment_from_spec_json, DigitalTwin, Environment};
+
+/// How often one kind of rewrite was applied during a migration
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+pub struct MigrationChange {
+    pub change: String,
+    pub count: usize,
+}
+
+/// Whether a parsed JSON document uses the V2.0 metamodel
+pub fn is_v2_json(value: &Value) -> bool {
+    let identified = |list: &str| {
+        value
+            .get(list)
+            .and_then(Value::as_array)
+            .is_some_and(|items| items.iter().any(|i| i.get("identification").is_some()))
+    };
+    value.get("assets").is_some()
+        || identified("assetAdministrationShells")
+        || identified("submodels")
+        || identified("conceptDescriptions")
+}
+
+/// Rewrite a V2.0 environment into V3.0 spec JSON
+pub fn migrate_v2(value: Value) -> Result<(Value, Vec<MigrationChange>), String> {
+    let Value::Object(mut env) = value else {
+        return Err("AAS V2.0 environment must be a JSON object".to_string());
+    };
+    let mut m = Migrator::default();
+
+    // Asset kinds by asset id, for the shells' assetInformation
+    let mut asset_kinds = BTreeMap::new();
+    if let Some(Value::Array(assets)) = env.remove("assets") {
+        m.note("assets merged into the assetInformation of their shells");
+        for asset in &assets {
+            if let (Some(id), Some(kind)) = (identification(asset), asset.get("kind")) {
+                asset_kinds.insert(id, kind.clone());
+            }
+        }
+    }
+    for dropped in ["conceptDictionaries", "views"] {
+        if env.remove(dropped).is_some() {
+            m.note(&format!("{} dropped (removed in V3.0)", dropped));
+        }
+    }
+
+    let mut out = Map::new();
+    if let Some(Value::Array(shells)) = env.remove("assetAdministrationShells") {
+        let shells = shells
+            .into_iter()
+            .map(|s| m.shell(s, &asset_kinds))
+            .collect();
+        out.insert(
+            "assetAdministrationShells".to_string(),
+            Value::Array(shells),
+        );
+    }
+    if let Some(Value::Array(submodels)) = env.remove("submodels") {
+        let submodels = submodels.into_iter().map(|s| m.submodel(s)).collect();
+        out.insert("submodels".to_string(), Value::Array(submodels));
+    }
+    if let Some(Value::Array(cds)) = env.remove("conceptDescriptions") {
+        let cds = cds
+            .into_iter()
+            .map(|cd| m.concept_description(cd))
+            .collect();
+        out.insert("conceptDescriptions".to_string(), Value::Array(cds));
+    }
+
+    let changes = m
+        .changes
+        .into_iter()
+        .map(|(change, count)| MigrationChange { change, count })
+        .collect();
+    Ok((Value::Object(out), changes))
+}
+
+/// Read a V2.0 environment, returning it with the applied rewrites
+pub fn environment_from_v2_json(
+    value: Value,
+) -> Result<(Environment, Vec<MigrationChange>), String> {
+    let (v3, changes) = migrate_v2(value)?;
+    Ok((environment_from_spec_json(v3)?, changes))
+}
+
+fn identification(value: &Value) -> Option<String> {
+    value
+        .get("identification")
+        .and_then(|i| i.get("id"))
+        .and_then(Value::as_str)
+        .map(str::to_string)
+}
+
+/// Value of the last key of a V2.0 reference
+fn key_value(reference: &Value) -> Option<Value> {
+    reference
+        .get("keys")
+        .and_then(Value::as_array)
+        .and_then(|keys| keys.last())
+        .and_then(|key| key.get("value"))
+        .cloned()
+}
+
+fn object(value: Value) -> Map<String, Value> {
+    match value {
+        Value::Object(map) => map,
+        _ => Map::new(),
+    }
+}
+
+#[derive(Default)]
+struct Migrator {
+    changes: BTreeMap<String, usize>,
+}
+
+impl Migrator {
+    fn note(&mut self, change: &str) {
+        *self.changes.entry(change.to_string()).or_insert(0) += 1;
+    }
+
+    /// Attributes shared by identifiables, referables and qualifiables
+    fn common(&mut self, map: &mut Map<String, Value>) {
+        if let Some(identification) = map.remove("identification") {
+            if let Some(id) = identification.get("id") {
+                map.insert("id".to_string(), id.clone());
+            }
+            self.note("identification replaced by id");
+        }
+        if let Some(Value::Object(model_type)) = map.get("modelType") {
+            let name = model_type.get("name").cloned().unwrap_or(Value::Null);
+            map.insert("modelType".to_string(), name);
+            self.note("modelType object replaced by its name");
+        }
+        if let Some(Value::Array(constraints)) = map.remove("constraints") {
+            let (qualifiers, dropped): (Vec<_>, Vec<_>) = constraints.into_iter().partition(|c| {
+                c.get("modelType")
+                    .map(|t| t.get("name").unwrap_or(t) == "Qualifier")
+                    .unwrap_or(true)
+            });
+            if !dropped.is_empty() {
+                self.note("formula constraints dropped (removed in V3.0)");
+            }
+            if !qualifiers.is_empty() {
+                let qualifiers = qualifiers.into_iter().map(|q| self.qualifier(q)).collect();
+                map.insert("qualifiers".to_string(), Value::Array(qualifiers));
+                self.note("constraints replaced by qualifiers");
+            }
+        }
+        for reference in ["semanticId", "valueId", "derivedFrom"] {
+            self.reference_field(map, reference);
+        }
+        if let Some(Value::Array(specs)) = map.get_mut("embeddedDataSpecifications") {
+            for spec in specs.iter_mut().filter_map(Value::as_object_mut) {
+                self.reference_field(spec, "dataSpecification");
+                if let Some(Value::Object(content)) = spec.get_mut("dataSpecificationContent") {
+                    self.iec61360(content);
+                }
+            }
+        }
+        // Empty categories were common in V2.0 exports but are invalid in V3.0
+        if map.get("category").and_then(Value::as_str) == Some("") {
+            map.remove("category");
+        }
+    }
+
+    fn reference_field(&mut self, map: &mut Map<String, Value>, field: &str) {
+        if let Some(reference) = map.remove(field) {
+            map.insert(field.to_string(), self.reference(reference));
+        }
+    }
+
+    /// V3.0 references carry their type; keys lose `local` and `idType`
+    fn reference(&mut self, value: Value) -> Value {
+        let mut map = object(value);
+        if map.contains_key("type") {
+            return Value::Object(map);
+        }
+        let keys: Vec<Value> = match map.remove("keys") {
+            Some(Value::Array(keys)) => keys
+                .into_iter()
+                .map(|key| {
+                    let mut key = object(key);
+                    key.remove("local");
+                    key.remove("idType");
+                    if key.get("type").and_then(Value::as_str) == Some("Asset") {
+                        key.insert("type".to_string(), json!("GlobalReference"));
+                        self.note("Asset keys replaced by GlobalReference keys");
+                    }
+                    Value::Object(key)
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
+        let external = keys
+            .first()
+            .and_then(|k| k.get("type"))
+            .is_some_and(|t| t == "GlobalReference");
+        self.note("reference type derived from its first key");
+        json!({
+            "type": if external { "ExternalReference" } else { "ModelReference" },
+            "keys": keys,
+        })
+    }
+
+    fn shell(&mut self, value: Value, asset_kinds: &BTreeMap<String, Value>) -> Value {
+        let mut map = object(value);
+        self.common(&mut map);
+        if let Some(asset) = map.remove("asset") {
+            let global_asset_id = key_value(&asset);
+            let kind = global_asset_id
+                .as_ref()
+                .and_then(Value::as_str)
+                .and_then(|id| asset_kinds.get(id))
+                .cloned()
+                .unwrap_or(json!("Instance"));
+            let mut info = Map::new();
+            info.insert("assetKind".to_string(), kind);
+            if let Some(id) = global_asset_id {
+                info.insert("globalAssetId".to_string(), id);
+            }
+            map.insert("assetInformation".to_string(), Value::Object(info));
+            self.note("asset reference replaced by assetInformation");
+        }
+        if let Some(Value::Array(submodels)) = map.remove("submodels") {
+            let submodels = submodels.into_iter().map(|r| self.reference(r)).collect();
+            map.insert("submodels".to_string(), Value::Array(submodels));
+        }
+        for dropped in ["views", "conceptDictionaries", "security"] {
+            if map.remove(dropped).is_some() {
+                self.note(&format!("shell {} dropped (removed in V3.0)", dropped));
+            }
+        }
+        Value::Object(map)
+    }
+
+    fn submodel(&mut self, value: Value) -> Value {
+        let mut map = object(value);
+        self.common(&mut map);
+        self.elements(&mut map, "submodelElements");
+        Value::Object(map)
+    }
+
+    fn concept_description(&mut self, value: Value) -> Value {
+        let mut map = object(value);
+        self.common(&mut map);
+        if let Some(Value::Array(refs)) = map.remove("isCaseOf") {
+            let refs = refs.into_iter().map(|r| self.reference(r)).collect();
+            map.insert("isCaseOf".to_string(), Value::Array(refs));
+        }
+        Value::Object(map)
+    }
+
+    fn qualifier(&mut self, value: Value) -> Value {
+        let mut map = object(value);
+        self.common(&mut map);
+        map.remove("modelType");
+        self.value_type(&mut map, "valueType");
+        Value::Object(map)
+    }
+
+    fn iec61360(&mut self, content: &mut Map<String, Value>) {
+        self.reference_field(content, "unitId");
+        match content.get("dataType").and_then(Value::as_str) {
+            Some("") => {
+                content.remove("dataType");
+            }
+            Some("URL") => {
+                content.insert("dataType".to_string(), json!("IRI"));
+                self.note("IEC 61360 data type URL replaced by IRI");
+            }
+            _ => {}
+        }
+    }
+
+    fn elements(&mut self, map: &mut Map<String, Value>, field: &str) {
+        if let Some(Value::Array(elements)) = map.remove(field) {
+            let elements = elements.into_iter().map(|e| self.element(e)).collect();
+            map.insert(field.to_string(), Value::Array(elements));
+        }
+    }
+
+    fn element(&mut self, value: Value) -> Value {
+        let mut map = object(value);
+        self.common(&mut map);
+        if map.remove("kind").is_some() {
+            self.note("kind removed from submodel elements");
+        }
+        let model_type = map
+            .get("modelType")
+            .and_then(Value::as_str)
+            .unwrap_or("Property")
+            .to_string();
+        match model_type.as_str() {
+            "Property" | "Range" => self.value_type(&mut map, "valueType"),
+            "MultiLanguageProperty" => {
+                if let Some(strings) = map.get_mut("value").and_then(|v| v.get_mut("langString")) {
+                    let strings = strings.take();
+                    map.insert("value".to_string(), strings);
+                    self.note("langString wrapper removed from multi-language values");
+                }
+            }
+            "File" | "Blob" => {
+                if let Some(mime_type) = map.remove("mimeType") {
+                    map.insert("contentType".to_string(), mime_type);
+                    self.note("mimeType renamed to contentType");
+                }
+            }
+            "ReferenceElement" => self.reference_field(&mut map, "value"),
+            "RelationshipElement" | "AnnotatedRelationshipElement" => {
+                self.reference_field(&mut map, "first");
+                self.reference_field(&mut map, "second");
+                if let Some(annotations) = map.remove("annotation") {
+                    map.insert("annotations".to_string(), annotations);
+                    self.note("annotation renamed to annotations");
+                }
+                self.elements(&mut map, "annotations");
+            }
+            "Entity" => {
+                if let Some(asset) = map.remove("asset") {
+                    if let Some(id) = key_value(&asset) {
+                        map.insert("globalAssetId".to_string(), id);
+                    }
+                    self.note("entity asset reference replaced by globalAssetId");
+                }
+                self.elements(&mut map, "statements");
+            }
+            "Operation" => {
+                for kind in ["input", "output", "inoutput"] {
+                    let v2 = format!("{}Variable", kind);
+                    let v3 = format!("{}Variables", kind);
+                    if let Some(Value::Array(vars)) = map.remove(&v2) {
+                        self.note("operation variables renamed to the plural");
+                        let vars = vars
+                            .into_iter()
+                            .map(|var| {
+                                let mut var = object(var);
+                                var.remove("modelType");
+                                if let Some(value) = var.remove("value") {
+                                    var.insert("value".to_string(), self.element(value));
+                                }
+                                Value::Object(var)
+                            })
+                            .collect();
+                        map.insert(v3, Value::Array(vars));
+                    }
+                }
+            }
+            "BasicEvent" => {
+                map.insert("modelType".to_string(), json!("BasicEventElement"));
+                map.insert("direction".to_string(), json!("output"));
+                map.insert("state".to_string(), json!("on"));
+                self.reference_field(&mut map, "observed");
+                self.note("BasicEvent replaced by an outgoing BasicEventElement");
+            }
+            "SubmodelElementCollection" => {
+                let ordered = map.remove("ordered");
+                let allow_duplicates = map.remove("allowDuplicates");
+                if ordered.is_some() || allow_duplicates.is_some() {
+                    self.note("ordered/allowDuplicates dropped from collections");
+                }
+                self.elements(&mut map, "value");
+            }
+            _ => {}
+        }
+        Value::Object(map)
+    }
+
+    /// V2.0 value types are bare XSD names, optionally wrapped in
+    /// `{"dataObjectType": {"name": ..}}`; V3.0 uses prefixed names
+    fn value_type(&mut self, map: &mut Map<String, Value>, field: &str) {
+        let Some(v2) = map.get(field) else { return };
+        let name = v2
+            .get("dataObjectType")
+            .and_then(|t| t.get("name"))
+            .unwrap_or(v2)
+            .as_str()
+            .unwrap_or("")
+            .to_string();
+        if name.starts_with("xs:") {
+            return;
+        }
+        let v3 = match name.as_str() {
+            "double" | "float" | "decimal" => "xs:double",
+            "int" | "integer" | "long" | "short" | "byte" | "unsignedInt" | "unsignedLong"
+            | "unsignedShort" | "unsignedByte" | "nonNegativeInteger" | "positiveInteger"
+            | "negativeInteger" | "nonPositiveInteger" => "xs:int",
+            "boolean" => "xs:boolean",
+            "dateTime" | "dateTimeStamp" => "xs:dateTime",
+            "string" | "langString" | "anyURI" => "xs:string",
+            "" => {
+                map.remove(field);
+                return;
+            }
+            other => {
+                self.note(&format!("value type '{}' read as xs:string", other));
+                "xs:string"
+            }
+        };
+        map.insert(field.to_string(), json!(v3));
+        self.note("value types prefixed with xs:");
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl DigitalTwin {
+    /// What had to be rewritten when the twin was loaded from V2.0 JSON, as
+    /// an array of `{change, count}`; empty for V3 input
+    pub fn get_migration_report(&self) -> String {
+        serde_json::to_string(&self.migration_report).unwrap_or_else(|_| "[]".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const V2: &str = r#"{
+        "assetAdministrationShells": [{
+            "idShort": "Motor",
+            "identification": {"idType": "IRI", "id": "urn:example:aas:motor"},
+            "modelType": {"name": "AssetAdministrationShell"},
+            "asset": {"keys": [{"type": "Asset", "local": true, "value": "urn:example:asset:motor", "idType": "IRI"}]},
+            "submodels": [{"keys": [{"type": "Submodel", "local": true, "value": "urn:example:sm:np", "idType": "IRI"}]}]
+        }],
+        "assets": [{"identification": {"idType": "IRI", "id": "urn:example:asset:motor"}, "kind": "Type", "modelType": {"name": "Asset"}}],
+        "submodels": [{
+            "idShort": "Nameplate",
+            "identification": {"idType": "IRI", "id": "urn:example:sm:np"},
+            "modelType": {"name": "Submodel"},
+            "kind": "Instance",
+            "semanticId": {"keys": [{"type": "GlobalReference", "local": false, "value": "https://admin-shell.io/zvei/nameplate/1/0/Nameplate", "idType": "IRI"}]},
+            "submodelElements": [
+                {"idShort": "Voltage", "modelType": {"name": "Property"}, "kind": "Instance",
+                 "valueType": {"dataObjectType": {"name": "float"}}, "value": "400"},
+                {"idShort": "Manufacturer", "modelType": {"name": "MultiLanguageProperty"},
+                 "value": {"langString": [{"language": "en", "text": "ACME"}]}},
+                {"idShort": "Manual", "modelType": {"name": "File"}, "mimeType": "application/pdf", "value": "/aasx/manual.pdf"},
+                {"idShort": "Address", "modelType": {"name": "SubmodelElementCollection"}, "ordered": false,
+                 "allowDuplicates": false, "value": [
+                    {"idShort": "City", "modelType": {"name": "Property"}, "valueType": "string", "value": "Berlin",
+                     "constraints": [{"modelType": {"name": "Qualifier"}, "type": "Multiplicity", "valueType": "string", "value": "One"}]}
+                ]}
+            ]
+        }]
+    }"#;
+
+    #[test]
+    fn test_v2_migration() {
+        let twin = DigitalTwin::new(V2).unwrap();
+        assert_eq!(twin.data.id, "urn:example:aas:motor");
+        assert_eq!(
+            twin.data.asset_information.global_asset_id.as_deref(),
+            Some("urn:example:asset:motor")
+        );
+        assert_eq!(twin.get_property_display("Nameplate.Voltage"), "400 ");
+        assert_eq!(twin.get_property_display("Nameplate.Manufacturer"), "ACME");
+        assert_eq!(
+            twin.get_property_display("Nameplate.Address.City"),
+            "Berlin "
+        );
+        let manual = twin.find_element("Nameplate.Manual").unwrap();
+        assert!(matches!(&manual.kind, crate::ElementKind::File(f)
+            if f.content_type.as_deref() == Some("application/pdf")));
+
+        let report: Vec<Value> = serde_json::from_str(&twin.get_migration_report()).unwrap();
+        let count = |change: &str| {
+            report
+                .iter()
+                .find(|c| c["change"] == change)
+                .map(|c| c["count"].as_u64().unwrap())
+        };
+        assert_eq!(count("identification replaced by id"), Some(2));
+        assert_eq!(count("kind removed from submodel elements"), Some(1));
+        assert_eq!(count("mimeType renamed to contentType"), Some(1));
+        assert_eq!(count("constraints replaced by qualifiers"), Some(1));
+        assert_eq!(count("value types prefixed with xs:"), Some(3));
+    }
+
+    #[test]
+    fn test_v3_input_is_not_migrated() {
+        let twin =
+            DigitalTwin::new(r#"{"id": "M-1", "asset_type": "Pump", "nameplate": []}"#).unwrap();
+        assert_eq!(twin.get_migration_report(), "[]");
+        assert!(!is_v2_json(
+            &json!({"assetAdministrationShells": [{"id": "x"}]})
+        ));
+    }
+}
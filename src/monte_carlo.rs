@@ -0,0 +1,248 @@
+// --- Monte Carlo runs ---
+// Many randomized runs of the simulation in one call, so a what-if study of
+// thousands of runs crosses the WASM boundary once. Every run starts from a
+// copy of the twin as it stands, draws its simulation inputs ("load",
+// "ambient_temperature", "fault.bearing_wear") from the given distributions,
+// gets its own noise seed and advances `ticks` steps of the step size. The
+// report aggregates the peak and last value of every signal over the runs
+// and counts the runs in which the motor tripped into the Fault state. Runs
+// are derived from the twin's seed, so a study repeats exactly.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "wasm")]
+use tsify::Tsify;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+#[cfg(feature = "wasm")]
+use crate::to_js;
+use crate::{DigitalTwin, OperationalState, SimulationRng, SimulationValues, TwinError};
+
+/// How an input is drawn for each run
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum ParameterDistribution {
+    Constant { value: f64 },
+    Uniform { min: f64, max: f64 },
+    Normal { mean: f64, std_dev: f64 },
+}
+
+impl ParameterDistribution {
+    pub fn validate(&self, name: &str) -> Result<(), TwinError> {
+        let valid = match *self {
+            ParameterDistribution::Constant { value } => value.is_finite(),
+            ParameterDistribution::Uniform { min, max } => {
+                min.is_finite() && max.is_finite() && min <= max
+            }
+            ParameterDistribution::Normal { mean, std_dev } => {
+                mean.is_finite() && std_dev.is_finite() && std_dev >= 0.0
+            }
+        };
+        if valid {
+            Ok(())
+        } else {
+            Err(TwinError::invalid_argument(format!(
+                "Invalid distribution of '{}': {:?}",
+                name, self
+            )))
+        }
+    }
+
+    pub fn sample(&self, rng: &mut SimulationRng) -> f64 {
+        match *self {
+            ParameterDistribution::Constant { value } => value,
+            ParameterDistribution::Uniform { min, max } => min + (max - min) * rng.next_f64(),
+            ParameterDistribution::Normal { mean, std_dev } => mean + std_dev * rng.gaussian(),
+        }
+    }
+}
+
+/// Distribution of one quantity over the runs
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+pub struct RunStatistics {
+    pub mean: f64,
+    pub min: f64,
+    pub max: f64,
+    pub p5: f64,
+    pub p50: f64,
+    pub p95: f64,
+}
+
+impl RunStatistics {
+    /// Statistics of a non-empty list of values; percentiles interpolate
+    /// between the nearest values
+    fn of(mut values: Vec<f64>) -> RunStatistics {
+        values.sort_by(f64::total_cmp);
+        let percentile = |p: f64| {
+            let rank = p * (values.len() - 1) as f64;
+            let (low, high) = (rank.floor() as usize, rank.ceil() as usize);
+            values[low] + (values[high] - values[low]) * (rank - rank.floor())
+        };
+        RunStatistics {
+            mean: values.iter().sum::<f64>() / values.len() as f64,
+            min: values[0],
+            max: values[values.len() - 1],
+            p5: percentile(0.05),
+            p50: percentile(0.5),
+            p95: percentile(0.95),
+        }
+    }
+}
+
+/// Aggregate outcome of a Monte Carlo study
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+pub struct MonteCarloReport {
+    pub runs: u32,
+    pub ticks: u32,
+    /// Seconds per tick
+    pub dt: f64,
+    /// Runs in which the motor entered the Fault state
+    pub failed_runs: u32,
+    /// Entries into the Fault state, over the runs
+    pub faults_per_run: RunStatistics,
+    /// Highest value of each signal during a run, over the runs
+    pub peak: BTreeMap<String, RunStatistics>,
+    /// Value of each signal at the end of a run, over the runs
+    pub last: BTreeMap<String, RunStatistics>,
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl DigitalTwin {
+    /// Run `runs` randomized copies of the simulation for `ticks` steps each
+    /// and return the `MonteCarloReport`; `param_distributions` maps inputs
+    /// to a `ParameterDistribution` as JSON, e.g.
+    /// `{"load": {"type": "uniform", "min": 0.8, "max": 1.2}}`
+    #[cfg(feature = "wasm")]
+    #[wasm_bindgen(unchecked_return_type = "MonteCarloReport")]
+    pub fn run_monte_carlo(
+        &self,
+        runs: u32,
+        ticks: u32,
+        param_distributions: &str,
+    ) -> Result<JsValue, TwinError> {
+        let distributions = serde_json::from_str(param_distributions)
+            .map_err(|e| TwinError::parse(format!("Invalid parameter distributions: {}", e)))?;
+        Ok(to_js(&self.monte_carlo(runs, ticks, &distributions)?))
+    }
+}
+
+impl DigitalTwin {
+    /// Rust-side `run_monte_carlo`
+    pub fn monte_carlo(
+        &self,
+        runs: u32,
+        ticks: u32,
+        distributions: &BTreeMap<String, ParameterDistribution>,
+    ) -> Result<MonteCarloReport, TwinError> {
+        if runs == 0 {
+            return Err(TwinError::invalid_argument(
+                "A study needs at least one run",
+            ));
+        }
+        for (name, distribution) in distributions {
+            distribution.validate(name)?;
+        }
+        let dt = self.simulation.step_size();
+        let mut rng = SimulationRng::new(self.simulation.seed() ^ 0x4D43_4D43);
+        let mut peaks: BTreeMap<String, Vec<f64>> = BTreeMap::new();
+        let mut lasts: BTreeMap<String, Vec<f64>> = BTreeMap::new();
+        let mut faults = Vec::new();
+        for _ in 0..runs {
+            let mut twin = self.clone_twin();
+            twin.set_seed(rng.next_u64());
+            for (name, distribution) in distributions {
+                twin.set_simulation_input(name, distribution.sample(&mut rng));
+            }
+            let mut peak = twin.simulation_values().clone();
+            let mut entries = 0;
+            for _ in 0..ticks {
+                let before = twin.operational_state();
+                twin.step_models(dt);
+                let state = twin.operational_state();
+                if state == OperationalState::Fault && before != state {
+                    entries += 1;
+                }
+                for (key, value) in twin.simulation_values() {
+                    let highest = peak.entry(key.clone()).or_insert(*value);
+                    *highest = highest.max(*value);
+                }
+            }
+            collect(&mut peaks, &peak);
+            collect(&mut lasts, twin.simulation_values());
+            faults.push(f64::from(entries));
+        }
+        let statistics = |values: BTreeMap<String, Vec<f64>>| {
+            values
+                .into_iter()
+                .map(|(key, values)| (key, RunStatistics::of(values)))
+                .collect()
+        };
+        Ok(MonteCarloReport {
+            runs,
+            ticks,
+            dt,
+            failed_runs: faults.iter().filter(|f| **f > 0.0).count() as u32,
+            faults_per_run: RunStatistics::of(faults),
+            peak: statistics(peaks),
+            last: statistics(lasts),
+        })
+    }
+}
+
+fn collect(samples: &mut BTreeMap<String, Vec<f64>>, values: &SimulationValues) {
+    for (key, value) in values {
+        samples.entry(key.clone()).or_default().push(*value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"{"id": "M-1", "asset_type": "Motor", "nameplate": []}"#;
+
+    #[test]
+    fn test_monte_carlo_statistics() {
+        let twin = DigitalTwin::new(CONFIG).unwrap();
+        let distributions: BTreeMap<String, ParameterDistribution> = serde_json::from_str(
+            r#"{"load": {"type": "uniform", "min": 1.5, "max": 3.5},
+                "ambient_temperature": {"type": "normal", "mean": 30, "std_dev": 5}}"#,
+        )
+        .unwrap();
+        let report = twin.monte_carlo(40, 100, &distributions).unwrap();
+        assert_eq!((report.runs, report.dt), (40, 0.1));
+        // Loads beyond the breakdown torque of 2.5 stall the motor
+        assert!(report.failed_runs > 5 && report.failed_runs < 35);
+        assert_eq!(report.faults_per_run.max, 1.0);
+        let rpm = &report.last["motor.rpm"];
+        assert!(rpm.min < 100.0 && rpm.max > 1300.0);
+        assert!(rpm.p5 <= rpm.p50 && rpm.p50 <= rpm.p95);
+        assert!(report.peak["motor.current"].min > report.last["motor.current"].min);
+        // The study repeats exactly
+        assert_eq!(twin.monte_carlo(40, 100, &distributions).unwrap(), report);
+        assert_eq!(twin.tick_count, 0);
+
+        let invalid = BTreeMap::from([(
+            "load".to_string(),
+            ParameterDistribution::Uniform { min: 2.0, max: 1.0 },
+        )]);
+        assert!(twin.monte_carlo(10, 10, &invalid).is_err());
+        assert!(twin.monte_carlo(0, 10, &BTreeMap::new()).is_err());
+    }
+
+    #[test]
+    fn test_run_statistics_percentiles() {
+        let statistics = RunStatistics::of((0..=100).rev().map(f64::from).collect());
+        assert_eq!((statistics.min, statistics.max), (0.0, 100.0));
+        assert_eq!(
+            (statistics.p5, statistics.p50, statistics.p95),
+            (5.0, 50.0, 95.0)
+        );
+        assert_eq!(RunStatistics::of(vec![2.0]).p95, 2.0);
+    }
+}
@@ -0,0 +1,360 @@
+// --- Electrical motor model ---
+// Induction motor parameterized from its nameplate: rated voltage, power,
+// speed, frequency and power factor give the synchronous speed, rated slip,
+// torque and current. The torque-speed curve follows Kloss's formula through
+// the rated point with breakdown at 2.5 times rated torque; for a commanded
+// load the motor settles at the slip where the curve meets the load torque,
+// and overloads beyond breakdown stall it. Current is split into a constant
+// magnetizing part and an active part proportional to the torque, so it
+// equals the rated current at rated load. The load can also be given as a
+// torque, and a supply voltage off the rating scales the breakdown torque
+// with its square, the magnetizing current with it and the active current
+// inversely.
+
+use std::f64::consts::TAU;
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::thermal::nameplate_number;
+use crate::{
+    positive_parameter, unknown_parameter, DigitalTwin, FaultKind, SimulationModel, SimulationRng,
+    SimulationValues, TwinError,
+};
+
+/// Input carrying the commanded load as a fraction of rated torque
+pub const LOAD_INPUT: &str = "load";
+
+/// Input carrying the load torque in Nm; when set it replaces the commanded
+/// load fraction
+pub const LOAD_TORQUE_INPUT: &str = "load_torque";
+
+/// Input carrying the line-to-line supply voltage in V; missing means rated
+/// voltage
+pub const SUPPLY_VOLTAGE_INPUT: &str = "supply_voltage";
+
+/// Load of new twins: rated torque
+pub const DEFAULT_LOAD: f64 = 1.0;
+
+/// Input that is 1 while the motor is connected to the supply and 0 while it
+/// is switched off; a missing input counts as connected
+pub const ENERGIZED_INPUT: &str = "energized";
+
+/// Stator current of a stalled motor in multiples of rated current
+const LOCKED_ROTOR_CURRENT: f64 = 6.0;
+
+/// Induction motor following a commanded load
+#[derive(Clone, Debug, PartialEq)]
+pub struct MotorModel {
+    /// Line-to-line voltage in V
+    pub rated_voltage: f64,
+    /// Mechanical output power in W
+    pub rated_power: f64,
+    /// Shaft speed at rated load in 1/min
+    pub rated_speed: f64,
+    /// Supply frequency in Hz
+    pub frequency: f64,
+    /// cos phi at rated load
+    pub power_factor: f64,
+    pub efficiency: f64,
+    /// Stator current at rated load in A
+    pub rated_current: f64,
+    /// Breakdown torque over rated torque
+    pub breakdown_ratio: f64,
+    /// Time constant of speed changes in s
+    pub time_constant: f64,
+    rpm: f64,
+}
+
+impl Default for MotorModel {
+    /// 400 V, 7.5 kW, 1440 1/min, 50 Hz, cos phi 0.85
+    fn default() -> MotorModel {
+        MotorModel::rated(400.0, 7500.0, 1440.0, 50.0, 0.85, 0.9)
+    }
+}
+
+impl MotorModel {
+    /// A motor with the rated current computed from the other ratings
+    pub fn rated(
+        voltage: f64,
+        power: f64,
+        speed: f64,
+        frequency: f64,
+        power_factor: f64,
+        efficiency: f64,
+    ) -> MotorModel {
+        MotorModel {
+            rated_voltage: voltage,
+            rated_power: power,
+            rated_speed: speed,
+            frequency,
+            power_factor,
+            efficiency,
+            rated_current: power / (3f64.sqrt() * voltage * power_factor * efficiency),
+            breakdown_ratio: 2.5,
+            time_constant: 2.0,
+            rpm: 0.0,
+        }
+    }
+
+    /// Take Voltage, Power (W or kW), RPM, Frequency, PowerFactor,
+    /// Efficiency and Current from the twin's nameplate where present
+    pub fn from_nameplate(twin: &DigitalTwin) -> MotorModel {
+        let positive =
+            |id_short: &str| nameplate_number(twin, id_short).filter(|(value, _)| *value > 0.0);
+        let defaults = MotorModel::default();
+        let value = |id_short: &str, default: f64| positive(id_short).map_or(default, |(v, _)| v);
+        let power = match positive("Power") {
+            Some((kw, unit)) if unit.as_deref() != Some("W") => kw * 1000.0,
+            Some((w, _)) => w,
+            None => defaults.rated_power,
+        };
+        let efficiency = match value("Efficiency", defaults.efficiency) {
+            percent if percent > 1.0 => percent / 100.0,
+            fraction => fraction,
+        };
+        let mut model = MotorModel::rated(
+            value("Voltage", defaults.rated_voltage),
+            power,
+            value("RPM", defaults.rated_speed),
+            value("Frequency", defaults.frequency),
+            value("PowerFactor", defaults.power_factor).min(1.0),
+            efficiency.min(1.0),
+        );
+        if let Some((current, _)) = positive("Current") {
+            model.rated_current = current;
+        }
+        model
+    }
+
+    /// Speed of the rotating field in 1/min, for the pole count that puts
+    /// it just above the rated speed
+    pub fn synchronous_speed(&self) -> f64 {
+        let field = 60.0 * self.frequency;
+        field / (field / self.rated_speed).floor().max(1.0)
+    }
+
+    fn rated_slip(&self) -> f64 {
+        let sync = self.synchronous_speed();
+        ((sync - self.rated_speed) / sync).clamp(1e-3, 0.5)
+    }
+
+    /// Torque at rated power and speed in Nm
+    pub fn rated_torque(&self) -> f64 {
+        self.rated_power / (self.rated_speed * TAU / 60.0)
+    }
+
+    /// Steady-state slip under a load torque; None when the load exceeds
+    /// the breakdown torque
+    pub fn slip_at(&self, torque: f64) -> Option<f64> {
+        if torque <= 0.0 {
+            return Some(0.0);
+        }
+        let k = self.breakdown_ratio;
+        let breakdown_slip = self.rated_slip() * (k + (k * k - 1.0).sqrt());
+        let ratio = k * self.rated_torque() / torque;
+        (ratio >= 1.0).then(|| breakdown_slip * (ratio - (ratio * ratio - 1.0).sqrt()))
+    }
+
+    /// Stator current at a load torque in A
+    pub fn current_at(&self, torque: f64) -> f64 {
+        self.current_at_voltage(torque, 1.0)
+    }
+
+    /// Stator current at a load torque and a supply voltage given over the
+    /// rated voltage
+    pub fn current_at_voltage(&self, torque: f64, voltage_ratio: f64) -> f64 {
+        let cos = self.power_factor;
+        let magnetizing = (1.0 - cos * cos).sqrt() * voltage_ratio;
+        let active = cos * torque / self.rated_torque() / voltage_ratio;
+        self.rated_current * (magnetizing * magnetizing + active * active).sqrt()
+    }
+
+    fn outputs(&self, torque: f64, current: f64) -> SimulationValues {
+        SimulationValues::from([
+            ("rpm".to_string(), self.rpm),
+            ("torque".to_string(), torque),
+            ("current".to_string(), current),
+            (
+                "slip".to_string(),
+                1.0 - self.rpm / self.synchronous_speed(),
+            ),
+            ("power".to_string(), torque * self.rpm * TAU / 60.0 / 1000.0),
+        ])
+    }
+}
+
+impl SimulationModel for MotorModel {
+    fn init(&mut self) -> SimulationValues {
+        self.rpm = 0.0;
+        self.outputs(0.0, 0.0)
+    }
+
+    fn step(
+        &mut self,
+        dt: f64,
+        inputs: &SimulationValues,
+        rng: &mut SimulationRng,
+    ) -> SimulationValues {
+        let load = match inputs.get(LOAD_TORQUE_INPUT) {
+            Some(torque) => torque / self.rated_torque(),
+            None => inputs.get(LOAD_INPUT).copied().unwrap_or(DEFAULT_LOAD),
+        } * (1.0 + FaultKind::Overload.severity(inputs));
+        // The driven machine's torque fluctuates slightly
+        let torque = (load * self.rated_torque() * (1.0 + rng.noise() * 0.005)).max(0.0);
+        let voltage = inputs
+            .get(SUPPLY_VOLTAGE_INPUT)
+            .map_or(1.0, |v| v / self.rated_voltage);
+        if voltage <= 0.0 || inputs.get(ENERGIZED_INPUT).is_some_and(|on| *on <= 0.0) {
+            // Switched off, the motor coasts down without torque or current
+            self.rpm *= (-dt / self.time_constant).exp();
+            return self.outputs(0.0, 0.0);
+        }
+        // On two phases the breakdown torque halves and the remaining
+        // phases carry up to sqrt(3) times the current
+        let phase_loss = FaultKind::PhaseLoss.severity(inputs);
+        let capacity = (1.0 - 0.5 * phase_loss) * voltage * voltage;
+        let (target, current) = match self.slip_at(torque / capacity) {
+            Some(slip) => (
+                self.synchronous_speed() * (1.0 - slip),
+                self.current_at_voltage(torque, voltage) * (1.0 + (3f64.sqrt() - 1.0) * phase_loss),
+            ),
+            None => (0.0, LOCKED_ROTOR_CURRENT * self.rated_current * voltage),
+        };
+        self.rpm += (target - self.rpm) * (1.0 - (-dt / self.time_constant).exp());
+        self.outputs(torque, current)
+    }
+
+    fn resume(&mut self, outputs: &SimulationValues) {
+        self.rpm = outputs.get("rpm").copied().unwrap_or(0.0);
+    }
+
+    fn parameters(&self) -> SimulationValues {
+        SimulationValues::from([
+            ("rated_speed".to_string(), self.rated_speed),
+            ("rated_current".to_string(), self.rated_current),
+            ("frequency".to_string(), self.frequency),
+            ("breakdown_ratio".to_string(), self.breakdown_ratio),
+            ("time_constant".to_string(), self.time_constant),
+        ])
+    }
+
+    fn set_parameter(&mut self, name: &str, value: f64) -> Result<(), String> {
+        let value = positive_parameter(name, value)?;
+        match name {
+            "rated_speed" => self.rated_speed = value,
+            "rated_current" => self.rated_current = value,
+            "frequency" => self.frequency = value,
+            "breakdown_ratio" if value <= 1.0 => {
+                return Err(format!("Breakdown ratio must be above 1, got {}", value));
+            }
+            "breakdown_ratio" => self.breakdown_ratio = value,
+            "time_constant" => self.time_constant = value,
+            _ => return Err(unknown_parameter(name)),
+        }
+        Ok(())
+    }
+
+    fn unit(&self, output: &str) -> Option<&str> {
+        match output {
+            "rpm" => Some("1/min"),
+            "torque" => Some("Nm"),
+            "current" => Some("A"),
+            "power" => Some("kW"),
+            _ => None,
+        }
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl DigitalTwin {
+    /// Command the load of the driven machine as a fraction of rated torque
+    /// (1.0 at the nameplate rating); it takes effect from the next tick
+    pub fn set_load(&mut self, fraction: f64) -> Result<(), TwinError> {
+        if !fraction.is_finite() || fraction < 0.0 {
+            return Err(TwinError::invalid_argument(format!(
+                "Load must be a non-negative fraction of rated torque, got {}",
+                fraction
+            )));
+        }
+        self.set_simulation_input(LOAD_INPUT, fraction);
+        Ok(())
+    }
+
+    /// The commanded load as a fraction of rated torque
+    pub fn get_load(&self) -> f64 {
+        self.simulation_input(LOAD_INPUT).unwrap_or(DEFAULT_LOAD)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rated_point_from_nameplate() {
+        let twin = DigitalTwin::new(include_str!("../twin_config.example.json")).unwrap();
+        let motor = MotorModel::from_nameplate(&twin);
+        assert_eq!(motor.synchronous_speed(), 1500.0);
+        assert!((motor.rated_torque() - 49.74).abs() < 0.01);
+        // The curve passes through the nameplate rating
+        let slip = motor.slip_at(motor.rated_torque()).unwrap();
+        assert!((1500.0 * (1.0 - slip) - 1440.0).abs() < 1e-6);
+        assert!((motor.current_at(motor.rated_torque()) - 15.2).abs() < 1e-9);
+        assert!(motor.slip_at(3.0 * motor.rated_torque()).is_none());
+        assert!((MotorModel::default().rated_current - 14.15).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_load_changes_slip_and_current() {
+        let mut twin = DigitalTwin::new(include_str!("../twin_config.example.json")).unwrap();
+        let run = |twin: &mut DigitalTwin| {
+            for _ in 0..30 {
+                twin.advance_simulation();
+            }
+            let values = twin.simulation_values();
+            (values["motor.rpm"], values["motor.current"])
+        };
+        let (rated_rpm, rated_current) = run(&mut twin);
+        assert!((rated_rpm - 1440.0).abs() < 2.0);
+        assert!((rated_current - 15.2).abs() < 0.2);
+
+        twin.set_load(0.5).unwrap();
+        let (light_rpm, light_current) = run(&mut twin);
+        assert!(light_rpm > rated_rpm && light_rpm < 1500.0);
+        assert!(light_current < rated_current);
+
+        let mut motor = MotorModel::from_nameplate(&twin);
+        motor.init();
+        let overload = SimulationValues::from([(LOAD_INPUT.to_string(), 3.0)]);
+        let stalled = motor.step(1.0, &overload, &mut SimulationRng::new(0));
+        assert_eq!(stalled["rpm"], 0.0);
+        assert!((stalled["current"] - 6.0 * 15.2).abs() < 1e-9);
+        assert!(twin.set_load(-0.1).is_err());
+        assert_eq!(twin.get_load(), 0.5);
+    }
+
+    #[test]
+    fn test_load_torque_and_supply_voltage_inputs() {
+        let mut motor = MotorModel::default();
+        let mut steady = |inputs: &[(&str, f64)]| {
+            let inputs: SimulationValues =
+                inputs.iter().map(|(k, v)| (k.to_string(), *v)).collect();
+            motor.init();
+            motor.step(100.0, &inputs, &mut SimulationRng::new(0))
+        };
+        let rated = steady(&[]);
+        let half_torque = MotorModel::default().rated_torque() / 2.0;
+        let by_torque = steady(&[(LOAD_TORQUE_INPUT, half_torque), (LOAD_INPUT, 2.0)]);
+        let by_fraction = steady(&[(LOAD_INPUT, 0.5)]);
+        assert_eq!(by_torque["rpm"], by_fraction["rpm"]);
+
+        // An undervoltage slows the motor down and draws more current
+        let sagging = steady(&[(SUPPLY_VOLTAGE_INPUT, 360.0)]);
+        assert!(sagging["rpm"] < rated["rpm"]);
+        assert!(sagging["current"] > rated["current"]);
+        // At half voltage the breakdown torque falls below the rated load
+        assert_eq!(steady(&[(SUPPLY_VOLTAGE_INPUT, 200.0)])["rpm"], 0.0);
+        assert_eq!(steady(&[(SUPPLY_VOLTAGE_INPUT, 0.0)])["current"], 0.0);
+    }
+}
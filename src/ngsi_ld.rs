@@ -0,0 +1,190 @@
+// --- NGSI-LD export ---
+// The shell as one NGSI-LD entity for a FIWARE context broker (Orion-LD).
+// Nameplate elements become top-level attributes; each submodel becomes a
+// Property whose value is a JSON object of its elements. References and
+// relationships become Relationships pointing at the referenced identifier.
+// Operations, capabilities and events have no counterpart and are left out.
+
+use serde_json::{json, Map, Value};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{dtdl_name, AssetAdministrationShell, DigitalTwin, ElementKind, SubmodelElement};
+
+pub const NGSI_LD_CORE_CONTEXT: &str =
+    "https://uri.etsi.org/ngsi-ld/v1/ngsi-ld-core-context.jsonld";
+
+/// An NGSI-LD entity for a shell. Shell ids that are not URIs are turned into
+/// `urn:ngsi-ld:{type}:{id}`; the type is the asset type.
+pub fn shell_to_ngsi_ld(shell: &AssetAdministrationShell) -> Value {
+    let entity_type = dtdl_name(
+        shell
+            .asset_information
+            .asset_type
+            .as_deref()
+            .unwrap_or("AssetAdministrationShell"),
+    );
+    let id = if shell.id.contains(':') {
+        shell.id.clone()
+    } else {
+        format!("urn:ngsi-ld:{}:{}", entity_type, shell.id)
+    };
+
+    let mut entity = Map::new();
+    entity.insert("id".to_string(), json!(id));
+    entity.insert("type".to_string(), json!(entity_type));
+    for element in &shell.nameplate {
+        if let Some(attribute) = attribute(element) {
+            entity.insert(attribute_name(&element.id_short), attribute);
+        }
+    }
+    for sm in &shell.submodels {
+        entity.insert(
+            attribute_name(&sm.id_short),
+            json!({"type": "Property", "value": compound(&sm.submodel_elements)}),
+        );
+    }
+    entity.insert("@context".to_string(), json!([NGSI_LD_CORE_CONTEXT]));
+    Value::Object(entity)
+}
+
+/// Attribute names must not clash with the entity's own members
+fn attribute_name(id_short: &str) -> String {
+    let name = dtdl_name(id_short);
+    match name.as_str() {
+        "id" | "type" => format!("{}_", name),
+        _ => name,
+    }
+}
+
+/// A top-level Property, LanguageProperty or Relationship
+fn attribute(element: &SubmodelElement) -> Option<Value> {
+    let object = match &element.kind {
+        ElementKind::ReferenceElement(r) => r.value.as_ref().and_then(|r| r.key_value()),
+        ElementKind::RelationshipElement(r) => r.second.as_ref().and_then(|r| r.key_value()),
+        ElementKind::AnnotatedRelationshipElement(r) => {
+            r.second.as_ref().and_then(|r| r.key_value())
+        }
+        ElementKind::Entity(e) if e.statements.is_empty() => e.global_asset_id.as_deref(),
+        ElementKind::MultiLanguageProperty(p) => {
+            let map: Map<String, Value> = p
+                .value
+                .iter()
+                .map(|s| (s.language.clone(), json!(s.text)))
+                .collect();
+            return Some(json!({"type": "LanguageProperty", "languageMap": map}));
+        }
+        _ => {
+            let mut attribute = json!({"type": "Property", "value": value(element)?});
+            if let Some(unit) = element.unit() {
+                attribute["unitCode"] = json!(unit);
+            }
+            return Some(attribute);
+        }
+    };
+    object.map(|object| json!({"type": "Relationship", "object": object}))
+}
+
+/// Elements as a JSON object keyed by idShort
+fn compound(elements: &[SubmodelElement]) -> Value {
+    Value::Object(
+        elements
+            .iter()
+            .filter_map(|e| Some((e.id_short.clone(), value(e)?)))
+            .collect(),
+    )
+}
+
+/// Plain JSON value of an element; typed properties become numbers and booleans
+fn value(element: &SubmodelElement) -> Option<Value> {
+    Some(match &element.kind {
+        ElementKind::Property(_) | ElementKind::Range(_) => element.json_value(),
+        ElementKind::MultiLanguageProperty(p) => Value::Object(
+            p.value
+                .iter()
+                .map(|s| (s.language.clone(), json!(s.text)))
+                .collect(),
+        ),
+        ElementKind::File(f) => json!(f.value),
+        ElementKind::Blob(b) => json!(b.value),
+        ElementKind::ReferenceElement(r) => json!(r.value.as_ref().and_then(|r| r.key_value())),
+        ElementKind::RelationshipElement(r) => json!({
+            "first": r.first.as_ref().and_then(|r| r.key_value()),
+            "second": r.second.as_ref().and_then(|r| r.key_value()),
+        }),
+        ElementKind::AnnotatedRelationshipElement(r) => json!({
+            "first": r.first.as_ref().and_then(|r| r.key_value()),
+            "second": r.second.as_ref().and_then(|r| r.key_value()),
+            "annotations": compound(&r.annotations),
+        }),
+        ElementKind::Entity(e) => {
+            let mut entity = compound(&e.statements);
+            if let Some(id) = &e.global_asset_id {
+                entity["globalAssetId"] = json!(id);
+            }
+            entity
+        }
+        ElementKind::SubmodelElementCollection(c) => compound(&c.value),
+        ElementKind::SubmodelElementList(l) => {
+            Value::Array(l.value.iter().filter_map(value).collect())
+        }
+        ElementKind::Operation(_)
+        | ElementKind::Capability(_)
+        | ElementKind::BasicEventElement(_) => return None,
+    })
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl DigitalTwin {
+    /// Export the twin as an NGSI-LD entity (JSON-LD) for a context broker
+    pub fn get_ngsi_ld(&self) -> String {
+        serde_json::to_string_pretty(&shell_to_ngsi_ld(&self.data))
+            .unwrap_or_else(|_| "{}".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ngsi_ld_entity() {
+        let json = r#"{
+            "id": "motor-17",
+            "asset_type": "Siemens 1LE1",
+            "nameplate": [
+                {"id_short": "Voltage", "value": "400", "unit": "V", "value_type": "xs:double"},
+                {"id_short": "type", "value": "IE3"},
+                {"id_short": "Plant", "model_type": "ReferenceElement", "value": {
+                    "type": "ExternalReference", "keys": [{"type": "GlobalReference", "value": "urn:ngsi-ld:Plant:P1"}]}}
+            ],
+            "submodels": [{
+                "id": "urn:example:sm:td",
+                "id_short": "TechnicalData",
+                "submodel_elements": [
+                    {"id_short": "Poles", "value": "4", "value_type": "xs:int"},
+                    {"id_short": "Ambient", "model_type": "Range", "value_type": "xs:double", "min": "-20", "max": "40"}
+                ]
+            }]
+        }"#;
+        let entity: Value =
+            serde_json::from_str(&DigitalTwin::new(json).unwrap().get_ngsi_ld()).unwrap();
+
+        assert_eq!(entity["id"], "urn:ngsi-ld:Siemens_1LE1:motor-17");
+        assert_eq!(entity["type"], "Siemens_1LE1");
+        assert_eq!(
+            entity["Voltage"],
+            json!({"type": "Property", "value": 400.0, "unitCode": "V"})
+        );
+        assert_eq!(entity["type_"]["value"], "IE3");
+        assert_eq!(
+            entity["Plant"],
+            json!({"type": "Relationship", "object": "urn:ngsi-ld:Plant:P1"})
+        );
+        assert_eq!(
+            entity["TechnicalData"]["value"],
+            json!({"Poles": 4, "Ambient": {"min": -20.0, "max": 40.0}})
+        );
+        assert_eq!(entity["@context"][0], NGSI_LD_CORE_CONTEXT);
+    }
+}
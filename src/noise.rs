@@ -0,0 +1,212 @@
+// --- Sensor noise ---
+// Noise models put on simulated signals as a sensor would read them: white
+// Gaussian or uniform noise, a random-walk drift and the quantization of an
+// A/D converter, applied in the order they are listed. They are configured
+// per signal key ("motor.current") in the `noise` map of the simulation
+// profile, for built-in and configured models alike, so filtering code can be
+// validated against data that looks like the field's. All randomness comes
+// from the simulation's seeded generator.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "wasm")]
+use tsify::Tsify;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{DigitalTwin, SimulationRng, TwinError, SIMULATION_SECTION};
+
+/// One source of noise on a signal
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum NoiseModel {
+    /// White noise with the given standard deviation
+    Gaussian { std_dev: f64 },
+    /// White noise uniform within plus and minus the amplitude
+    Uniform { amplitude: f64 },
+    /// Drift growing by a Gaussian step with a standard deviation of `rate`
+    /// per square root of a second
+    RandomWalk { rate: f64 },
+    /// Rounding to multiples of the step, e.g. one LSB of the converter
+    Quantization { step: f64 },
+}
+
+impl NoiseModel {
+    /// Check that the parameter is finite and non-negative, a quantization
+    /// step positive
+    pub fn validate(&self, key: &str) -> Result<(), TwinError> {
+        let (value, valid) = match *self {
+            NoiseModel::Gaussian { std_dev: v }
+            | NoiseModel::Uniform { amplitude: v }
+            | NoiseModel::RandomWalk { rate: v } => (v, v >= 0.0),
+            NoiseModel::Quantization { step } => (step, step > 0.0),
+        };
+        if valid && value.is_finite() {
+            Ok(())
+        } else {
+            Err(TwinError::validation(
+                Some(SIMULATION_SECTION),
+                format!("Invalid noise parameter {} on signal '{}'", value, key),
+            ))
+        }
+    }
+}
+
+/// The noise models of a signal and the drift they accumulated
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct SignalNoise {
+    models: Vec<NoiseModel>,
+    drift: f64,
+}
+
+impl SignalNoise {
+    pub(crate) fn new(models: Vec<NoiseModel>) -> SignalNoise {
+        SignalNoise { models, drift: 0.0 }
+    }
+
+    /// The value as read after a step of `dt` seconds
+    pub(crate) fn apply(&mut self, value: f64, dt: f64, rng: &mut SimulationRng) -> f64 {
+        let mut reading = value;
+        for model in &self.models {
+            reading = match *model {
+                NoiseModel::Gaussian { std_dev } => reading + std_dev * rng.gaussian(),
+                NoiseModel::Uniform { amplitude } => reading + amplitude * rng.noise(),
+                NoiseModel::RandomWalk { rate } => {
+                    self.drift += rate * dt.sqrt() * rng.gaussian();
+                    reading + self.drift
+                }
+                NoiseModel::Quantization { step } => (reading / step).round() * step,
+            };
+        }
+        reading
+    }
+
+    pub(crate) fn same_models(&self, other: &SignalNoise) -> bool {
+        self.models == other.models
+    }
+
+    pub(crate) fn drift(&self) -> f64 {
+        self.drift
+    }
+
+    pub(crate) fn set_drift(&mut self, drift: f64) {
+        self.drift = drift;
+    }
+
+    pub(crate) fn reset(&mut self) {
+        self.drift = 0.0;
+    }
+}
+
+/// Noise state of every configured signal
+pub(crate) fn signal_noise(
+    config: &BTreeMap<String, Vec<NoiseModel>>,
+) -> BTreeMap<String, SignalNoise> {
+    config
+        .iter()
+        .map(|(key, models)| (key.clone(), SignalNoise::new(models.clone())))
+        .collect()
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl DigitalTwin {
+    /// Put the noise models given as a JSON array on a signal
+    /// ("motor.current"), replacing its earlier ones; an empty array removes
+    /// the noise. Takes effect from the next tick.
+    pub fn set_signal_noise(&mut self, key: &str, noise_json: &str) -> Result<(), TwinError> {
+        let models = serde_json::from_str(noise_json)
+            .map_err(|e| TwinError::parse(format!("Invalid noise models: {}", e)))?;
+        self.set_noise(key, models)
+    }
+}
+
+impl DigitalTwin {
+    /// Rust-side `set_signal_noise`
+    pub fn set_noise(&mut self, key: &str, models: Vec<NoiseModel>) -> Result<(), TwinError> {
+        if key.is_empty() {
+            return Err(TwinError::invalid_argument("Signal key must be non-empty"));
+        }
+        for model in &models {
+            model.validate(key)?;
+        }
+        if models.is_empty() {
+            self.profile.noise.remove(key);
+        } else {
+            self.profile.noise.insert(key.to_string(), models);
+        }
+        self.simulation.set_noise(signal_noise(&self.profile.noise));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn readings(models: Vec<NoiseModel>, value: f64, count: usize) -> Vec<f64> {
+        let mut noise = SignalNoise::new(models);
+        let mut rng = SimulationRng::new(7);
+        (0..count)
+            .map(|_| noise.apply(value, 1.0, &mut rng))
+            .collect()
+    }
+
+    #[test]
+    fn test_noise_models() {
+        let gaussian = readings(vec![NoiseModel::Gaussian { std_dev: 2.0 }], 10.0, 5000);
+        let mean = gaussian.iter().sum::<f64>() / 5000.0;
+        let variance = gaussian.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / 5000.0;
+        assert!((mean - 10.0).abs() < 0.1);
+        assert!((variance.sqrt() - 2.0).abs() < 0.1);
+
+        let uniform = readings(vec![NoiseModel::Uniform { amplitude: 0.5 }], 1.0, 1000);
+        assert!(uniform.iter().all(|v| (0.5..1.5).contains(v)));
+
+        // Quantization after the noise reads whole steps only
+        let quantized = readings(
+            vec![
+                NoiseModel::Gaussian { std_dev: 1.0 },
+                NoiseModel::Quantization { step: 0.25 },
+            ],
+            3.0,
+            100,
+        );
+        assert!(quantized.iter().all(|v| (v / 0.25).fract() == 0.0));
+
+        // A random walk wanders off over time
+        let walk = readings(vec![NoiseModel::RandomWalk { rate: 1.0 }], 0.0, 2000);
+        let spread = walk[1000..].iter().map(|v| v * v).sum::<f64>() / 1000.0;
+        assert!(spread > 100.0);
+        assert!(NoiseModel::Quantization { step: 0.0 }
+            .validate("x")
+            .is_err());
+    }
+
+    #[test]
+    fn test_signal_noise_on_twin() {
+        let mut twin = DigitalTwin::new(
+            r#"{"id": "M-1", "asset_type": "Motor", "nameplate": [],
+                "simulation": {"noise": {"motor.rpm": [{"type": "quantization", "step": 100}]}}}"#,
+        )
+        .unwrap();
+        for _ in 0..50 {
+            twin.advance_simulation();
+            assert_eq!(twin.simulation_values()["motor.rpm"] % 100.0, 0.0);
+        }
+        twin.set_signal_noise("motor.current", r#"[{"type": "uniform", "amplitude": 1}]"#)
+            .unwrap();
+        twin.set_signal_noise("motor.rpm", "[]").unwrap();
+        twin.advance_simulation();
+        assert_ne!(twin.simulation_values()["motor.rpm"] % 100.0, 0.0);
+        assert_eq!(twin.simulation_profile().noise.len(), 1);
+
+        assert!(twin
+            .set_signal_noise("motor.rpm", r#"[{"type": "pink"}]"#)
+            .is_err_and(|e| e.code() == "PARSE_ERROR"));
+        assert!(twin
+            .set_signal_noise("motor.rpm", r#"[{"type": "gaussian", "std_dev": -1}]"#)
+            .is_err_and(|e| e.code() == "VALIDATION_FAILED"));
+    }
+}
@@ -0,0 +1,356 @@
+// --- OPC UA NodeSet2 export ---
+// Maps the twin's structure onto an OPC UA information model for import into
+// an OPC UA server: the shell is an Object below the Objects folder,
+// submodels and containers (collections, lists, entities) become Objects,
+// everything else becomes a Variable. Node ids are string ids built from the
+// element paths ("ns=1;s=TechnicalData.MaxTorque"), so they stay stable
+// across exports. Units become EngineeringUnits properties.
+
+use quick_xml::escape::escape;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    select_lang, AssetAdministrationShell, DataTypeDefXsd, DigitalTwin, ElementKind, LangString,
+    SubmodelElement, NAMEPLATE_ID_SHORT,
+};
+
+pub const NODESET_NAMESPACE: &str = "http://opcfoundation.org/UA/2011/03/UANodeSet.xsd";
+const TYPES_NAMESPACE: &str = "http://opcfoundation.org/UA/2008/02/Types.xsd";
+const UNECE_UNITS_NAMESPACE: &str = "http://www.opcfoundation.org/UA/units/un/cefact";
+
+/// Aliases for the standard nodes referenced by the export (namespace 0)
+const ALIASES: &[(&str, &str)] = &[
+    ("Boolean", "i=1"),
+    ("Int32", "i=6"),
+    ("Double", "i=11"),
+    ("String", "i=12"),
+    ("DateTime", "i=13"),
+    ("ByteString", "i=15"),
+    ("LocalizedText", "i=21"),
+    ("Organizes", "i=35"),
+    ("HasTypeDefinition", "i=40"),
+    ("HasProperty", "i=46"),
+    ("HasComponent", "i=47"),
+    ("Range", "i=884"),
+    ("EUInformation", "i=887"),
+];
+const OBJECTS_FOLDER: &str = "i=85";
+const BASE_OBJECT_TYPE: &str = "i=58";
+const PROPERTY_TYPE: &str = "i=68";
+const BASE_DATA_VARIABLE_TYPE: &str = "i=63";
+const RANGE_XML_ENCODING: &str = "i=885";
+const EU_INFORMATION_XML_ENCODING: &str = "i=888";
+
+struct Node {
+    variable: Option<Variable>,
+    node_id: String,
+    browse_name: String,
+    display_name: String,
+    description: Option<String>,
+    parent: String,
+    /// Reference from the parent to this node
+    reference: &'static str,
+    type_definition: &'static str,
+}
+
+struct Variable {
+    data_type: &'static str,
+    /// Content of the `<Value>` element, already in XML
+    value: String,
+}
+
+/// Generate a NodeSet2 document for a shell, with its nodes in `namespace_uri`
+pub fn shell_to_nodeset(shell: &AssetAdministrationShell, namespace_uri: &str) -> String {
+    let shell_node = format!("ns=1;s={}", shell.id);
+    let mut nodes = vec![Node {
+        variable: None,
+        node_id: shell_node.clone(),
+        browse_name: "AssetAdministrationShell".to_string(),
+        display_name: text(&shell.display_name)
+            .or(shell.asset_information.asset_type.clone())
+            .unwrap_or_else(|| shell.id.clone()),
+        description: text(&shell.description),
+        parent: OBJECTS_FOLDER.to_string(),
+        reference: "Organizes",
+        type_definition: BASE_OBJECT_TYPE,
+    }];
+
+    let mut submodels = Vec::new();
+    if !shell.nameplate.is_empty() {
+        submodels.push((NAMEPLATE_ID_SHORT, &[][..], &shell.nameplate));
+    }
+    for sm in &shell.submodels {
+        submodels.push((
+            sm.id_short.as_str(),
+            &sm.display_name[..],
+            &sm.submodel_elements,
+        ));
+    }
+    for (id_short, display_name, elements) in submodels {
+        let node_id = format!("ns=1;s={}", id_short);
+        nodes.push(Node {
+            variable: None,
+            node_id: node_id.clone(),
+            browse_name: id_short.to_string(),
+            display_name: text(display_name).unwrap_or_else(|| id_short.to_string()),
+            description: None,
+            parent: shell_node.clone(),
+            reference: "HasComponent",
+            type_definition: BASE_OBJECT_TYPE,
+        });
+        for element in elements.iter() {
+            element_nodes(&mut nodes, id_short, &node_id, &element.id_short, element);
+        }
+    }
+
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    out.push_str(&format!(
+        "<UANodeSet xmlns=\"{}\" xmlns:uax=\"{}\">\n",
+        NODESET_NAMESPACE, TYPES_NAMESPACE
+    ));
+    out.push_str(&format!(
+        "  <NamespaceUris>\n    <Uri>{}</Uri>\n  </NamespaceUris>\n",
+        escape(namespace_uri)
+    ));
+    out.push_str("  <Aliases>\n");
+    for (alias, node_id) in ALIASES {
+        out.push_str(&format!(
+            "    <Alias Alias=\"{}\">{}</Alias>\n",
+            alias, node_id
+        ));
+    }
+    out.push_str("  </Aliases>\n");
+    for node in &nodes {
+        write_node(&mut out, node);
+    }
+    out.push_str("</UANodeSet>\n");
+    out
+}
+
+/// Nodes for an element and everything below it; `name` is the browse name
+/// (the idShort, or "[i]" for list items)
+fn element_nodes(
+    nodes: &mut Vec<Node>,
+    path: &str,
+    parent: &str,
+    name: &str,
+    element: &SubmodelElement,
+) {
+    let path = if name.starts_with('[') {
+        format!("{}{}", path, name)
+    } else {
+        format!("{}.{}", path, name)
+    };
+    let node_id = format!("ns=1;s={}", path);
+    let container = element.is_container() || matches!(element.kind, ElementKind::Entity(_));
+    nodes.push(Node {
+        variable: (!container).then(|| variable(element)),
+        node_id: node_id.clone(),
+        browse_name: name.to_string(),
+        display_name: text(&element.display_name).unwrap_or_else(|| name.to_string()),
+        description: text(&element.description),
+        parent: parent.to_string(),
+        reference: "HasComponent",
+        type_definition: if container {
+            BASE_OBJECT_TYPE
+        } else {
+            BASE_DATA_VARIABLE_TYPE
+        },
+    });
+
+    if let Some(unit) = element.unit() {
+        let unit = escape(unit);
+        nodes.push(Node {
+            variable: Some(Variable {
+                data_type: "EUInformation",
+                value: format!(
+                    "<uax:ExtensionObject><uax:TypeId><uax:Identifier>{}</uax:Identifier></uax:TypeId>\
+                     <uax:Body><uax:EUInformation><uax:NamespaceUri>{}</uax:NamespaceUri>\
+                     <uax:UnitId>-1</uax:UnitId><uax:DisplayName><uax:Text>{}</uax:Text></uax:DisplayName>\
+                     <uax:Description><uax:Text>{}</uax:Text></uax:Description>\
+                     </uax:EUInformation></uax:Body></uax:ExtensionObject>",
+                    EU_INFORMATION_XML_ENCODING, UNECE_UNITS_NAMESPACE, unit, unit
+                ),
+            }),
+            node_id: format!("{}.EngineeringUnits", node_id),
+            browse_name: "EngineeringUnits".to_string(),
+            display_name: "EngineeringUnits".to_string(),
+            description: None,
+            parent: node_id.clone(),
+            reference: "HasProperty",
+            type_definition: PROPERTY_TYPE,
+        });
+    }
+
+    let list = matches!(element.kind, ElementKind::SubmodelElementList(_));
+    for (i, child) in element.children().iter().enumerate() {
+        let name = if list {
+            format!("[{}]", i)
+        } else {
+            child.id_short.clone()
+        };
+        element_nodes(nodes, &path, &node_id, &name, child);
+    }
+}
+
+/// Data type and value of a non-container element
+fn variable(element: &SubmodelElement) -> Variable {
+    let value = |tag: &str, v: &str| format!("<uax:{0}>{1}</uax:{0}>", tag, escape(v));
+    match &element.kind {
+        ElementKind::Property(p) => {
+            let data_type = match p.value_type {
+                Some(DataTypeDefXsd::Int) => "Int32",
+                Some(DataTypeDefXsd::Double) => "Double",
+                Some(DataTypeDefXsd::Boolean) => "Boolean",
+                Some(DataTypeDefXsd::DateTime) => "DateTime",
+                Some(DataTypeDefXsd::String) | None => "String",
+            };
+            Variable {
+                data_type,
+                value: value(data_type, &p.value),
+            }
+        }
+        ElementKind::MultiLanguageProperty(mlp) => Variable {
+            data_type: "LocalizedText",
+            value: select_lang(&mlp.value, "en")
+                .map(|s| {
+                    format!(
+                        "<uax:LocalizedText><uax:Locale>{}</uax:Locale><uax:Text>{}</uax:Text></uax:LocalizedText>",
+                        escape(&s.language),
+                        escape(&s.text)
+                    )
+                })
+                .unwrap_or_default(),
+        },
+        ElementKind::Range(r) => match (r.min_f64(), r.max_f64()) {
+            (Ok(Some(low)), Ok(Some(high))) => Variable {
+                data_type: "Range",
+                value: format!(
+                    "<uax:ExtensionObject><uax:TypeId><uax:Identifier>{}</uax:Identifier></uax:TypeId>\
+                     <uax:Body><uax:Range><uax:Low>{}</uax:Low><uax:High>{}</uax:High></uax:Range>\
+                     </uax:Body></uax:ExtensionObject>",
+                    RANGE_XML_ENCODING, low, high
+                ),
+            },
+            // Open or non-numeric intervals are shown as text
+            _ => Variable {
+                data_type: "String",
+                value: value("String", element.display_value().trim()),
+            },
+        },
+        ElementKind::Blob(b) => Variable {
+            data_type: "ByteString",
+            value: value("ByteString", b.value.as_deref().unwrap_or("")),
+        },
+        _ => Variable {
+            data_type: "String",
+            value: value("String", &element.display_value()),
+        },
+    }
+}
+
+fn text(strings: &[LangString]) -> Option<String> {
+    select_lang(strings, "en").map(|s| s.text.clone())
+}
+
+fn write_node(out: &mut String, node: &Node) {
+    let class = if node.variable.is_some() {
+        "UAVariable"
+    } else {
+        "UAObject"
+    };
+    out.push_str(&format!(
+        "  <{} NodeId=\"{}\" BrowseName=\"1:{}\" ParentNodeId=\"{}\"",
+        class,
+        escape(&node.node_id),
+        escape(&node.browse_name),
+        escape(&node.parent)
+    ));
+    if let Some(v) = &node.variable {
+        out.push_str(&format!(" DataType=\"{}\"", v.data_type));
+    }
+    out.push_str(">\n");
+    out.push_str(&format!(
+        "    <DisplayName>{}</DisplayName>\n",
+        escape(&node.display_name)
+    ));
+    if let Some(description) = &node.description {
+        out.push_str(&format!(
+            "    <Description>{}</Description>\n",
+            escape(description)
+        ));
+    }
+    out.push_str("    <References>\n");
+    out.push_str(&format!(
+        "      <Reference ReferenceType=\"HasTypeDefinition\">{}</Reference>\n",
+        node.type_definition
+    ));
+    out.push_str(&format!(
+        "      <Reference ReferenceType=\"{}\" IsForward=\"false\">{}</Reference>\n",
+        node.reference,
+        escape(&node.parent)
+    ));
+    out.push_str("    </References>\n");
+    if let Some(v) = node.variable.as_ref().filter(|v| !v.value.is_empty()) {
+        out.push_str(&format!("    <Value>{}</Value>\n", v.value));
+    }
+    out.push_str(&format!("  </{}>\n", class));
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl DigitalTwin {
+    /// Export the twin structure as an OPC UA NodeSet2.xml. The nodes live in
+    /// `namespace_uri` (default: the shell id).
+    pub fn get_opcua_nodeset(&self, namespace_uri: Option<String>) -> String {
+        let namespace_uri = namespace_uri.unwrap_or_else(|| self.data.id.clone());
+        shell_to_nodeset(&self.data, &namespace_uri)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_opcua_nodeset() {
+        let json = r#"{
+            "id": "urn:example:aas:motor",
+            "asset_type": "Siemens 1LE1",
+            "nameplate": [
+                {"id_short": "Voltage", "value": "400", "unit": "V", "value_type": "xs:double"},
+                {"id_short": "Markings", "model_type": "SubmodelElementList",
+                 "type_value_list_element": "Property", "value": [{"value": "CE & UL", "unit": null}]}
+            ],
+            "submodels": [{
+                "id": "urn:example:sm:td",
+                "id_short": "TechnicalData",
+                "submodel_elements": [{
+                    "id_short": "Ambient",
+                    "model_type": "SubmodelElementCollection",
+                    "value": [{"id_short": "Temperature", "model_type": "Range", "min": "-20", "max": "40"}]
+                }]
+            }]
+        }"#;
+        let xml = DigitalTwin::new(json).unwrap().get_opcua_nodeset(None);
+
+        assert!(xml.contains("<Uri>urn:example:aas:motor</Uri>"));
+        assert!(xml.contains(
+            "<UAObject NodeId=\"ns=1;s=urn:example:aas:motor\" BrowseName=\"1:AssetAdministrationShell\" ParentNodeId=\"i=85\">"
+        ));
+        assert!(xml.contains(
+            "<UAVariable NodeId=\"ns=1;s=Nameplate.Voltage\" BrowseName=\"1:Voltage\" ParentNodeId=\"ns=1;s=Nameplate\" DataType=\"Double\">"
+        ));
+        assert!(xml.contains("<Value><uax:Double>400</uax:Double></Value>"));
+        assert!(xml.contains("NodeId=\"ns=1;s=Nameplate.Voltage.EngineeringUnits\""));
+        assert!(xml.contains("<uax:DisplayName><uax:Text>V</uax:Text></uax:DisplayName>"));
+        assert!(xml.contains(
+            "<UAVariable NodeId=\"ns=1;s=Nameplate.Markings[0]\" BrowseName=\"1:[0]\" ParentNodeId=\"ns=1;s=Nameplate.Markings\" DataType=\"String\">"
+        ));
+        assert!(xml.contains("<uax:String>CE &amp; UL</uax:String>"));
+        assert!(xml.contains(
+            "<UAObject NodeId=\"ns=1;s=TechnicalData.Ambient\" BrowseName=\"1:Ambient\" ParentNodeId=\"ns=1;s=TechnicalData\">"
+        ));
+        assert!(xml.contains("<uax:Low>-20</uax:Low><uax:High>40</uax:High>"));
+    }
+}
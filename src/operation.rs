@@ -0,0 +1,215 @@
+// --- Operation invocation ---
+// Operation elements only describe a signature; the behavior behind them is
+// registered at runtime (from Rust or JavaScript) and dispatched by path
+
+use serde_json::{Map, Value};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{DigitalTwin, ElementKind, Operation, OperationVariable, SubmodelElement, TwinError};
+
+/// Behavior behind an Operation: receives the input (and inoutput) arguments as
+/// a JSON object keyed by idShort and returns the output (and inoutput) values
+pub type OperationHandler = Box<dyn Fn(&Map<String, Value>) -> Result<Map<String, Value>, String>>;
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl DigitalTwin {
+    /// Invoke the Operation at `id_short_path` with a JSON object of arguments
+    /// (`{"Speed": 1500}`); returns the outputs as a JSON object string
+    pub fn invoke_operation(
+        &self,
+        id_short_path: &str,
+        args_json: &str,
+    ) -> Result<String, TwinError> {
+        let args: Map<String, Value> = serde_json::from_str(args_json).map_err(|e| {
+            TwinError::invalid_argument(format!("Invalid operation arguments: {}", e))
+        })?;
+        let outputs = self.call_operation(id_short_path, args)?;
+        Ok(Value::Object(outputs).to_string())
+    }
+
+    /// Register a JavaScript function as the handler of the Operation at `id_short_path`.
+    /// It is called with the arguments object and must return an object of outputs.
+    #[cfg(feature = "wasm")]
+    pub fn register_operation_handler(
+        &mut self,
+        id_short_path: &str,
+        handler: js_sys::Function,
+    ) -> Result<(), TwinError> {
+        self.register_operation(
+            id_short_path,
+            Box::new(move |args| {
+                let js_args = js_sys::JSON::parse(&Value::Object(args.clone()).to_string())
+                    .map_err(|e| format!("{:?}", e))?;
+                let result = handler
+                    .call1(&JsValue::NULL, &js_args)
+                    .map_err(|e| format!("Operation handler threw: {:?}", e))?;
+                let json: String = js_sys::JSON::stringify(&result)
+                    .map_err(|e| format!("{:?}", e))?
+                    .into();
+                serde_json::from_str(&json)
+                    .map_err(|e| format!("Operation handler must return an object: {}", e))
+            }),
+        )
+    }
+}
+
+impl DigitalTwin {
+    /// Register the behavior of the Operation element at `path`
+    pub fn register_operation(
+        &mut self,
+        path: &str,
+        handler: OperationHandler,
+    ) -> Result<(), TwinError> {
+        self.operation_at(path)?;
+        self.operation_handlers.insert(path.to_string(), handler);
+        Ok(())
+    }
+
+    /// Validate `args` against the Operation's signature, run its handler and
+    /// return the declared outputs
+    pub fn call_operation(
+        &self,
+        path: &str,
+        mut args: Map<String, Value>,
+    ) -> Result<Map<String, Value>, TwinError> {
+        let operation = self.operation_at(path)?;
+        let handler = self.operation_handlers.get(path).ok_or_else(|| {
+            TwinError::failed(
+                Some(path),
+                format!("No handler registered for operation '{}'", path),
+            )
+        })?;
+
+        let inputs = operation
+            .input_variables
+            .iter()
+            .chain(&operation.inoutput_variables);
+        for var in inputs {
+            let id_short = &var.value.id_short;
+            if args.contains_key(id_short) {
+                continue;
+            }
+            let default = default_argument(&var.value).ok_or_else(|| {
+                TwinError::invalid_argument(format!(
+                    "Missing input '{}' for operation '{}'",
+                    id_short, path
+                ))
+            })?;
+            args.insert(id_short.clone(), default);
+        }
+
+        let mut results = handler(&args).map_err(|e| TwinError::failed(Some(path), e))?;
+        variable_names(&operation.output_variables)
+            .chain(variable_names(&operation.inoutput_variables))
+            .map(|id_short| {
+                results
+                    .remove(id_short)
+                    .map(|v| (id_short.to_string(), v))
+                    .ok_or_else(|| {
+                        TwinError::failed(
+                            Some(path),
+                            format!("Operation '{}' did not return output '{}'", path, id_short),
+                        )
+                    })
+            })
+            .collect()
+    }
+
+    fn operation_at(&self, path: &str) -> Result<&Operation, TwinError> {
+        let element = self
+            .find_element(path)
+            .map_err(|_| TwinError::not_found(path, format!("Operation '{}' not found", path)))?;
+        match &element.kind {
+            ElementKind::Operation(op) => Ok(op),
+            other => Err(TwinError::type_mismatch(
+                path,
+                format!(
+                    "Element '{}' is a {}, not an Operation",
+                    path,
+                    other.model_type()
+                ),
+            )),
+        }
+    }
+}
+
+fn variable_names(vars: &[OperationVariable]) -> impl Iterator<Item = &str> {
+    vars.iter().map(|v| v.value.id_short.as_str())
+}
+
+/// A Property variable with a non-empty value acts as the argument's default
+fn default_argument(element: &SubmodelElement) -> Option<Value> {
+    match &element.kind {
+        ElementKind::Property(p) if !p.value.is_empty() => Some(Value::String(p.value.clone())),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pump_twin() -> DigitalTwin {
+        DigitalTwin::new(
+            r#"{
+                "id": "PUMP-1",
+                "asset_type": "KSB Etanorm",
+                "nameplate": [{
+                    "id_short": "ComputeFlow",
+                    "model_type": "Operation",
+                    "input_variables": [
+                        {"value": {"id_short": "Speed", "value": "", "unit": "1/min"}},
+                        {"value": {"id_short": "Efficiency", "value": "0.8", "unit": null}}
+                    ],
+                    "output_variables": [
+                        {"value": {"id_short": "Flow", "value": "", "unit": "m3/h"}}
+                    ]
+                }]
+            }"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_invoke_registered_operation() {
+        let mut twin = pump_twin();
+        twin.register_operation(
+            "ComputeFlow",
+            Box::new(|args| {
+                let speed = args["Speed"].as_f64().ok_or("Speed must be a number")?;
+                let efficiency: f64 = args["Efficiency"].as_str().unwrap().parse().unwrap();
+                let mut out = Map::new();
+                out.insert("Flow".into(), Value::from(speed * 0.02 * efficiency));
+                out.insert("Debug".into(), Value::from("dropped"));
+                Ok(out)
+            }),
+        )
+        .unwrap();
+
+        let out = twin
+            .invoke_operation("ComputeFlow", r#"{"Speed": 1500}"#)
+            .unwrap();
+        assert_eq!(out, r#"{"Flow":24.0}"#);
+
+        let missing = twin.call_operation("ComputeFlow", Map::new());
+        assert!(missing
+            .unwrap_err()
+            .message()
+            .contains("Missing input 'Speed'"));
+    }
+
+    #[test]
+    fn test_operation_requires_handler() {
+        let mut twin = pump_twin();
+        let args: Map<String, Value> = serde_json::from_str(r#"{"Speed": 1500}"#).unwrap();
+        assert!(twin
+            .call_operation("ComputeFlow", args)
+            .unwrap_err()
+            .message()
+            .contains("No handler"));
+        assert!(twin
+            .register_operation("Nope", Box::new(|_| Ok(Map::new())))
+            .is_err());
+    }
+}
@@ -0,0 +1,604 @@
+// --- JSON Patch ---
+// Delta updates (RFC 6902) pushed from a backend instead of full
+// re-hydration. Operations address elements by JSON pointer
+// ("/TechnicalData/Markings/0") or idShortPath ("TechnicalData.Markings[0]")
+// on the twin's element tree: "/Voltage" is a nameplate element, a single
+// segment naming a submodel is that submodel. A scalar value sets an
+// element's value; an object adds or replaces a whole element (or submodel)
+// in the twin's JSON format. A patch applies completely or not at all.
+//
+// Merge patches (RFC 7386) cover the simpler case of partial value updates:
+// the document mirrors the element tree by idShort, `null` removes an
+// element and a scalar sets (or creates) a Property.
+//
+// `diff` goes the other way and describes how to turn one twin's elements
+// and submodels into another's as a JSON Patch; shell-level information such
+// as the asset type is not compared.
+
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    join_path, parse_path, DataTypeDefXsd, DigitalTwin, ElementKind, PathSegment, Property,
+    Submodel, SubmodelElement, TwinError, NAMEPLATE_ID_SHORT,
+};
+
+#[derive(Deserialize)]
+struct PatchOperation {
+    op: String,
+    path: String,
+    #[serde(default)]
+    value: Option<Value>,
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl DigitalTwin {
+    /// Apply a JSON Patch (an array of add, remove and replace operations)
+    pub fn apply_patch(&mut self, patch_json: &str) -> Result<(), TwinError> {
+        let patch: Value = serde_json::from_str(patch_json)
+            .map_err(|e| TwinError::parse(format!("Invalid JSON Patch: {}", e)))?;
+        self.patch(&patch)
+    }
+
+    /// Apply a JSON Merge Patch of values, e.g.
+    /// `{"Voltage": 230, "TechnicalData": {"MaxTorque": "260", "Obsolete": null}}`
+    pub fn merge(&mut self, json: &str) -> Result<(), TwinError> {
+        let patch: Value = serde_json::from_str(json)
+            .map_err(|e| TwinError::parse(format!("Invalid JSON Merge Patch: {}", e)))?;
+        self.merge_patch(&patch)
+    }
+
+    /// The JSON Patch that turns this twin's elements and submodels into the
+    /// other twin's, e.g. to show what changed since commissioning
+    pub fn diff(&self, other: &DigitalTwin) -> String {
+        self.diff_patch(other).to_string()
+    }
+}
+
+impl DigitalTwin {
+    /// Rust-side `apply_patch`; on failure the twin is left unchanged
+    pub fn patch(&mut self, patch: &Value) -> Result<(), TwinError> {
+        let operations: Vec<PatchOperation> = serde_json::from_value(patch.clone())
+            .map_err(|e| TwinError::invalid_argument(format!("Invalid JSON Patch: {}", e)))?;
+        self.transaction(|twin| {
+            operations
+                .iter()
+                .try_for_each(|operation| twin.patch_operation(operation))
+        })
+    }
+
+    /// Rust-side `merge`; on failure the twin is left unchanged
+    pub fn merge_patch(&mut self, patch: &Value) -> Result<(), TwinError> {
+        let Value::Object(members) = patch else {
+            return Err(TwinError::invalid_argument(
+                "A JSON Merge Patch of a twin must be an object",
+            ));
+        };
+        self.transaction(|twin| twin.merge_members("", members))
+    }
+
+    /// Rust-side `diff`: removals first within each container, list items
+    /// by index, unchanged parts left out
+    pub fn diff_patch(&self, other: &DigitalTwin) -> Value {
+        let mut ops = Vec::new();
+        for sm in &self.data.submodels {
+            let pointer = pointer_to("", &sm.id_short);
+            match other
+                .data
+                .submodels
+                .iter()
+                .find(|o| o.id_short == sm.id_short)
+            {
+                None => ops.push(json!({"op": "remove", "path": pointer})),
+                Some(o) if without_elements(sm) != without_elements(o) => {
+                    ops.push(json!({"op": "replace", "path": pointer, "value": o}))
+                }
+                Some(o) => diff_elements(
+                    &pointer,
+                    &sm.submodel_elements,
+                    &o.submodel_elements,
+                    false,
+                    &mut ops,
+                ),
+            }
+        }
+        diff_elements(
+            "",
+            &self.data.nameplate,
+            &other.data.nameplate,
+            false,
+            &mut ops,
+        );
+        for o in &other.data.submodels {
+            if !self
+                .data
+                .submodels
+                .iter()
+                .any(|sm| sm.id_short == o.id_short)
+            {
+                ops.push(json!({"op": "add", "path": pointer_to("", &o.id_short), "value": o}));
+            }
+        }
+        Value::Array(ops)
+    }
+
+    /// Run `edit`, restoring the shell if it fails
+    fn transaction(
+        &mut self,
+        edit: impl FnOnce(&mut DigitalTwin) -> Result<(), TwinError>,
+    ) -> Result<(), TwinError> {
+        self.tracked(|twin| {
+            let backup = twin.data.clone();
+            edit(twin).inspect_err(|_| twin.data = backup)
+        })
+    }
+
+    fn merge_members(
+        &mut self,
+        prefix: &str,
+        members: &Map<String, Value>,
+    ) -> Result<(), TwinError> {
+        for (key, value) in members {
+            let path = join_path(prefix, key);
+            let parsed = parse_path(&path)?;
+            let path = parsed.to_string();
+            let submodel = self.submodel_index(&path);
+            let nameplate = path == NAMEPLATE_ID_SHORT;
+            let element = self.find_element_by_path(&path).filter(|_| !nameplate);
+            match value {
+                Value::Null => match (submodel, element) {
+                    (Some(i), _) => {
+                        self.data.submodels.remove(i);
+                    }
+                    (None, Some(_)) => {
+                        self.remove_element_at(&path)?;
+                    }
+                    // Removing what is not there is no change
+                    (None, None) => {}
+                },
+                Value::Object(children) => {
+                    if submodel.is_none() && !nameplate && element.is_none() {
+                        return Err(TwinError::not_found(
+                            &path,
+                            format!("Property '{}' not found", path),
+                        ));
+                    }
+                    self.merge_members(&path, children)?;
+                }
+                Value::Array(_) => {
+                    return Err(TwinError::invalid_argument(format!(
+                        "Cannot merge an array into '{}'; address list items by index",
+                        path
+                    )))
+                }
+                scalar if element.is_some() => {
+                    self.set_property(&path, &scalar_text(&path, scalar)?)?
+                }
+                scalar => {
+                    let value_type = match scalar {
+                        Value::Number(_) => Some(DataTypeDefXsd::Double),
+                        Value::Bool(_) => Some(DataTypeDefXsd::Boolean),
+                        _ => None,
+                    };
+                    let property = ElementKind::Property(Property {
+                        value: scalar_text(&path, scalar)?,
+                        unit: None,
+                        value_type,
+                    });
+                    let (parent, _) = parsed.split_last();
+                    self.insert_element(&parent.to_string(), SubmodelElement::new(key, property))?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn patch_operation(&mut self, operation: &PatchOperation) -> Result<(), TwinError> {
+        let (path, append) = patch_path(&operation.path)?;
+        let value = || {
+            operation.value.as_ref().ok_or_else(|| {
+                TwinError::invalid_argument(format!(
+                    "JSON Patch '{}' of '{}' needs a value",
+                    operation.op, operation.path
+                ))
+            })
+        };
+        match operation.op.as_str() {
+            "remove" => {
+                if let Some(i) = self.submodel_index(&path) {
+                    self.data.submodels.remove(i);
+                    return Ok(());
+                }
+                self.patch_target(&path)?;
+                self.remove_element_at(&path).map(|_| ())
+            }
+            "replace" => self.replace_at(&path, value()?),
+            "add" if append => {
+                let element = element_from(&path, value()?)?;
+                self.insert_element(&path, element).map(|_| ())
+            }
+            "add" if self.submodel_index(&path).is_some() => self.replace_at(&path, value()?),
+            // Adding at a list index inserts, adding an existing member replaces it
+            "add" if !path.ends_with(']') && self.find_element_by_path(&path).is_some() => {
+                self.replace_at(&path, value()?)
+            }
+            "add" => self.add_at(&path, value()?),
+            op => Err(TwinError::invalid_argument(format!(
+                "Unsupported JSON Patch operation '{}'",
+                op
+            ))),
+        }
+    }
+
+    fn submodel_index(&self, path: &str) -> Option<usize> {
+        self.data.submodels.iter().position(|s| s.id_short == path)
+    }
+
+    /// Patch paths name elements exactly; there is no bare-idShort search
+    fn patch_target(&self, path: &str) -> Result<(), TwinError> {
+        match self.find_element_by_path(path) {
+            Some(_) => Ok(()),
+            None => Err(TwinError::not_found(
+                path,
+                format!("Property '{}' not found", path),
+            )),
+        }
+    }
+
+    fn replace_at(&mut self, path: &str, value: &Value) -> Result<(), TwinError> {
+        if let Some(i) = self.submodel_index(path) {
+            let submodel: Submodel = serde_json::from_value(value.clone())
+                .map_err(|e| TwinError::parse(format!("Invalid submodel JSON: {}", e)))?;
+            self.data.submodels[i] = submodel;
+            return self
+                .check_lists()
+                .map_err(|e| TwinError::validation(Some(path), e));
+        }
+        self.patch_target(path)?;
+        if !value.is_object() {
+            return self.set_property(path, &scalar_text(path, value)?);
+        }
+        let mut element = element_from(path, value)?;
+        let target = self.find_element_mut(path)?;
+        if element.id_short.is_empty() {
+            element.id_short = target.id_short.clone();
+        }
+        *target = element;
+        self.check_lists()
+            .map_err(|e| TwinError::validation(Some(path), e))
+    }
+
+    fn add_at(&mut self, path: &str, value: &Value) -> Result<(), TwinError> {
+        let parsed = parse_path(path)?;
+        let (parent, last) = parsed.split_last();
+        let parent = parent.to_string();
+        let is_submodel = value.get("submodel_elements").is_some();
+        match last {
+            PathSegment::IdShort(id_short) if parent.is_empty() && is_submodel => {
+                let submodel: Submodel = serde_json::from_value(value.clone())
+                    .map_err(|e| TwinError::parse(format!("Invalid submodel JSON: {}", e)))?;
+                if &submodel.id_short != id_short {
+                    return Err(TwinError::validation(
+                        Some(path),
+                        format!("Submodel added as '{}' is '{}'", path, submodel.id_short),
+                    ));
+                }
+                self.data.submodels.push(submodel);
+                Ok(())
+            }
+            PathSegment::IdShort(id_short) => {
+                let mut element = element_from(path, value)?;
+                if element.id_short.is_empty() {
+                    element.id_short = id_short.clone();
+                }
+                if &element.id_short != id_short {
+                    return Err(TwinError::validation(
+                        Some(path),
+                        format!("Element added as '{}' is '{}'", path, element.id_short),
+                    ));
+                }
+                self.insert_element(&parent, element).map(|_| ())
+            }
+            PathSegment::Index(index) => {
+                let element = element_from(path, value)?;
+                self.insert_element(&parent, element)?;
+                let (_, items, list) = self.children_at_mut(&parent)?;
+                if !list || *index >= items.len() {
+                    return Err(TwinError::invalid_argument(format!(
+                        "Cannot add an item at '{}'",
+                        path
+                    )));
+                }
+                let item = items.pop().expect("inserted above");
+                items.insert(*index, item);
+                Ok(())
+            }
+        }
+    }
+}
+
+fn diff_elements(
+    pointer: &str,
+    old: &[SubmodelElement],
+    new: &[SubmodelElement],
+    list: bool,
+    ops: &mut Vec<Value>,
+) {
+    if list {
+        for (i, (o, n)) in old.iter().zip(new).enumerate() {
+            diff_element(&pointer_to(pointer, &i.to_string()), o, n, ops);
+        }
+        // Trailing items go from the end so the indices stay valid
+        for i in (new.len()..old.len()).rev() {
+            ops.push(json!({"op": "remove", "path": pointer_to(pointer, &i.to_string())}));
+        }
+        for item in new.iter().skip(old.len()) {
+            ops.push(json!({"op": "add", "path": pointer_to(pointer, "-"), "value": item}));
+        }
+        return;
+    }
+    for o in old {
+        match new.iter().find(|n| n.id_short == o.id_short) {
+            Some(n) => diff_element(&pointer_to(pointer, &o.id_short), o, n, ops),
+            None => ops.push(json!({"op": "remove", "path": pointer_to(pointer, &o.id_short)})),
+        }
+    }
+    for n in new {
+        if !old.iter().any(|o| o.id_short == n.id_short) {
+            ops.push(json!({"op": "add", "path": pointer_to(pointer, &n.id_short), "value": n}));
+        }
+    }
+}
+
+fn diff_element(pointer: &str, old: &SubmodelElement, new: &SubmodelElement, ops: &mut Vec<Value>) {
+    let (old_json, new_json) = (to_value(old), to_value(new));
+    if old_json == new_json {
+        return;
+    }
+    let (old_rest, new_rest) = (without_children(old), without_children(new));
+    // Equal apart from their children: only containers get here
+    if old_rest == new_rest {
+        let list = matches!(old.kind, ElementKind::SubmodelElementList(_));
+        diff_elements(pointer, old.children(), new.children(), list, ops);
+        return;
+    }
+    // A Property that differs only in its value travels as just the value
+    if let ElementKind::Property(n) = &new.kind {
+        let mut updated = old.clone();
+        if let ElementKind::Property(p) = &mut updated.kind {
+            p.value = n.value.clone();
+            if to_value(&updated) == new_json {
+                ops.push(json!({"op": "replace", "path": pointer, "value": n.value}));
+                return;
+            }
+        }
+    }
+    ops.push(json!({"op": "replace", "path": pointer, "value": new_json}));
+}
+
+fn to_value<T: serde::Serialize>(value: &T) -> Value {
+    serde_json::to_value(value).unwrap_or(Value::Null)
+}
+
+/// The element as JSON with its children left out
+fn without_children(element: &SubmodelElement) -> Value {
+    let mut element = element.clone();
+    if let Some(children) = element.children_vec_mut() {
+        children.clear();
+    }
+    to_value(&element)
+}
+
+fn without_elements(submodel: &Submodel) -> Value {
+    let mut submodel = submodel.clone();
+    submodel.submodel_elements.clear();
+    to_value(&submodel)
+}
+
+/// Append an escaped JSON pointer segment
+fn pointer_to(pointer: &str, segment: &str) -> String {
+    format!(
+        "{}/{}",
+        pointer,
+        segment.replace('~', "~0").replace('/', "~1")
+    )
+}
+
+/// The idShortPath a patch path stands for, and whether it ends in the
+/// JSON pointer append marker "-"
+fn patch_path(path: &str) -> Result<(String, bool), TwinError> {
+    let Some(pointer) = path.strip_prefix('/') else {
+        return Ok((parse_path(path)?.to_string(), false));
+    };
+    let mut segments: Vec<String> = pointer
+        .split('/')
+        .map(|s| s.replace("~1", "/").replace("~0", "~"))
+        .collect();
+    let append = segments.last().is_some_and(|s| s == "-");
+    if append {
+        segments.pop();
+    }
+    if segments.is_empty() || segments == [""] {
+        return Err(TwinError::invalid_argument(format!(
+            "JSON Patch path '{}' does not name an element",
+            path
+        )));
+    }
+    Ok((parse_path(&segments.join("."))?.to_string(), append))
+}
+
+fn element_from(path: &str, value: &Value) -> Result<SubmodelElement, TwinError> {
+    serde_json::from_value(value.clone())
+        .map_err(|e| TwinError::parse(format!("Invalid element JSON for '{}': {}", path, e)))
+}
+
+fn scalar_text(path: &str, value: &Value) -> Result<String, TwinError> {
+    match value {
+        Value::String(s) => Ok(s.clone()),
+        Value::Number(_) | Value::Bool(_) => Ok(value.to_string()),
+        _ => Err(TwinError::invalid_argument(format!(
+            "Cannot set '{}' to {}",
+            path, value
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::DigitalTwin;
+    use serde_json::json;
+
+    const TWIN: &str = r#"{
+        "id": "MOTOR-12345",
+        "asset_type": "Siemens 1LE1",
+        "nameplate": [
+            {"id_short": "Voltage", "value": "400", "unit": "V", "value_type": "xs:double"},
+            {"id_short": "Markings", "model_type": "SubmodelElementList",
+             "type_value_list_element": "Property", "value": [{"value": "CE", "unit": null}]}
+        ],
+        "submodels": [{
+            "id": "urn:example:sm:td",
+            "id_short": "TechnicalData",
+            "submodel_elements": [{"id_short": "MaxTorque", "value": "250", "unit": "Nm"}]
+        }]
+    }"#;
+
+    #[test]
+    fn test_apply_patch() {
+        let mut twin = DigitalTwin::new(TWIN).unwrap();
+        twin.apply_patch(
+            r#"[
+                {"op": "replace", "path": "/Voltage", "value": 230},
+                {"op": "add", "path": "/TechnicalData/Speed", "value": {"value": "1500", "unit": "rpm"}},
+                {"op": "add", "path": "/Markings/0", "value": {"value": "UKCA", "unit": null}},
+                {"op": "add", "path": "/Markings/-", "value": {"value": "UL", "unit": null}},
+                {"op": "remove", "path": "TechnicalData.MaxTorque"},
+                {"op": "add", "path": "/Service", "value": {"id": "urn:example:sm:service",
+                 "id_short": "Service", "submodel_elements": []}}
+            ]"#,
+        )
+        .unwrap();
+
+        assert_eq!(twin.get_property_display("Voltage"), "230 V");
+        assert_eq!(twin.get_property_display("TechnicalData.Speed"), "1500 rpm");
+        assert_eq!(
+            twin.list_properties_display(),
+            "Voltage, Markings[0], Markings[1], Markings[2], TechnicalData.Speed"
+        );
+        assert_eq!(twin.get_property_display("Markings[0]"), "UKCA ");
+        assert_eq!(twin.get_property_display("Markings[2]"), "UL ");
+        assert!(twin.get_aas_json().contains("urn:example:sm:service"));
+
+        twin.patch(&json!([{"op": "remove", "path": "/Service"}]))
+            .unwrap();
+        assert!(!twin.get_aas_json().contains("urn:example:sm:service"));
+    }
+
+    #[test]
+    fn test_failed_patch_leaves_the_twin_unchanged() {
+        let mut twin = DigitalTwin::new(TWIN).unwrap();
+        let before = twin.get_aas_json();
+        let err = twin
+            .patch(&json!([
+                {"op": "remove", "path": "/TechnicalData/MaxTorque"},
+                {"op": "replace", "path": "/Voltage", "value": "high"}
+            ]))
+            .unwrap_err();
+        assert_eq!(err.code(), "TYPE_MISMATCH");
+        assert_eq!(twin.get_aas_json(), before);
+
+        for patch in [
+            json!([{"op": "move", "path": "/Voltage", "from": "/Markings"}]),
+            json!([{"op": "replace", "path": "", "value": {}}]),
+            json!([{"op": "replace", "path": "/Speed", "value": 1}]),
+            json!({"op": "remove", "path": "/Voltage"}),
+        ] {
+            assert!(twin.patch(&patch).is_err(), "{}", patch);
+        }
+        assert_eq!(twin.get_aas_json(), before);
+    }
+
+    #[test]
+    fn test_merge_patch() {
+        let mut twin = DigitalTwin::new(TWIN).unwrap();
+        twin.merge(
+            r#"{
+                "Voltage": 230,
+                "Markings": {"0": "UKCA"},
+                "TechnicalData": {"MaxTorque": null, "Speed": 1500},
+                "Nameplate": {"SerialNumber": "S-1"},
+                "Unknown": null
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(twin.get_property_display("Voltage"), "230 V");
+        assert_eq!(twin.get_property_display("Markings[0]"), "UKCA ");
+        assert_eq!(
+            twin.list_properties_display(),
+            "Voltage, Markings[0], SerialNumber, TechnicalData.Speed"
+        );
+        assert_eq!(
+            twin.property_value("TechnicalData.Speed").unwrap().value,
+            json!(1500.0)
+        );
+
+        let before = twin.get_aas_json();
+        for patch in [
+            json!({"Voltage": 400, "Missing": {"X": 1}}),
+            json!({"Voltage": "high"}),
+            json!({"Markings": ["CE"]}),
+            json!([1]),
+        ] {
+            assert!(twin.merge_patch(&patch).is_err(), "{}", patch);
+        }
+        assert_eq!(twin.get_aas_json(), before);
+    }
+
+    #[test]
+    fn test_diff_round_trips_through_apply_patch() {
+        let old = DigitalTwin::new(TWIN).unwrap();
+        let mut new = DigitalTwin::new(TWIN).unwrap();
+        new.merge_patch(&json!({"Voltage": 230, "TechnicalData": {"Speed": 1500}}))
+            .unwrap();
+        new.patch(&json!([
+            {"op": "replace", "path": "/Markings/0", "value": {"value": "UKCA", "unit": "-"}},
+            {"op": "add", "path": "/Markings/-", "value": {"value": "UL", "unit": null}},
+            {"op": "add", "path": "/Service", "value": {"id": "urn:example:sm:service",
+             "id_short": "Service", "submodel_elements": []}}
+        ]))
+        .unwrap();
+
+        let patch = old.diff_patch(&new);
+        let ops: Vec<_> = patch
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|op| {
+                format!(
+                    "{} {}",
+                    op["op"].as_str().unwrap(),
+                    op["path"].as_str().unwrap()
+                )
+            })
+            .collect();
+        assert_eq!(
+            ops,
+            [
+                "add /TechnicalData/Speed",
+                "replace /Voltage",
+                "replace /Markings/0",
+                "add /Markings/-",
+                "add /Service",
+            ]
+        );
+        assert_eq!(patch[1]["value"], "230");
+        assert_eq!(patch[2]["value"]["unit"], "-");
+
+        let mut patched = DigitalTwin::new(TWIN).unwrap();
+        patched.apply_patch(&old.diff(&new)).unwrap();
+        assert_eq!(patched.get_aas_json(), new.get_aas_json());
+        assert_eq!(new.diff_patch(&patched), json!([]));
+    }
+}
@@ -0,0 +1,514 @@
+// --- Simulation profiles ---
+// The `simulation` section of a configuration defines models made of
+// signals, so that one WASM binary can simulate a pump, a conveyor or a
+// compressor without code. Each signal follows a first-order lag towards its
+// base value plus weighted dependencies on other signals and inputs
+// ("motor.rpm", "load"), is kept within its range and carries uniform noise.
+// The built-in motor models stay attached unless the profile switches them
+// off, the `noise` map puts sensor noise models on any signal, the
+// `telemetry` map feeds signals from the real asset instead, the `limits`
+// map raises events when signals cross limits and `parameters` tunes the
+// models.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+#[cfg(feature = "wasm")]
+use tsify::Tsify;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::limit::validate_limits;
+use crate::{
+    unknown_parameter, DigitalTwin, NoiseModel, SimulationModel, SimulationRng, SimulationValues,
+    TelemetrySource, TwinError, MOTOR_MODEL, THERMAL_MODEL, VIBRATION_MODEL, WEAR_MODEL,
+};
+
+/// Key of the profile in a configuration
+pub const SIMULATION_SECTION: &str = "simulation";
+
+/// One simulated signal of a configured model
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[cfg_attr(feature = "wasm", tsify(missing_as_null))]
+#[serde(deny_unknown_fields)]
+pub struct SignalDefinition {
+    pub name: String,
+    #[serde(default)]
+    pub unit: Option<String>,
+    /// Value the signal settles at without dependencies
+    #[serde(default)]
+    pub base: f64,
+    /// Gains on other signals ("motor.rpm") or inputs ("load") added to the
+    /// base value
+    #[serde(default)]
+    pub depends_on: BTreeMap<String, f64>,
+    #[serde(default)]
+    pub min: Option<f64>,
+    #[serde(default)]
+    pub max: Option<f64>,
+    /// Amplitude of the uniform noise on the reported value
+    #[serde(default)]
+    pub noise: f64,
+    /// Seconds the signal needs for 63 % of a change; 0 follows at once
+    #[serde(default)]
+    pub time_constant: f64,
+    /// Value at the start, the base value if not given
+    #[serde(default)]
+    pub initial: Option<f64>,
+}
+
+/// A configured model and its signals, computed in order
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[serde(deny_unknown_fields)]
+pub struct ModelDefinition {
+    pub name: String,
+    pub signals: Vec<SignalDefinition>,
+}
+
+/// The `simulation` section of a configuration
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[serde(deny_unknown_fields)]
+pub struct SimulationProfile {
+    /// Whether the built-in motor, thermal, vibration and wear models run
+    /// next to the configured ones
+    #[serde(default = "builtin_default")]
+    pub builtin: bool,
+    /// Models in the order they are stepped
+    #[serde(default)]
+    pub models: Vec<ModelDefinition>,
+    /// Sensor noise by signal key ("motor.current"), applied in order
+    #[serde(default)]
+    pub noise: BTreeMap<String, Vec<NoiseModel>>,
+    /// Signals fed from ingested readings, by signal key
+    #[serde(default)]
+    pub telemetry: BTreeMap<String, TelemetrySource>,
+    /// Limits by signal key whose crossing emits an event
+    #[serde(default)]
+    pub limits: BTreeMap<String, Vec<f64>>,
+    /// Model parameters set over their defaults, keyed like
+    /// "motor.time_constant"
+    #[serde(default)]
+    pub parameters: BTreeMap<String, f64>,
+}
+
+fn builtin_default() -> bool {
+    true
+}
+
+impl Default for SimulationProfile {
+    /// Only the built-in models
+    fn default() -> SimulationProfile {
+        SimulationProfile {
+            builtin: true,
+            models: Vec::new(),
+            noise: BTreeMap::new(),
+            telemetry: BTreeMap::new(),
+            limits: BTreeMap::new(),
+            parameters: BTreeMap::new(),
+        }
+    }
+}
+
+impl SimulationProfile {
+    /// The profile in a configuration, the default one if it has none
+    pub(crate) fn from_config(config: &Value) -> Result<SimulationProfile, TwinError> {
+        match config.get(SIMULATION_SECTION) {
+            Some(section) => serde_json::from_value(section.clone())
+                .map_err(|e| TwinError::parse(format!("Invalid simulation profile: {}", e))),
+            None => Ok(SimulationProfile::default()),
+        }
+    }
+
+    /// Check names, ranges, noise, telemetry and limit parameters and that
+    /// dependencies on models name known ones
+    pub fn validate(&self) -> Result<(), TwinError> {
+        let invalid =
+            |message: String| Err(TwinError::validation(Some(SIMULATION_SECTION), message));
+        let builtin: &[&str] = if self.builtin {
+            &[MOTOR_MODEL, THERMAL_MODEL, VIBRATION_MODEL, WEAR_MODEL]
+        } else {
+            &[]
+        };
+        let mut models = BTreeSet::new();
+        for model in &self.models {
+            if model.name.is_empty() || model.name.contains('.') {
+                return invalid(format!(
+                    "Model name '{}' must be non-empty and without '.'",
+                    model.name
+                ));
+            }
+            if builtin.contains(&model.name.as_str()) {
+                return invalid(format!("Model '{}' is a built-in model", model.name));
+            }
+            if !models.insert(model.name.as_str()) {
+                return invalid(format!("Model '{}' is defined twice", model.name));
+            }
+        }
+        let outputs: BTreeSet<String> = self
+            .models
+            .iter()
+            .flat_map(|m| {
+                m.signals
+                    .iter()
+                    .map(move |s| format!("{}.{}", m.name, s.name))
+            })
+            .collect();
+        for model in &self.models {
+            let mut names = BTreeSet::new();
+            for signal in &model.signals {
+                let key = format!("{}.{}", model.name, signal.name);
+                if signal.name.is_empty() || signal.name.contains('.') {
+                    return invalid(format!(
+                        "Signal name '{}' must be non-empty and without '.'",
+                        key
+                    ));
+                }
+                if !names.insert(signal.name.as_str()) {
+                    return invalid(format!("Signal '{}' is defined twice", key));
+                }
+                let mut numbers = [Some(signal.base), signal.min, signal.max, signal.initial]
+                    .into_iter()
+                    .flatten()
+                    .chain(signal.depends_on.values().copied());
+                if numbers.any(|n| !n.is_finite())
+                    || !(signal.noise >= 0.0 && signal.noise.is_finite())
+                    || !(signal.time_constant >= 0.0 && signal.time_constant.is_finite())
+                {
+                    return invalid(format!(
+                        "Signal '{}' needs finite numbers and a non-negative noise and time constant",
+                        key
+                    ));
+                }
+                if let (Some(min), Some(max)) = (signal.min, signal.max) {
+                    if min > max {
+                        return invalid(format!("Signal '{}' has min above max", key));
+                    }
+                }
+                for source in signal.depends_on.keys() {
+                    let known = match source.split_once('.') {
+                        None => true,
+                        Some((model, _)) if builtin.contains(&model) => true,
+                        Some(_) => outputs.contains(source),
+                    };
+                    if !known {
+                        return invalid(format!(
+                            "Signal '{}' depends on the unknown signal '{}'",
+                            key, source
+                        ));
+                    }
+                }
+            }
+        }
+        for (key, models) in &self.noise {
+            for model in models {
+                model.validate(key)?;
+            }
+        }
+        for (key, source) in &self.telemetry {
+            source.validate(key)?;
+        }
+        for (key, limits) in &self.limits {
+            validate_limits(key, limits)?;
+        }
+        if let Some((key, value)) = self.parameters.iter().find(|(_, v)| !v.is_finite()) {
+            return invalid(format!(
+                "Parameter '{}' must be a finite number, got {}",
+                key, value
+            ));
+        }
+        Ok(())
+    }
+
+    /// Inputs the configured signals depend on ("load"), as opposed to
+    /// other signals ("motor.rpm")
+    pub(crate) fn inputs(&self) -> impl Iterator<Item = &str> {
+        self.models
+            .iter()
+            .flat_map(|m| &m.signals)
+            .flat_map(|s| s.depends_on.keys())
+            .filter(|key| !key.contains('.'))
+            .map(String::as_str)
+    }
+}
+
+/// A model running the signals of a `ModelDefinition`
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProfileModel {
+    definition: ModelDefinition,
+    // Noise-free value of each signal
+    values: Vec<f64>,
+}
+
+impl ProfileModel {
+    pub fn new(definition: ModelDefinition) -> ProfileModel {
+        ProfileModel {
+            values: vec![0.0; definition.signals.len()],
+            definition,
+        }
+    }
+
+    /// Outputs with the given noise on each signal, none where missing
+    fn outputs(&self, noise: &[f64]) -> SimulationValues {
+        self.definition
+            .signals
+            .iter()
+            .zip(&self.values)
+            .enumerate()
+            .map(|(i, (signal, value))| {
+                let noisy = value + noise.get(i).copied().unwrap_or(0.0);
+                (signal.name.clone(), clamp(signal, noisy))
+            })
+            .collect()
+    }
+}
+
+fn clamp(signal: &SignalDefinition, value: f64) -> f64 {
+    let value = signal.min.map_or(value, |min| value.max(min));
+    signal.max.map_or(value, |max| value.min(max))
+}
+
+impl SimulationModel for ProfileModel {
+    fn init(&mut self) -> SimulationValues {
+        self.values = self
+            .definition
+            .signals
+            .iter()
+            .map(|s| clamp(s, s.initial.unwrap_or(s.base)))
+            .collect();
+        self.outputs(&[])
+    }
+
+    fn step(
+        &mut self,
+        dt: f64,
+        inputs: &SimulationValues,
+        rng: &mut SimulationRng,
+    ) -> SimulationValues {
+        let prefix = format!("{}.", self.definition.name);
+        for i in 0..self.values.len() {
+            let signal = &self.definition.signals[i];
+            // Signals of this model are read as already computed this tick
+            let source = |key: &str| match key.strip_prefix(&prefix) {
+                Some(own) => self
+                    .definition
+                    .signals
+                    .iter()
+                    .position(|s| s.name == own)
+                    .map_or(0.0, |j| self.values[j]),
+                None => inputs.get(key).copied().unwrap_or(0.0),
+            };
+            let target = clamp(
+                signal,
+                signal.base
+                    + signal
+                        .depends_on
+                        .iter()
+                        .map(|(key, gain)| gain * source(key))
+                        .sum::<f64>(),
+            );
+            let value = self.values[i];
+            self.values[i] = if signal.time_constant > 0.0 {
+                value + (target - value) * (1.0 - (-dt / signal.time_constant).exp())
+            } else {
+                target
+            };
+        }
+        let noise: Vec<f64> = self
+            .definition
+            .signals
+            .iter()
+            .map(|s| s.noise * rng.noise())
+            .collect();
+        self.outputs(&noise)
+    }
+
+    fn resume(&mut self, outputs: &SimulationValues) {
+        for (signal, value) in self.definition.signals.iter().zip(&mut self.values) {
+            if let Some(output) = outputs.get(&signal.name) {
+                *value = *output;
+            }
+        }
+    }
+
+    /// The noise-free value of each signal
+    fn internal_state(&self) -> SimulationValues {
+        self.definition
+            .signals
+            .iter()
+            .zip(&self.values)
+            .map(|(signal, value)| (signal.name.clone(), *value))
+            .collect()
+    }
+
+    fn restore_internal_state(&mut self, state: &SimulationValues) {
+        self.resume(state);
+    }
+
+    /// Base value, noise and time constant of each signal, keyed like
+    /// "flow.time_constant"
+    fn parameters(&self) -> SimulationValues {
+        self.definition
+            .signals
+            .iter()
+            .flat_map(|s| {
+                [
+                    (format!("{}.base", s.name), s.base),
+                    (format!("{}.noise", s.name), s.noise),
+                    (format!("{}.time_constant", s.name), s.time_constant),
+                ]
+            })
+            .collect()
+    }
+
+    fn set_parameter(&mut self, name: &str, value: f64) -> Result<(), String> {
+        let (signal, parameter) = name
+            .rsplit_once('.')
+            .ok_or_else(|| unknown_parameter(name))?;
+        let signal = self
+            .definition
+            .signals
+            .iter_mut()
+            .find(|s| s.name == signal)
+            .ok_or_else(|| unknown_parameter(name))?;
+        match parameter {
+            "base" => signal.base = value,
+            "noise" | "time_constant" if value < 0.0 => {
+                return Err(format!("Parameter '{}' must not be negative", name));
+            }
+            "noise" => signal.noise = value,
+            "time_constant" => signal.time_constant = value,
+            _ => return Err(unknown_parameter(name)),
+        }
+        Ok(())
+    }
+
+    fn unit(&self, output: &str) -> Option<&str> {
+        self.definition
+            .signals
+            .iter()
+            .find(|s| s.name == output)?
+            .unit
+            .as_deref()
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl DigitalTwin {
+    /// Replace the configured models with a `SimulationProfile` given as
+    /// JSON, as in the `simulation` section of a configuration; the
+    /// simulation restarts
+    pub fn load_simulation_profile(&mut self, profile_json: &str) -> Result<(), TwinError> {
+        let profile = serde_json::from_str(profile_json)
+            .map_err(|e| TwinError::parse(format!("Invalid simulation profile: {}", e)))?;
+        self.set_simulation_profile(profile)
+    }
+}
+
+impl DigitalTwin {
+    /// Rust-side `load_simulation_profile`
+    pub fn set_simulation_profile(&mut self, profile: SimulationProfile) -> Result<(), TwinError> {
+        profile.validate()?;
+        let previous = std::mem::replace(&mut self.profile, profile);
+        let mut registry = self.builtin_models();
+        for (key, value) in &self.profile.parameters {
+            if let Err(e) = registry.set_parameter(key, *value) {
+                self.profile = previous;
+                return Err(e);
+            }
+        }
+        self.simulation = registry;
+        self.tick_count = 0;
+        Ok(())
+    }
+
+    pub fn simulation_profile(&self) -> &SimulationProfile {
+        &self.profile
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PUMP: &str = r#"{
+        "id": "P-1", "asset_type": "Pump", "nameplate": [],
+        "simulation": {"models": [{"name": "pump", "signals": [
+            {"name": "flow", "unit": "m3/h", "depends_on": {"motor.rpm": 0.1},
+             "max": 120, "time_constant": 2},
+            {"name": "pressure", "unit": "bar", "base": 1,
+             "depends_on": {"pump.flow": 0.05}, "noise": 0.01}
+        ]}]}
+    }"#;
+
+    #[test]
+    fn test_profile_from_config() {
+        let mut twin = DigitalTwin::new(PUMP).unwrap();
+        assert_eq!(
+            twin.list_simulation_models(),
+            "motor, thermal, vibration, wear, pump"
+        );
+        assert_eq!(twin.simulation_values()["pump.pressure"], 1.0);
+        for _ in 0..60 {
+            twin.advance_simulation();
+        }
+        let values = twin.simulation_values();
+        // 1440 rpm would give 144 m3/h, beyond the range
+        assert!(values["pump.flow"] <= 120.0 && values["pump.flow"] > 119.9);
+        assert!((values["pump.pressure"] - 7.0).abs() < 0.02);
+        let live = twin.live_values();
+        assert_eq!(live["pump.flow"].unit.as_deref(), Some("m3/h"));
+    }
+
+    #[test]
+    fn test_profile_without_builtin_models() {
+        let mut twin = DigitalTwin::new(PUMP).unwrap();
+        twin.load_simulation_profile(
+            r#"{"builtin": false, "models": [{"name": "belt", "signals": [
+                {"name": "speed", "base": 1.5, "initial": 0, "time_constant": 1},
+                {"name": "load", "depends_on": {"load": 40.0}}
+            ]}]}"#,
+        )
+        .unwrap();
+        assert_eq!(twin.list_simulation_models(), "belt");
+        twin.set_load(0.5).unwrap();
+        twin.advance_simulation();
+        let values = twin.simulation_values();
+        assert!((values["belt.speed"] - 1.5 * (1.0 - (-1f64).exp())).abs() < 1e-12);
+        assert_eq!(values["belt.load"], 20.0);
+        assert!(!values.contains_key("motor.rpm"));
+    }
+
+    #[test]
+    fn test_invalid_profiles() {
+        let mut twin = DigitalTwin::new(PUMP).unwrap();
+        let mut error = |profile: &str| twin.load_simulation_profile(profile).unwrap_err().code();
+        assert_eq!(
+            error(r#"{"models": [{"name": "x", "signal": []}]}"#),
+            "PARSE_ERROR"
+        );
+        assert_eq!(
+            error(r#"{"models": [{"name": "motor", "signals": []}]}"#),
+            "VALIDATION_FAILED"
+        );
+        assert_eq!(
+            error(r#"{"models": [{"name": "x", "signals": [{"name": "a", "min": 2, "max": 1}]}]}"#),
+            "VALIDATION_FAILED"
+        );
+        assert_eq!(
+            error(
+                r#"{"models": [{"name": "x", "signals": [
+                    {"name": "a", "depends_on": {"x.b": 1}}]}]}"#
+            ),
+            "VALIDATION_FAILED"
+        );
+        assert!(DigitalTwin::new(
+            r#"{"id": "P-1", "asset_type": "Pump", "nameplate": [], "simulation": {"models": 1}}"#
+        )
+        .is_err());
+        assert_eq!(twin.simulation_profile().models[0].name, "pump");
+    }
+}
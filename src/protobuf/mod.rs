@@ -0,0 +1,777 @@
+// --- Protobuf ---
+// Binary snapshots following proto/snap_to_twin.proto, for the gRPC-based
+// shop-floor services. The messages mirror the model one to one; only the
+// enumerations change shape (their AAS names become strings).
+
+use prost::Message;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    enum_name, AdministrativeInformation, AnnotatedRelationshipElement, AssetAdministrationShell,
+    AssetInformation, BasicEventElement, Blob, Capability, ConceptDescription,
+    DataSpecificationIec61360, DigitalTwin, ElementKind, EmbeddedDataSpecification, Entity,
+    Extension, File, Key, LangString, MultiLanguageProperty, Operation, OperationVariable,
+    Property, Qualifier, Range, Reference, ReferenceElement, RelationshipElement, Resource,
+    SpecificAssetId, Submodel, SubmodelElement, SubmodelElementCollection, SubmodelElementList,
+    TwinError, TwinState,
+};
+
+pub mod pb;
+
+use pb::submodel_element::Kind;
+
+/// Encode twin state as a `TwinSnapshot` message
+pub fn state_to_protobuf(state: &TwinState) -> Vec<u8> {
+    pb::TwinSnapshot {
+        shell: Some(state.shell.to_proto()),
+        rpm: state.rpm,
+        tick_count: state.tick_count,
+        simulation: state.simulation.clone(),
+    }
+    .encode_to_vec()
+}
+
+/// Decode a `TwinSnapshot` message
+pub fn state_from_protobuf(bytes: &[u8]) -> Result<TwinState, String> {
+    let snapshot =
+        pb::TwinSnapshot::decode(bytes).map_err(|e| format!("Invalid twin protobuf: {}", e))?;
+    Ok(TwinState {
+        shell: required(snapshot.shell, "TwinSnapshot.shell")?,
+        rpm: snapshot.rpm,
+        tick_count: snapshot.tick_count,
+        simulation: snapshot.simulation,
+    })
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl DigitalTwin {
+    /// Encode the twin (model and simulation state) as a protobuf `TwinSnapshot`
+    pub fn to_protobuf(&self) -> Vec<u8> {
+        state_to_protobuf(&self.state())
+    }
+
+    /// Recreate a twin from a protobuf `TwinSnapshot`
+    pub fn from_protobuf(bytes: &[u8]) -> Result<DigitalTwin, TwinError> {
+        DigitalTwin::from_state(state_from_protobuf(bytes).map_err(TwinError::parse)?)
+    }
+}
+
+trait ToProto {
+    type Message;
+    fn to_proto(&self) -> Self::Message;
+}
+
+trait FromProto: Sized {
+    type Message;
+    fn from_proto(message: Self::Message) -> Result<Self, String>;
+}
+
+fn list<T: ToProto>(items: &[T]) -> Vec<T::Message> {
+    items.iter().map(ToProto::to_proto).collect()
+}
+
+fn opt<T: ToProto>(item: &Option<T>) -> Option<T::Message> {
+    item.as_ref().map(ToProto::to_proto)
+}
+
+fn from_list<T: FromProto>(items: Vec<T::Message>) -> Result<Vec<T>, String> {
+    items.into_iter().map(T::from_proto).collect()
+}
+
+fn from_opt<T: FromProto>(message: Option<T::Message>) -> Result<Option<T>, String> {
+    message.map(T::from_proto).transpose()
+}
+
+fn required<T: FromProto>(message: Option<T::Message>, field: &str) -> Result<T, String> {
+    T::from_proto(message.ok_or_else(|| format!("{} is required", field))?)
+}
+
+fn opt_name<T: Serialize>(value: &Option<T>) -> Option<String> {
+    value.as_ref().map(enum_name)
+}
+
+/// An enumeration from its AAS name
+fn parse_enum<T: DeserializeOwned>(name: &str, field: &str) -> Result<T, String> {
+    serde_json::from_value(serde_json::Value::String(name.to_string()))
+        .map_err(|_| format!("{} has invalid value '{}'", field, name))
+}
+
+fn opt_enum<T: DeserializeOwned>(name: Option<String>, field: &str) -> Result<Option<T>, String> {
+    name.map(|n| parse_enum(&n, field)).transpose()
+}
+
+impl ToProto for LangString {
+    type Message = pb::LangString;
+    fn to_proto(&self) -> pb::LangString {
+        pb::LangString {
+            language: self.language.clone(),
+            text: self.text.clone(),
+        }
+    }
+}
+
+impl FromProto for LangString {
+    type Message = pb::LangString;
+    fn from_proto(m: pb::LangString) -> Result<Self, String> {
+        Ok(LangString {
+            language: m.language,
+            text: m.text,
+        })
+    }
+}
+
+impl ToProto for Key {
+    type Message = pb::Key;
+    fn to_proto(&self) -> pb::Key {
+        pb::Key {
+            r#type: enum_name(&self.key_type),
+            value: self.value.clone(),
+        }
+    }
+}
+
+impl FromProto for Key {
+    type Message = pb::Key;
+    fn from_proto(m: pb::Key) -> Result<Self, String> {
+        Ok(Key {
+            key_type: parse_enum(&m.r#type, "Key.type")?,
+            value: m.value,
+        })
+    }
+}
+
+impl ToProto for Reference {
+    type Message = pb::Reference;
+    fn to_proto(&self) -> pb::Reference {
+        pb::Reference {
+            r#type: enum_name(&self.reference_type),
+            referred_semantic_id: self
+                .referred_semantic_id
+                .as_ref()
+                .map(|r| Box::new(r.to_proto())),
+            keys: list(&self.keys),
+        }
+    }
+}
+
+impl FromProto for Reference {
+    type Message = pb::Reference;
+    fn from_proto(m: pb::Reference) -> Result<Self, String> {
+        Ok(Reference {
+            reference_type: parse_enum(&m.r#type, "Reference.type")?,
+            referred_semantic_id: m
+                .referred_semantic_id
+                .map(|r| Reference::from_proto(*r).map(Box::new))
+                .transpose()?,
+            keys: from_list(m.keys)?,
+        })
+    }
+}
+
+impl ToProto for Qualifier {
+    type Message = pb::Qualifier;
+    fn to_proto(&self) -> pb::Qualifier {
+        pb::Qualifier {
+            r#type: self.qualifier_type.clone(),
+            value_type: enum_name(&self.value_type),
+            value: self.value.clone(),
+            value_id: opt(&self.value_id),
+            kind: enum_name(&self.kind),
+            semantic_id: opt(&self.semantic_id),
+        }
+    }
+}
+
+impl FromProto for Qualifier {
+    type Message = pb::Qualifier;
+    fn from_proto(m: pb::Qualifier) -> Result<Self, String> {
+        Ok(Qualifier {
+            qualifier_type: m.r#type,
+            value_type: parse_enum(&m.value_type, "Qualifier.value_type")?,
+            value: m.value,
+            value_id: from_opt(m.value_id)?,
+            kind: parse_enum(&m.kind, "Qualifier.kind")?,
+            semantic_id: from_opt(m.semantic_id)?,
+        })
+    }
+}
+
+impl ToProto for Extension {
+    type Message = pb::Extension;
+    fn to_proto(&self) -> pb::Extension {
+        pb::Extension {
+            name: self.name.clone(),
+            value_type: opt_name(&self.value_type),
+            value: self.value.clone(),
+            refers_to: list(&self.refers_to),
+            semantic_id: opt(&self.semantic_id),
+        }
+    }
+}
+
+impl FromProto for Extension {
+    type Message = pb::Extension;
+    fn from_proto(m: pb::Extension) -> Result<Self, String> {
+        Ok(Extension {
+            name: m.name,
+            value_type: opt_enum(m.value_type, "Extension.value_type")?,
+            value: m.value,
+            refers_to: from_list(m.refers_to)?,
+            semantic_id: from_opt(m.semantic_id)?,
+        })
+    }
+}
+
+impl ToProto for AdministrativeInformation {
+    type Message = pb::AdministrativeInformation;
+    fn to_proto(&self) -> pb::AdministrativeInformation {
+        pb::AdministrativeInformation {
+            version: self.version.clone(),
+            revision: self.revision.clone(),
+            creator: opt(&self.creator),
+            template_id: self.template_id.clone(),
+        }
+    }
+}
+
+impl FromProto for AdministrativeInformation {
+    type Message = pb::AdministrativeInformation;
+    fn from_proto(m: pb::AdministrativeInformation) -> Result<Self, String> {
+        Ok(AdministrativeInformation {
+            version: m.version,
+            revision: m.revision,
+            creator: from_opt(m.creator)?,
+            template_id: m.template_id,
+        })
+    }
+}
+
+impl ToProto for SpecificAssetId {
+    type Message = pb::SpecificAssetId;
+    fn to_proto(&self) -> pb::SpecificAssetId {
+        pb::SpecificAssetId {
+            name: self.name.clone(),
+            value: self.value.clone(),
+            external_subject_id: opt(&self.external_subject_id),
+            semantic_id: opt(&self.semantic_id),
+        }
+    }
+}
+
+impl FromProto for SpecificAssetId {
+    type Message = pb::SpecificAssetId;
+    fn from_proto(m: pb::SpecificAssetId) -> Result<Self, String> {
+        Ok(SpecificAssetId {
+            name: m.name,
+            value: m.value,
+            external_subject_id: from_opt(m.external_subject_id)?,
+            semantic_id: from_opt(m.semantic_id)?,
+        })
+    }
+}
+
+impl ToProto for Resource {
+    type Message = pb::Resource;
+    fn to_proto(&self) -> pb::Resource {
+        pb::Resource {
+            path: self.path.clone(),
+            content_type: self.content_type.clone(),
+        }
+    }
+}
+
+impl FromProto for Resource {
+    type Message = pb::Resource;
+    fn from_proto(m: pb::Resource) -> Result<Self, String> {
+        Ok(Resource {
+            path: m.path,
+            content_type: m.content_type,
+        })
+    }
+}
+
+impl ToProto for AssetInformation {
+    type Message = pb::AssetInformation;
+    fn to_proto(&self) -> pb::AssetInformation {
+        pb::AssetInformation {
+            asset_kind: enum_name(&self.asset_kind),
+            global_asset_id: self.global_asset_id.clone(),
+            specific_asset_ids: list(&self.specific_asset_ids),
+            asset_type: self.asset_type.clone(),
+            default_thumbnail: opt(&self.default_thumbnail),
+        }
+    }
+}
+
+impl FromProto for AssetInformation {
+    type Message = pb::AssetInformation;
+    fn from_proto(m: pb::AssetInformation) -> Result<Self, String> {
+        Ok(AssetInformation {
+            asset_kind: parse_enum(&m.asset_kind, "AssetInformation.asset_kind")?,
+            global_asset_id: m.global_asset_id,
+            specific_asset_ids: from_list(m.specific_asset_ids)?,
+            asset_type: m.asset_type,
+            default_thumbnail: from_opt(m.default_thumbnail)?,
+        })
+    }
+}
+
+impl ToProto for DataSpecificationIec61360 {
+    type Message = pb::DataSpecificationIec61360;
+    fn to_proto(&self) -> pb::DataSpecificationIec61360 {
+        pb::DataSpecificationIec61360 {
+            preferred_name: list(&self.preferred_name),
+            short_name: list(&self.short_name),
+            unit: self.unit.clone(),
+            unit_id: opt(&self.unit_id),
+            symbol: self.symbol.clone(),
+            data_type: opt_name(&self.data_type),
+            definition: list(&self.definition),
+            value_format: self.value_format.clone(),
+        }
+    }
+}
+
+impl FromProto for DataSpecificationIec61360 {
+    type Message = pb::DataSpecificationIec61360;
+    fn from_proto(m: pb::DataSpecificationIec61360) -> Result<Self, String> {
+        Ok(DataSpecificationIec61360 {
+            preferred_name: from_list(m.preferred_name)?,
+            short_name: from_list(m.short_name)?,
+            unit: m.unit,
+            unit_id: from_opt(m.unit_id)?,
+            symbol: m.symbol,
+            data_type: opt_enum(m.data_type, "DataSpecificationIec61360.data_type")?,
+            definition: from_list(m.definition)?,
+            value_format: m.value_format,
+        })
+    }
+}
+
+impl ToProto for EmbeddedDataSpecification {
+    type Message = pb::EmbeddedDataSpecification;
+    fn to_proto(&self) -> pb::EmbeddedDataSpecification {
+        pb::EmbeddedDataSpecification {
+            data_specification: Some(self.data_specification.to_proto()),
+            data_specification_content: Some(self.data_specification_content.to_proto()),
+        }
+    }
+}
+
+impl FromProto for EmbeddedDataSpecification {
+    type Message = pb::EmbeddedDataSpecification;
+    fn from_proto(m: pb::EmbeddedDataSpecification) -> Result<Self, String> {
+        Ok(EmbeddedDataSpecification {
+            data_specification: required(
+                m.data_specification,
+                "EmbeddedDataSpecification.data_specification",
+            )?,
+            data_specification_content: required(
+                m.data_specification_content,
+                "EmbeddedDataSpecification.data_specification_content",
+            )?,
+        })
+    }
+}
+
+impl ToProto for ConceptDescription {
+    type Message = pb::ConceptDescription;
+    fn to_proto(&self) -> pb::ConceptDescription {
+        pb::ConceptDescription {
+            id: self.id.clone(),
+            id_short: self.id_short.clone(),
+            administration: opt(&self.administration),
+            is_case_of: list(&self.is_case_of),
+            embedded_data_specifications: list(&self.embedded_data_specifications),
+        }
+    }
+}
+
+impl FromProto for ConceptDescription {
+    type Message = pb::ConceptDescription;
+    fn from_proto(m: pb::ConceptDescription) -> Result<Self, String> {
+        Ok(ConceptDescription {
+            id: m.id,
+            id_short: m.id_short,
+            administration: from_opt(m.administration)?,
+            is_case_of: from_list(m.is_case_of)?,
+            embedded_data_specifications: from_list(m.embedded_data_specifications)?,
+        })
+    }
+}
+
+impl ToProto for OperationVariable {
+    type Message = pb::OperationVariable;
+    fn to_proto(&self) -> pb::OperationVariable {
+        pb::OperationVariable {
+            value: Some(self.value.to_proto()),
+        }
+    }
+}
+
+impl FromProto for OperationVariable {
+    type Message = pb::OperationVariable;
+    fn from_proto(m: pb::OperationVariable) -> Result<Self, String> {
+        Ok(OperationVariable {
+            value: required(m.value, "OperationVariable.value")?,
+        })
+    }
+}
+
+impl ToProto for ElementKind {
+    type Message = Kind;
+    fn to_proto(&self) -> Kind {
+        match self {
+            ElementKind::Property(p) => Kind::Property(pb::Property {
+                value: p.value.clone(),
+                unit: p.unit.clone(),
+                value_type: opt_name(&p.value_type),
+            }),
+            ElementKind::MultiLanguageProperty(mlp) => {
+                Kind::MultiLanguageProperty(pb::MultiLanguageProperty {
+                    value: list(&mlp.value),
+                })
+            }
+            ElementKind::Range(r) => Kind::Range(pb::Range {
+                value_type: opt_name(&r.value_type),
+                min: r.min.clone(),
+                max: r.max.clone(),
+                unit: r.unit.clone(),
+            }),
+            ElementKind::File(f) => Kind::File(pb::File {
+                value: f.value.clone(),
+                content_type: f.content_type.clone(),
+            }),
+            ElementKind::Blob(b) => Kind::Blob(pb::Blob {
+                value: b.value.clone(),
+                content_type: b.content_type.clone(),
+            }),
+            ElementKind::ReferenceElement(r) => Kind::ReferenceElement(pb::ReferenceElement {
+                value: opt(&r.value),
+            }),
+            ElementKind::RelationshipElement(r) => {
+                Kind::RelationshipElement(pb::RelationshipElement {
+                    first: opt(&r.first),
+                    second: opt(&r.second),
+                })
+            }
+            ElementKind::AnnotatedRelationshipElement(r) => {
+                Kind::AnnotatedRelationshipElement(pb::AnnotatedRelationshipElement {
+                    first: opt(&r.first),
+                    second: opt(&r.second),
+                    annotations: list(&r.annotations),
+                })
+            }
+            ElementKind::Entity(e) => Kind::Entity(pb::Entity {
+                entity_type: enum_name(&e.entity_type),
+                global_asset_id: e.global_asset_id.clone(),
+                statements: list(&e.statements),
+            }),
+            ElementKind::Operation(op) => Kind::Operation(pb::Operation {
+                input_variables: list(&op.input_variables),
+                output_variables: list(&op.output_variables),
+                inoutput_variables: list(&op.inoutput_variables),
+            }),
+            ElementKind::Capability(_) => Kind::Capability(pb::Capability {}),
+            ElementKind::BasicEventElement(e) => Kind::BasicEventElement(pb::BasicEventElement {
+                observed: Some(e.observed.to_proto()),
+                direction: enum_name(&e.direction),
+                state: enum_name(&e.state),
+                message_topic: e.message_topic.clone(),
+                message_broker: opt(&e.message_broker),
+                last_update: e.last_update.clone(),
+                min_interval: e.min_interval.clone(),
+                max_interval: e.max_interval.clone(),
+            }),
+            ElementKind::SubmodelElementCollection(c) => {
+                Kind::SubmodelElementCollection(pb::SubmodelElementCollection {
+                    value: list(&c.value),
+                })
+            }
+            ElementKind::SubmodelElementList(l) => {
+                Kind::SubmodelElementList(pb::SubmodelElementList {
+                    order_relevant: l.order_relevant,
+                    type_value_list_element: enum_name(&l.type_value_list_element),
+                    semantic_id_list_element: opt(&l.semantic_id_list_element),
+                    value_type_list_element: opt_name(&l.value_type_list_element),
+                    value: list(&l.value),
+                })
+            }
+        }
+    }
+}
+
+impl FromProto for ElementKind {
+    type Message = Kind;
+    fn from_proto(kind: Kind) -> Result<Self, String> {
+        Ok(match kind {
+            Kind::Property(p) => ElementKind::Property(Property {
+                value: p.value,
+                unit: p.unit,
+                value_type: opt_enum(p.value_type, "Property.value_type")?,
+            }),
+            Kind::MultiLanguageProperty(mlp) => {
+                ElementKind::MultiLanguageProperty(MultiLanguageProperty {
+                    value: from_list(mlp.value)?,
+                })
+            }
+            Kind::Range(r) => ElementKind::Range(Range {
+                value_type: opt_enum(r.value_type, "Range.value_type")?,
+                min: r.min,
+                max: r.max,
+                unit: r.unit,
+            }),
+            Kind::File(f) => ElementKind::File(File {
+                value: f.value,
+                content_type: f.content_type,
+            }),
+            Kind::Blob(b) => ElementKind::Blob(Blob {
+                value: b.value,
+                content_type: b.content_type,
+            }),
+            Kind::ReferenceElement(r) => ElementKind::ReferenceElement(ReferenceElement {
+                value: from_opt(r.value)?,
+            }),
+            Kind::RelationshipElement(r) => ElementKind::RelationshipElement(RelationshipElement {
+                first: from_opt(r.first)?,
+                second: from_opt(r.second)?,
+            }),
+            Kind::AnnotatedRelationshipElement(r) => {
+                ElementKind::AnnotatedRelationshipElement(AnnotatedRelationshipElement {
+                    first: from_opt(r.first)?,
+                    second: from_opt(r.second)?,
+                    annotations: from_list(r.annotations)?,
+                })
+            }
+            Kind::Entity(e) => ElementKind::Entity(Entity {
+                entity_type: parse_enum(&e.entity_type, "Entity.entity_type")?,
+                global_asset_id: e.global_asset_id,
+                statements: from_list(e.statements)?,
+            }),
+            Kind::Operation(op) => ElementKind::Operation(Operation {
+                input_variables: from_list(op.input_variables)?,
+                output_variables: from_list(op.output_variables)?,
+                inoutput_variables: from_list(op.inoutput_variables)?,
+            }),
+            Kind::Capability(_) => ElementKind::Capability(Capability {}),
+            Kind::BasicEventElement(e) => ElementKind::BasicEventElement(BasicEventElement {
+                observed: required(e.observed, "BasicEventElement.observed")?,
+                direction: parse_enum(&e.direction, "BasicEventElement.direction")?,
+                state: parse_enum(&e.state, "BasicEventElement.state")?,
+                message_topic: e.message_topic,
+                message_broker: from_opt(e.message_broker)?,
+                last_update: e.last_update,
+                min_interval: e.min_interval,
+                max_interval: e.max_interval,
+            }),
+            Kind::SubmodelElementCollection(c) => {
+                ElementKind::SubmodelElementCollection(SubmodelElementCollection {
+                    value: from_list(c.value)?,
+                })
+            }
+            Kind::SubmodelElementList(l) => ElementKind::SubmodelElementList(SubmodelElementList {
+                order_relevant: l.order_relevant,
+                type_value_list_element: parse_enum(
+                    &l.type_value_list_element,
+                    "SubmodelElementList.type_value_list_element",
+                )?,
+                semantic_id_list_element: from_opt(l.semantic_id_list_element)?,
+                value_type_list_element: opt_enum(
+                    l.value_type_list_element,
+                    "SubmodelElementList.value_type_list_element",
+                )?,
+                value: from_list(l.value)?,
+            }),
+        })
+    }
+}
+
+impl ToProto for SubmodelElement {
+    type Message = pb::SubmodelElement;
+    fn to_proto(&self) -> pb::SubmodelElement {
+        pb::SubmodelElement {
+            id_short: self.id_short.clone(),
+            display_name: list(&self.display_name),
+            description: list(&self.description),
+            category: opt_name(&self.category),
+            semantic_id: opt(&self.semantic_id),
+            qualifiers: list(&self.qualifiers),
+            extensions: list(&self.extensions),
+            embedded_data_specifications: list(&self.embedded_data_specifications),
+            kind: Some(self.kind.to_proto()),
+        }
+    }
+}
+
+impl FromProto for SubmodelElement {
+    type Message = pb::SubmodelElement;
+    fn from_proto(m: pb::SubmodelElement) -> Result<Self, String> {
+        let kind = required(m.kind, "SubmodelElement.kind")
+            .map_err(|e| format!("element '{}': {}", m.id_short, e))?;
+        Ok(SubmodelElement {
+            id_short: m.id_short,
+            display_name: from_list(m.display_name)?,
+            description: from_list(m.description)?,
+            category: opt_enum(m.category, "SubmodelElement.category")?,
+            semantic_id: from_opt(m.semantic_id)?,
+            qualifiers: from_list(m.qualifiers)?,
+            extensions: from_list(m.extensions)?,
+            embedded_data_specifications: from_list(m.embedded_data_specifications)?,
+            kind,
+        })
+    }
+}
+
+impl ToProto for Submodel {
+    type Message = pb::Submodel;
+    fn to_proto(&self) -> pb::Submodel {
+        pb::Submodel {
+            id: self.id.clone(),
+            id_short: self.id_short.clone(),
+            display_name: list(&self.display_name),
+            description: list(&self.description),
+            administration: opt(&self.administration),
+            semantic_id: opt(&self.semantic_id),
+            qualifiers: list(&self.qualifiers),
+            extensions: list(&self.extensions),
+            kind: enum_name(&self.kind),
+            submodel_elements: list(&self.submodel_elements),
+        }
+    }
+}
+
+impl FromProto for Submodel {
+    type Message = pb::Submodel;
+    fn from_proto(m: pb::Submodel) -> Result<Self, String> {
+        Ok(Submodel {
+            id: m.id,
+            id_short: m.id_short,
+            display_name: from_list(m.display_name)?,
+            description: from_list(m.description)?,
+            administration: from_opt(m.administration)?,
+            semantic_id: from_opt(m.semantic_id)?,
+            qualifiers: from_list(m.qualifiers)?,
+            extensions: from_list(m.extensions)?,
+            kind: parse_enum(&m.kind, "Submodel.kind")?,
+            submodel_elements: from_list(m.submodel_elements)?,
+        })
+    }
+}
+
+impl ToProto for AssetAdministrationShell {
+    type Message = pb::AssetAdministrationShell;
+    fn to_proto(&self) -> pb::AssetAdministrationShell {
+        pb::AssetAdministrationShell {
+            id: self.id.clone(),
+            display_name: list(&self.display_name),
+            description: list(&self.description),
+            asset_information: Some(self.asset_information.to_proto()),
+            administration: opt(&self.administration),
+            nameplate: list(&self.nameplate),
+            submodels: list(&self.submodels),
+            extensions: list(&self.extensions),
+            concept_descriptions: self
+                .concept_descriptions
+                .values()
+                .map(ToProto::to_proto)
+                .collect(),
+        }
+    }
+}
+
+impl FromProto for AssetAdministrationShell {
+    type Message = pb::AssetAdministrationShell;
+    fn from_proto(m: pb::AssetAdministrationShell) -> Result<Self, String> {
+        Ok(AssetAdministrationShell {
+            id: m.id,
+            display_name: from_list(m.display_name)?,
+            description: from_list(m.description)?,
+            asset_information: required(
+                m.asset_information,
+                "AssetAdministrationShell.asset_information",
+            )?,
+            administration: from_opt(m.administration)?,
+            nameplate: from_list(m.nameplate)?,
+            submodels: from_list(m.submodels)?,
+            extensions: from_list(m.extensions)?,
+            concept_descriptions: from_list::<ConceptDescription>(m.concept_descriptions)?
+                .into_iter()
+                .map(|cd| (cd.id.clone(), cd))
+                .collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_protobuf_round_trip() {
+        let json = r#"{
+            "id": "urn:example:aas:motor",
+            "asset_type": "Siemens 1LE1",
+            "nameplate": [
+                {"id_short": "Voltage", "value": "400", "unit": "V", "value_type": "xs:double",
+                 "category": "PARAMETER", "semantic_id": "0173-1#02-AAE416#004"},
+                {"id_short": "Markings", "model_type": "SubmodelElementList",
+                 "type_value_list_element": "Property", "value": [{"value": "CE", "unit": null}]},
+                {"id_short": "Overheated", "model_type": "BasicEventElement",
+                 "observed": {"type": "ModelReference", "keys": [{"type": "Property", "value": "Voltage"}]},
+                 "direction": "output", "state": "on"}
+            ]
+        }"#;
+        let mut twin = DigitalTwin::new(json).unwrap();
+        twin.tick_simulation();
+
+        let bytes = state_to_protobuf(&twin.state());
+        let restored = DigitalTwin::from_state(state_from_protobuf(&bytes).unwrap()).unwrap();
+        assert_eq!(restored.get_aas_json(), twin.get_aas_json());
+        assert_eq!(restored.tick_count, 1);
+
+        let snapshot = pb::TwinSnapshot::decode(bytes.as_slice()).unwrap();
+        let voltage = &snapshot.shell.unwrap().nameplate[0];
+        assert_eq!(voltage.category.as_deref(), Some("PARAMETER"));
+        assert!(
+            matches!(&voltage.kind, Some(Kind::Property(p)) if p.value_type.as_deref() == Some("xs:double"))
+        );
+    }
+
+    #[test]
+    fn test_protobuf_errors() {
+        let missing_kind = pb::TwinSnapshot {
+            shell: Some(pb::AssetAdministrationShell {
+                id: "M-1".to_string(),
+                asset_information: Some(pb::AssetInformation {
+                    asset_kind: "Instance".to_string(),
+                    ..Default::default()
+                }),
+                nameplate: vec![pb::SubmodelElement {
+                    id_short: "Voltage".to_string(),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let err = state_from_protobuf(&missing_kind.encode_to_vec()).unwrap_err();
+        assert_eq!(err, "element 'Voltage': SubmodelElement.kind is required");
+
+        let mut bad_kind = missing_kind.clone();
+        bad_kind
+            .shell
+            .as_mut()
+            .unwrap()
+            .asset_information
+            .as_mut()
+            .unwrap()
+            .asset_kind = "Robot".to_string();
+        let err = state_from_protobuf(&bad_kind.encode_to_vec()).unwrap_err();
+        assert_eq!(err, "AssetInformation.asset_kind has invalid value 'Robot'");
+
+        assert!(state_from_protobuf(&[0x0a, 0xff]).is_err());
+    }
+}
@@ -0,0 +1,400 @@
+// Message types for proto/snap_to_twin.proto, written out with the prost
+// derives so the build needs no protoc. Keep field tags in sync with the schema.
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct TwinSnapshot {
+    #[prost(message, optional, tag = "1")]
+    pub shell: Option<AssetAdministrationShell>,
+    #[prost(double, tag = "2")]
+    pub rpm: f64,
+    #[prost(uint32, tag = "3")]
+    pub tick_count: u32,
+    #[prost(btree_map = "string, double", tag = "4")]
+    pub simulation: ::std::collections::BTreeMap<String, f64>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct AssetAdministrationShell {
+    #[prost(string, tag = "1")]
+    pub id: String,
+    #[prost(message, repeated, tag = "2")]
+    pub display_name: Vec<LangString>,
+    #[prost(message, repeated, tag = "3")]
+    pub description: Vec<LangString>,
+    #[prost(message, optional, tag = "4")]
+    pub asset_information: Option<AssetInformation>,
+    #[prost(message, optional, tag = "5")]
+    pub administration: Option<AdministrativeInformation>,
+    #[prost(message, repeated, tag = "6")]
+    pub nameplate: Vec<SubmodelElement>,
+    #[prost(message, repeated, tag = "7")]
+    pub submodels: Vec<Submodel>,
+    #[prost(message, repeated, tag = "8")]
+    pub extensions: Vec<Extension>,
+    #[prost(message, repeated, tag = "9")]
+    pub concept_descriptions: Vec<ConceptDescription>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Submodel {
+    #[prost(string, tag = "1")]
+    pub id: String,
+    #[prost(string, tag = "2")]
+    pub id_short: String,
+    #[prost(message, repeated, tag = "3")]
+    pub display_name: Vec<LangString>,
+    #[prost(message, repeated, tag = "4")]
+    pub description: Vec<LangString>,
+    #[prost(message, optional, tag = "5")]
+    pub administration: Option<AdministrativeInformation>,
+    #[prost(message, optional, tag = "6")]
+    pub semantic_id: Option<Reference>,
+    #[prost(message, repeated, tag = "7")]
+    pub qualifiers: Vec<Qualifier>,
+    #[prost(message, repeated, tag = "8")]
+    pub extensions: Vec<Extension>,
+    #[prost(string, tag = "9")]
+    pub kind: String,
+    #[prost(message, repeated, tag = "10")]
+    pub submodel_elements: Vec<SubmodelElement>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct SubmodelElement {
+    #[prost(string, tag = "1")]
+    pub id_short: String,
+    #[prost(message, repeated, tag = "2")]
+    pub display_name: Vec<LangString>,
+    #[prost(message, repeated, tag = "3")]
+    pub description: Vec<LangString>,
+    #[prost(string, optional, tag = "4")]
+    pub category: Option<String>,
+    #[prost(message, optional, tag = "5")]
+    pub semantic_id: Option<Reference>,
+    #[prost(message, repeated, tag = "6")]
+    pub qualifiers: Vec<Qualifier>,
+    #[prost(message, repeated, tag = "7")]
+    pub extensions: Vec<Extension>,
+    #[prost(message, repeated, tag = "8")]
+    pub embedded_data_specifications: Vec<EmbeddedDataSpecification>,
+    #[prost(
+        oneof = "submodel_element::Kind",
+        tags = "10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23"
+    )]
+    pub kind: Option<submodel_element::Kind>,
+}
+
+pub mod submodel_element {
+    #[derive(Clone, PartialEq, prost::Oneof)]
+    pub enum Kind {
+        #[prost(message, tag = "10")]
+        Property(super::Property),
+        #[prost(message, tag = "11")]
+        MultiLanguageProperty(super::MultiLanguageProperty),
+        #[prost(message, tag = "12")]
+        Range(super::Range),
+        #[prost(message, tag = "13")]
+        File(super::File),
+        #[prost(message, tag = "14")]
+        Blob(super::Blob),
+        #[prost(message, tag = "15")]
+        ReferenceElement(super::ReferenceElement),
+        #[prost(message, tag = "16")]
+        RelationshipElement(super::RelationshipElement),
+        #[prost(message, tag = "17")]
+        AnnotatedRelationshipElement(super::AnnotatedRelationshipElement),
+        #[prost(message, tag = "18")]
+        Entity(super::Entity),
+        #[prost(message, tag = "19")]
+        Operation(super::Operation),
+        #[prost(message, tag = "20")]
+        Capability(super::Capability),
+        #[prost(message, tag = "21")]
+        BasicEventElement(super::BasicEventElement),
+        #[prost(message, tag = "22")]
+        SubmodelElementCollection(super::SubmodelElementCollection),
+        #[prost(message, tag = "23")]
+        SubmodelElementList(super::SubmodelElementList),
+    }
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Property {
+    #[prost(string, tag = "1")]
+    pub value: String,
+    #[prost(string, optional, tag = "2")]
+    pub unit: Option<String>,
+    #[prost(string, optional, tag = "3")]
+    pub value_type: Option<String>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct MultiLanguageProperty {
+    #[prost(message, repeated, tag = "1")]
+    pub value: Vec<LangString>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Range {
+    #[prost(string, optional, tag = "1")]
+    pub value_type: Option<String>,
+    #[prost(string, optional, tag = "2")]
+    pub min: Option<String>,
+    #[prost(string, optional, tag = "3")]
+    pub max: Option<String>,
+    #[prost(string, optional, tag = "4")]
+    pub unit: Option<String>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct File {
+    #[prost(string, optional, tag = "1")]
+    pub value: Option<String>,
+    #[prost(string, optional, tag = "2")]
+    pub content_type: Option<String>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Blob {
+    #[prost(string, optional, tag = "1")]
+    pub value: Option<String>,
+    #[prost(string, optional, tag = "2")]
+    pub content_type: Option<String>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct ReferenceElement {
+    #[prost(message, optional, tag = "1")]
+    pub value: Option<Reference>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct RelationshipElement {
+    #[prost(message, optional, tag = "1")]
+    pub first: Option<Reference>,
+    #[prost(message, optional, tag = "2")]
+    pub second: Option<Reference>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct AnnotatedRelationshipElement {
+    #[prost(message, optional, tag = "1")]
+    pub first: Option<Reference>,
+    #[prost(message, optional, tag = "2")]
+    pub second: Option<Reference>,
+    #[prost(message, repeated, tag = "3")]
+    pub annotations: Vec<SubmodelElement>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Entity {
+    #[prost(string, tag = "1")]
+    pub entity_type: String,
+    #[prost(string, optional, tag = "2")]
+    pub global_asset_id: Option<String>,
+    #[prost(message, repeated, tag = "3")]
+    pub statements: Vec<SubmodelElement>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct OperationVariable {
+    #[prost(message, optional, tag = "1")]
+    pub value: Option<SubmodelElement>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Operation {
+    #[prost(message, repeated, tag = "1")]
+    pub input_variables: Vec<OperationVariable>,
+    #[prost(message, repeated, tag = "2")]
+    pub output_variables: Vec<OperationVariable>,
+    #[prost(message, repeated, tag = "3")]
+    pub inoutput_variables: Vec<OperationVariable>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Capability {}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct BasicEventElement {
+    #[prost(message, optional, tag = "1")]
+    pub observed: Option<Reference>,
+    #[prost(string, tag = "2")]
+    pub direction: String,
+    #[prost(string, tag = "3")]
+    pub state: String,
+    #[prost(string, optional, tag = "4")]
+    pub message_topic: Option<String>,
+    #[prost(message, optional, tag = "5")]
+    pub message_broker: Option<Reference>,
+    #[prost(string, optional, tag = "6")]
+    pub last_update: Option<String>,
+    #[prost(string, optional, tag = "7")]
+    pub min_interval: Option<String>,
+    #[prost(string, optional, tag = "8")]
+    pub max_interval: Option<String>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct SubmodelElementCollection {
+    #[prost(message, repeated, tag = "1")]
+    pub value: Vec<SubmodelElement>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct SubmodelElementList {
+    #[prost(bool, tag = "1")]
+    pub order_relevant: bool,
+    #[prost(string, tag = "2")]
+    pub type_value_list_element: String,
+    #[prost(message, optional, tag = "3")]
+    pub semantic_id_list_element: Option<Reference>,
+    #[prost(string, optional, tag = "4")]
+    pub value_type_list_element: Option<String>,
+    #[prost(message, repeated, tag = "5")]
+    pub value: Vec<SubmodelElement>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct LangString {
+    #[prost(string, tag = "1")]
+    pub language: String,
+    #[prost(string, tag = "2")]
+    pub text: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Key {
+    #[prost(string, tag = "1")]
+    pub r#type: String,
+    #[prost(string, tag = "2")]
+    pub value: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Reference {
+    #[prost(string, tag = "1")]
+    pub r#type: String,
+    #[prost(message, optional, boxed, tag = "2")]
+    pub referred_semantic_id: Option<Box<Reference>>,
+    #[prost(message, repeated, tag = "3")]
+    pub keys: Vec<Key>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Qualifier {
+    #[prost(string, tag = "1")]
+    pub r#type: String,
+    #[prost(string, tag = "2")]
+    pub value_type: String,
+    #[prost(string, optional, tag = "3")]
+    pub value: Option<String>,
+    #[prost(message, optional, tag = "4")]
+    pub value_id: Option<Reference>,
+    #[prost(string, tag = "5")]
+    pub kind: String,
+    #[prost(message, optional, tag = "6")]
+    pub semantic_id: Option<Reference>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Extension {
+    #[prost(string, tag = "1")]
+    pub name: String,
+    #[prost(string, optional, tag = "2")]
+    pub value_type: Option<String>,
+    #[prost(string, optional, tag = "3")]
+    pub value: Option<String>,
+    #[prost(message, repeated, tag = "4")]
+    pub refers_to: Vec<Reference>,
+    #[prost(message, optional, tag = "5")]
+    pub semantic_id: Option<Reference>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct AdministrativeInformation {
+    #[prost(string, optional, tag = "1")]
+    pub version: Option<String>,
+    #[prost(string, optional, tag = "2")]
+    pub revision: Option<String>,
+    #[prost(message, optional, tag = "3")]
+    pub creator: Option<Reference>,
+    #[prost(string, optional, tag = "4")]
+    pub template_id: Option<String>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct AssetInformation {
+    #[prost(string, tag = "1")]
+    pub asset_kind: String,
+    #[prost(string, optional, tag = "2")]
+    pub global_asset_id: Option<String>,
+    #[prost(message, repeated, tag = "3")]
+    pub specific_asset_ids: Vec<SpecificAssetId>,
+    #[prost(string, optional, tag = "4")]
+    pub asset_type: Option<String>,
+    #[prost(message, optional, tag = "5")]
+    pub default_thumbnail: Option<Resource>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct SpecificAssetId {
+    #[prost(string, tag = "1")]
+    pub name: String,
+    #[prost(string, tag = "2")]
+    pub value: String,
+    #[prost(message, optional, tag = "3")]
+    pub external_subject_id: Option<Reference>,
+    #[prost(message, optional, tag = "4")]
+    pub semantic_id: Option<Reference>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Resource {
+    #[prost(string, tag = "1")]
+    pub path: String,
+    #[prost(string, optional, tag = "2")]
+    pub content_type: Option<String>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct ConceptDescription {
+    #[prost(string, tag = "1")]
+    pub id: String,
+    #[prost(string, optional, tag = "2")]
+    pub id_short: Option<String>,
+    #[prost(message, optional, tag = "3")]
+    pub administration: Option<AdministrativeInformation>,
+    #[prost(message, repeated, tag = "4")]
+    pub is_case_of: Vec<Reference>,
+    #[prost(message, repeated, tag = "5")]
+    pub embedded_data_specifications: Vec<EmbeddedDataSpecification>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct EmbeddedDataSpecification {
+    #[prost(message, optional, tag = "1")]
+    pub data_specification: Option<Reference>,
+    #[prost(message, optional, tag = "2")]
+    pub data_specification_content: Option<DataSpecificationIec61360>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct DataSpecificationIec61360 {
+    #[prost(message, repeated, tag = "1")]
+    pub preferred_name: Vec<LangString>,
+    #[prost(message, repeated, tag = "2")]
+    pub short_name: Vec<LangString>,
+    #[prost(string, optional, tag = "3")]
+    pub unit: Option<String>,
+    #[prost(message, optional, tag = "4")]
+    pub unit_id: Option<Reference>,
+    #[prost(string, optional, tag = "5")]
+    pub symbol: Option<String>,
+    #[prost(string, optional, tag = "6")]
+    pub data_type: Option<String>,
+    #[prost(message, repeated, tag = "7")]
+    pub definition: Vec<LangString>,
+    #[prost(string, optional, tag = "8")]
+    pub value_format: Option<String>,
+}
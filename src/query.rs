@@ -0,0 +1,480 @@
+// --- Queries ---
+// A JSONPath-like subset over the element tree, for dashboards that pull a
+// group of values in one call:
+//
+//   TechnicalData.*                  every element directly in a submodel
+//   Markings[*].MarkingName          a field of every list item
+//   **[?(@.unit == 'V')]             all elements in volts, at any depth
+//   $..[?(@.value > 100)]            the same in JSONPath spelling
+//
+// Paths start at the shell like idShortPaths do: a first step that names no
+// submodel is looked up in the nameplate. Filters compare `value`, `unit`,
+// `semanticId` or `idShort` with a literal; numbers compare numerically, and
+// a bare `[?(@.unit)]` tests that the field is set.
+
+use serde::Serialize;
+#[cfg(feature = "wasm")]
+use tsify::Tsify;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+#[cfg(feature = "wasm")]
+use crate::to_js;
+use crate::{join_path, DigitalTwin, ElementKind, SubmodelElement, TwinError};
+
+/// One element matched by `query`
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+pub struct QueryMatch {
+    pub path: String,
+    /// Typed like `get_property` values
+    #[cfg_attr(feature = "wasm", tsify(type = "ElementValue"))]
+    pub value: serde_json::Value,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Select {
+    Name(String),
+    /// `*`: every child
+    Any,
+    /// `**` or `..`: the node itself and all its descendants
+    Descend,
+    Index(usize),
+    /// `[*]`: every item of a list
+    AnyIndex,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Op {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Filter {
+    field: String,
+    /// None tests that the field is set
+    test: Option<(Op, String)>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Step {
+    select: Select,
+    filters: Vec<Filter>,
+}
+
+/// A position in the tree: the shell, a submodel (or the nameplate) or an element
+#[derive(Clone)]
+enum Node<'a> {
+    Root,
+    Elements {
+        name: &'a str,
+        path: String,
+        elements: &'a [SubmodelElement],
+    },
+    Element {
+        path: String,
+        index: Option<usize>,
+        element: &'a SubmodelElement,
+    },
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl DigitalTwin {
+    /// Query elements with a JSONPath-like expression ("TechnicalData.*",
+    /// "**[?(@.unit == 'V')]"); returns an array of `{path, value}`
+    #[cfg(feature = "wasm")]
+    #[wasm_bindgen(unchecked_return_type = "QueryMatch[]")]
+    pub fn query(&self, expr: &str) -> Result<JsValue, TwinError> {
+        Ok(to_js(&self.query_matches(expr)?))
+    }
+}
+
+impl DigitalTwin {
+    /// Rust-side `query`; matches come in document order, each path once
+    pub fn query_matches(&self, expr: &str) -> Result<Vec<QueryMatch>, TwinError> {
+        let steps = parse_query(expr)
+            .map_err(|e| TwinError::invalid_argument(format!("Invalid query '{}': {}", expr, e)))?;
+        let in_nameplate = match steps.first().map(|s| &s.select) {
+            Some(Select::Name(name)) => {
+                name != crate::NAMEPLATE_ID_SHORT
+                    && !self.data.submodels.iter().any(|s| &s.id_short == name)
+            }
+            _ => false,
+        };
+        let mut nodes = vec![if in_nameplate {
+            Node::Elements {
+                name: crate::NAMEPLATE_ID_SHORT,
+                path: String::new(),
+                elements: &self.data.nameplate,
+            }
+        } else {
+            Node::Root
+        }];
+        for step in &steps {
+            let mut next = Vec::new();
+            for node in &nodes {
+                match &step.select {
+                    Select::Descend => self.descend(node, &mut next),
+                    select => next.extend(self.children(node).into_iter().filter(|child| {
+                        match select {
+                            Select::Name(name) => node_name(child) == Some(name.as_str()),
+                            Select::Index(index) => {
+                                matches!(child, Node::Element { index: Some(i), .. } if i == index)
+                            }
+                            Select::AnyIndex => {
+                                matches!(child, Node::Element { index: Some(_), .. })
+                            }
+                            _ => true,
+                        }
+                    })),
+                }
+            }
+            next.retain(|node| step.filters.iter().all(|f| f.matches(node)));
+            nodes = next;
+        }
+
+        let mut seen = std::collections::HashSet::new();
+        Ok(nodes
+            .into_iter()
+            .filter_map(|node| match node {
+                Node::Element { path, element, .. } if seen.insert(path.clone()) => {
+                    Some(QueryMatch {
+                        path,
+                        value: element.json_value(),
+                    })
+                }
+                _ => None,
+            })
+            .collect())
+    }
+
+    fn children<'a>(&'a self, node: &Node<'a>) -> Vec<Node<'a>> {
+        match node {
+            Node::Root => self
+                .data
+                .submodels
+                .iter()
+                .map(|sm| Node::Elements {
+                    name: &sm.id_short,
+                    path: sm.id_short.clone(),
+                    elements: &sm.submodel_elements,
+                })
+                .chain(std::iter::once(Node::Elements {
+                    name: crate::NAMEPLATE_ID_SHORT,
+                    path: String::new(),
+                    elements: &self.data.nameplate,
+                }))
+                .collect(),
+            Node::Elements { path, elements, .. } => elements
+                .iter()
+                .map(|element| Node::Element {
+                    path: join_path(path, &element.id_short),
+                    index: None,
+                    element,
+                })
+                .collect(),
+            Node::Element { path, element, .. } => match &element.kind {
+                ElementKind::SubmodelElementList(list) => list
+                    .value
+                    .iter()
+                    .enumerate()
+                    .map(|(i, item)| Node::Element {
+                        path: format!("{}[{}]", path, i),
+                        index: Some(i),
+                        element: item,
+                    })
+                    .collect(),
+                _ => element
+                    .children()
+                    .iter()
+                    .map(|child| Node::Element {
+                        path: join_path(path, &child.id_short),
+                        index: None,
+                        element: child,
+                    })
+                    .collect(),
+            },
+        }
+    }
+
+    /// `node` and its descendants, depth-first
+    fn descend<'a>(&'a self, node: &Node<'a>, out: &mut Vec<Node<'a>>) {
+        out.push(node.clone());
+        for child in self.children(node) {
+            self.descend(&child, out);
+        }
+    }
+}
+
+fn node_name<'a>(node: &Node<'a>) -> Option<&'a str> {
+    match node {
+        Node::Root => None,
+        Node::Elements { name, .. } => Some(name),
+        Node::Element { element, .. } => Some(&element.id_short),
+    }
+}
+
+impl Filter {
+    fn matches(&self, node: &Node) -> bool {
+        let Node::Element { element, .. } = node else {
+            return false;
+        };
+        let actual = match self.field.as_str() {
+            "value" => match element.json_value() {
+                serde_json::Value::String(s) => Some(s),
+                serde_json::Value::Null => None,
+                v @ (serde_json::Value::Number(_) | serde_json::Value::Bool(_)) => {
+                    Some(v.to_string())
+                }
+                _ => None,
+            },
+            "unit" => element.unit().map(str::to_string),
+            "semanticId" => element
+                .semantic_id
+                .as_ref()
+                .and_then(|r| r.key_value())
+                .map(str::to_string),
+            _ => Some(element.id_short.clone()),
+        };
+        match (&self.test, actual) {
+            (None, actual) => actual.is_some_and(|a| !a.is_empty()),
+            (Some((op, _)), None) => *op == Op::Ne,
+            (Some((op, expected)), Some(actual)) => compare(&actual, *op, expected),
+        }
+    }
+}
+
+fn compare(actual: &str, op: Op, expected: &str) -> bool {
+    if let (Ok(a), Ok(b)) = (actual.trim().parse::<f64>(), expected.parse::<f64>()) {
+        return match op {
+            Op::Eq => a == b,
+            Op::Ne => a != b,
+            Op::Lt => a < b,
+            Op::Le => a <= b,
+            Op::Gt => a > b,
+            Op::Ge => a >= b,
+        };
+    }
+    match op {
+        Op::Eq => actual == expected,
+        Op::Ne => actual != expected,
+        // Text only has an order where both sides are numbers
+        _ => false,
+    }
+}
+
+fn parse_query(expr: &str) -> Result<Vec<Step>, String> {
+    let expr = expr.trim();
+    let mut rest = expr.strip_prefix('$').unwrap_or(expr);
+    let mut steps: Vec<Step> = Vec::new();
+    let push = |steps: &mut Vec<Step>, select| {
+        steps.push(Step {
+            select,
+            filters: Vec::new(),
+        })
+    };
+    while !rest.is_empty() {
+        if let Some(r) = rest.strip_prefix("..") {
+            push(&mut steps, Select::Descend);
+            rest = r;
+        } else if let Some(r) = rest.strip_prefix('.') {
+            rest = r;
+        } else if rest.starts_with('[') {
+            let close = closing_bracket(rest).ok_or("unclosed '['")?;
+            let inner = rest[1..close].trim();
+            if let Some(filter) = inner.strip_prefix('?') {
+                if matches!(
+                    steps.last().map(|s| &s.select),
+                    None | Some(Select::Descend)
+                ) {
+                    push(&mut steps, Select::Any);
+                }
+                let filter = parse_filter(filter)?;
+                steps.last_mut().expect("pushed above").filters.push(filter);
+            } else {
+                if steps.is_empty() {
+                    return Err("it starts with an index".to_string());
+                }
+                let select = match inner {
+                    "*" => Select::AnyIndex,
+                    index => Select::Index(
+                        index
+                            .parse()
+                            .map_err(|_| format!("'{}' is not an index", index))?,
+                    ),
+                };
+                push(&mut steps, select);
+            }
+            rest = &rest[close + 1..];
+        } else {
+            let end = rest.find(['.', '[']).unwrap_or(rest.len());
+            let select = match rest[..end].trim() {
+                "*" => Select::Any,
+                "**" => Select::Descend,
+                name => Select::Name(name.to_string()),
+            };
+            push(&mut steps, select);
+            rest = &rest[end..];
+        }
+    }
+    if steps.is_empty() {
+        return Err("empty expression".to_string());
+    }
+    Ok(steps)
+}
+
+/// Index of the `]` closing the bracket `s` starts with, skipping quoted text
+fn closing_bracket(s: &str) -> Option<usize> {
+    let mut quote = None;
+    for (i, c) in s.char_indices().skip(1) {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '\'' | '"') => quote = Some(c),
+            (None, ']') => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+fn parse_filter(filter: &str) -> Result<Filter, String> {
+    let filter = filter.trim();
+    let filter = filter
+        .strip_prefix('(')
+        .and_then(|f| f.strip_suffix(')'))
+        .unwrap_or(filter)
+        .trim();
+    let condition = filter
+        .strip_prefix("@.")
+        .ok_or_else(|| format!("filter '{}' does not start with '@.'", filter))?;
+    let end = condition
+        .find(|c: char| !c.is_ascii_alphanumeric())
+        .unwrap_or(condition.len());
+    let field = &condition[..end];
+    if !matches!(field, "value" | "unit" | "semanticId" | "idShort") {
+        return Err(format!("unknown field '{}'", field));
+    }
+    let condition = condition[end..].trim();
+    if condition.is_empty() {
+        return Ok(Filter {
+            field: field.to_string(),
+            test: None,
+        });
+    }
+    let (op, literal) = [
+        ("==", Op::Eq),
+        ("!=", Op::Ne),
+        ("<=", Op::Le),
+        (">=", Op::Ge),
+        ("<", Op::Lt),
+        (">", Op::Gt),
+    ]
+    .into_iter()
+    .find_map(|(token, op)| condition.strip_prefix(token).map(|l| (op, l.trim())))
+    .ok_or_else(|| format!("unknown operator in '{}'", condition))?;
+    let literal = ['\'', '"']
+        .into_iter()
+        .find_map(|q| literal.strip_prefix(q).and_then(|l| l.strip_suffix(q)))
+        .unwrap_or(literal);
+    Ok(Filter {
+        field: field.to_string(),
+        test: Some((op, literal.to_string())),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const JSON: &str = r#"{
+        "id": "MOTOR-12345",
+        "asset_type": "Siemens 1LE1",
+        "nameplate": [
+            {"id_short": "Voltage", "value": "400", "unit": "V", "value_type": "xs:double"},
+            {
+                "id_short": "Markings",
+                "model_type": "SubmodelElementList",
+                "type_value_list_element": "SubmodelElementCollection",
+                "value": [
+                    {"model_type": "SubmodelElementCollection", "value": [
+                        {"id_short": "MarkingName", "value": "CE"}
+                    ]},
+                    {"model_type": "SubmodelElementCollection", "value": [
+                        {"id_short": "MarkingName", "value": "UKCA"}
+                    ]}
+                ]
+            }
+        ],
+        "submodels": [{
+            "id": "urn:example:sm:td",
+            "id_short": "TechnicalData",
+            "submodel_elements": [
+                {"id_short": "MaxTorque", "value": "250", "unit": "Nm", "value_type": "xs:double",
+                 "semantic_id": "0173-1#02-BAE098#004"},
+                {"id_short": "RatedVoltage", "value": "230", "unit": "V", "value_type": "xs:double"}
+            ]
+        }]
+    }"#;
+
+    fn paths(twin: &DigitalTwin, expr: &str) -> Vec<String> {
+        twin.query_matches(expr)
+            .unwrap()
+            .into_iter()
+            .map(|m| m.path)
+            .collect()
+    }
+
+    #[test]
+    fn test_query_wildcards_and_filters() {
+        let twin = DigitalTwin::new(JSON).unwrap();
+        assert_eq!(
+            paths(&twin, "TechnicalData.*"),
+            ["TechnicalData.MaxTorque", "TechnicalData.RatedVoltage"]
+        );
+        assert_eq!(
+            paths(&twin, "Markings[*].MarkingName"),
+            ["Markings[0].MarkingName", "Markings[1].MarkingName"]
+        );
+        assert_eq!(
+            paths(&twin, "**[?(@.unit == 'V')]"),
+            ["TechnicalData.RatedVoltage", "Voltage"]
+        );
+        assert_eq!(
+            paths(&twin, "$..[?(@.value > 240)]"),
+            ["TechnicalData.MaxTorque", "Voltage"]
+        );
+        assert_eq!(
+            paths(&twin, "*.*[?(@.semanticId == '0173-1#02-BAE098#004')]"),
+            ["TechnicalData.MaxTorque"]
+        );
+        assert_eq!(
+            paths(&twin, "Nameplate.Markings[1].*"),
+            ["Markings[1].MarkingName"]
+        );
+
+        let matches = twin.query_matches("Voltage").unwrap();
+        assert_eq!(matches[0].value, serde_json::json!(400.0));
+    }
+
+    #[test]
+    fn test_query_errors() {
+        let twin = DigitalTwin::new(JSON).unwrap();
+        for expr in [
+            "",
+            "[0]",
+            "Markings[x]",
+            "**[?(@.colour == 'red')]",
+            "*[?(@.value ~ 1)",
+        ] {
+            let err = twin.query_matches(expr).unwrap_err();
+            assert_eq!(err.code(), "INVALID_ARGUMENT", "{}", expr);
+        }
+        assert!(twin.query_matches("Unknown.*").unwrap().is_empty());
+    }
+}
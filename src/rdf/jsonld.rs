@@ -0,0 +1,112 @@
+// JSON-LD serialization of the RDF graph, compacted with the AAS prefixes
+
+use serde_json::{json, Map, Value};
+
+use super::{Object, Resource, AAS_RDF_NAMESPACE, RDF_NAMESPACE, XSD_NAMESPACE};
+
+/// The `@context` of exported documents
+fn aas_context() -> Value {
+    json!({
+        "aas": AAS_RDF_NAMESPACE,
+        "rdf": RDF_NAMESPACE,
+        "xs": XSD_NAMESPACE,
+    })
+}
+
+/// Write resources as a JSON-LD document with an `@graph`
+pub fn to_json_ld(graph: &[Resource]) -> Value {
+    json!({
+        "@context": aas_context(),
+        "@graph": graph.iter().map(node).collect::<Vec<_>>(),
+    })
+}
+
+fn node(resource: &Resource) -> Value {
+    let mut out = Map::new();
+    if let Some(iri) = &resource.iri {
+        out.insert("@id".to_string(), Value::String(iri.clone()));
+    }
+    let rdf_type = format!("{}type", RDF_NAMESPACE);
+    for (predicate, object) in &resource.properties {
+        let (key, value) = if *predicate == rdf_type {
+            let Object::Iri(class) = object else { continue };
+            ("@type".to_string(), Value::String(compact(class)))
+        } else {
+            (compact(predicate), value(object))
+        };
+        match out.get_mut(&key) {
+            None => {
+                out.insert(key, value);
+            }
+            Some(Value::Array(values)) => values.push(value),
+            Some(existing) => *existing = Value::Array(vec![existing.take(), value]),
+        }
+    }
+    Value::Object(out)
+}
+
+fn value(object: &Object) -> Value {
+    match object {
+        Object::Iri(iri) => json!({ "@id": compact(iri) }),
+        Object::Literal {
+            value,
+            datatype: None,
+        } => Value::String(value.clone()),
+        Object::Literal {
+            value,
+            datatype: Some(datatype),
+        } => json!({ "@value": value, "@type": compact(datatype) }),
+        Object::Blank(nested) => node(nested),
+    }
+}
+
+/// Compact IRI (`aas:Referable/idShort`) for IRIs in one of the context namespaces
+fn compact(iri: &str) -> String {
+    [
+        ("aas", AAS_RDF_NAMESPACE),
+        ("rdf", RDF_NAMESPACE),
+        ("xs", XSD_NAMESPACE),
+    ]
+    .iter()
+    .find_map(|(prefix, ns)| {
+        iri.strip_prefix(ns)
+            .map(|local| format!("{}:{}", prefix, local))
+    })
+    .unwrap_or_else(|| iri.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::DigitalTwin;
+
+    #[test]
+    fn test_to_json_ld() {
+        let json = r#"{
+            "id": "urn:example:aas:motor",
+            "asset_type": "Siemens 1LE1",
+            "nameplate": [
+                {"id_short": "Voltage", "value": "400", "unit": "V", "value_type": "xs:double"},
+                {"id_short": "Speed", "value": "1450"}
+            ]
+        }"#;
+        let doc: serde_json::Value =
+            serde_json::from_str(&DigitalTwin::new(json).unwrap().to_json_ld()).unwrap();
+
+        assert_eq!(doc["@context"]["aas"], "https://admin-shell.io/aas/3/0/");
+        let shell = &doc["@graph"][0];
+        assert_eq!(shell["@id"], "urn:example:aas:motor");
+        assert_eq!(shell["@type"], "aas:AssetAdministrationShell");
+        assert_eq!(
+            shell["aas:AssetAdministrationShell/assetInformation"]
+                ["aas:AssetInformation/assetKind"]["@id"],
+            "aas:AssetKind/Instance"
+        );
+
+        let elements = doc["@graph"][1]["aas:Submodel/submodelElements"]
+            .as_array()
+            .unwrap();
+        assert_eq!(elements[0]["aas:Property/value"], "400");
+        assert_eq!(elements[0]["aas:Property/valueType"]["@id"], "xs:double");
+        assert_eq!(elements[1]["aas:Referable/idShort"], "Speed");
+    }
+}
@@ -0,0 +1,529 @@
+// --- RDF ---
+// The AAS RDF mapping: every class and attribute of the metamodel has an IRI
+// in the AAS namespace ("…/Referable/idShort"), enumeration values are IRIs
+// ("…/AssetKind/Instance") and nested structures become blank nodes.
+// The graph is built once and written as Turtle or JSON-LD.
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    enum_name, AdministrativeInformation, AssetInformation, ConceptDescription, DataTypeDefXsd,
+    DigitalTwin, ElementKind, EmbeddedDataSpecification, Environment, EnvironmentShell, Extension,
+    LangString, OperationVariable, Qualifier, Reference, Submodel, SubmodelElement,
+};
+
+mod jsonld;
+mod turtle;
+
+pub use jsonld::to_json_ld;
+pub use turtle::to_turtle;
+
+/// Namespace of the AAS 3.0 RDF vocabulary
+pub const AAS_RDF_NAMESPACE: &str = "https://admin-shell.io/aas/3/0/";
+pub const RDF_NAMESPACE: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#";
+pub const XSD_NAMESPACE: &str = "http://www.w3.org/2001/XMLSchema#";
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Object {
+    Iri(String),
+    Literal {
+        value: String,
+        /// Datatype IRI; plain literals are xs:string
+        datatype: Option<String>,
+    },
+    /// A nested resource without an IRI of its own
+    Blank(Resource),
+}
+
+/// A subject with its predicate/object pairs
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Resource {
+    /// IRI of the subject; `None` for blank nodes
+    pub iri: Option<String>,
+    pub properties: Vec<(String, Object)>,
+}
+
+impl Resource {
+    fn new(iri: Option<String>, class: &str) -> Resource {
+        let mut resource = Resource {
+            iri,
+            properties: Vec::new(),
+        };
+        resource.add(&format!("{}type", RDF_NAMESPACE), Object::Iri(aas(class)));
+        resource
+    }
+
+    fn blank(class: &str) -> Resource {
+        Resource::new(None, class)
+    }
+
+    fn add(&mut self, predicate: &str, object: Object) {
+        self.properties.push((predicate.to_string(), object));
+    }
+
+    fn literal(&mut self, attribute: &str, value: &str) {
+        self.add(
+            &aas(attribute),
+            Object::Literal {
+                value: value.to_string(),
+                datatype: None,
+            },
+        );
+    }
+
+    fn opt_literal(&mut self, attribute: &str, value: Option<&str>) {
+        if let Some(value) = value {
+            self.literal(attribute, value);
+        }
+    }
+
+    /// An enumeration value, e.g. `…/AssetKind/Instance`
+    fn enumeration(&mut self, attribute: &str, enumeration: &str, value: &str) {
+        self.add(
+            &aas(attribute),
+            Object::Iri(aas(&format!("{}/{}", enumeration, value))),
+        );
+    }
+
+    fn value_type(&mut self, attribute: &str, value_type: DataTypeDefXsd) {
+        let local = value_type.as_str().trim_start_matches("xs:");
+        self.add(
+            &aas(attribute),
+            Object::Iri(format!("{}{}", XSD_NAMESPACE, local)),
+        );
+    }
+
+    fn nested(&mut self, attribute: &str, resource: Resource) {
+        self.add(&aas(attribute), Object::Blank(resource));
+    }
+
+    fn index(&mut self, index: usize) {
+        self.add(
+            &aas("index"),
+            Object::Literal {
+                value: index.to_string(),
+                datatype: Some(format!("{}integer", XSD_NAMESPACE)),
+            },
+        );
+    }
+
+    fn reference(&mut self, attribute: &str, r: Option<&Reference>) {
+        if let Some(r) = r {
+            self.nested(attribute, reference(r));
+        }
+    }
+
+    fn lang_strings(&mut self, attribute: &str, class: &str, strings: &[LangString]) {
+        for s in strings {
+            let mut lang = Resource::blank(class);
+            lang.literal("AbstractLangString/language", &s.language);
+            lang.literal("AbstractLangString/text", &s.text);
+            self.nested(attribute, lang);
+        }
+    }
+}
+
+/// IRI of a class, attribute or enumeration value in the AAS vocabulary
+pub fn aas(local: &str) -> String {
+    format!("{}{}", AAS_RDF_NAMESPACE, local)
+}
+
+/// Subject IRI of an identifiable: its id when that is an absolute IRI
+fn identifiable_iri(id: &str) -> Option<String> {
+    let (scheme, _) = id.split_once(':')?;
+    let valid_scheme = scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+        && scheme
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "+-.".contains(c));
+    let valid_chars = !id
+        .chars()
+        .any(|c| c.is_whitespace() || "<>\"{}|^`\\".contains(c));
+    (valid_scheme && valid_chars).then(|| id.to_string())
+}
+
+/// The RDF graph of an environment: one resource per identifiable
+pub fn environment_graph(env: &Environment) -> Vec<Resource> {
+    env.asset_administration_shells
+        .iter()
+        .map(shell)
+        .chain(env.submodels.iter().map(submodel))
+        .chain(env.concept_descriptions.iter().map(concept_description))
+        .collect()
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl DigitalTwin {
+    /// Export the twin as RDF (Turtle) following the AAS RDF mapping, e.g. for
+    /// loading into a triple store
+    pub fn to_rdf_turtle(&self) -> String {
+        to_turtle(&environment_graph(&Environment::from_shell(&self.data)))
+    }
+
+    /// Export the twin as JSON-LD with the AAS vocabulary as `@context`, for
+    /// semantic-web tooling and knowledge graphs
+    pub fn to_json_ld(&self) -> String {
+        let graph = environment_graph(&Environment::from_shell(&self.data));
+        serde_json::to_string_pretty(&to_json_ld(&graph)).unwrap_or_else(|_| "{}".to_string())
+    }
+}
+
+fn reference(r: &Reference) -> Resource {
+    let mut resource = Resource::blank("Reference");
+    resource.enumeration(
+        "Reference/type",
+        "ReferenceTypes",
+        &enum_name(&r.reference_type),
+    );
+    if let Some(referred) = &r.referred_semantic_id {
+        resource.nested("Reference/referredSemanticId", reference(referred));
+    }
+    for (i, key) in r.keys.iter().enumerate() {
+        let mut k = Resource::blank("Key");
+        k.enumeration("Key/type", "KeyTypes", &enum_name(&key.key_type));
+        k.literal("Key/value", &key.value);
+        k.index(i);
+        resource.nested("Reference/keys", k);
+    }
+    resource
+}
+
+fn extensions(resource: &mut Resource, extensions: &[Extension]) {
+    for e in extensions {
+        let mut ext = Resource::blank("Extension");
+        ext.reference("HasSemantics/semanticId", e.semantic_id.as_ref());
+        ext.literal("Extension/name", &e.name);
+        if let Some(t) = e.value_type {
+            ext.value_type("Extension/valueType", t);
+        }
+        ext.opt_literal("Extension/value", e.value.as_deref());
+        for r in &e.refers_to {
+            ext.nested("Extension/refersTo", reference(r));
+        }
+        resource.nested("HasExtensions/extensions", ext);
+    }
+}
+
+fn administration(resource: &mut Resource, admin: Option<&AdministrativeInformation>) {
+    let Some(admin) = admin else { return };
+    let mut a = Resource::blank("AdministrativeInformation");
+    a.opt_literal(
+        "AdministrativeInformation/version",
+        admin.version.as_deref(),
+    );
+    a.opt_literal(
+        "AdministrativeInformation/revision",
+        admin.revision.as_deref(),
+    );
+    a.reference("AdministrativeInformation/creator", admin.creator.as_ref());
+    a.opt_literal(
+        "AdministrativeInformation/templateId",
+        admin.template_id.as_deref(),
+    );
+    resource.nested("Identifiable/administration", a);
+}
+
+fn qualifiers(resource: &mut Resource, qualifiers: &[Qualifier]) {
+    for q in qualifiers {
+        let mut qualifier = Resource::blank("Qualifier");
+        qualifier.reference("HasSemantics/semanticId", q.semantic_id.as_ref());
+        qualifier.enumeration("Qualifier/kind", "QualifierKind", &enum_name(&q.kind));
+        qualifier.literal("Qualifier/type", &q.qualifier_type);
+        qualifier.value_type("Qualifier/valueType", q.value_type);
+        qualifier.opt_literal("Qualifier/value", q.value.as_deref());
+        qualifier.reference("Qualifier/valueId", q.value_id.as_ref());
+        resource.nested("Qualifiable/qualifiers", qualifier);
+    }
+}
+
+fn data_specifications(resource: &mut Resource, specs: &[EmbeddedDataSpecification]) {
+    for spec in specs {
+        let c = &spec.data_specification_content;
+        let mut content = Resource::blank("DataSpecificationIec61360");
+        content.lang_strings(
+            "DataSpecificationIec61360/preferredName",
+            "LangStringPreferredNameTypeIec61360",
+            &c.preferred_name,
+        );
+        content.lang_strings(
+            "DataSpecificationIec61360/shortName",
+            "LangStringShortNameTypeIec61360",
+            &c.short_name,
+        );
+        content.opt_literal("DataSpecificationIec61360/unit", c.unit.as_deref());
+        content.reference("DataSpecificationIec61360/unitId", c.unit_id.as_ref());
+        content.opt_literal("DataSpecificationIec61360/symbol", c.symbol.as_deref());
+        if let Some(t) = &c.data_type {
+            content.enumeration(
+                "DataSpecificationIec61360/dataType",
+                "DataTypeIec61360",
+                &enum_name(t),
+            );
+        }
+        content.lang_strings(
+            "DataSpecificationIec61360/definition",
+            "LangStringDefinitionTypeIec61360",
+            &c.definition,
+        );
+        content.opt_literal(
+            "DataSpecificationIec61360/valueFormat",
+            c.value_format.as_deref(),
+        );
+
+        let mut embedded = Resource::blank("EmbeddedDataSpecification");
+        embedded.nested(
+            "EmbeddedDataSpecification/dataSpecification",
+            reference(&spec.data_specification),
+        );
+        embedded.nested(
+            "EmbeddedDataSpecification/dataSpecificationContent",
+            content,
+        );
+        resource.nested("HasDataSpecification/embeddedDataSpecifications", embedded);
+    }
+}
+
+fn asset_information(info: &AssetInformation) -> Resource {
+    let mut resource = Resource::blank("AssetInformation");
+    resource.enumeration(
+        "AssetInformation/assetKind",
+        "AssetKind",
+        &enum_name(&info.asset_kind),
+    );
+    resource.opt_literal(
+        "AssetInformation/globalAssetId",
+        info.global_asset_id.as_deref(),
+    );
+    for id in &info.specific_asset_ids {
+        let mut specific = Resource::blank("SpecificAssetId");
+        specific.reference("HasSemantics/semanticId", id.semantic_id.as_ref());
+        specific.literal("SpecificAssetId/name", &id.name);
+        specific.literal("SpecificAssetId/value", &id.value);
+        specific.reference(
+            "SpecificAssetId/externalSubjectId",
+            id.external_subject_id.as_ref(),
+        );
+        resource.nested("AssetInformation/specificAssetIds", specific);
+    }
+    resource.opt_literal("AssetInformation/assetType", info.asset_type.as_deref());
+    if let Some(thumbnail) = &info.default_thumbnail {
+        let mut t = Resource::blank("Resource");
+        t.literal("Resource/path", &thumbnail.path);
+        t.opt_literal("Resource/contentType", thumbnail.content_type.as_deref());
+        resource.nested("AssetInformation/defaultThumbnail", t);
+    }
+    resource
+}
+
+fn shell(shell: &EnvironmentShell) -> Resource {
+    let mut resource = Resource::new(identifiable_iri(&shell.id), "AssetAdministrationShell");
+    extensions(&mut resource, &shell.extensions);
+    resource.opt_literal("Referable/idShort", shell.id_short.as_deref());
+    resource.lang_strings(
+        "Referable/displayName",
+        "LangStringNameType",
+        &shell.display_name,
+    );
+    resource.lang_strings(
+        "Referable/description",
+        "LangStringTextType",
+        &shell.description,
+    );
+    administration(&mut resource, shell.administration.as_ref());
+    resource.literal("Identifiable/id", &shell.id);
+    resource.nested(
+        "AssetAdministrationShell/assetInformation",
+        asset_information(&shell.asset_information),
+    );
+    for r in &shell.submodels {
+        resource.nested("AssetAdministrationShell/submodels", reference(r));
+    }
+    resource
+}
+
+fn submodel(sm: &Submodel) -> Resource {
+    let mut resource = Resource::new(identifiable_iri(&sm.id), "Submodel");
+    extensions(&mut resource, &sm.extensions);
+    resource.literal("Referable/idShort", &sm.id_short);
+    resource.lang_strings(
+        "Referable/displayName",
+        "LangStringNameType",
+        &sm.display_name,
+    );
+    resource.lang_strings(
+        "Referable/description",
+        "LangStringTextType",
+        &sm.description,
+    );
+    administration(&mut resource, sm.administration.as_ref());
+    resource.literal("Identifiable/id", &sm.id);
+    resource.enumeration("HasKind/kind", "ModellingKind", &enum_name(&sm.kind));
+    resource.reference("HasSemantics/semanticId", sm.semantic_id.as_ref());
+    qualifiers(&mut resource, &sm.qualifiers);
+    for e in &sm.submodel_elements {
+        resource.nested("Submodel/submodelElements", element(e));
+    }
+    resource
+}
+
+fn concept_description(cd: &ConceptDescription) -> Resource {
+    let mut resource = Resource::new(identifiable_iri(&cd.id), "ConceptDescription");
+    resource.opt_literal("Referable/idShort", cd.id_short.as_deref());
+    administration(&mut resource, cd.administration.as_ref());
+    resource.literal("Identifiable/id", &cd.id);
+    data_specifications(&mut resource, &cd.embedded_data_specifications);
+    for r in &cd.is_case_of {
+        resource.nested("ConceptDescription/isCaseOf", reference(r));
+    }
+    resource
+}
+
+fn operation_variables(resource: &mut Resource, attribute: &str, vars: &[OperationVariable]) {
+    for var in vars {
+        let mut v = Resource::blank("OperationVariable");
+        v.nested("OperationVariable/value", element(&var.value));
+        resource.nested(attribute, v);
+    }
+}
+
+fn element(e: &SubmodelElement) -> Resource {
+    let model_type = e.kind.model_type();
+    let mut resource = Resource::blank(model_type);
+    extensions(&mut resource, &e.spec_extensions());
+    if let Some(category) = &e.category {
+        resource.literal("Referable/category", &enum_name(category));
+    }
+    if !e.id_short.is_empty() {
+        resource.literal("Referable/idShort", &e.id_short);
+    }
+    resource.lang_strings(
+        "Referable/displayName",
+        "LangStringNameType",
+        &e.display_name,
+    );
+    resource.lang_strings(
+        "Referable/description",
+        "LangStringTextType",
+        &e.description,
+    );
+    resource.reference("HasSemantics/semanticId", e.semantic_id.as_ref());
+    qualifiers(&mut resource, &e.qualifiers);
+    data_specifications(&mut resource, &e.embedded_data_specifications);
+
+    let attr = |name: &str| format!("{}/{}", model_type, name);
+    match &e.kind {
+        ElementKind::Property(p) => {
+            resource.value_type(
+                &attr("valueType"),
+                p.value_type.unwrap_or(DataTypeDefXsd::String),
+            );
+            if !p.value.is_empty() {
+                resource.literal(&attr("value"), &p.value);
+            }
+        }
+        ElementKind::MultiLanguageProperty(mlp) => {
+            resource.lang_strings(&attr("value"), "LangStringTextType", &mlp.value);
+        }
+        ElementKind::Range(r) => {
+            resource.value_type(
+                &attr("valueType"),
+                r.value_type.unwrap_or(DataTypeDefXsd::Double),
+            );
+            resource.opt_literal(&attr("min"), r.min.as_deref());
+            resource.opt_literal(&attr("max"), r.max.as_deref());
+        }
+        ElementKind::File(f) => {
+            resource.opt_literal(&attr("value"), f.value.as_deref());
+            resource.opt_literal(&attr("contentType"), f.content_type.as_deref());
+        }
+        ElementKind::Blob(b) => {
+            resource.opt_literal(&attr("value"), b.value.as_deref());
+            resource.opt_literal(&attr("contentType"), b.content_type.as_deref());
+        }
+        ElementKind::ReferenceElement(r) => {
+            resource.reference(&attr("value"), r.value.as_ref());
+        }
+        ElementKind::RelationshipElement(r) => {
+            resource.reference("RelationshipElement/first", r.first.as_ref());
+            resource.reference("RelationshipElement/second", r.second.as_ref());
+        }
+        ElementKind::AnnotatedRelationshipElement(r) => {
+            resource.reference("RelationshipElement/first", r.first.as_ref());
+            resource.reference("RelationshipElement/second", r.second.as_ref());
+            for a in &r.annotations {
+                resource.nested(&attr("annotations"), element(a));
+            }
+        }
+        ElementKind::Entity(entity) => {
+            for s in &entity.statements {
+                resource.nested(&attr("statements"), element(s));
+            }
+            resource.enumeration(
+                &attr("entityType"),
+                "EntityType",
+                &enum_name(&entity.entity_type),
+            );
+            resource.opt_literal(&attr("globalAssetId"), entity.global_asset_id.as_deref());
+        }
+        ElementKind::Operation(op) => {
+            operation_variables(&mut resource, &attr("inputVariables"), &op.input_variables);
+            operation_variables(
+                &mut resource,
+                &attr("outputVariables"),
+                &op.output_variables,
+            );
+            operation_variables(
+                &mut resource,
+                &attr("inoutputVariables"),
+                &op.inoutput_variables,
+            );
+        }
+        ElementKind::Capability(_) => {}
+        ElementKind::BasicEventElement(event) => {
+            resource.nested(&attr("observed"), reference(&event.observed));
+            resource.enumeration(
+                &attr("direction"),
+                "Direction",
+                &enum_name(&event.direction),
+            );
+            resource.enumeration(&attr("state"), "StateOfEvent", &enum_name(&event.state));
+            resource.opt_literal(&attr("messageTopic"), event.message_topic.as_deref());
+            resource.reference(&attr("messageBroker"), event.message_broker.as_ref());
+            resource.opt_literal(&attr("lastUpdate"), event.last_update.as_deref());
+            resource.opt_literal(&attr("minInterval"), event.min_interval.as_deref());
+            resource.opt_literal(&attr("maxInterval"), event.max_interval.as_deref());
+        }
+        ElementKind::SubmodelElementCollection(c) => {
+            for item in &c.value {
+                resource.nested(&attr("value"), element(item));
+            }
+        }
+        ElementKind::SubmodelElementList(l) => {
+            resource.add(
+                &aas(&attr("orderRelevant")),
+                Object::Literal {
+                    value: l.order_relevant.to_string(),
+                    datatype: Some(format!("{}boolean", XSD_NAMESPACE)),
+                },
+            );
+            resource.reference(
+                &attr("semanticIdListElement"),
+                l.semantic_id_list_element.as_ref(),
+            );
+            resource.enumeration(
+                &attr("typeValueListElement"),
+                "AasSubmodelElements",
+                &enum_name(&l.type_value_list_element),
+            );
+            if let Some(t) = l.value_type_list_element {
+                resource.value_type(&attr("valueTypeListElement"), t);
+            }
+            for (i, item) in l.value.iter().enumerate() {
+                let mut item = element(item);
+                item.index(i);
+                resource.nested(&attr("value"), item);
+            }
+        }
+    }
+    resource
+}
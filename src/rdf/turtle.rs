@@ -0,0 +1,124 @@
+// Turtle serialization of the RDF graph; blank nodes are written inline
+
+use super::{Object, Resource, AAS_RDF_NAMESPACE, RDF_NAMESPACE, XSD_NAMESPACE};
+
+const PREFIXES: &[(&str, &str)] = &[
+    ("aas", AAS_RDF_NAMESPACE),
+    ("rdf", RDF_NAMESPACE),
+    ("xs", XSD_NAMESPACE),
+];
+
+/// Write resources as a Turtle document
+pub fn to_turtle(graph: &[Resource]) -> String {
+    let mut out = String::new();
+    for (prefix, namespace) in PREFIXES {
+        out.push_str(&format!("@prefix {}: <{}> .\n", prefix, namespace));
+    }
+    for (i, resource) in graph.iter().enumerate() {
+        out.push('\n');
+        match &resource.iri {
+            Some(iri) => out.push_str(&iri_term(iri)),
+            None => out.push_str(&format!("_:n{}", i)),
+        }
+        out.push(' ');
+        properties(&mut out, resource, 1);
+        out.push_str(" .\n");
+    }
+    out
+}
+
+fn properties(out: &mut String, resource: &Resource, depth: usize) {
+    let indent = "    ".repeat(depth);
+    for (i, (predicate, object)) in resource.properties.iter().enumerate() {
+        if i > 0 {
+            out.push_str(" ;\n");
+            out.push_str(&indent);
+        }
+        out.push_str(&predicate_term(predicate));
+        out.push(' ');
+        match object {
+            Object::Iri(iri) => out.push_str(&iri_term(iri)),
+            Object::Literal { value, datatype } => {
+                out.push_str(&literal(value));
+                if let Some(datatype) = datatype {
+                    out.push_str("^^");
+                    out.push_str(&iri_term(datatype));
+                }
+            }
+            Object::Blank(nested) => {
+                out.push_str("[\n");
+                out.push_str(&"    ".repeat(depth + 1));
+                properties(out, nested, depth + 1);
+                out.push('\n');
+                out.push_str(&indent);
+                out.push(']');
+            }
+        }
+    }
+}
+
+fn predicate_term(iri: &str) -> String {
+    if iri == format!("{}type", RDF_NAMESPACE) {
+        return "a".to_string();
+    }
+    iri_term(iri)
+}
+
+/// A prefixed name where the local part allows it, otherwise `<iri>`
+fn iri_term(iri: &str) -> String {
+    for (prefix, namespace) in PREFIXES {
+        if let Some(local) = iri.strip_prefix(namespace) {
+            let simple = local.starts_with(|c: char| c.is_ascii_alphabetic())
+                && local
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+            if simple {
+                return format!("{}:{}", prefix, local);
+            }
+        }
+    }
+    format!("<{}>", iri)
+}
+
+fn literal(value: &str) -> String {
+    let mut out = String::with_capacity(value.len() + 2);
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::DigitalTwin;
+
+    #[test]
+    fn test_to_rdf_turtle() {
+        let json = r#"{
+            "id": "urn:example:aas:motor",
+            "asset_type": "Siemens 1LE1",
+            "nameplate": [
+                {"id_short": "Voltage", "value": "400", "unit": "V", "value_type": "xs:double"},
+                {"id_short": "Note", "value": "say \"hi\""}
+            ]
+        }"#;
+        let turtle = DigitalTwin::new(json).unwrap().to_rdf_turtle();
+
+        assert!(turtle.starts_with("@prefix aas: <https://admin-shell.io/aas/3/0/> ."));
+        assert!(turtle.contains("<urn:example:aas:motor> a aas:AssetAdministrationShell ;"));
+        assert!(turtle.contains("<urn:example:aas:motor/submodels/nameplate> a aas:Submodel ;"));
+        assert!(turtle.contains("<https://admin-shell.io/aas/3/0/Property/valueType> xs:double"));
+        assert!(turtle.contains("<https://admin-shell.io/aas/3/0/Property/value> \"400\""));
+        assert!(turtle.contains(r#""say \"hi\"""#));
+        assert!(turtle.contains("<https://admin-shell.io/aas/3/0/AssetKind/Instance>"));
+    }
+}
@@ -0,0 +1,407 @@
+// --- Scenario playback ---
+// A recorded time series played into the twin for commissioning rehearsals
+// and regression tests. Each row has a time in seconds from the start of the
+// scenario, the simulation inputs that take effect at that time and the
+// outputs expected there. Rows come as JSON or as CSV with a `time` column,
+// input columns named after the input ("load") and expectation columns
+// prefixed with "expect:" ("expect:motor.rpm"). Playback advances the
+// simulation in steps of at most the step size at a chosen speed (1x, 10x)
+// or row by row, and records every expectation that is missed by more than
+// the tolerance.
+
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "wasm")]
+use tsify::Tsify;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+#[cfg(feature = "wasm")]
+use crate::to_js;
+use crate::{DigitalTwin, SimulationValues, TwinError};
+
+/// Prefix of CSV columns holding expected outputs
+pub const EXPECT_COLUMN_PREFIX: &str = "expect:";
+
+/// Largest absolute difference between an expected and a simulated output
+/// unless the scenario sets one
+pub const DEFAULT_SCENARIO_TOLERANCE: f64 = 1e-6;
+
+/// One point in time of a scenario
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[serde(deny_unknown_fields)]
+pub struct ScenarioRow {
+    /// Seconds from the start of the scenario
+    pub time: f64,
+    /// Inputs set at this time, e.g. `{"load": 0.5}`
+    #[serde(default)]
+    pub inputs: SimulationValues,
+    /// Outputs expected at this time, before the inputs take effect
+    #[serde(default)]
+    pub expect: SimulationValues,
+}
+
+/// A recorded time series with the tolerance of its expectations
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[serde(deny_unknown_fields)]
+pub struct Scenario {
+    #[serde(default = "tolerance_default")]
+    pub tolerance: f64,
+    pub rows: Vec<ScenarioRow>,
+}
+
+fn tolerance_default() -> f64 {
+    DEFAULT_SCENARIO_TOLERANCE
+}
+
+impl Scenario {
+    /// Parse a CSV scenario: a header with a `time` column, input columns
+    /// and "expect:" columns; empty cells leave a value unset
+    pub fn from_csv(csv: &str) -> Result<Scenario, TwinError> {
+        let mut lines = csv.lines().filter(|line| !line.trim().is_empty());
+        let header: Vec<&str> = lines
+            .next()
+            .ok_or_else(|| TwinError::parse("Scenario CSV has no header"))?
+            .split(',')
+            .map(str::trim)
+            .collect();
+        let time_column = header
+            .iter()
+            .position(|column| *column == "time")
+            .ok_or_else(|| TwinError::parse("Scenario CSV has no 'time' column"))?;
+        let mut rows = Vec::new();
+        for (number, line) in lines.enumerate() {
+            let cells: Vec<&str> = line.split(',').map(str::trim).collect();
+            if cells.len() != header.len() {
+                return Err(TwinError::parse(format!(
+                    "Scenario row {} has {} cells, the header {}",
+                    number + 1,
+                    cells.len(),
+                    header.len()
+                )));
+            }
+            let mut row = ScenarioRow {
+                time: 0.0,
+                inputs: SimulationValues::new(),
+                expect: SimulationValues::new(),
+            };
+            for (i, (column, cell)) in header.iter().zip(&cells).enumerate() {
+                if cell.is_empty() && i != time_column {
+                    continue;
+                }
+                let value: f64 = cell.parse().map_err(|_| {
+                    TwinError::parse(format!(
+                        "Scenario row {}: '{}' in column '{}' is not a number",
+                        number + 1,
+                        cell,
+                        column
+                    ))
+                })?;
+                if i == time_column {
+                    row.time = value;
+                } else if let Some(output) = column.strip_prefix(EXPECT_COLUMN_PREFIX) {
+                    row.expect.insert(output.to_string(), value);
+                } else {
+                    row.inputs.insert(column.to_string(), value);
+                }
+            }
+            rows.push(row);
+        }
+        Ok(Scenario {
+            tolerance: DEFAULT_SCENARIO_TOLERANCE,
+            rows,
+        })
+    }
+
+    /// Check that times are finite, non-negative and in order and the
+    /// tolerance non-negative
+    pub fn validate(&self) -> Result<(), TwinError> {
+        let invalid = |message: String| Err(TwinError::validation(None, message));
+        if !(self.tolerance >= 0.0 && self.tolerance.is_finite()) {
+            return invalid(format!("Invalid scenario tolerance {}", self.tolerance));
+        }
+        let mut last = 0.0;
+        for row in &self.rows {
+            if !row.time.is_finite() || row.time < last {
+                return invalid(format!(
+                    "Scenario time {} is not after the previous {}",
+                    row.time, last
+                ));
+            }
+            let values = row.inputs.values().chain(row.expect.values());
+            if let Some(value) = values.into_iter().find(|v| !v.is_finite()) {
+                return invalid(format!("Scenario value {} at {} s", value, row.time));
+            }
+            last = row.time;
+        }
+        Ok(())
+    }
+}
+
+/// An expectation the simulation missed
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[cfg_attr(feature = "wasm", tsify(missing_as_null))]
+pub struct ScenarioFailure {
+    /// Seconds from the start of the scenario
+    pub time: f64,
+    pub output: String,
+    pub expected: f64,
+    /// None when the output does not exist
+    pub actual: Option<f64>,
+}
+
+/// Progress and outcome of the playback
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+pub struct ScenarioReport {
+    /// Seconds of the scenario played
+    pub position: f64,
+    pub rows_played: usize,
+    pub rows: usize,
+    pub finished: bool,
+    /// Expectations checked so far
+    pub checks: usize,
+    pub failures: Vec<ScenarioFailure>,
+}
+
+/// A scenario being played back
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct ScenarioPlayer {
+    scenario: Scenario,
+    speed: f64,
+    position: f64,
+    next_row: usize,
+    checks: usize,
+    failures: Vec<ScenarioFailure>,
+}
+
+impl ScenarioPlayer {
+    fn report(&self) -> ScenarioReport {
+        ScenarioReport {
+            position: self.position,
+            rows_played: self.next_row,
+            rows: self.scenario.rows.len(),
+            finished: self.next_row == self.scenario.rows.len(),
+            checks: self.checks,
+            failures: self.failures.clone(),
+        }
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl DigitalTwin {
+    /// Load a `Scenario` given as JSON for playback at 1x from its start,
+    /// replacing a scenario loaded before
+    pub fn load_scenario_json(&mut self, scenario_json: &str) -> Result<(), TwinError> {
+        let scenario = serde_json::from_str(scenario_json)
+            .map_err(|e| TwinError::parse(format!("Invalid scenario: {}", e)))?;
+        self.load_scenario(scenario)
+    }
+
+    /// Load a scenario given as CSV, with `tolerance` for its expectations
+    pub fn load_scenario_csv(&mut self, csv: &str, tolerance: f64) -> Result<(), TwinError> {
+        let scenario = Scenario {
+            tolerance,
+            ..Scenario::from_csv(csv)?
+        };
+        self.load_scenario(scenario)
+    }
+
+    /// Scenario seconds played per second of `play_scenario`, e.g. 10 for
+    /// 10x
+    pub fn set_scenario_speed(&mut self, speed: f64) -> Result<(), TwinError> {
+        if !speed.is_finite() || speed <= 0.0 {
+            return Err(TwinError::invalid_argument(format!(
+                "Scenario speed must be a positive factor, got {}",
+                speed
+            )));
+        }
+        self.scenario_player()?.speed = speed;
+        Ok(())
+    }
+
+    /// Play the scenario for `seconds` of wall-clock time at its speed and
+    /// return the number of rows reached
+    pub fn play_scenario(&mut self, seconds: f64) -> Result<usize, TwinError> {
+        if !seconds.is_finite() || seconds < 0.0 {
+            return Err(TwinError::invalid_argument(format!(
+                "Playback time must be a non-negative number of seconds, got {}",
+                seconds
+            )));
+        }
+        let player = self.scenario_player()?;
+        let target = player.position + seconds * player.speed;
+        let mut played = 0;
+        while self.play_next_row(Some(target))? {
+            played += 1;
+        }
+        let position = self.scenario_player()?.position;
+        self.advance_span(target - position)?;
+        self.scenario_player()?.position = target;
+        Ok(played)
+    }
+
+    /// Play up to the next row and return whether there was one
+    pub fn step_scenario(&mut self) -> Result<bool, TwinError> {
+        self.scenario_player()?;
+        self.play_next_row(None)
+    }
+
+    /// The `ScenarioReport` of the loaded scenario
+    #[cfg(feature = "wasm")]
+    #[wasm_bindgen(unchecked_return_type = "ScenarioReport")]
+    pub fn get_scenario_report(&self) -> Result<JsValue, TwinError> {
+        Ok(to_js(&self.scenario_report()?))
+    }
+}
+
+impl DigitalTwin {
+    /// Rust-side `load_scenario_json`
+    pub fn load_scenario(&mut self, scenario: Scenario) -> Result<(), TwinError> {
+        scenario.validate()?;
+        self.scenario = Some(ScenarioPlayer {
+            scenario,
+            speed: 1.0,
+            position: 0.0,
+            next_row: 0,
+            checks: 0,
+            failures: Vec::new(),
+        });
+        Ok(())
+    }
+
+    /// Rust-side `get_scenario_report`
+    pub fn scenario_report(&self) -> Result<ScenarioReport, TwinError> {
+        self.scenario
+            .as_ref()
+            .map(ScenarioPlayer::report)
+            .ok_or_else(no_scenario)
+    }
+
+    fn scenario_player(&mut self) -> Result<&mut ScenarioPlayer, TwinError> {
+        self.scenario.as_mut().ok_or_else(no_scenario)
+    }
+
+    /// Advance to the next row if it lies before `until`, check its
+    /// expectations and set its inputs
+    fn play_next_row(&mut self, until: Option<f64>) -> Result<bool, TwinError> {
+        let Some(player) = self.scenario.as_mut() else {
+            return Ok(false);
+        };
+        let Some(row) = player.scenario.rows.get(player.next_row).cloned() else {
+            return Ok(false);
+        };
+        if until.is_some_and(|until| row.time > until) {
+            return Ok(false);
+        }
+        let span = row.time - player.position;
+        self.advance_span(span)?;
+
+        let values = self.simulation_values().clone();
+        let Some(player) = self.scenario.as_mut() else {
+            return Ok(false);
+        };
+        for (output, expected) in &row.expect {
+            let actual = values.get(output).copied();
+            player.checks += 1;
+            if !actual.is_some_and(|a| (a - expected).abs() <= player.scenario.tolerance) {
+                player.failures.push(ScenarioFailure {
+                    time: row.time,
+                    output: output.clone(),
+                    expected: *expected,
+                    actual,
+                });
+            }
+        }
+        player.position = row.time;
+        player.next_row += 1;
+        for (name, value) in row.inputs {
+            self.set_simulation_input(&name, value);
+        }
+        Ok(true)
+    }
+}
+
+fn no_scenario() -> TwinError {
+    TwinError::failed(None, "No scenario is loaded")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"{"id": "M-1", "asset_type": "Motor", "nameplate": []}"#;
+
+    #[test]
+    fn test_scenario_drives_inputs_and_checks_outputs() {
+        let mut reference = DigitalTwin::new(CONFIG).unwrap();
+        reference.set_load(0.5).unwrap();
+        for _ in 0..20 {
+            reference.advance_by(0.1).unwrap();
+        }
+        let rpm = reference.simulation_values()["motor.rpm"];
+
+        let mut twin = DigitalTwin::new(CONFIG).unwrap();
+        let csv = format!(
+            "time,load,expect:motor.rpm\n0,0.5,\n2,,{}\n3,1,{}\n",
+            rpm,
+            rpm + 1.0
+        );
+        twin.load_scenario_csv(&csv, 1e-9).unwrap();
+        assert_eq!(twin.play_scenario(1.0).unwrap(), 1);
+        twin.set_scenario_speed(10.0).unwrap();
+        assert_eq!(twin.play_scenario(0.5).unwrap(), 2);
+
+        let report = twin.scenario_report().unwrap();
+        assert!(report.finished && report.checks == 2);
+        assert_eq!(report.position, 6.0);
+        assert_eq!(report.failures.len(), 1);
+        assert_eq!(report.failures[0].time, 3.0);
+        assert_eq!(twin.get_load(), 1.0);
+        assert!((twin.simulation_time() - 6.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_scenario_rows_step_by_step() {
+        let mut twin = DigitalTwin::new(CONFIG).unwrap();
+        assert!(twin.step_scenario().is_err());
+        twin.load_scenario_json(
+            r#"{"rows": [
+                {"time": 0, "inputs": {"supply_voltage": 380}},
+                {"time": 5, "expect": {"motor.slip": -1}}
+            ]}"#,
+        )
+        .unwrap();
+        assert!(twin.step_scenario().unwrap());
+        assert!(twin.step_scenario().unwrap());
+        assert!(!twin.step_scenario().unwrap());
+        assert!((twin.simulation_time() - 5.0).abs() < 1e-9);
+        assert_eq!(twin.scenario_report().unwrap().failures.len(), 1);
+
+        assert!(twin
+            .load_scenario_csv("time,load\n2,1\n1,0\n", 0.0)
+            .is_err_and(|e| e.code() == "VALIDATION_FAILED"));
+        assert!(twin
+            .load_scenario_csv("load\n1\n", 0.0)
+            .is_err_and(|e| e.code() == "PARSE_ERROR"));
+        assert!(twin.load_scenario_csv("time,load\n0,x\n", 0.0).is_err());
+        assert!(twin.set_scenario_speed(0.0).is_err());
+    }
+
+    #[test]
+    fn test_scenario_span_is_bounded() {
+        let mut twin = DigitalTwin::new(CONFIG).unwrap();
+        twin.load_scenario_json(r#"{"rows": [{"time": 1e9, "inputs": {"load": 1}}]}"#)
+            .unwrap();
+        assert!(twin
+            .step_scenario()
+            .is_err_and(|e| e.code() == "INVALID_ARGUMENT"));
+        assert!(twin
+            .play_scenario(1e9)
+            .is_err_and(|e| e.code() == "INVALID_ARGUMENT"));
+        assert_eq!(twin.simulation_time(), 0.0);
+        assert_eq!(twin.scenario_report().unwrap().position, 0.0);
+    }
+}
@@ -0,0 +1,257 @@
+// --- Behavior scripts ---
+// Rhai scripts attached to the twin run after the simulation models on every
+// tick, so integrators can add asset-specific logic without rebuilding the
+// WASM module. A script sees the property values in `props` (numbers and
+// booleans typed by valueType, everything else as strings), the model
+// outputs in `sim`, the simulation inputs in `inputs`, its own `state` map
+// kept from tick to tick, and `dt`, `time` and `tick`. Values it assigns in
+// `props` and `inputs` are written back to the twin. Every run is limited in
+// operations so that a runaway loop cannot hang the page.
+
+use std::collections::BTreeMap;
+
+use rhai::{Dynamic, Engine, Map, Scope, AST};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{DataTypeDefXsd, DigitalTwin, ElementKind, TwinError};
+
+/// Operations a script may perform per tick
+const MAX_OPERATIONS: u64 = 100_000;
+
+struct Script {
+    name: String,
+    ast: AST,
+    state: Map,
+}
+
+/// The engine with the attached scripts and their last errors
+pub(crate) struct ScriptRegistry {
+    engine: Engine,
+    scripts: Vec<Script>,
+    errors: BTreeMap<String, String>,
+}
+
+impl Default for ScriptRegistry {
+    fn default() -> ScriptRegistry {
+        let mut engine = Engine::new();
+        engine.set_max_operations(MAX_OPERATIONS);
+        ScriptRegistry {
+            engine,
+            scripts: Vec::new(),
+            errors: BTreeMap::new(),
+        }
+    }
+}
+
+/// A property value as scripts see it
+fn to_dynamic(value: &str, value_type: Option<DataTypeDefXsd>) -> Dynamic {
+    let trimmed = value.trim();
+    let typed = match value_type {
+        Some(DataTypeDefXsd::Int) => trimmed.parse::<i64>().ok().map(Dynamic::from),
+        Some(DataTypeDefXsd::Double) | None => trimmed.parse::<f64>().ok().map(Dynamic::from),
+        Some(DataTypeDefXsd::Boolean) => match trimmed {
+            "true" | "1" => Some(Dynamic::TRUE),
+            "false" | "0" => Some(Dynamic::FALSE),
+            _ => None,
+        },
+        _ => None,
+    };
+    typed.unwrap_or_else(|| value.into())
+}
+
+/// A script value as the property value it is written as
+fn to_value(value: &Dynamic) -> String {
+    if let Ok(number) = value.as_float() {
+        number.to_string()
+    } else if let Some(text) = value.clone().try_cast::<String>() {
+        text
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl DigitalTwin {
+    /// Compile a Rhai script and run it on every tick from the next one on,
+    /// after the scripts attached before it
+    pub fn attach_script(&mut self, name: &str, source: &str) -> Result<(), TwinError> {
+        if name.is_empty() {
+            return Err(TwinError::invalid_argument("Script name must be non-empty"));
+        }
+        if self.scripts.scripts.iter().any(|s| s.name == name) {
+            return Err(TwinError::validation(
+                None,
+                format!("A script '{}' is already attached", name),
+            ));
+        }
+        let ast = self
+            .scripts
+            .engine
+            .compile(source)
+            .map_err(|e| TwinError::parse(format!("Script '{}': {}", name, e)))?;
+        self.scripts.scripts.push(Script {
+            name: name.to_string(),
+            ast,
+            state: Map::new(),
+        });
+        Ok(())
+    }
+
+    /// Stop running a script and drop its state and last error
+    pub fn detach_script(&mut self, name: &str) -> Result<(), TwinError> {
+        let registry = &mut self.scripts;
+        let index = registry
+            .scripts
+            .iter()
+            .position(|s| s.name == name)
+            .ok_or_else(|| TwinError::not_found(name, format!("No script '{}'", name)))?;
+        registry.scripts.remove(index);
+        registry.errors.remove(name);
+        Ok(())
+    }
+
+    /// Names of the attached scripts in the order they run
+    pub fn list_scripts(&self) -> Vec<String> {
+        self.scripts
+            .scripts
+            .iter()
+            .map(|s| s.name.clone())
+            .collect()
+    }
+
+    /// Why the last run of a script failed, None if it succeeded
+    pub fn get_script_error(&self, name: &str) -> Option<String> {
+        self.scripts.errors.get(name).cloned()
+    }
+}
+
+impl DigitalTwin {
+    /// Run every script once; a failing script keeps its error and does not
+    /// stop the others
+    pub(crate) fn run_scripts(&mut self, dt: f64) {
+        for index in 0..self.scripts.scripts.len() {
+            let name = self.scripts.scripts[index].name.clone();
+            match self.run_script(index, dt) {
+                Ok(()) => self.scripts.errors.remove(&name),
+                Err(e) => self.scripts.errors.insert(name, e),
+            };
+        }
+    }
+
+    fn run_script(&mut self, index: usize, dt: f64) -> Result<(), String> {
+        let props: Map = self
+            .elements()
+            .filter_map(|(path, element)| match &element.kind {
+                ElementKind::Property(p) => Some((path.into(), to_dynamic(&p.value, p.value_type))),
+                _ => None,
+            })
+            .collect();
+        let to_map = |values: &crate::SimulationValues| -> Map {
+            values
+                .iter()
+                .map(|(key, value)| (key.into(), Dynamic::from(*value)))
+                .collect()
+        };
+        let inputs = to_map(self.simulation.inputs());
+        let mut scope = Scope::new();
+        scope.push("props", props.clone());
+        scope.push_constant("sim", to_map(self.simulation.values()));
+        scope.push("inputs", inputs.clone());
+        scope.push_constant("dt", dt);
+        scope.push_constant("time", self.simulation.elapsed());
+        scope.push_constant("tick", i64::from(self.tick_count));
+
+        let registry = &mut self.scripts;
+        let script = &mut registry.scripts[index];
+        scope.push("state", std::mem::take(&mut script.state));
+        let result = registry
+            .engine
+            .run_ast_with_scope(&mut scope, &script.ast)
+            .map_err(|e| e.to_string());
+        script.state = scope.get_value("state").unwrap_or_default();
+        result?;
+
+        let written = |name: &str, before: &Map| -> Vec<(String, Dynamic)> {
+            let after: Map = scope.get_value(name).unwrap_or_default();
+            after
+                .into_iter()
+                .filter(|(key, value)| {
+                    before.get(key.as_str()).map(to_value) != Some(to_value(value))
+                })
+                .map(|(key, value)| (key.to_string(), value))
+                .collect()
+        };
+        for (name, value) in written("inputs", &inputs) {
+            let value = value
+                .as_float()
+                .map_err(|_| format!("Input '{}' must be a number", name))?;
+            self.set_simulation_input(&name, value);
+        }
+        for (path, value) in written("props", &props) {
+            self.set_property(&path, &to_value(&value))
+                .map_err(|e| e.to_string())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn twin() -> DigitalTwin {
+        DigitalTwin::new(
+            r#"{"id": "M-1", "asset_type": "Motor", "nameplate": [
+                {"id_short": "Speed", "value": "0", "value_type": "xs:double"},
+                {"id_short": "Status", "value": "idle"}
+            ]}"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_script_reads_and_writes_twin() {
+        let mut twin = twin();
+        twin.attach_script(
+            "mirror",
+            r#"
+                props["Speed"] = sim["motor.rpm"];
+                if props["Speed"] > 1000.0 { props["Status"] = "running"; }
+                state.runs = (state.runs ?? 0) + 1;
+                if state.runs == 3 { inputs["load"] = 0.5; }
+            "#,
+        )
+        .unwrap();
+        for _ in 0..20 {
+            twin.advance_simulation();
+        }
+        assert_eq!(twin.get_script_error("mirror"), None);
+        let rpm = twin.simulation_values()["motor.rpm"];
+        assert_eq!(twin.get_property_f64("Speed").unwrap(), rpm);
+        assert_eq!(twin.get_property_display("Status"), "running ");
+        assert_eq!(twin.get_load(), 0.5);
+        assert_eq!(twin.list_scripts(), ["mirror"]);
+    }
+
+    #[test]
+    fn test_script_errors_are_kept() {
+        let mut twin = twin();
+        assert!(twin
+            .attach_script("broken", "props[")
+            .is_err_and(|e| e.code() == "PARSE_ERROR"));
+        twin.attach_script("typo", r#"props["Missing"] = 1;"#)
+            .unwrap();
+        twin.attach_script("forever", "loop {}").unwrap();
+        assert!(twin.attach_script("typo", "").is_err());
+        twin.advance_simulation();
+        assert!(twin.get_script_error("typo").unwrap().contains("Missing"));
+        assert!(twin.get_script_error("forever").is_some());
+
+        twin.detach_script("typo").unwrap();
+        twin.detach_script("forever").unwrap();
+        assert_eq!(twin.get_script_error("typo"), None);
+        assert!(twin.list_scripts().is_empty());
+        assert!(twin.detach_script("typo").is_err());
+    }
+}
@@ -0,0 +1,167 @@
+// --- Warm start ---
+// The simulation state as plain JSON, so a twin can be persisted mid-run and
+// resumed later, even in another session: the model outputs, the state the
+// models keep beyond them (the noise-free winding temperature, the drift of
+// a sensor), the inputs, the operational state and the noise generator.
+// Unlike `TwinState` it leaves the information model out. Numbers are read
+// back exactly, so a resumed run continues bit for bit; the generator's
+// 64-bit words are written as decimal strings, which JavaScript numbers
+// could not hold exactly. Telemetry readings are not part of it; they are
+// stale by the time a twin is resumed.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "wasm")]
+use tsify::Tsify;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{DigitalTwin, OperationalState, SimulationRng, SimulationValues, TwinError};
+
+/// Everything needed to continue a simulation where it stood
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[cfg_attr(feature = "wasm", tsify(missing_as_null))]
+#[serde(deny_unknown_fields)]
+pub struct SimulationState {
+    /// Simulated seconds since the last reset
+    pub time: f64,
+    pub tick_count: u32,
+    /// Seed the simulation restarts from on reset
+    pub seed: String,
+    /// State of the noise generator
+    pub rng: String,
+    /// Outputs of the models, keyed like "motor.rpm"
+    pub values: SimulationValues,
+    /// State of each model beyond its outputs, by model name
+    #[serde(default)]
+    pub models: BTreeMap<String, SimulationValues>,
+    #[serde(default)]
+    pub inputs: SimulationValues,
+    /// Accumulated random-walk drift of noisy signals
+    #[serde(default)]
+    pub noise_drift: SimulationValues,
+    /// Operational state and the simulated time it was entered at, none
+    /// while the machine sits in its initial state
+    #[serde(default)]
+    pub operational_state: Option<OperationalState>,
+    #[serde(default)]
+    pub state_since: f64,
+}
+
+fn parse_word(name: &str, word: &str) -> Result<u64, TwinError> {
+    word.parse()
+        .map_err(|_| TwinError::parse(format!("Invalid {} '{}' in simulation state", name, word)))
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl DigitalTwin {
+    /// The `SimulationState` as JSON
+    pub fn export_sim_state(&self) -> String {
+        serde_json::to_string(&self.simulation_state()).unwrap_or_else(|_| "{}".to_string())
+    }
+
+    /// Continue from a state written by `export_sim_state`; values of models
+    /// not attached to this twin are ignored
+    pub fn import_sim_state(&mut self, state_json: &str) -> Result<(), TwinError> {
+        let state = serde_json::from_str(state_json)
+            .map_err(|e| TwinError::parse(format!("Invalid simulation state: {}", e)))?;
+        self.set_simulation_state(&state)
+    }
+}
+
+impl DigitalTwin {
+    /// Rust-side `export_sim_state`
+    pub fn simulation_state(&self) -> SimulationState {
+        let (operational_state, state_since) = self.state_machine.entered();
+        SimulationState {
+            time: self.simulation_time(),
+            tick_count: self.tick_count,
+            seed: self.simulation.seed().to_string(),
+            rng: self.simulation.rng().state().to_string(),
+            values: self.simulation_values().clone(),
+            models: self.simulation.model_states(),
+            inputs: self.simulation.inputs().clone(),
+            noise_drift: self.simulation.noise_drift(),
+            operational_state,
+            state_since,
+        }
+    }
+
+    /// Rust-side `import_sim_state`
+    pub fn set_simulation_state(&mut self, state: &SimulationState) -> Result<(), TwinError> {
+        let seed = parse_word("seed", &state.seed)?;
+        let rng = parse_word("generator state", &state.rng)?;
+        if !state.time.is_finite() || state.time < 0.0 || !state.state_since.is_finite() {
+            return Err(TwinError::invalid_argument(format!(
+                "Simulation time must be a non-negative number of seconds, got {}",
+                state.time
+            )));
+        }
+        self.resume_models(&state.values, state.time);
+        self.set_seed(seed);
+        let registry = &mut self.simulation;
+        registry.restore_model_states(&state.models);
+        registry.set_inputs(state.inputs.clone());
+        registry.set_noise_drift(&state.noise_drift);
+        registry.set_rng(SimulationRng::new(rng));
+        self.state_machine
+            .set_entered(state.operational_state, state.state_since);
+        self.tick_count = state.tick_count;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"{"id": "M-1", "asset_type": "Motor", "nameplate": [],
+        "simulation": {"noise": {"motor.current": [{"type": "random_walk", "rate": 0.1}]}}}"#;
+
+    #[test]
+    fn test_warm_start_continues_exactly() {
+        let mut twin = DigitalTwin::new(CONFIG).unwrap();
+        twin.set_seed(u64::MAX - 1);
+        twin.inject_fault("sensor_drift", 0.5).unwrap();
+        twin.set_load(0.8).unwrap();
+        for _ in 0..40 {
+            twin.advance_by(0.5).unwrap();
+        }
+        let exported = twin.export_sim_state();
+
+        let mut resumed = DigitalTwin::new(CONFIG).unwrap();
+        resumed.import_sim_state(&exported).unwrap();
+        assert_eq!(resumed.export_sim_state(), exported);
+        for _ in 0..40 {
+            twin.advance_by(0.5).unwrap();
+            resumed.advance_by(0.5).unwrap();
+        }
+        assert_eq!(resumed.simulation_values(), twin.simulation_values());
+        assert_eq!(resumed.get_load(), 0.8);
+        assert_eq!(resumed.get_operational_state(), "Running");
+
+        resumed.reset_simulation();
+        twin.reset_simulation();
+        resumed.advance_simulation();
+        twin.advance_simulation();
+        assert_eq!(resumed.simulation_values(), twin.simulation_values());
+    }
+
+    #[test]
+    fn test_invalid_sim_state() {
+        let mut twin = DigitalTwin::new(CONFIG).unwrap();
+        let mut state = twin.simulation_state();
+        state.rng = "-1".to_string();
+        assert!(twin
+            .set_simulation_state(&state)
+            .is_err_and(|e| e.code() == "PARSE_ERROR"));
+        assert!(twin
+            .import_sim_state(r#"{"time": 1}"#)
+            .is_err_and(|e| e.code() == "PARSE_ERROR"));
+        state.rng = "1".to_string();
+        state.time = f64::NAN;
+        assert!(twin.set_simulation_state(&state).is_err());
+    }
+}
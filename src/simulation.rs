@@ -0,0 +1,1245 @@
+// --- Simulation models ---
+// The live behavior of a twin comes from simulation models attached to it by
+// name. Every tick steps them in the order they were attached; each one sees
+// the latest outputs of all models ("motor.rpm"), the external inputs
+// ("load"), the tick number and the simulated time as its inputs, so a
+// thermal model can follow the speed the motor model produced in the same
+// tick. New twins carry the motor model as "motor", the thermal model as
+// "thermal", the vibration model as "vibration" and the wear model as
+// "wear". Ticks advance the simulated time by the step the caller chooses,
+// so a render loop can step by its frame time. All noise comes from one
+// seeded generator, so the same seed replays the same values tick for tick
+// in every browser.
+
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+#[cfg(feature = "wasm")]
+use tsify::Tsify;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::history::SignalHistory;
+use crate::noise::{signal_noise, SignalNoise};
+use crate::telemetry::{telemetry_feeds, TelemetryFeed};
+#[cfg(feature = "wasm")]
+use crate::to_js;
+use crate::{
+    DigitalTwin, MotorModel, ProfileModel, PropertyChange, TelemetrySource, ThermalModel,
+    TwinError, VibrationModel, WearModel, THERMAL_MODEL, VIBRATION_MODEL, WEAR_MODEL,
+};
+
+/// Named simulation values, e.g. `{"motor.rpm": 1440.0}`
+pub type SimulationValues = BTreeMap<String, f64>;
+
+/// Name of the built-in motor model
+pub const MOTOR_MODEL: &str = "motor";
+
+/// Key of the motor speed among the simulation values
+pub(crate) const MOTOR_RPM: &str = "motor.rpm";
+
+/// Seconds per step of `tick_to` unless set otherwise
+pub const DEFAULT_STEP_SIZE: f64 = 0.1;
+
+/// Steps `tick_to` takes at most in one call, an hour at the default step
+/// size
+pub const MAX_CATCH_UP_STEPS: u32 = 36_000;
+
+/// Input carrying the number of the tick being computed
+pub const TICK_INPUT: &str = "tick";
+
+/// Input carrying the simulated time in seconds at the end of the tick
+pub const TIME_INPUT: &str = "time";
+
+/// All simulated signals after one tick
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+pub struct SimulationFrame {
+    pub tick: u32,
+    /// Simulated seconds since the last reset; the timestamp of every signal
+    pub time: f64,
+    /// Length of this tick in seconds
+    pub dt: f64,
+    pub signals: Vec<SimulationSignal>,
+}
+
+/// Latest value of a simulated signal with its unit
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[cfg_attr(feature = "wasm", tsify(missing_as_null))]
+pub struct LiveValue {
+    pub value: f64,
+    pub unit: Option<String>,
+}
+
+/// Evenly spaced samples of a waveform
+#[derive(Serialize, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+pub struct SampleBlock {
+    /// Samples per second
+    pub sample_rate: f64,
+    /// Simulated time of the first sample in seconds
+    pub start_time: f64,
+    pub samples: Vec<f64>,
+}
+
+/// One simulated signal, e.g. `motor.rpm`
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+pub struct SimulationSignal {
+    pub name: String,
+    pub value: f64,
+    /// Simulated time in seconds at which the value was computed
+    pub timestamp: f64,
+}
+
+/// Seed of new twins, so runs are reproducible unless a seed is chosen
+pub const DEFAULT_SEED: u64 = 0x5EED;
+
+/// Small, portable pseudo-random generator (SplitMix64) behind all
+/// simulation noise; it depends only on integer arithmetic, so every
+/// platform produces the same sequence for a seed
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SimulationRng {
+    state: u64,
+}
+
+impl SimulationRng {
+    pub fn new(seed: u64) -> SimulationRng {
+        SimulationRng { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// The generator's state; `SimulationRng::new` with it continues the
+    /// sequence
+    pub fn state(&self) -> u64 {
+        self.state
+    }
+
+    /// Uniform in [0, 1)
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Uniform in [-1, 1)
+    pub fn noise(&mut self) -> f64 {
+        self.next_f64() * 2.0 - 1.0
+    }
+
+    /// Standard normal (Box-Muller)
+    pub fn gaussian(&mut self) -> f64 {
+        let u = 1.0 - self.next_f64();
+        let v = self.next_f64();
+        (-2.0 * u.ln()).sqrt() * (2.0 * std::f64::consts::PI * v).cos()
+    }
+}
+
+/// Error of `set_parameter` for a name the model does not have
+pub fn unknown_parameter(name: &str) -> String {
+    format!("No parameter '{}'", name)
+}
+
+/// `value` if it is above zero, for `set_parameter`
+pub fn positive_parameter(name: &str, value: f64) -> Result<f64, String> {
+    if value > 0.0 {
+        Ok(value)
+    } else {
+        Err(format!(
+            "Parameter '{}' must be positive, got {}",
+            name, value
+        ))
+    }
+}
+
+/// Behavior that evolves part of the twin's state over time
+pub trait SimulationModel {
+    /// Reset to the initial state and return the initial outputs
+    fn init(&mut self) -> SimulationValues;
+
+    /// Advance by `dt` seconds and return the outputs, keyed by name
+    /// without the model prefix ("rpm"). Random variation must be drawn
+    /// from `rng` to keep seeded runs reproducible.
+    fn step(
+        &mut self,
+        dt: f64,
+        inputs: &SimulationValues,
+        rng: &mut SimulationRng,
+    ) -> SimulationValues;
+
+    /// Continue from outputs reported earlier, e.g. after restoring a
+    /// snapshot; models that derive their state from inputs need nothing
+    fn resume(&mut self, _outputs: &SimulationValues) {}
+
+    /// Unit of an output, e.g. "degC"
+    fn unit(&self, _output: &str) -> Option<&str> {
+        None
+    }
+
+    /// State beyond the outputs that `resume` cannot recover, e.g. an
+    /// accumulated error, for warm starts
+    fn internal_state(&self) -> SimulationValues {
+        SimulationValues::new()
+    }
+
+    /// Take back a state reported by `internal_state`, after `resume`
+    fn restore_internal_state(&mut self, _state: &SimulationValues) {}
+
+    /// Tunable parameters and their values, e.g. `{"time_constant": 2.0}`
+    fn parameters(&self) -> SimulationValues {
+        SimulationValues::new()
+    }
+
+    /// Change one of `parameters` to a finite value; the model continues
+    /// from its state with the new value
+    fn set_parameter(&mut self, name: &str, _value: f64) -> Result<(), String> {
+        Err(unknown_parameter(name))
+    }
+
+    /// Time-domain samples of the last tick, for models that synthesize a
+    /// waveform
+    fn samples(&self) -> Option<&SampleBlock> {
+        None
+    }
+}
+
+/// The models attached to a twin and their latest outputs
+pub(crate) struct SimulationRegistry {
+    models: Vec<(String, Box<dyn SimulationModel>)>,
+    values: SimulationValues,
+    // Simulated seconds since the last reset
+    elapsed: f64,
+    // Restarted from the seed on every reset
+    seed: u64,
+    rng: SimulationRng,
+    // Set from outside, like the commanded load; kept across resets
+    inputs: SimulationValues,
+    // Seconds per step when following the wall clock
+    step_size: f64,
+    // Wall-clock time in ms the simulated time corresponds to, once
+    // `tick_to` started following it
+    clock: Option<f64>,
+    // Sensor noise on the outputs, by signal key
+    noise: BTreeMap<String, SignalNoise>,
+    // Signals fed from real readings while fresh, by signal key
+    telemetry: BTreeMap<String, TelemetryFeed>,
+    // Playback controls of the real-time ticks; kept across resets
+    paused: bool,
+    speed: f64,
+    // Latest values of every signal, restarted on resets
+    history: SignalHistory,
+    // Timestamp in ms and simulated time of the first telemetry record
+    // since the last reset, which later readings are placed relative to
+    telemetry_epoch: Option<(f64, f64)>,
+}
+
+impl Default for SimulationRegistry {
+    fn default() -> SimulationRegistry {
+        SimulationRegistry {
+            models: Vec::new(),
+            values: SimulationValues::new(),
+            elapsed: 0.0,
+            seed: DEFAULT_SEED,
+            rng: SimulationRng::new(DEFAULT_SEED),
+            inputs: SimulationValues::new(),
+            step_size: DEFAULT_STEP_SIZE,
+            clock: None,
+            noise: BTreeMap::new(),
+            telemetry: BTreeMap::new(),
+            paused: false,
+            speed: 1.0,
+            history: SignalHistory::default(),
+            telemetry_epoch: None,
+        }
+    }
+}
+
+impl SimulationRegistry {
+    /// Continue where `other` stands, including its noise sequence
+    pub(crate) fn continue_from(&mut self, other: &SimulationRegistry) {
+        self.resume(&other.values, other.elapsed);
+        self.seed = other.seed;
+        self.rng = other.rng;
+        self.inputs = other.inputs.clone();
+        self.step_size = other.step_size;
+        self.clock = other.clock;
+        self.noise = other.noise.clone();
+        self.telemetry = other.telemetry.clone();
+        self.paused = other.paused;
+        self.speed = other.speed;
+        self.history = other.history.clone();
+        self.telemetry_epoch = other.telemetry_epoch;
+    }
+
+    pub(crate) fn value(&self, key: &str) -> Option<f64> {
+        self.values.get(key).copied()
+    }
+
+    pub(crate) fn values(&self) -> &SimulationValues {
+        &self.values
+    }
+
+    pub(crate) fn inputs(&self) -> &SimulationValues {
+        &self.inputs
+    }
+
+    pub(crate) fn set_inputs(&mut self, inputs: SimulationValues) {
+        self.inputs = inputs;
+    }
+
+    pub(crate) fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub(crate) fn step_size(&self) -> f64 {
+        self.step_size
+    }
+
+    pub(crate) fn history(&self) -> &SignalHistory {
+        &self.history
+    }
+
+    pub(crate) fn history_mut(&mut self) -> &mut SignalHistory {
+        &mut self.history
+    }
+
+    /// Simulated time at which a reading stamped `timestamp_ms` was taken
+    pub(crate) fn reading_time(&mut self, timestamp_ms: f64) -> f64 {
+        let elapsed = self.elapsed;
+        let (epoch_ms, epoch) = *self.telemetry_epoch.get_or_insert((timestamp_ms, elapsed));
+        epoch + (timestamp_ms - epoch_ms) / 1000.0
+    }
+
+    /// Parameters of all models, keyed like "motor.time_constant"
+    pub(crate) fn parameters(&self) -> SimulationValues {
+        self.models
+            .iter()
+            .flat_map(|(name, model)| {
+                model
+                    .parameters()
+                    .into_iter()
+                    .map(move |(key, value)| (format!("{}.{}", name, key), value))
+            })
+            .collect()
+    }
+
+    /// Set a parameter keyed like "motor.time_constant"
+    pub(crate) fn set_parameter(&mut self, key: &str, value: f64) -> Result<(), TwinError> {
+        let not_found = || TwinError::not_found(key, format!("No simulation parameter '{}'", key));
+        let (name, parameter) = key.split_once('.').ok_or_else(not_found)?;
+        let (_, model) = self
+            .models
+            .iter_mut()
+            .find(|(n, _)| n == name)
+            .ok_or_else(not_found)?;
+        if !model.parameters().contains_key(parameter) {
+            return Err(not_found());
+        }
+        if !value.is_finite() {
+            return Err(TwinError::invalid_argument(format!(
+                "Parameter '{}' must be a finite number, got {}",
+                key, value
+            )));
+        }
+        model
+            .set_parameter(parameter, value)
+            .map_err(TwinError::invalid_argument)
+    }
+
+    /// Internal state of the models that have any, by model name
+    pub(crate) fn model_states(&self) -> BTreeMap<String, SimulationValues> {
+        self.models
+            .iter()
+            .map(|(name, model)| (name.clone(), model.internal_state()))
+            .filter(|(_, state)| !state.is_empty())
+            .collect()
+    }
+
+    pub(crate) fn restore_model_states(&mut self, states: &BTreeMap<String, SimulationValues>) {
+        for (name, model) in &mut self.models {
+            if let Some(state) = states.get(name) {
+                model.restore_internal_state(state);
+            }
+        }
+    }
+
+    /// Drift of the noisy signals that accumulated any
+    pub(crate) fn noise_drift(&self) -> SimulationValues {
+        self.noise
+            .iter()
+            .filter(|(_, noise)| noise.drift() != 0.0)
+            .map(|(key, noise)| (key.clone(), noise.drift()))
+            .collect()
+    }
+
+    pub(crate) fn set_noise_drift(&mut self, drift: &SimulationValues) {
+        for (key, noise) in &mut self.noise {
+            noise.set_drift(drift.get(key).copied().unwrap_or(0.0));
+        }
+    }
+
+    pub(crate) fn elapsed(&self) -> f64 {
+        self.elapsed
+    }
+
+    /// The noise generator as it stands, to continue a run exactly
+    pub(crate) fn rng(&self) -> SimulationRng {
+        self.rng
+    }
+
+    pub(crate) fn set_rng(&mut self, rng: SimulationRng) {
+        self.rng = rng;
+    }
+
+    /// Replace the noise models, keeping the drift of signals that keep
+    /// theirs
+    pub(crate) fn set_noise(&mut self, mut noise: BTreeMap<String, SignalNoise>) {
+        for (key, signal) in &mut noise {
+            if let Some(old) = self.noise.get(key).filter(|old| old.same_models(signal)) {
+                *signal = old.clone();
+            }
+        }
+        self.noise = noise;
+    }
+
+    /// Map the signals in `config` to telemetry, keeping the readings of
+    /// those mapped before
+    pub(crate) fn set_telemetry(&mut self, config: &BTreeMap<String, TelemetrySource>) {
+        self.telemetry = telemetry_feeds(config, &self.telemetry);
+    }
+
+    pub(crate) fn telemetry(&self) -> &BTreeMap<String, TelemetryFeed> {
+        &self.telemetry
+    }
+
+    pub(crate) fn telemetry_feed(&mut self, key: &str) -> Option<&mut TelemetryFeed> {
+        self.telemetry.get_mut(key)
+    }
+
+    pub(crate) fn attach(&mut self, name: &str, mut model: Box<dyn SimulationModel>) {
+        let initial = model.init();
+        self.record(name, initial);
+        self.models.push((name.to_string(), model));
+    }
+
+    /// Swap the model attached under `name` for another, keeping its place
+    /// in the stepping order; the new model starts from its initial state
+    fn replace(&mut self, name: &str, mut model: Box<dyn SimulationModel>) -> bool {
+        let Some(index) = self.models.iter().position(|(n, _)| n == name) else {
+            return false;
+        };
+        let initial = model.init();
+        let prefix = format!("{}.", name);
+        self.values.retain(|key, _| !key.starts_with(&prefix));
+        self.record(name, initial);
+        self.models[index].1 = model;
+        true
+    }
+
+    fn record(&mut self, name: &str, outputs: SimulationValues) {
+        for (key, value) in outputs {
+            self.values.insert(format!("{}.{}", name, key), value);
+        }
+    }
+
+    /// Continue every model from its outputs in `values`; values of models
+    /// not attached here are ignored
+    pub(crate) fn resume(&mut self, values: &SimulationValues, elapsed: f64) {
+        self.elapsed = elapsed;
+        self.clock = None;
+        self.history.clear();
+        self.telemetry_epoch = None;
+        for (name, model) in &mut self.models {
+            let prefix = format!("{}.", name);
+            let outputs: SimulationValues = values
+                .iter()
+                .filter_map(|(key, value)| Some((key.strip_prefix(&prefix)?.to_string(), *value)))
+                .collect();
+            model.resume(&outputs);
+            for (key, value) in outputs {
+                self.values.insert(format!("{}{}", prefix, key), value);
+            }
+        }
+    }
+
+    fn reset(&mut self) {
+        self.values.clear();
+        self.elapsed = 0.0;
+        self.clock = None;
+        self.rng = SimulationRng::new(self.seed);
+        self.noise.values_mut().for_each(SignalNoise::reset);
+        self.telemetry.values_mut().for_each(TelemetryFeed::reset);
+        for i in 0..self.models.len() {
+            let initial = self.models[i].1.init();
+            let name = self.models[i].0.clone();
+            self.record(&name, initial);
+        }
+        self.history.clear();
+        self.history.record(&self.values, 0.0);
+        self.telemetry_epoch = None;
+    }
+
+    fn step(&mut self, dt: f64, tick: u32) {
+        self.elapsed += dt;
+        for i in 0..self.models.len() {
+            let mut inputs = self.values.clone();
+            inputs.extend(self.inputs.iter().map(|(k, v)| (k.clone(), *v)));
+            inputs.insert(TICK_INPUT.to_string(), tick as f64);
+            inputs.insert(TIME_INPUT.to_string(), self.elapsed);
+            let mut outputs = self.models[i].1.step(dt, &inputs, &mut self.rng);
+            let name = self.models[i].0.clone();
+            for (key, value) in &mut outputs {
+                let key = format!("{}.{}", name, key);
+                if let Some(noise) = self.noise.get_mut(&key) {
+                    *value = noise.apply(*value, dt, &mut self.rng);
+                }
+                if let Some(reading) = self
+                    .telemetry
+                    .get(&key)
+                    .and_then(|t| t.live_value(self.elapsed))
+                {
+                    *value = reading;
+                }
+            }
+            self.record(&name, outputs);
+        }
+        self.history.record(&self.values, self.elapsed);
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl DigitalTwin {
+    /// Names of the attached simulation models, comma-separated
+    pub fn list_simulation_models(&self) -> String {
+        self.simulation
+            .models
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Detach a simulation model; its outputs are dropped
+    pub fn detach_simulation_model(&mut self, name: &str) -> Result<(), TwinError> {
+        let registry = &mut self.simulation;
+        let index = registry
+            .models
+            .iter()
+            .position(|(n, _)| n == name)
+            .ok_or_else(|| TwinError::not_found(name, format!("No simulation model '{}'", name)))?;
+        registry.models.remove(index);
+        let prefix = format!("{}.", name);
+        registry.values.retain(|key, _| !key.starts_with(&prefix));
+        Ok(())
+    }
+
+    /// Advance all models by `dt_seconds` and return the `SimulationFrame`
+    /// with every signal; dt may differ from tick to tick (frame time)
+    #[cfg(feature = "wasm")]
+    #[wasm_bindgen(unchecked_return_type = "SimulationFrame")]
+    pub fn tick(&mut self, dt_seconds: f64) -> Result<JsValue, TwinError> {
+        Ok(to_js(&self.advance_by(dt_seconds)?))
+    }
+
+    /// Advance all models by `dt` seconds and return their outputs as a JSON
+    /// object (`{"motor.rpm": 10.5}`)
+    pub fn step_simulation(&mut self, dt: f64) -> Result<String, TwinError> {
+        self.advance_by(dt)?;
+        Ok(serde_json::to_string(self.simulation.values()).unwrap_or_else(|_| "{}".to_string()))
+    }
+
+    /// Seed the noise of all models and restart its sequence; the same seed
+    /// makes the following ticks repeat exactly
+    pub fn set_seed(&mut self, seed: u64) {
+        self.simulation.seed = seed;
+        self.simulation.rng = SimulationRng::new(seed);
+    }
+
+    /// The latest value and unit of every simulated signal, keyed like
+    /// "motor.temperature"
+    #[cfg(feature = "wasm")]
+    #[wasm_bindgen(unchecked_return_type = "Record<string, LiveValue>")]
+    pub fn get_live_values(&self) -> JsValue {
+        to_js(&self.live_values())
+    }
+
+    /// The time-domain samples a model synthesized in the last tick, e.g.
+    /// the "vibration" waveform, as a `SampleBlock`
+    #[cfg(feature = "wasm")]
+    #[wasm_bindgen(unchecked_return_type = "SampleBlock")]
+    pub fn get_samples(&self, model: &str) -> Result<JsValue, TwinError> {
+        Ok(to_js(self.samples(model)?))
+    }
+
+    /// Simulated seconds since the last reset
+    pub fn simulation_time(&self) -> f64 {
+        self.simulation.elapsed()
+    }
+
+    /// The latest outputs of all models as a JSON object
+    pub fn get_simulation_values(&self) -> String {
+        serde_json::to_string(self.simulation.values()).unwrap_or_else(|_| "{}".to_string())
+    }
+
+    /// Advance the simulation in steps of `set_step_size` seconds until it
+    /// has caught up with the wall-clock time `timestamp_ms` (e.g.
+    /// `Date.now()`) at the playback speed and return the number of steps
+    /// taken. The first call starts the clock; time left over for less than
+    /// a step carries over to the next call, so the twin stays in sync when
+    /// the tab is throttled. After more than `MAX_CATCH_UP_STEPS` behind, the
+    /// rest is skipped.
+    pub fn tick_to(&mut self, timestamp_ms: f64) -> Result<u32, TwinError> {
+        if !timestamp_ms.is_finite() {
+            return Err(TwinError::invalid_argument(format!(
+                "Timestamp must be a number of milliseconds, got {}",
+                timestamp_ms
+            )));
+        }
+        let clock = match self.simulation.clock {
+            Some(clock) if !self.simulation.paused => clock,
+            // While paused the clock moves on without the simulation
+            _ => {
+                self.simulation.clock = Some(timestamp_ms);
+                return Ok(0);
+            }
+        };
+        let step_ms = self.simulation.step_size * 1000.0 / self.simulation.speed;
+        let behind = ((timestamp_ms - clock) / step_ms).floor().max(0.0);
+        let steps = behind.min(f64::from(MAX_CATCH_UP_STEPS)) as u32;
+        for _ in 0..steps {
+            self.step_models(self.simulation.step_size);
+        }
+        self.simulation.clock = Some(if behind > f64::from(MAX_CATCH_UP_STEPS) {
+            timestamp_ms
+        } else {
+            clock + f64::from(steps) * step_ms
+        });
+        Ok(steps)
+    }
+
+    /// Stop the real-time ticks (`tick`, `step_simulation`,
+    /// `tick_simulation` and `tick_to`) until `resume`; batch runs, scenarios
+    /// and co-simulation steps are not affected
+    pub fn pause(&mut self) {
+        self.simulation.paused = true;
+    }
+
+    /// Continue the real-time ticks; `tick_to` continues from the time of
+    /// its next call, without catching up on the pause
+    pub fn resume(&mut self) {
+        self.simulation.paused = false;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.simulation.paused
+    }
+
+    /// Simulated seconds per second of the real-time ticks, e.g. 10 for
+    /// fast-forward
+    pub fn set_speed(&mut self, multiplier: f64) -> Result<(), TwinError> {
+        if !multiplier.is_finite() || multiplier <= 0.0 {
+            return Err(TwinError::invalid_argument(format!(
+                "Speed must be a positive multiplier, got {}",
+                multiplier
+            )));
+        }
+        self.simulation.speed = multiplier;
+        Ok(())
+    }
+
+    pub fn get_speed(&self) -> f64 {
+        self.simulation.speed
+    }
+
+    /// Seconds of simulated time per step of `tick_to`
+    pub fn set_step_size(&mut self, seconds: f64) -> Result<(), TwinError> {
+        if !seconds.is_finite() || seconds <= 0.0 {
+            return Err(TwinError::invalid_argument(format!(
+                "Step size must be a positive number of seconds, got {}",
+                seconds
+            )));
+        }
+        self.simulation.step_size = seconds;
+        Ok(())
+    }
+
+    /// The tunable parameters of all models as an object keyed like
+    /// "motor.time_constant"
+    #[cfg(feature = "wasm")]
+    #[wasm_bindgen(unchecked_return_type = "Record<string, number>")]
+    pub fn get_sim_params(&self) -> JsValue {
+        to_js(&self.simulation_parameters())
+    }
+
+    /// Change a model parameter ("motor.rated_speed", "thermal.time_constant",
+    /// "vibration.noise") from the next tick on; it is kept in the
+    /// simulation profile, so copies and restarts of the twin keep it
+    pub fn set_sim_param(&mut self, name: &str, value: f64) -> Result<(), TwinError> {
+        self.simulation.set_parameter(name, value)?;
+        self.profile.parameters.insert(name.to_string(), value);
+        Ok(())
+    }
+
+    /// Names of the model outputs in the order `tick_n` reports them
+    pub fn get_simulation_keys(&self) -> Vec<String> {
+        self.simulation.values().keys().cloned().collect()
+    }
+
+    /// Advance `count` steps (at most `MAX_CATCH_UP_STEPS`) of `dt` seconds
+    /// in one call, for fast-forward playback. Returns a Float64Array with a
+    /// row per step: the simulated time followed by the outputs in the order
+    /// `get_simulation_keys` gave before the call; outputs a model starts
+    /// emitting on the way are left out, outputs it stops emitting are NaN.
+    pub fn tick_n(&mut self, count: u32, dt: f64) -> Result<Vec<f64>, TwinError> {
+        check_time_step(dt)?;
+        if count > MAX_CATCH_UP_STEPS {
+            return Err(TwinError::invalid_argument(format!(
+                "At most {} steps can be taken at once, got {}",
+                MAX_CATCH_UP_STEPS, count
+            )));
+        }
+        let keys = self.get_simulation_keys();
+        let mut rows = Vec::new();
+        for _ in 0..count {
+            self.step_models(dt);
+            rows.push(self.simulation.elapsed());
+            let values = self.simulation.values();
+            rows.extend(
+                keys.iter()
+                    .map(|key| values.get(key).copied().unwrap_or(f64::NAN)),
+            );
+        }
+        Ok(rows)
+    }
+}
+
+fn check_time_step(dt: f64) -> Result<(), TwinError> {
+    if !dt.is_finite() || dt < 0.0 {
+        return Err(TwinError::invalid_argument(format!(
+            "Time step must be a non-negative number of seconds, got {}",
+            dt
+        )));
+    }
+    Ok(())
+}
+
+impl DigitalTwin {
+    /// Attach a model under a unique name; it is initialized right away and
+    /// stepped after the models attached before it
+    pub fn attach_simulation_model(
+        &mut self,
+        name: &str,
+        model: Box<dyn SimulationModel>,
+    ) -> Result<(), TwinError> {
+        if name.is_empty() || name.contains('.') {
+            return Err(TwinError::invalid_argument(format!(
+                "Simulation model name '{}' must be non-empty and without '.'",
+                name
+            )));
+        }
+        if self.simulation.models.iter().any(|(n, _)| n == name) {
+            return Err(TwinError::validation(
+                None,
+                format!("A simulation model '{}' is already attached", name),
+            ));
+        }
+        self.simulation.attach(name, model);
+        Ok(())
+    }
+
+    /// Rust-side `tick`; `dt` must be a finite, non-negative number of
+    /// seconds of frame time, which the playback speed scales. While paused
+    /// nothing is stepped and the frame has a dt of 0.
+    pub fn advance_by(&mut self, dt: f64) -> Result<SimulationFrame, TwinError> {
+        check_time_step(dt)?;
+        let dt = self.playback_time(dt);
+        if !self.simulation.paused {
+            self.step_models(dt);
+        }
+        let time = self.simulation.elapsed();
+        Ok(SimulationFrame {
+            tick: self.tick_count,
+            time,
+            dt,
+            signals: self
+                .simulation
+                .values()
+                .iter()
+                .map(|(name, value)| SimulationSignal {
+                    name: name.clone(),
+                    value: *value,
+                    timestamp: time,
+                })
+                .collect(),
+        })
+    }
+
+    /// Swap an attached model for another under the same name
+    pub fn replace_simulation_model(
+        &mut self,
+        name: &str,
+        model: Box<dyn SimulationModel>,
+    ) -> Result<(), TwinError> {
+        if self.simulation.replace(name, model) {
+            Ok(())
+        } else {
+            Err(TwinError::not_found(
+                name,
+                format!("No simulation model '{}'", name),
+            ))
+        }
+    }
+
+    /// Set an input every model sees from the next tick on, e.g. "load";
+    /// inputs are kept when the simulation is reset
+    pub fn set_simulation_input(&mut self, name: &str, value: f64) {
+        self.simulation.inputs.insert(name.to_string(), value);
+    }
+
+    pub fn simulation_input(&self, name: &str) -> Option<f64> {
+        self.simulation.inputs.get(name).copied()
+    }
+
+    /// Rust-side `get_sim_params`
+    pub fn simulation_parameters(&self) -> SimulationValues {
+        self.simulation.parameters()
+    }
+
+    /// Remove an input, returning its value
+    pub fn remove_simulation_input(&mut self, name: &str) -> Option<f64> {
+        self.simulation.inputs.remove(name)
+    }
+
+    /// Rust-side `get_samples`
+    pub fn samples(&self, model_name: &str) -> Result<&SampleBlock, TwinError> {
+        let (_, model) = self
+            .simulation
+            .models
+            .iter()
+            .find(|(n, _)| n == model_name)
+            .ok_or_else(|| {
+                TwinError::not_found(model_name, format!("No simulation model '{}'", model_name))
+            })?;
+        model.samples().ok_or_else(|| {
+            TwinError::invalid_argument(format!("Simulation model '{}' has no samples", model_name))
+        })
+    }
+
+    /// Rust-side `get_live_values`
+    pub fn live_values(&self) -> BTreeMap<String, LiveValue> {
+        let registry = &self.simulation;
+        registry
+            .values()
+            .iter()
+            .map(|(key, value)| {
+                let unit = key.split_once('.').and_then(|(name, output)| {
+                    let (_, model) = registry.models.iter().find(|(n, _)| n == name)?;
+                    model.unit(output).map(str::to_string)
+                });
+                (
+                    key.clone(),
+                    LiveValue {
+                        value: *value,
+                        unit,
+                    },
+                )
+            })
+            .collect()
+    }
+
+    /// Rust-side `get_simulation_values`
+    pub fn simulation_values(&self) -> &SimulationValues {
+        self.simulation.values()
+    }
+
+    /// The models new twins carry: the motor, the thermal model, the
+    /// vibration model and the wear model, all configured from the nameplate,
+    /// unless the simulation profile leaves them out, followed by the models
+    /// of the profile
+    pub(crate) fn builtin_models(&self) -> SimulationRegistry {
+        let mut registry = SimulationRegistry {
+            noise: signal_noise(&self.profile.noise),
+            telemetry: telemetry_feeds(&self.profile.telemetry, &BTreeMap::new()),
+            ..SimulationRegistry::default()
+        };
+        if self.profile.builtin {
+            registry.attach(MOTOR_MODEL, Box::new(MotorModel::from_nameplate(self)));
+            registry.attach(THERMAL_MODEL, Box::new(ThermalModel::from_nameplate(self)));
+            registry.attach(VIBRATION_MODEL, Box::new(VibrationModel::default()));
+            registry.attach(WEAR_MODEL, Box::new(WearModel::from_nameplate(self)));
+        }
+        for model in &self.profile.models {
+            registry.attach(&model.name, Box::new(ProfileModel::new(model.clone())));
+        }
+        // Checked when the profile was set
+        for (key, value) in &self.profile.parameters {
+            let _ = registry.set_parameter(key, *value);
+        }
+        registry
+    }
+
+    /// Continue the attached models from earlier outputs
+    pub(crate) fn resume_models(&mut self, values: &SimulationValues, elapsed: f64) {
+        self.simulation.resume(values, elapsed);
+    }
+
+    /// Return all models to their initial state
+    pub(crate) fn reset_models(&mut self) {
+        self.simulation.reset();
+    }
+
+    /// Simulated seconds for `seconds` of frame time, 0 while paused
+    pub(crate) fn playback_time(&self, seconds: f64) -> f64 {
+        if self.simulation.paused {
+            0.0
+        } else {
+            seconds * self.simulation.speed
+        }
+    }
+
+    /// Advance by `seconds` in equal steps of at most the step size; a span
+    /// of more than `MAX_CATCH_UP_STEPS` steps is refused without stepping
+    pub(crate) fn advance_span(&mut self, seconds: f64) -> Result<(), TwinError> {
+        if seconds <= 0.0 {
+            return Ok(());
+        }
+        let steps = (seconds / self.simulation.step_size).ceil().max(1.0);
+        if !seconds.is_finite() || steps > f64::from(MAX_CATCH_UP_STEPS) {
+            return Err(TwinError::invalid_argument(format!(
+                "Advancing {} s at a step size of {} s takes more than {} steps",
+                seconds, self.simulation.step_size, MAX_CATCH_UP_STEPS
+            )));
+        }
+        for _ in 0..steps as u32 {
+            self.step_models(seconds / steps);
+        }
+        Ok(())
+    }
+
+    /// Count a tick and step every model, notifying change listeners of the
+    /// outputs under "Simulation.<model>.<output>"
+    pub(crate) fn step_models(&mut self, dt: f64) {
+        self.tick_count += 1;
+        let before = self.simulation.values().clone();
+        self.simulation.step(dt, self.tick_count);
+        let changes: Vec<PropertyChange> = self
+            .simulation
+            .values()
+            .iter()
+            .filter(|(key, value)| before.get(*key) != Some(value))
+            .map(|(key, value)| PropertyChange {
+                path: format!("Simulation.{}", key),
+                old_value: before.get(key).map(f64::to_string),
+                new_value: Some(value.to_string()),
+            })
+            .collect();
+        changes.iter().for_each(|change| self.notify_change(change));
+        self.check_limits(&before);
+        #[cfg(feature = "scripting")]
+        self.run_scripts(dt);
+        self.update_state();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Bearing temperature following the motor speed
+    struct HeatingModel {
+        temperature: f64,
+    }
+
+    impl SimulationModel for HeatingModel {
+        fn init(&mut self) -> SimulationValues {
+            self.temperature = 20.0;
+            SimulationValues::from([("temperature".to_string(), self.temperature)])
+        }
+
+        fn step(
+            &mut self,
+            dt: f64,
+            inputs: &SimulationValues,
+            _rng: &mut SimulationRng,
+        ) -> SimulationValues {
+            let rpm = inputs.get("motor.rpm").copied().unwrap_or(0.0);
+            self.temperature += dt * rpm / 10.0;
+            SimulationValues::from([("temperature".to_string(), self.temperature)])
+        }
+    }
+
+    #[test]
+    fn test_models_are_ticked_together() {
+        let mut twin =
+            DigitalTwin::new(r#"{"id": "M-1", "asset_type": "Pump", "nameplate": []}"#).unwrap();
+        assert_eq!(
+            twin.list_simulation_models(),
+            "motor, thermal, vibration, wear"
+        );
+        twin.attach_simulation_model("heating", Box::new(HeatingModel { temperature: 0.0 }))
+            .unwrap();
+        assert_eq!(twin.simulation_values()["heating.temperature"], 20.0);
+
+        let sample = twin.advance_simulation();
+        let values = twin.simulation_values();
+        assert_eq!(values["motor.rpm"], sample.rpm);
+        // The heating model saw the speed of the same tick
+        assert_eq!(values["heating.temperature"], 20.0 + sample.rpm / 10.0);
+
+        twin.reset_simulation();
+        assert_eq!(twin.simulation_values()["heating.temperature"], 20.0);
+        assert_eq!(twin.simulation_values()["motor.rpm"], 0.0);
+    }
+
+    #[test]
+    fn test_attach_and_detach_models() {
+        let mut twin =
+            DigitalTwin::new(r#"{"id": "M-1", "asset_type": "Pump", "nameplate": []}"#).unwrap();
+        let heating = || Box::new(HeatingModel { temperature: 0.0 });
+        assert!(twin
+            .attach_simulation_model(MOTOR_MODEL, heating())
+            .is_err_and(|e| e.code() == "VALIDATION_FAILED"));
+        assert!(twin
+            .attach_simulation_model("a.b", heating())
+            .is_err_and(|e| e.code() == "INVALID_ARGUMENT"));
+
+        twin.detach_simulation_model(MOTOR_MODEL).unwrap();
+        twin.detach_simulation_model(THERMAL_MODEL).unwrap();
+        twin.detach_simulation_model(VIBRATION_MODEL).unwrap();
+        twin.detach_simulation_model(WEAR_MODEL).unwrap();
+        assert_eq!(twin.list_simulation_models(), "");
+        assert_eq!(twin.advance_simulation().rpm, 0.0);
+        assert_eq!(twin.get_simulation_values(), "{}");
+        assert!(twin.detach_simulation_model(MOTOR_MODEL).is_err());
+    }
+
+    #[test]
+    fn test_variable_time_steps() {
+        let config = r#"{"id": "M-1", "asset_type": "Pump", "nameplate": []}"#;
+        let mut fixed = DigitalTwin::new(config).unwrap();
+        let mut frames = DigitalTwin::new(config).unwrap();
+        fixed.advance_simulation();
+        frames.advance_by(0.25).unwrap();
+        let frame = frames.advance_by(0.75).unwrap();
+        assert_eq!(frame.tick, 2);
+        assert_eq!(frame.time, 1.0);
+        assert_eq!(frame.dt, 0.75);
+        let rpm = frame.signals.iter().find(|s| s.name == MOTOR_RPM).unwrap();
+        assert_eq!((rpm.name.as_str(), rpm.timestamp), (MOTOR_RPM, 1.0));
+        // Two shorter steps cover about as much ramp-up as one full second
+        assert!((rpm.value - fixed.simulation_values()[MOTOR_RPM]).abs() < 1.0);
+
+        assert!(frames
+            .advance_by(-1.0)
+            .is_err_and(|e| e.code() == "INVALID_ARGUMENT"));
+        assert!(frames.advance_by(f64::NAN).is_err());
+        frames.reset_simulation();
+        assert_eq!(frames.simulation_time(), 0.0);
+    }
+
+    #[test]
+    fn test_seeded_runs_repeat() {
+        let config = r#"{"id": "M-1", "asset_type": "Pump", "nameplate": []}"#;
+        let run = |seed: u64| {
+            let mut twin = DigitalTwin::new(config).unwrap();
+            twin.set_seed(seed);
+            (0..5)
+                .map(|_| twin.advance_simulation().rpm)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(run(42), run(42));
+        assert_ne!(run(42), run(7));
+
+        // A reset replays the run from the seed
+        let mut twin = DigitalTwin::new(config).unwrap();
+        twin.set_seed(42);
+        twin.advance_simulation();
+        twin.reset_simulation();
+        let replay: Vec<f64> = (0..5).map(|_| twin.advance_simulation().rpm).collect();
+        assert_eq!(replay, run(42));
+
+        let mut rng = SimulationRng::new(1);
+        assert!((0..100)
+            .map(|_| rng.noise())
+            .all(|n| (-1.0..1.0).contains(&n)));
+    }
+
+    #[test]
+    fn test_coupled_motor_signals() {
+        let config = r#"{"id": "M-1", "asset_type": "Pump", "nameplate": []}"#;
+        let mut twin = DigitalTwin::new(config).unwrap();
+        let initial = twin.live_values();
+        assert_eq!(initial["thermal.winding_temperature"].value, 25.0);
+        assert_eq!(initial["motor.torque"].unit.as_deref(), Some("Nm"));
+        for _ in 0..120 {
+            twin.advance_by(1.0).unwrap();
+        }
+        let live = twin.live_values();
+        let value = |signal: &str| live[signal].value;
+        assert_eq!(
+            live.keys().map(String::as_str).collect::<Vec<_>>(),
+            [
+                "motor.current",
+                "motor.power",
+                "motor.rpm",
+                "motor.slip",
+                "motor.torque",
+                "thermal.overtemperature",
+                "thermal.winding_temperature",
+                "vibration.peak",
+                "vibration.rms",
+                "vibration.shaft_frequency",
+                "wear.damage",
+                "wear.rate",
+                "wear.rul"
+            ]
+        );
+        // Faster means more load, more current, more heat and more vibration
+        assert!(value("motor.rpm") > 1000.0);
+        assert!(value("motor.torque") > initial["motor.torque"].value);
+        assert!(value("motor.current") > initial["motor.current"].value);
+        assert!(value("thermal.winding_temperature") > 25.0);
+        assert!(value("vibration.rms") > 1.0);
+    }
+
+    #[test]
+    fn test_tick_n_matches_single_ticks() {
+        let config = r#"{"id": "M-1", "asset_type": "Pump", "nameplate": []}"#;
+        let mut batched = DigitalTwin::new(config).unwrap();
+        let mut single = DigitalTwin::new(config).unwrap();
+        let keys = batched.get_simulation_keys();
+        let rows = batched.tick_n(50, 0.1).unwrap();
+        assert_eq!(rows.len(), 50 * (1 + keys.len()));
+        for row in rows.chunks(1 + keys.len()) {
+            let frame = single.advance_by(0.1).unwrap();
+            assert_eq!(row[0], frame.time);
+            let values: Vec<f64> = frame.signals.iter().map(|s| s.value).collect();
+            assert_eq!(&row[1..], values.as_slice());
+        }
+        assert_eq!(batched.status().last_tick, 50);
+        assert!(batched.tick_n(1, f64::NAN).is_err());
+        assert!(batched.tick_n(0, 1.0).unwrap().is_empty());
+        assert!(batched
+            .tick_n(MAX_CATCH_UP_STEPS + 1, 0.1)
+            .is_err_and(|e| e.code() == "INVALID_ARGUMENT"));
+        assert_eq!(batched.status().last_tick, 50);
+
+        // An output that only appears after init keeps the rows aligned
+        struct LateModel;
+        impl SimulationModel for LateModel {
+            fn init(&mut self) -> SimulationValues {
+                SimulationValues::new()
+            }
+
+            fn step(
+                &mut self,
+                _dt: f64,
+                _inputs: &SimulationValues,
+                _rng: &mut SimulationRng,
+            ) -> SimulationValues {
+                SimulationValues::from([("alarm".to_string(), 1.0)])
+            }
+        }
+        batched
+            .attach_simulation_model("late", Box::new(LateModel))
+            .unwrap();
+        let keys = batched.get_simulation_keys();
+        let rows = batched.tick_n(3, 0.1).unwrap();
+        assert_eq!(batched.get_simulation_keys().len(), keys.len() + 1);
+        assert_eq!(rows.len(), 3 * (1 + keys.len()));
+        let rpm = 1 + keys.iter().position(|k| k == MOTOR_RPM).unwrap();
+        assert_eq!(
+            rows[2 * (1 + keys.len()) + rpm],
+            batched.simulation_values()[MOTOR_RPM]
+        );
+    }
+
+    #[test]
+    fn test_tick_to_follows_wall_clock() {
+        let config = r#"{"id": "M-1", "asset_type": "Pump", "nameplate": []}"#;
+        let mut twin = DigitalTwin::new(config).unwrap();
+        let start = 1_700_000_000_000.0;
+        assert_eq!(twin.tick_to(start).unwrap(), 0);
+        assert_eq!(twin.tick_to(start + 250.0).unwrap(), 2);
+        // The remaining 50 ms count towards the next step
+        assert_eq!(twin.tick_to(start + 310.0).unwrap(), 1);
+        assert!((twin.simulation_time() - 0.3).abs() < 1e-9);
+        assert_eq!(twin.tick_to(start).unwrap(), 0);
+
+        // A long sleep is caught up to the limit and then skipped
+        let late = start + 300.0 + 2.0 * 3_600_000.0;
+        assert_eq!(twin.tick_to(late).unwrap(), MAX_CATCH_UP_STEPS);
+        assert_eq!(twin.tick_to(late + 99.0).unwrap(), 0);
+
+        twin.set_step_size(1.0).unwrap();
+        assert!(twin.set_step_size(0.0).is_err());
+        assert!(twin.tick_to(f64::NAN).is_err());
+        twin.reset_simulation();
+        assert_eq!(twin.tick_to(late + 5000.0).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_sim_params() {
+        let mut twin = DigitalTwin::new(
+            r#"{"id": "M-1", "asset_type": "Motor", "nameplate": [],
+                "simulation": {"parameters": {"motor.time_constant": 0.5}}}"#,
+        )
+        .unwrap();
+        let params = twin.simulation_parameters();
+        assert_eq!(params["motor.time_constant"], 0.5);
+        assert_eq!(params["thermal.ambient_temperature"], 25.0);
+
+        twin.set_sim_param("motor.rated_speed", 1470.0).unwrap();
+        for _ in 0..20 {
+            twin.advance_simulation();
+        }
+        assert!((twin.simulation_values()["motor.rpm"] - 1470.0).abs() < 2.0);
+        let copy = twin.clone_twin();
+        assert_eq!(copy.simulation_parameters()["motor.rated_speed"], 1470.0);
+
+        let mut error =
+            |name: &str, value: f64| twin.set_sim_param(name, value).unwrap_err().code();
+        assert_eq!(error("motor.torque", 1.0), "NOT_FOUND");
+        assert_eq!(error("pump.time_constant", 1.0), "NOT_FOUND");
+        assert_eq!(error("motor.time_constant", -1.0), "INVALID_ARGUMENT");
+        assert_eq!(error("motor.breakdown_ratio", 0.9), "INVALID_ARGUMENT");
+        assert_eq!(error("thermal.time_constant", f64::NAN), "INVALID_ARGUMENT");
+        assert!(DigitalTwin::new(
+            r#"{"id": "M-1", "asset_type": "Motor", "nameplate": [],
+                "simulation": {"parameters": {"motor.rpm": 3000}}}"#
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_pause_resume_and_speed() {
+        let config = r#"{"id": "M-1", "asset_type": "Motor", "nameplate": []}"#;
+        let mut twin = DigitalTwin::new(config).unwrap();
+        twin.set_speed(10.0).unwrap();
+        let frame = twin.advance_by(0.05).unwrap();
+        assert_eq!((frame.tick, frame.dt), (1, 0.5));
+        twin.advance_simulation();
+        assert!((twin.simulation_time() - 10.5).abs() < 1e-9);
+
+        twin.pause();
+        let frame = twin.advance_by(0.05).unwrap();
+        twin.advance_simulation();
+        assert_eq!((frame.tick, frame.dt), (2, 0.0));
+        assert_eq!(twin.tick_count, 2);
+        // tick_to follows the clock while paused and does not catch up
+        assert_eq!(twin.tick_to(0.0).unwrap(), 0);
+        assert_eq!(twin.tick_to(60_000.0).unwrap(), 0);
+        twin.resume();
+        assert!(!twin.is_paused());
+        // 100 ms of wall time at 10x are ten steps of 0.1 s
+        assert_eq!(twin.tick_to(60_100.0).unwrap(), 10);
+        assert!(twin.set_speed(0.0).is_err());
+        assert_eq!(twin.get_speed(), 10.0);
+    }
+}
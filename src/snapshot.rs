@@ -0,0 +1,120 @@
+// --- Snapshots and copies ---
+// "What-if" edits in the UI work on a copy of the twin or against a snapshot
+// that is restored afterwards. Both stay in memory as model structs, so
+// nothing is serialized across the WASM boundary.
+
+use std::collections::{BTreeMap, HashMap};
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+#[cfg(feature = "scripting")]
+use crate::script::ScriptRegistry;
+use crate::simulation::SimulationRegistry;
+use crate::{DigitalTwin, SimulationRng, TwinState};
+
+/// The state of a twin at one point in time, opaque to JavaScript
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Clone, Debug)]
+pub struct TwinSnapshot {
+    state: TwinState,
+    // Simulated time and noise sequence, which `state` does not carry
+    time: f64,
+    rng: SimulationRng,
+    supplementary_files: BTreeMap<String, Vec<u8>>,
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl TwinSnapshot {
+    /// Simulation tick at which the snapshot was taken
+    pub fn tick_count(&self) -> u32 {
+        self.state.tick_count
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl DigitalTwin {
+    /// Capture the model, simulation state and package files
+    pub fn snapshot(&self) -> TwinSnapshot {
+        TwinSnapshot {
+            state: self.state(),
+            time: self.simulation_time(),
+            rng: self.simulation.rng(),
+            supplementary_files: self.supplementary_files.clone(),
+        }
+    }
+
+    /// Return to a snapshot. Operation handlers, listeners and queued events
+    /// are kept; a snapshot can be restored any number of times.
+    pub fn restore(&mut self, snapshot: &TwinSnapshot) {
+        self.tracked(|twin| {
+            twin.data = snapshot.state.shell.clone();
+            twin.resume_models(&snapshot.state.simulation, snapshot.time);
+            twin.simulation.set_rng(snapshot.rng);
+            twin.tick_count = snapshot.state.tick_count;
+            twin.supplementary_files = snapshot.supplementary_files.clone();
+        });
+    }
+
+    /// An independent copy of the twin. Handlers, listeners, scripts and
+    /// attached simulation models are owned by the original and are not
+    /// copied; the
+    /// copy carries the built-in models, continuing from the same values.
+    pub fn clone_twin(&self) -> DigitalTwin {
+        let mut copy = DigitalTwin {
+            data: self.data.clone(),
+            simulation: SimulationRegistry::default(),
+            profile: self.profile.clone(),
+            tick_count: self.tick_count,
+            state_machine: self.state_machine.clone(),
+            #[cfg(feature = "scripting")]
+            scripts: ScriptRegistry::default(),
+            scenario: self.scenario.clone(),
+            computed: self.computed.clone(),
+            operation_handlers: HashMap::new(),
+            event_listeners: Vec::new(),
+            change_listeners: Vec::new(),
+            event_queue: Vec::new(),
+            supplementary_files: self.supplementary_files.clone(),
+            migration_report: self.migration_report.clone(),
+        };
+        copy.simulation = copy.builtin_models();
+        copy.simulation.continue_from(&self.simulation);
+        copy
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"{
+        "id": "urn:example:aas:motor",
+        "asset_type": "Siemens 1LE1",
+        "nameplate": [{"id_short": "Voltage", "value": "400", "unit": "V", "value_type": "xs:double"}]
+    }"#;
+
+    #[test]
+    fn test_snapshot_restore_and_clone() {
+        let mut twin = DigitalTwin::new(CONFIG).unwrap();
+        twin.advance_simulation();
+        let snapshot = twin.snapshot();
+        assert_eq!(snapshot.tick_count(), 1);
+
+        let mut copy = twin.clone_twin();
+        copy.set_property("Voltage", "230").unwrap();
+        assert_eq!(copy.get_property_display("Voltage"), "230 V");
+        assert_eq!(twin.get_property_display("Voltage"), "400 V");
+
+        twin.set_property("Voltage", "690").unwrap();
+        twin.advance_simulation();
+        twin.restore(&snapshot);
+        assert_eq!(twin.get_property_display("Voltage"), "400 V");
+        assert_eq!(twin.state().tick_count, 1);
+
+        // The same snapshot can be restored again
+        twin.set_property("Voltage", "690").unwrap();
+        twin.restore(&snapshot);
+        assert_eq!(twin.get_property_display("Voltage"), "400 V");
+    }
+}
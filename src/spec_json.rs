@@ -0,0 +1,271 @@
+// --- AAS Part 1 JSON ---
+// The spec's JSON serialization of an environment: camelCase keys, a
+// `modelType` on every identifiable and submodel element. The legacy format
+// (snake_case field names) stays the default; both map onto the same model,
+// so the conversion works on the JSON tree. Property and range units have no
+// place in the metamodel and travel as a "unit" extension.
+
+use serde_json::{json, Map, Value};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    environment_from_v2_json, is_v2_json, parse_config, select_shell, DigitalTwin, Environment,
+    TwinError, UNIT_EXTENSION,
+};
+
+/// Which flavor of JSON to read or write
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum JsonFormat {
+    /// The twin's own format with snake_case keys (`id_short`, `asset_type`)
+    Legacy,
+    /// AAS Part 1 environment with camelCase keys (`idShort`, `assetInformation`)
+    Spec,
+}
+
+/// Whether a parsed JSON document is a spec-style environment
+pub fn is_spec_json(value: &Value) -> bool {
+    value.get("assetAdministrationShells").is_some()
+}
+
+/// Serialize an environment as AAS Part 1 JSON
+pub fn environment_to_spec_json(env: &Environment) -> Value {
+    let mut value = serde_json::to_value(env).unwrap_or_else(|_| json!({}));
+    rename_keys(&mut value, snake_to_camel);
+    for (list, model_type) in [
+        ("assetAdministrationShells", "AssetAdministrationShell"),
+        ("submodels", "Submodel"),
+        ("conceptDescriptions", "ConceptDescription"),
+    ] {
+        if let Some(Value::Array(items)) = value.get_mut(list) {
+            for item in items.iter_mut().filter_map(Value::as_object_mut) {
+                item.insert("modelType".to_string(), json!(model_type));
+            }
+        }
+    }
+    export_fixes(&mut value);
+    value
+}
+
+/// Read an AAS Part 1 JSON environment
+pub fn environment_from_spec_json(mut value: Value) -> Result<Environment, String> {
+    spec_to_legacy_keys(&mut value);
+    serde_json::from_value(value).map_err(|e| format!("Invalid AAS environment JSON: {}", e))
+}
+
+/// Read an environment in any JSON format (V2.0 input is migrated)
+pub fn environment_from_json(json: &[u8]) -> Result<Environment, String> {
+    let value: Value =
+        serde_json::from_slice(json).map_err(|e| format!("Invalid AAS environment JSON: {}", e))?;
+    if is_v2_json(&value) {
+        return environment_from_v2_json(value).map(|(env, _)| env);
+    }
+    if is_spec_json(&value) {
+        return environment_from_spec_json(value);
+    }
+    serde_json::from_value(value).map_err(|e| format!("Invalid AAS environment JSON: {}", e))
+}
+
+/// Rewrite a spec JSON tree into the legacy layout, keeping array positions
+pub(crate) fn spec_to_legacy_keys(value: &mut Value) {
+    import_fixes(value);
+    rename_keys(value, camel_to_snake);
+}
+
+fn rename_keys(value: &mut Value, rename: fn(&str) -> String) {
+    match value {
+        Value::Object(map) => {
+            *map = std::mem::take(map)
+                .into_iter()
+                .map(|(key, mut v)| {
+                    rename_keys(&mut v, rename);
+                    (rename(&key), v)
+                })
+                .collect();
+        }
+        Value::Array(items) => items.iter_mut().for_each(|v| rename_keys(v, rename)),
+        _ => {}
+    }
+}
+
+pub(crate) fn snake_to_camel(key: &str) -> String {
+    let mut out = String::with_capacity(key.len());
+    let mut upper = false;
+    for c in key.chars() {
+        match c {
+            '_' => upper = true,
+            c if upper => {
+                out.push(c.to_ascii_uppercase());
+                upper = false;
+            }
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+fn camel_to_snake(key: &str) -> String {
+    let mut out = String::with_capacity(key.len() + 4);
+    for c in key.chars() {
+        if c.is_ascii_uppercase() {
+            out.push('_');
+            out.push(c.to_ascii_lowercase());
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+fn has_unit(map: &Map<String, Value>) -> bool {
+    matches!(
+        map.get("modelType").and_then(Value::as_str),
+        Some("Property" | "Range")
+    )
+}
+
+/// Units into extensions, a modelType on IEC 61360 contents
+fn export_fixes(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            if has_unit(map) {
+                // Units are dropped from the element even when unset
+                if let Some(Value::String(unit)) = map.remove("unit") {
+                    let extension = json!({
+                        "name": UNIT_EXTENSION,
+                        "valueType": "xs:string",
+                        "value": unit,
+                    });
+                    match map.get_mut("extensions") {
+                        Some(Value::Array(extensions)) => extensions.insert(0, extension),
+                        _ => {
+                            map.insert("extensions".to_string(), json!([extension]));
+                        }
+                    }
+                }
+            }
+            if let Some(Value::Object(content)) = map.get_mut("dataSpecificationContent") {
+                content.insert("modelType".to_string(), json!("DataSpecificationIec61360"));
+            }
+            map.values_mut().for_each(export_fixes);
+        }
+        Value::Array(items) => items.iter_mut().for_each(export_fixes),
+        _ => {}
+    }
+}
+
+/// Unit extensions back into the unit field
+fn import_fixes(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            if has_unit(map) {
+                let unit = match map.get_mut("extensions") {
+                    Some(Value::Array(extensions)) => extensions
+                        .iter()
+                        .position(|e| e.get("name").and_then(Value::as_str) == Some(UNIT_EXTENSION))
+                        .map(|i| extensions.remove(i)),
+                    _ => None,
+                };
+                if let Some(unit) = unit.and_then(|e| e.get("value").cloned()) {
+                    map.insert("unit".to_string(), unit);
+                }
+            }
+            map.values_mut().for_each(import_fixes);
+        }
+        Value::Array(items) => items.iter_mut().for_each(import_fixes),
+        _ => {}
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl DigitalTwin {
+    /// Hydrate the twin from JSON in the given format. Spec JSON must be an
+    /// environment; its first shell is loaded.
+    pub fn from_json(json: &str, format: JsonFormat) -> Result<DigitalTwin, TwinError> {
+        let data = match format {
+            JsonFormat::Legacy => parse_config(json, None).map_err(TwinError::parse)?.0,
+            JsonFormat::Spec => {
+                let value: Value = serde_json::from_str(json)
+                    .map_err(|e| TwinError::parse(format!("Invalid AAS JSON: {}", e)))?;
+                select_shell(
+                    environment_from_spec_json(value).map_err(TwinError::parse)?,
+                    None,
+                )?
+            }
+        };
+        DigitalTwin::from_shell(data)
+    }
+
+    /// Export the twin as JSON in the given format
+    pub fn to_json(&self, format: JsonFormat) -> String {
+        match format {
+            JsonFormat::Legacy => self.get_aas_json(),
+            JsonFormat::Spec => serde_json::to_string_pretty(&environment_to_spec_json(
+                &Environment::from_shell(&self.data),
+            ))
+            .unwrap_or_else(|_| "{}".to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LEGACY: &str = r#"{
+        "id": "urn:example:aas:motor",
+        "asset_type": "Siemens 1LE1",
+        "nameplate": [
+            {"id_short": "Voltage", "value": "400", "unit": "V", "value_type": "xs:double"},
+            {
+                "id_short": "Markings",
+                "model_type": "SubmodelElementList",
+                "type_value_list_element": "Property",
+                "value": [{"value": "CE", "unit": null}]
+            }
+        ]
+    }"#;
+
+    #[test]
+    fn test_spec_json_round_trip() {
+        let twin = DigitalTwin::new(LEGACY).unwrap();
+        let spec = twin.to_json(JsonFormat::Spec);
+        let value: Value = serde_json::from_str(&spec).unwrap();
+
+        let shell = &value["assetAdministrationShells"][0];
+        assert_eq!(shell["modelType"], "AssetAdministrationShell");
+        assert_eq!(shell["assetInformation"]["assetType"], "Siemens 1LE1");
+        let voltage = &value["submodels"][0]["submodelElements"][0];
+        assert_eq!(voltage["idShort"], "Voltage");
+        assert_eq!(voltage["modelType"], "Property");
+        assert_eq!(voltage["valueType"], "xs:double");
+        assert_eq!(voltage["extensions"][0]["name"], "unit");
+        assert!(voltage.get("unit").is_none());
+        assert!(!spec.contains('_'));
+
+        let restored = DigitalTwin::from_json(&spec, JsonFormat::Spec).unwrap();
+        assert_eq!(restored.get_property_display("Voltage"), "400 V");
+        assert_eq!(
+            restored.get_property_display("Nameplate.Markings[0]"),
+            "CE "
+        );
+        // The legacy constructor recognizes spec JSON as well
+        assert_eq!(
+            DigitalTwin::new(&spec)
+                .unwrap()
+                .get_property_display("Voltage"),
+            "400 V"
+        );
+    }
+
+    #[test]
+    fn test_key_case_conversion() {
+        assert_eq!(
+            snake_to_camel("asset_administration_shells"),
+            "assetAdministrationShells"
+        );
+        assert_eq!(camel_to_snake("inoutputVariables"), "inoutput_variables");
+        assert_eq!(camel_to_snake("type"), "type");
+    }
+}
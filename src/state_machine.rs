@@ -0,0 +1,386 @@
+// --- Operational state machine ---
+// The twin is Off, Starting, Running, in Fault or in Maintenance. Commands
+// move it along a configurable transition table; Starting turns into Running
+// after the startup time, and overtemperature or a stalled motor put a
+// starting or running twin into Fault. Only a starting or running motor is
+// connected to the supply. Every change of state is written to the
+// OperationalState property of the OperationalData submodel and emitted
+// through its StateChanged event.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+#[cfg(feature = "wasm")]
+use tsify::Tsify;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::event::{format_timestamp, now_millis};
+use crate::{
+    enum_name, join_path, BasicEventElement, DataTypeDefXsd, DigitalTwin, Direction, ElementKind,
+    Key, KeyTypes, Reference, ReferenceTypes, StateOfEvent, SubmodelElement, TwinError,
+    ENERGIZED_INPUT, MOTOR_MODEL, OPERATIONAL_DATA_ID_SHORT, THERMAL_MODEL,
+};
+
+/// Property of the OperationalData submodel holding the state
+pub const OPERATIONAL_STATE_ID_SHORT: &str = "OperationalState";
+
+/// Event of the OperationalData submodel announcing state changes
+pub const STATE_CHANGED_ID_SHORT: &str = "StateChanged";
+
+/// Slip above which a running motor counts as stalled
+const STALL_SLIP: f64 = 0.5;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+pub enum OperationalState {
+    Off,
+    Starting,
+    Running,
+    Fault,
+    Maintenance,
+}
+
+impl OperationalState {
+    /// Whether the motor is connected to the supply
+    pub fn is_energized(self) -> bool {
+        matches!(self, OperationalState::Starting | OperationalState::Running)
+    }
+}
+
+/// A command moving the twin from one state to another
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+pub struct StateTransition {
+    pub from: OperationalState,
+    /// Command name, e.g. "start"
+    pub command: String,
+    pub to: OperationalState,
+}
+
+impl StateTransition {
+    fn new(from: OperationalState, command: &str, to: OperationalState) -> StateTransition {
+        StateTransition {
+            from,
+            command: command.to_string(),
+            to,
+        }
+    }
+}
+
+/// Initial state, timing and transition table of the state machine
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[serde(default)]
+pub struct StateMachineConfig {
+    /// State of new and reset twins
+    pub initial: OperationalState,
+    /// Simulated seconds from Starting to Running
+    pub startup_time: f64,
+    pub transitions: Vec<StateTransition>,
+}
+
+impl Default for StateMachineConfig {
+    /// Running, so that the simulation runs until stopped; "start", "stop"
+    /// and "reset" (out of Fault or Maintenance) as well as "maintain" from
+    /// Off; 5 s startup
+    fn default() -> StateMachineConfig {
+        use OperationalState::*;
+        StateMachineConfig {
+            initial: Running,
+            startup_time: 5.0,
+            transitions: vec![
+                StateTransition::new(Off, "start", Starting),
+                StateTransition::new(Starting, "stop", Off),
+                StateTransition::new(Running, "stop", Off),
+                StateTransition::new(Fault, "reset", Off),
+                StateTransition::new(Maintenance, "reset", Off),
+                StateTransition::new(Off, "maintain", Maintenance),
+            ],
+        }
+    }
+}
+
+/// The configuration with the current state and when it was entered
+#[derive(Clone, Debug, Default)]
+pub(crate) struct StateMachine {
+    config: StateMachineConfig,
+    state: Option<OperationalState>,
+    // Simulation time the state was entered at
+    since: f64,
+}
+
+impl StateMachine {
+    pub(crate) fn state(&self) -> OperationalState {
+        self.state.unwrap_or(self.config.initial)
+    }
+
+    /// The state entered, none while still in the initial one, and when
+    pub(crate) fn entered(&self) -> (Option<OperationalState>, f64) {
+        (self.state, self.since)
+    }
+
+    pub(crate) fn set_entered(&mut self, state: Option<OperationalState>, since: f64) {
+        self.state = state;
+        self.since = since;
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl DigitalTwin {
+    /// Apply a command ("start", "stop", "reset", ...) and return the new
+    /// state; fails if the current state has no transition for it
+    pub fn command(&mut self, name: &str) -> Result<String, TwinError> {
+        let from = self.operational_state();
+        let to = self
+            .state_machine
+            .config
+            .transitions
+            .iter()
+            .find(|t| t.from == from && t.command == name)
+            .map(|t| t.to)
+            .ok_or_else(|| {
+                TwinError::failed(
+                    None,
+                    format!(
+                        "Command '{}' is not possible in state {}",
+                        name,
+                        enum_name(&from)
+                    ),
+                )
+            })?;
+        self.enter_state(to, name)?;
+        Ok(enum_name(&to))
+    }
+
+    /// The current operational state, e.g. "Running"
+    pub fn get_operational_state(&self) -> String {
+        enum_name(&self.operational_state())
+    }
+
+    /// Replace the state machine with a `StateMachineConfig` given as JSON
+    /// (missing fields keep their defaults) and enter its initial state
+    pub fn configure_state_machine(&mut self, config_json: &str) -> Result<(), TwinError> {
+        let config = serde_json::from_str(config_json).map_err(|e| {
+            TwinError::invalid_argument(format!("Invalid state machine configuration: {}", e))
+        })?;
+        self.set_state_machine(config)
+    }
+}
+
+impl DigitalTwin {
+    /// Rust-side `get_operational_state`
+    pub fn operational_state(&self) -> OperationalState {
+        self.state_machine.state()
+    }
+
+    /// Rust-side `configure_state_machine`
+    pub fn set_state_machine(&mut self, config: StateMachineConfig) -> Result<(), TwinError> {
+        if !config.startup_time.is_finite() || config.startup_time < 0.0 {
+            return Err(TwinError::invalid_argument(format!(
+                "Startup time must be a non-negative number of seconds, got {}",
+                config.startup_time
+            )));
+        }
+        if config.transitions.iter().any(|t| t.command.is_empty()) {
+            return Err(TwinError::invalid_argument(
+                "Transitions need a command name",
+            ));
+        }
+        let initial = config.initial;
+        self.state_machine.config = config;
+        self.enter_state(initial, "configure")
+    }
+
+    /// Back to the initial state, as when the simulation is reset
+    pub(crate) fn reset_state(&mut self) {
+        let initial = self.state_machine.config.initial;
+        // The state changes even where OperationalData cannot be written
+        let _ = self.enter_state(initial, "reset");
+        self.state_machine.since = 0.0;
+    }
+
+    /// Transitions the simulation causes: leaving Starting after the startup
+    /// time and faults from overtemperature, or from a stall once the motor
+    /// has been running for the startup time
+    pub(crate) fn update_state(&mut self) {
+        let state = self.operational_state();
+        if !state.is_energized() {
+            return;
+        }
+        let value = |model: &str, output: &str| {
+            let key = format!("{}.{}", model, output);
+            self.simulation.value(&key).unwrap_or(0.0)
+        };
+        let started = self.simulation.elapsed() - self.state_machine.since
+            >= self.state_machine.config.startup_time;
+        let cause = if value(THERMAL_MODEL, "overtemperature") > 0.0 {
+            Some((OperationalState::Fault, "overtemperature"))
+        } else if !started {
+            None
+        } else if state == OperationalState::Running && value(MOTOR_MODEL, "slip") > STALL_SLIP {
+            Some((OperationalState::Fault, "stall"))
+        } else if state == OperationalState::Starting {
+            Some((OperationalState::Running, "started"))
+        } else {
+            None
+        };
+        if let Some((to, cause)) = cause {
+            let _ = self.enter_state(to, cause);
+        }
+    }
+
+    /// Switch to a state: supply the motor accordingly, write the state to
+    /// OperationalData and emit the StateChanged event. Staying in the same
+    /// state changes nothing.
+    fn enter_state(&mut self, to: OperationalState, cause: &str) -> Result<(), TwinError> {
+        let from = self.operational_state();
+        self.state_machine.state = Some(to);
+        self.set_simulation_input(ENERGIZED_INPUT, if to.is_energized() { 1.0 } else { 0.0 });
+        if from == to {
+            return Ok(());
+        }
+        self.state_machine.since = self.simulation.elapsed();
+        self.tracked(|twin| {
+            twin.write_operational_value(
+                OPERATIONAL_STATE_ID_SHORT,
+                &enum_name(&to),
+                DataTypeDefXsd::String,
+                None,
+            )
+        })?;
+        let payload = json!({"from": from, "to": to, "cause": cause});
+        self.announce(
+            STATE_CHANGED_ID_SHORT,
+            Some(OPERATIONAL_STATE_ID_SHORT),
+            payload,
+        )
+    }
+
+    /// Emit an event through the OperationalData event `id_short`, adding it
+    /// with the submodel, or the property `observed` of it, as the observed
+    /// element. A switched-off event only silences the announcement.
+    pub(crate) fn announce(
+        &mut self,
+        id_short: &str,
+        observed: Option<&str>,
+        payload: Value,
+    ) -> Result<(), TwinError> {
+        let path = join_path(OPERATIONAL_DATA_ID_SHORT, id_short);
+        if self.find_element(&path).is_err() {
+            self.add_operational_event(id_short, observed)?;
+        }
+        let _ = self.emit(&path, payload, &format_timestamp(now_millis()));
+        Ok(())
+    }
+
+    fn add_operational_event(
+        &mut self,
+        id_short: &str,
+        observed: Option<&str>,
+    ) -> Result<String, TwinError> {
+        let mut keys = vec![Key {
+            key_type: KeyTypes::Submodel,
+            value: self.operational_data_id(),
+        }];
+        keys.extend(observed.map(|property| Key {
+            key_type: KeyTypes::Property,
+            value: property.to_string(),
+        }));
+        let observed = Reference {
+            reference_type: ReferenceTypes::ModelReference,
+            referred_semantic_id: None,
+            keys,
+        };
+        let event = BasicEventElement {
+            observed,
+            direction: Direction::Output,
+            state: StateOfEvent::On,
+            message_topic: None,
+            message_broker: None,
+            last_update: None,
+            min_interval: None,
+            max_interval: None,
+        };
+        self.insert_element(
+            OPERATIONAL_DATA_ID_SHORT,
+            SubmodelElement::new(id_short, ElementKind::BasicEventElement(event)),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"{"id": "M-1", "asset_type": "Motor", "nameplate": []}"#;
+
+    #[test]
+    fn test_commands_follow_transitions() {
+        let mut twin = DigitalTwin::new(CONFIG).unwrap();
+        assert_eq!(twin.get_operational_state(), "Running");
+        assert!(twin
+            .command("start")
+            .is_err_and(|e| e.code() == "OPERATION_FAILED"));
+
+        assert_eq!(twin.command("stop").unwrap(), "Off");
+        assert_eq!(
+            twin.get_property_display("OperationalData.OperationalState"),
+            "Off "
+        );
+        let events: serde_json::Value = serde_json::to_value(twin.take_event_messages()).unwrap();
+        assert_eq!(events[0]["source"], "OperationalData.StateChanged");
+        assert_eq!(
+            events[0]["payload"],
+            json!({"from": "Running", "to": "Off", "cause": "stop"})
+        );
+
+        // A stopped motor coasts down
+        for _ in 0..30 {
+            twin.advance_simulation();
+        }
+        assert!(twin.simulation_values()["motor.rpm"] < 1.0);
+        assert_eq!(twin.simulation_values()["motor.current"], 0.0);
+
+        twin.command("start").unwrap();
+        for _ in 0..4 {
+            twin.advance_simulation();
+        }
+        assert_eq!(twin.operational_state(), OperationalState::Starting);
+        twin.advance_simulation();
+        assert_eq!(twin.operational_state(), OperationalState::Running);
+        assert!(twin.simulation_values()["motor.rpm"] > 1000.0);
+    }
+
+    #[test]
+    fn test_stall_faults_until_reset() {
+        let mut twin = DigitalTwin::new(CONFIG).unwrap();
+        twin.set_load(3.0).unwrap();
+        for _ in 0..5 {
+            twin.advance_simulation();
+        }
+        assert_eq!(twin.operational_state(), OperationalState::Fault);
+        assert!(twin.command("start").is_err());
+        assert_eq!(twin.command("reset").unwrap(), "Off");
+
+        twin.reset_simulation();
+        assert_eq!(twin.operational_state(), OperationalState::Running);
+    }
+
+    #[test]
+    fn test_configured_state_machine() {
+        let mut twin = DigitalTwin::new(CONFIG).unwrap();
+        twin.configure_state_machine(
+            r#"{"initial": "Off", "startup_time": 0,
+                "transitions": [{"from": "Off", "command": "run", "to": "Starting"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(twin.get_operational_state(), "Off");
+        assert!(twin.command("start").is_err());
+        twin.command("run").unwrap();
+        twin.advance_simulation();
+        assert_eq!(twin.get_operational_state(), "Running");
+        assert!(twin
+            .configure_state_machine(r#"{"startup_time": -1}"#)
+            .is_err_and(|e| e.code() == "INVALID_ARGUMENT"));
+    }
+}
@@ -0,0 +1,296 @@
+// --- Streaming environment parser ---
+// Large environment exports are fed in chunks instead of as one string. The
+// scanner only tracks nesting and string state; each item of the top-level
+// shell, submodel and concept description arrays is buffered on its own and
+// converted into the model as soon as it is complete, so memory peaks at the
+// largest single item rather than at the whole document. Other top-level
+// members are skipped. Both the legacy and the spec JSON layout are accepted.
+
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{environment_from_spec_json, select_shell, DigitalTwin, Environment, TwinError};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum List {
+    Shells,
+    Submodels,
+    ConceptDescriptions,
+}
+
+impl List {
+    fn from_key(key: &[u8]) -> Option<(List, bool)> {
+        match key {
+            b"assetAdministrationShells" => Some((List::Shells, true)),
+            b"asset_administration_shells" => Some((List::Shells, false)),
+            b"submodels" => Some((List::Submodels, false)),
+            b"conceptDescriptions" => Some((List::ConceptDescriptions, true)),
+            b"concept_descriptions" => Some((List::ConceptDescriptions, false)),
+            _ => None,
+        }
+    }
+
+    fn spec_key(&self) -> &'static str {
+        match self {
+            List::Shells => "assetAdministrationShells",
+            List::Submodels => "submodels",
+            List::ConceptDescriptions => "conceptDescriptions",
+        }
+    }
+}
+
+/// Incremental parser for AAS environment JSON
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Default)]
+pub struct EnvironmentStreamParser {
+    env: Environment,
+    depth: usize,
+    in_string: bool,
+    escaped: bool,
+    /// Inside the root object, before the next member name
+    expecting_key: bool,
+    collecting_key: bool,
+    key: Vec<u8>,
+    /// Member whose value is being read, if it is one of the item arrays
+    member: Option<(List, bool)>,
+    list: Option<(List, bool)>,
+    item: Option<Vec<u8>>,
+    done: bool,
+    bytes_read: usize,
+    error: Option<String>,
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl EnvironmentStreamParser {
+    #[cfg_attr(feature = "wasm", wasm_bindgen(constructor))]
+    pub fn new() -> EnvironmentStreamParser {
+        EnvironmentStreamParser::default()
+    }
+
+    /// Feed the next chunk of the document; chunks may split anywhere, even
+    /// inside a UTF-8 sequence
+    pub fn push_chunk(&mut self, chunk: &[u8]) -> Result<(), TwinError> {
+        self.push(chunk).map_err(TwinError::parse)
+    }
+
+    /// Bytes consumed so far, e.g. for a progress bar
+    pub fn bytes_read(&self) -> usize {
+        self.bytes_read
+    }
+
+    /// Complete parsing and build a twin from the shell with the given id
+    /// (or the first one)
+    pub fn finish(self, shell_id: Option<String>) -> Result<DigitalTwin, TwinError> {
+        let env = self.finish_environment().map_err(TwinError::parse)?;
+        DigitalTwin::from_shell(select_shell(env, shell_id.as_deref())?)
+    }
+}
+
+impl EnvironmentStreamParser {
+    /// Rust-side `push_chunk`; after an error every further call fails with it
+    pub fn push(&mut self, chunk: &[u8]) -> Result<(), String> {
+        if let Some(e) = &self.error {
+            return Err(e.clone());
+        }
+        for &b in chunk {
+            if let Err(e) = self.byte(b) {
+                let e = format!(
+                    "Invalid AAS environment JSON at byte {}: {}",
+                    self.bytes_read, e
+                );
+                self.error = Some(e.clone());
+                return Err(e);
+            }
+            self.bytes_read += 1;
+        }
+        Ok(())
+    }
+
+    /// The environment read so far, once the document is complete
+    pub fn finish_environment(self) -> Result<Environment, String> {
+        if let Some(e) = self.error {
+            return Err(e);
+        }
+        if !self.done {
+            return Err(format!(
+                "Invalid AAS environment JSON: document ends after {} bytes",
+                self.bytes_read
+            ));
+        }
+        Ok(self.env)
+    }
+
+    fn byte(&mut self, b: u8) -> Result<(), String> {
+        if let Some(item) = &mut self.item {
+            item.push(b);
+        }
+        if self.in_string {
+            if self.escaped {
+                self.escaped = false;
+            } else if b == b'\\' {
+                self.escaped = true;
+            } else if b == b'"' {
+                self.in_string = false;
+                if self.collecting_key {
+                    self.collecting_key = false;
+                    self.expecting_key = false;
+                    self.member = List::from_key(&self.key);
+                }
+                return Ok(());
+            }
+            if self.collecting_key {
+                self.key.push(b);
+            }
+            return Ok(());
+        }
+        if self.done && !b.is_ascii_whitespace() {
+            return Err("unexpected content after the environment".to_string());
+        }
+
+        match b {
+            b'"' => {
+                self.in_string = true;
+                if self.depth == 1 && self.expecting_key {
+                    self.collecting_key = true;
+                    self.key.clear();
+                }
+            }
+            b'{' | b'[' => {
+                self.depth += 1;
+                match self.depth {
+                    1 if b == b'{' => self.expecting_key = true,
+                    1 => return Err("an environment must be a JSON object".to_string()),
+                    2 if b == b'[' => self.list = self.member.take(),
+                    3 if self.list.is_some() && self.item.is_none() => self.item = Some(vec![b]),
+                    _ => {}
+                }
+            }
+            b'}' | b']' => {
+                if self.depth == 0 {
+                    return Err(format!("unbalanced '{}'", b as char));
+                }
+                self.depth -= 1;
+                match self.depth {
+                    0 => self.done = true,
+                    1 => self.list = None,
+                    2 => {
+                        if let (Some(item), Some(list)) = (self.item.take(), self.list) {
+                            self.add_item(list, &item)?;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            b',' if self.depth == 1 => {
+                self.expecting_key = true;
+                self.member = None;
+            }
+            b if b.is_ascii_whitespace() || b == b':' || b == b',' => {}
+            _ if self.depth == 0 => {
+                return Err("an environment must be a JSON object".to_string());
+            }
+            _ if self.depth == 2 && self.list.is_some() => {
+                return Err("items of the environment lists must be objects".to_string());
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn add_item(&mut self, (list, spec_key): (List, bool), item: &[u8]) -> Result<(), String> {
+        let value: Value = serde_json::from_slice(item).map_err(|e| e.to_string())?;
+        if value.get("identification").is_some() {
+            return Err("AAS V2.0 environments cannot be streamed; load them whole".to_string());
+        }
+        let spec = spec_key || value.get("idShort").is_some() || value.get("modelType").is_some();
+        if spec {
+            let env = environment_from_spec_json(json!({ list.spec_key(): [value] }))?;
+            self.env
+                .asset_administration_shells
+                .extend(env.asset_administration_shells);
+            self.env.submodels.extend(env.submodels);
+            self.env
+                .concept_descriptions
+                .extend(env.concept_descriptions);
+            return Ok(());
+        }
+        match list {
+            List::Shells => self.env.asset_administration_shells.push(typed(value)?),
+            List::Submodels => self.env.submodels.push(typed(value)?),
+            List::ConceptDescriptions => self.env.concept_descriptions.push(typed(value)?),
+        }
+        Ok(())
+    }
+}
+
+fn typed<T: DeserializeOwned>(value: Value) -> Result<T, String> {
+    serde_json::from_value(value).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{environment_from_json, environment_to_spec_json};
+
+    const ENVIRONMENT: &str = r#"{
+        "asset_administration_shells": [{
+            "id": "urn:example:aas:motor",
+            "asset_type": "Siemens 1LE1",
+            "submodels": [{"type": "ModelReference", "keys": [{"type": "Submodel", "value": "urn:example:sm:np"}]}]
+        }],
+        "comment": ["skipped {", {"nested": [1, 2]}],
+        "submodels": [{
+            "id": "urn:example:sm:np",
+            "id_short": "Nameplate",
+            "submodel_elements": [
+                {"id_short": "Note", "value": "braces } ] and \"quotes\" \\"},
+                {"id_short": "Maker", "value": "Müller"}
+            ]
+        }]
+    }"#;
+
+    fn stream(json: &[u8], chunk: usize) -> Result<Environment, String> {
+        let mut parser = EnvironmentStreamParser::new();
+        for part in json.chunks(chunk) {
+            parser.push(part)?;
+        }
+        parser.finish_environment()
+    }
+
+    #[test]
+    fn test_streaming_matches_whole_document() {
+        let whole = environment_from_json(ENVIRONMENT.as_bytes()).unwrap();
+        let expected = serde_json::to_string(&whole).unwrap();
+        for chunk in [1, 3, 7, 64, 4096] {
+            let env = stream(ENVIRONMENT.as_bytes(), chunk).unwrap();
+            assert_eq!(serde_json::to_string(&env).unwrap(), expected);
+        }
+
+        // The spec layout streams the same way
+        let spec = serde_json::to_vec(&environment_to_spec_json(&whole)).unwrap();
+        let env = stream(&spec, 5).unwrap();
+        assert_eq!(serde_json::to_string(&env).unwrap(), expected);
+
+        let shell = env.into_shell(None).unwrap();
+        assert_eq!(
+            shell.submodels[0].submodel_elements[1].display_value(),
+            "Müller "
+        );
+    }
+
+    #[test]
+    fn test_streaming_errors() {
+        let truncated = &ENVIRONMENT.as_bytes()[..ENVIRONMENT.len() - 10];
+        assert!(stream(truncated, 16)
+            .unwrap_err()
+            .contains("document ends after"));
+        assert!(stream(b"[1, 2]", 2)
+            .unwrap_err()
+            .contains("must be a JSON object"));
+        assert!(stream(br#"{"submodels": [{"id": 1}]}"#, 4)
+            .unwrap_err()
+            .starts_with("Invalid AAS environment JSON at byte"));
+    }
+}
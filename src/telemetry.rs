@@ -0,0 +1,444 @@
+// --- Hybrid telemetry ---
+// Signals can be fed from the real asset while the rest stays simulated,
+// e.g. the measured current driving the simulated winding temperature. A
+// signal mapped to telemetry in the `telemetry` map of the simulation
+// profile takes the latest ingested reading in place of its model's output,
+// so the models stepped after it see the real value. A reading older than
+// the signal's `stale_after` seconds of simulated time is ignored and the
+// model's output is used again until fresh data arrives. Batches of
+// timestamped records from the asset update mapped signals and properties
+// alike; property values go into the history and are checked against the
+// limits configured for their path. Record timestamps are placed on the
+// simulated timeline: the first record after a reset counts as read at the
+// simulated time it arrives, later ones as read their timestamp's distance
+// from it later (or earlier).
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+#[cfg(feature = "wasm")]
+use tsify::Tsify;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+#[cfg(feature = "wasm")]
+use crate::to_js;
+use crate::{BatchFailure, DigitalTwin, TwinError, SIMULATION_SECTION};
+
+/// Seconds a reading stays valid unless configured otherwise
+pub const DEFAULT_STALE_AFTER: f64 = 5.0;
+
+/// How a signal is fed from telemetry
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[serde(deny_unknown_fields)]
+pub struct TelemetrySource {
+    /// Seconds of simulated time after which a reading is stale
+    #[serde(default = "stale_after_default")]
+    pub stale_after: f64,
+}
+
+fn stale_after_default() -> f64 {
+    DEFAULT_STALE_AFTER
+}
+
+impl Default for TelemetrySource {
+    fn default() -> TelemetrySource {
+        TelemetrySource {
+            stale_after: DEFAULT_STALE_AFTER,
+        }
+    }
+}
+
+impl TelemetrySource {
+    /// Check that the staleness limit is a positive number of seconds
+    pub fn validate(&self, key: &str) -> Result<(), TwinError> {
+        if self.stale_after.is_finite() && self.stale_after > 0.0 {
+            Ok(())
+        } else {
+            Err(TwinError::validation(
+                Some(SIMULATION_SECTION),
+                format!(
+                    "Telemetry of signal '{}' needs a positive stale_after, got {}",
+                    key, self.stale_after
+                ),
+            ))
+        }
+    }
+}
+
+/// Where a signal's value currently comes from
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[cfg_attr(feature = "wasm", tsify(missing_as_null))]
+pub struct SignalSourceStatus {
+    pub key: String,
+    pub stale_after: f64,
+    /// Whether the latest reading is fresh and replaces the simulation
+    pub live: bool,
+    pub last_reading: Option<f64>,
+    /// Simulated seconds since the latest reading
+    pub age: Option<f64>,
+}
+
+/// A reading from the asset
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[serde(deny_unknown_fields)]
+pub struct TelemetryRecord {
+    /// Key of a signal mapped to telemetry ("motor.current") or idShortPath
+    /// of a property ("OperationalData.Temperature")
+    pub path: String,
+    /// When the reading was taken, in Unix milliseconds; it sets the
+    /// simulated time of the reading in the history and for staleness
+    pub timestamp: f64,
+    /// A number, or for properties also a string or boolean
+    pub value: Value,
+}
+
+/// Outcome of a batch of telemetry records
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+pub struct TelemetryReport {
+    /// Number of records in the batch
+    pub count: usize,
+    /// Number of records applied; the others are in `failures`
+    pub applied: usize,
+    pub failures: Vec<BatchFailure>,
+}
+
+/// A mapped signal and its latest reading with the simulated time it
+/// arrived at
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct TelemetryFeed {
+    source: TelemetrySource,
+    reading: Option<(f64, f64)>,
+}
+
+impl TelemetryFeed {
+    /// Keep a reading taken at `time` unless a later one is kept already
+    pub(crate) fn ingest(&mut self, value: f64, time: f64) {
+        if self.reading.is_none_or(|(_, received)| received <= time) {
+            self.reading = Some((value, time));
+        }
+    }
+
+    /// The reading if it is still fresh at `time`
+    pub(crate) fn live_value(&self, time: f64) -> Option<f64> {
+        let (value, received) = self.reading?;
+        (time - received <= self.source.stale_after).then_some(value)
+    }
+
+    pub(crate) fn reset(&mut self) {
+        self.reading = None;
+    }
+
+    fn status(&self, key: &str, time: f64) -> SignalSourceStatus {
+        SignalSourceStatus {
+            key: key.to_string(),
+            stale_after: self.source.stale_after,
+            live: self.live_value(time).is_some(),
+            last_reading: self.reading.map(|(value, _)| value),
+            age: self.reading.map(|(_, received)| time - received),
+        }
+    }
+}
+
+/// Feeds of every mapped signal, keeping the readings of `current` where a
+/// signal stays mapped
+pub(crate) fn telemetry_feeds(
+    config: &BTreeMap<String, TelemetrySource>,
+    current: &BTreeMap<String, TelemetryFeed>,
+) -> BTreeMap<String, TelemetryFeed> {
+    config
+        .iter()
+        .map(|(key, source)| {
+            let reading = current.get(key).and_then(|feed| feed.reading);
+            (
+                key.clone(),
+                TelemetryFeed {
+                    source: *source,
+                    reading,
+                },
+            )
+        })
+        .collect()
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl DigitalTwin {
+    /// Feed a signal ("motor.current") from telemetry instead of its model,
+    /// falling back to the model when no reading arrived for `stale_after`
+    /// seconds
+    pub fn map_telemetry(&mut self, key: &str, stale_after: f64) -> Result<(), TwinError> {
+        if key.is_empty() {
+            return Err(TwinError::invalid_argument("Signal key must be non-empty"));
+        }
+        let source = TelemetrySource { stale_after };
+        source.validate(key)?;
+        self.profile.telemetry.insert(key.to_string(), source);
+        self.simulation.set_telemetry(&self.profile.telemetry);
+        Ok(())
+    }
+
+    /// Simulate a signal again; nothing happens if it is not mapped
+    pub fn unmap_telemetry(&mut self, key: &str) {
+        if self.profile.telemetry.remove(key).is_some() {
+            self.simulation.set_telemetry(&self.profile.telemetry);
+        }
+    }
+
+    /// Record a reading of a mapped signal; it replaces the model's output
+    /// from the next tick on
+    pub fn ingest_signal(&mut self, key: &str, value: f64) -> Result<(), TwinError> {
+        let time = self.simulation_time();
+        self.ingest_signal_at(key, value, time)
+    }
+
+    /// Apply a JSON array of `TelemetryRecord`s in timestamp order and
+    /// return the `TelemetryReport`; a rejected record does not stop the
+    /// others
+    #[cfg(feature = "wasm")]
+    #[wasm_bindgen(unchecked_return_type = "TelemetryReport")]
+    pub fn ingest_telemetry(&mut self, records_json: &str) -> Result<JsValue, TwinError> {
+        let records: Vec<TelemetryRecord> = serde_json::from_str(records_json)
+            .map_err(|e| TwinError::parse(format!("Invalid telemetry records: {}", e)))?;
+        Ok(to_js(&self.ingest_records(records)))
+    }
+
+    /// A `SignalSourceStatus` for every signal mapped to telemetry
+    #[cfg(feature = "wasm")]
+    #[wasm_bindgen(unchecked_return_type = "SignalSourceStatus[]")]
+    pub fn get_signal_sources(&self) -> JsValue {
+        to_js(&self.signal_sources())
+    }
+}
+
+impl DigitalTwin {
+    /// Rust-side `ingest_telemetry`
+    pub fn ingest_records(&mut self, mut records: Vec<TelemetryRecord>) -> TelemetryReport {
+        records.sort_by(|a, b| a.timestamp.total_cmp(&b.timestamp));
+        let failures: Vec<BatchFailure> = self.tracked(|twin| {
+            records
+                .iter()
+                .filter_map(|record| {
+                    let error = twin.ingest_record(record).err()?;
+                    Some(BatchFailure::new(&record.path, error))
+                })
+                .collect()
+        });
+        TelemetryReport {
+            count: records.len(),
+            applied: records.len() - failures.len(),
+            failures,
+        }
+    }
+
+    /// `ingest_signal` for a reading taken at the simulated `time`
+    fn ingest_signal_at(&mut self, key: &str, value: f64, time: f64) -> Result<(), TwinError> {
+        if !value.is_finite() {
+            return Err(TwinError::invalid_argument(format!(
+                "Reading of '{}' must be a finite number, got {}",
+                key, value
+            )));
+        }
+        let feed = self.simulation.telemetry_feed(key).ok_or_else(|| {
+            TwinError::not_found(key, format!("Signal '{}' is not mapped to telemetry", key))
+        })?;
+        feed.ingest(value, time);
+        Ok(())
+    }
+
+    fn ingest_record(&mut self, record: &TelemetryRecord) -> Result<(), TwinError> {
+        let path = record.path.as_str();
+        if !record.timestamp.is_finite() {
+            return Err(TwinError::invalid_argument(format!(
+                "Timestamp of '{}' must be a finite number",
+                path
+            )));
+        }
+        if self.simulation.telemetry_feed(path).is_some() {
+            let value = record.value.as_f64().ok_or_else(|| {
+                TwinError::invalid_argument(format!(
+                    "Reading of '{}' must be a number, got {}",
+                    path, record.value
+                ))
+            })?;
+            let time = self.simulation.reading_time(record.timestamp);
+            return self.ingest_signal_at(path, value, time);
+        }
+        let value = match &record.value {
+            Value::String(s) => s.clone(),
+            Value::Number(_) | Value::Bool(_) => record.value.to_string(),
+            other => {
+                return Err(TwinError::invalid_argument(format!(
+                    "Value for '{}' must be a string, number or boolean, got {}",
+                    path, other
+                )))
+            }
+        };
+        let time = self.simulation.reading_time(record.timestamp);
+        let latest = self.simulation.history().latest_time(path);
+        if latest.is_some_and(|latest| latest > time) {
+            // A late reading only fills in the history; the property keeps
+            // the newer value
+            self.find_element(path)?;
+            let reading = value.trim().parse::<f64>().map_err(|_| {
+                TwinError::invalid_argument(format!(
+                    "Reading of '{}' must be a number, got {}",
+                    path, value
+                ))
+            })?;
+            self.simulation.history_mut().push(path, reading, time);
+            return Ok(());
+        }
+        let old = self.get_property_f64(path).ok();
+        self.set_property(path, &value)?;
+        if let Ok(new) = self.get_property_f64(path) {
+            self.simulation.history_mut().push(path, new, time);
+            if let Some(old) = old {
+                self.check_property_limits(path, old, new);
+            }
+        }
+        Ok(())
+    }
+
+    /// Rust-side `get_signal_sources`
+    pub fn signal_sources(&self) -> Vec<SignalSourceStatus> {
+        let time = self.simulation_time();
+        self.simulation
+            .telemetry()
+            .iter()
+            .map(|(key, feed)| feed.status(key, time))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_telemetry_replaces_simulation_until_stale() {
+        let mut twin = DigitalTwin::new(
+            r#"{"id": "M-1", "asset_type": "Motor", "nameplate": [],
+                "simulation": {"telemetry": {"motor.current": {"stale_after": 1}}}}"#,
+        )
+        .unwrap();
+        let mut simulated = twin.clone_twin();
+        twin.ingest_signal("motor.current", 30.0).unwrap();
+        for _ in 0..5 {
+            twin.advance_by(0.2).unwrap();
+            simulated.advance_by(0.2).unwrap();
+        }
+        let values = twin.simulation_values();
+        assert_eq!(values["motor.current"], 30.0);
+        // The simulated temperature follows the real, doubled current
+        let heated = values["thermal.winding_temperature"];
+        assert!(heated > simulated.simulation_values()["thermal.winding_temperature"]);
+        assert!(twin.signal_sources()[0].live);
+
+        // Without new readings the model takes over again
+        twin.advance_by(0.2).unwrap();
+        assert_ne!(twin.simulation_values()["motor.current"], 30.0);
+        let status = &twin.signal_sources()[0];
+        assert!(!status.live && status.last_reading == Some(30.0));
+
+        assert!(twin
+            .ingest_signal("thermal.winding_temperature", 80.0)
+            .is_err_and(|e| e.code() == "NOT_FOUND"));
+        assert!(twin.ingest_signal("motor.current", f64::NAN).is_err());
+        assert!(twin.map_telemetry("motor.rpm", 0.0).is_err());
+        twin.unmap_telemetry("motor.current");
+        assert!(twin.signal_sources().is_empty());
+    }
+
+    #[test]
+    fn test_telemetry_records_update_twin() {
+        let mut twin = DigitalTwin::new(
+            r#"{"id": "M-1", "asset_type": "Motor", "nameplate": [
+                {"id_short": "Temperature", "value": "20", "value_type": "xs:double"},
+                {"id_short": "Status", "value": "idle"}
+            ], "simulation": {"telemetry": {"motor.current": {}},
+                "limits": {"Temperature": [60]}}}"#,
+        )
+        .unwrap();
+        let records: Vec<TelemetryRecord> = serde_json::from_value(serde_json::json!([
+            {"path": "Temperature", "timestamp": 2000, "value": 70},
+            {"path": "Temperature", "timestamp": 1000, "value": "40"},
+            {"path": "motor.current", "timestamp": 1500, "value": 12.5},
+            {"path": "Status", "timestamp": 1500, "value": "running"},
+            {"path": "motor.current", "timestamp": 1600, "value": "high"},
+            {"path": "Missing", "timestamp": 1700, "value": 1}
+        ]))
+        .unwrap();
+        let report = twin.ingest_records(records);
+        assert_eq!((report.count, report.applied), (6, 4));
+        let failed: Vec<_> = report.failures.iter().map(|f| f.code.as_str()).collect();
+        assert_eq!(failed, ["INVALID_ARGUMENT", "NOT_FOUND"]);
+
+        // Applied in timestamp order, so the latest reading wins
+        assert_eq!(twin.get_property_f64("Temperature").unwrap(), 70.0);
+        assert_eq!(twin.get_property_display("Status"), "running ");
+        let values: Vec<f64> = twin
+            .history("Temperature", 0)
+            .unwrap()
+            .iter()
+            .map(|p| p.value)
+            .collect();
+        assert_eq!(values, [40.0, 70.0]);
+        let events = twin.take_event_messages();
+        let crossing = events
+            .iter()
+            .find(|e| e.source == "OperationalData.LimitCrossed")
+            .unwrap();
+        assert_eq!(crossing.payload["path"], "Temperature");
+        assert_eq!(crossing.payload["direction"], "rising");
+
+        twin.advance_by(0.1).unwrap();
+        assert_eq!(twin.simulation_values()["motor.current"], 12.5);
+    }
+
+    #[test]
+    fn test_record_timestamps_place_readings() {
+        let mut twin = DigitalTwin::new(
+            r#"{"id": "M-1", "asset_type": "Motor", "nameplate": [
+                {"id_short": "Temperature", "value": "20", "value_type": "xs:double"}
+            ]}"#,
+        )
+        .unwrap();
+        twin.advance_by(1.0).unwrap();
+        let record = |timestamp: f64, value: f64| TelemetryRecord {
+            path: "Temperature".to_string(),
+            timestamp,
+            value: serde_json::json!(value),
+        };
+        twin.ingest_records(vec![
+            record(3000.0, 50.0),
+            record(1000.0, 30.0),
+            record(2000.0, 40.0),
+        ]);
+        // A later batch with a reading older than the first one
+        twin.ingest_records(vec![record(500.0, 25.0)]);
+        let history: Vec<(f64, f64)> = twin
+            .history("Temperature", 0)
+            .unwrap()
+            .iter()
+            .map(|p| (p.time, p.value))
+            .collect();
+        assert_eq!(
+            history,
+            [(0.5, 25.0), (1.0, 30.0), (2.0, 40.0), (3.0, 50.0)]
+        );
+        assert_eq!(twin.get_property_f64("Temperature").unwrap(), 50.0);
+
+        let csv = twin
+            .export_history_csv(vec!["Temperature".to_string()], None, None)
+            .unwrap();
+        assert_eq!(
+            csv,
+            "time,Temperature\r\n0.5,25\r\n1,30\r\n2,40\r\n3,50\r\n"
+        );
+    }
+}
@@ -0,0 +1,300 @@
+// --- Thermal model ---
+// Winding temperature as a first-order lag: it approaches ambient plus the
+// rated temperature rise scaled by the square of the load, with the thermal
+// time constant of the motor. The insulation class on the nameplate sets the
+// rated rise and the temperature limit; above the limit the twin reports
+// overtemperature. An ambient temperature input overrides the nameplate's.
+
+use crate::{
+    positive_parameter, unknown_parameter, DigitalTwin, ElementKind, FaultKind, MotorModel,
+    SimulationModel, SimulationRng, SimulationValues, MOTOR_MODEL,
+};
+
+/// Name under which new twins carry the thermal model
+pub const THERMAL_MODEL: &str = "thermal";
+
+/// Input carrying the ambient temperature in degC
+pub const AMBIENT_TEMPERATURE_INPUT: &str = "ambient_temperature";
+
+/// Drift of the winding temperature reading at full sensor drift severity,
+/// in K/s
+pub const SENSOR_DRIFT_RATE: f64 = 0.05;
+
+/// Insulation class assumed when the nameplate has none or an unknown one
+pub const DEFAULT_INSULATION_CLASS: &str = "F";
+
+/// Temperature rise at rated load and temperature limit (IEC 60034-1) in
+/// kelvin and degrees Celsius
+fn insulation_limits(class: &str) -> Option<(f64, f64)> {
+    match class.trim().to_ascii_uppercase().as_str() {
+        "A" => Some((60.0, 105.0)),
+        "E" => Some((75.0, 120.0)),
+        "B" => Some((80.0, 130.0)),
+        "F" => Some((105.0, 155.0)),
+        "H" => Some((125.0, 180.0)),
+        _ => None,
+    }
+}
+
+/// Numeric value and unit of a property found by idShort, e.g. "Current"
+pub(crate) fn nameplate_number(
+    twin: &DigitalTwin,
+    id_short: &str,
+) -> Option<(f64, Option<String>)> {
+    let element = twin.find_element(id_short).ok()?;
+    match &element.kind {
+        ElementKind::Property(p) => Some((
+            p.value.trim().parse().ok()?,
+            element.unit().map(str::to_string),
+        )),
+        _ => None,
+    }
+}
+
+/// First-order winding temperature model fed by the motor current
+#[derive(Clone, Debug, PartialEq)]
+pub struct ThermalModel {
+    /// Current at rated load in A; the load is the motor current over it
+    pub rated_current: f64,
+    pub ambient_temperature: f64,
+    /// Thermal time constant in seconds
+    pub time_constant: f64,
+    /// Winding temperature rise at rated load in K
+    pub rated_rise: f64,
+    /// Highest permissible winding temperature in degC
+    pub temperature_limit: f64,
+    temperature: f64,
+    // Error of the temperature reading from an injected sensor drift
+    drift: f64,
+}
+
+impl Default for ThermalModel {
+    /// Class F insulation, the current of the default motor, 20 minutes
+    /// time constant
+    fn default() -> ThermalModel {
+        ThermalModel {
+            rated_current: MotorModel::default().rated_current,
+            ambient_temperature: 25.0,
+            time_constant: 1200.0,
+            rated_rise: 105.0,
+            temperature_limit: 155.0,
+            temperature: 25.0,
+            drift: 0.0,
+        }
+    }
+}
+
+impl ThermalModel {
+    /// A model for an insulation class ("F"); None for unknown classes
+    pub fn for_class(class: &str) -> Option<ThermalModel> {
+        let (rated_rise, temperature_limit) = insulation_limits(class)?;
+        Some(ThermalModel {
+            rated_rise,
+            temperature_limit,
+            ..ThermalModel::default()
+        })
+    }
+
+    /// Take the insulation class, ambient temperature and thermal time
+    /// constant from the twin's nameplate where present; the rated current
+    /// is the motor model's
+    pub fn from_nameplate(twin: &DigitalTwin) -> ThermalModel {
+        let number = |id_short: &str| nameplate_number(twin, id_short);
+        let class = match twin.find_element("InsulationClass").map(|e| &e.kind) {
+            Ok(ElementKind::Property(p)) => p.value.as_str(),
+            _ => DEFAULT_INSULATION_CLASS,
+        };
+        let mut model = ThermalModel::for_class(class).unwrap_or_default();
+        model.rated_current = MotorModel::from_nameplate(twin).rated_current;
+        if let Some((ambient, _)) = number("AmbientTemperature") {
+            model.ambient_temperature = ambient;
+        }
+        if let Some((tau, unit)) = number("ThermalTimeConstant").filter(|(t, _)| *t > 0.0) {
+            model.time_constant = match unit.as_deref() {
+                Some("min") => tau * 60.0,
+                Some("h") => tau * 3600.0,
+                _ => tau,
+            };
+        }
+        model.temperature = model.ambient_temperature;
+        model
+    }
+
+    /// Outputs as the temperature sensor reads them
+    fn outputs(&self) -> SimulationValues {
+        let reading = self.temperature + self.drift;
+        let over = reading > self.temperature_limit;
+        SimulationValues::from([
+            ("winding_temperature".to_string(), reading),
+            ("overtemperature".to_string(), if over { 1.0 } else { 0.0 }),
+        ])
+    }
+}
+
+impl SimulationModel for ThermalModel {
+    fn init(&mut self) -> SimulationValues {
+        self.temperature = self.ambient_temperature;
+        self.drift = 0.0;
+        self.outputs()
+    }
+
+    fn step(
+        &mut self,
+        dt: f64,
+        inputs: &SimulationValues,
+        _rng: &mut SimulationRng,
+    ) -> SimulationValues {
+        let current = inputs
+            .get(&format!("{}.current", MOTOR_MODEL))
+            .copied()
+            .unwrap_or(0.0);
+        let load = current / self.rated_current;
+        let ambient = inputs
+            .get(AMBIENT_TEMPERATURE_INPUT)
+            .copied()
+            .unwrap_or(self.ambient_temperature);
+        let target = ambient + self.rated_rise * load * load;
+        // Exact step of the lag, stable for any dt
+        self.temperature += (target - self.temperature) * (1.0 - (-dt / self.time_constant).exp());
+        self.drift = match FaultKind::SensorDrift.severity(inputs) {
+            0.0 => 0.0,
+            severity => self.drift + severity * SENSOR_DRIFT_RATE * dt,
+        };
+        self.outputs()
+    }
+
+    fn resume(&mut self, outputs: &SimulationValues) {
+        self.temperature = outputs
+            .get("winding_temperature")
+            .copied()
+            .unwrap_or(self.ambient_temperature);
+    }
+
+    fn parameters(&self) -> SimulationValues {
+        SimulationValues::from([
+            ("ambient_temperature".to_string(), self.ambient_temperature),
+            ("time_constant".to_string(), self.time_constant),
+            ("rated_rise".to_string(), self.rated_rise),
+            ("temperature_limit".to_string(), self.temperature_limit),
+            ("rated_current".to_string(), self.rated_current),
+        ])
+    }
+
+    fn set_parameter(&mut self, name: &str, value: f64) -> Result<(), String> {
+        match name {
+            "ambient_temperature" => self.ambient_temperature = value,
+            "temperature_limit" => self.temperature_limit = value,
+            "time_constant" => self.time_constant = positive_parameter(name, value)?,
+            "rated_rise" => self.rated_rise = positive_parameter(name, value)?,
+            "rated_current" => self.rated_current = positive_parameter(name, value)?,
+            _ => return Err(unknown_parameter(name)),
+        }
+        Ok(())
+    }
+
+    /// The true temperature and the reading's drift
+    fn internal_state(&self) -> SimulationValues {
+        SimulationValues::from([
+            ("temperature".to_string(), self.temperature),
+            ("drift".to_string(), self.drift),
+        ])
+    }
+
+    fn restore_internal_state(&mut self, state: &SimulationValues) {
+        if let (Some(temperature), Some(drift)) = (state.get("temperature"), state.get("drift")) {
+            self.temperature = *temperature;
+            self.drift = *drift;
+        }
+    }
+
+    fn unit(&self, output: &str) -> Option<&str> {
+        match output {
+            "winding_temperature" => Some("degC"),
+            _ => None,
+        }
+    }
+}
+
+impl DigitalTwin {
+    /// Whether the thermal model reports the winding above its limit
+    pub fn is_overtemperature(&self) -> bool {
+        self.simulation_values()
+            .get(&format!("{}.overtemperature", THERMAL_MODEL))
+            .is_some_and(|flag| *flag > 0.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_thermal_model_from_nameplate() {
+        let mut twin = DigitalTwin::new(
+            r#"{"id": "M-1", "asset_type": "Motor", "nameplate": [
+                {"id_short": "Current", "value": "10", "unit": "A"},
+                {"id_short": "InsulationClass", "value": "b"},
+                {"id_short": "ThermalTimeConstant", "value": "1", "unit": "min"}
+            ]}"#,
+        )
+        .unwrap();
+        let model = ThermalModel::from_nameplate(&twin);
+        assert_eq!(
+            (
+                model.rated_current,
+                model.time_constant,
+                model.temperature_limit
+            ),
+            (10.0, 60.0, 130.0)
+        );
+        assert!(twin.list_simulation_models().contains(THERMAL_MODEL));
+        assert_eq!(
+            twin.simulation_values()["thermal.winding_temperature"],
+            25.0
+        );
+
+        // A 30 % overload heats the class B winding past its limit
+        twin.set_load(1.3).unwrap();
+        let mut first_over = None;
+        for tick in 1..=900 {
+            twin.advance_by(1.0).unwrap();
+            if first_over.is_none() && twin.is_overtemperature() {
+                first_over = Some(tick);
+                assert!(twin.status().overtemperature);
+            }
+        }
+        assert!(first_over.is_some_and(|tick| tick > 60));
+        // The overtemperature trips the motor
+        assert_eq!(twin.get_operational_state(), "Fault");
+        twin.reset_simulation();
+        assert!(!twin.is_overtemperature());
+    }
+
+    #[test]
+    fn test_thermal_lag() {
+        let mut model = ThermalModel::for_class("F").unwrap();
+        model.time_constant = 100.0;
+        model.init();
+        let inputs = SimulationValues::from([("motor.current".to_string(), model.rated_current)]);
+        let mut rng = SimulationRng::new(0);
+        // After one time constant 63 % of the rise is reached, however it
+        // is stepped
+        let coarse = model.clone().step(100.0, &inputs, &mut rng)["winding_temperature"];
+        let mut fine = 0.0;
+        for _ in 0..10 {
+            fine = model.step(10.0, &inputs, &mut rng)["winding_temperature"];
+        }
+        assert!((coarse - fine).abs() < 1e-9);
+        assert!((coarse - (25.0 + 105.0 * 0.632)).abs() < 0.1);
+        assert!(ThermalModel::for_class("Z").is_none());
+
+        // Without current the winding cools to the ambient input
+        let mut inputs = SimulationValues::from([(AMBIENT_TEMPERATURE_INPUT.to_string(), 40.0)]);
+        model.init();
+        let settled = model.step(10_000.0, &inputs, &mut rng)["winding_temperature"];
+        assert!((settled - 40.0).abs() < 1e-6);
+        inputs.clear();
+        let settled = model.step(10_000.0, &inputs, &mut rng)["winding_temperature"];
+        assert!((settled - 25.0).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,247 @@
+// --- Time Series submodel ---
+// The recorded signal history as a Time Series submodel (IDTA 02008), so
+// other AAS tooling can consume the twin's data without knowing its API. The
+// Metadata names the twin and describes a record: the time plus one property
+// per signal. A single internal segment holds a record for every point in
+// time at which any of the signals has a value; times are simulated seconds
+// since the last reset, so they are relative points in time. Signal keys
+// become idShorts with the characters an idShort cannot hold replaced
+// ("motor.rpm" -> "motor_rpm").
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    dtdl_name, DataTypeDefXsd, DigitalTwin, ElementKind, LangString, MultiLanguageProperty,
+    Property, Reference, Submodel, SubmodelElement, SubmodelElementCollection, TwinError,
+};
+
+/// idShort of the Time Series submodel
+pub const TIME_SERIES_ID_SHORT: &str = "TimeSeries";
+
+/// Semantic id of the Time Series submodel (IDTA 02008-1-1)
+pub const TIME_SERIES_SEMANTIC_ID: &str = "https://admin-shell.io/idta/TimeSeries/1/1";
+
+const SEMANTIC_PREFIX: &str = "https://admin-shell.io/idta/TimeSeries/";
+
+/// An element carrying the Time Series semantic id `concept`, e.g.
+/// "Segments/1/1"
+fn element(id_short: &str, concept: &str, kind: ElementKind) -> SubmodelElement {
+    let mut element = SubmodelElement::new(id_short, kind);
+    element.semantic_id = Some(Reference::global(&format!(
+        "{}{}",
+        SEMANTIC_PREFIX, concept
+    )));
+    element
+}
+
+fn collection(id_short: &str, concept: &str, value: Vec<SubmodelElement>) -> SubmodelElement {
+    element(
+        id_short,
+        concept,
+        ElementKind::SubmodelElementCollection(SubmodelElementCollection { value }),
+    )
+}
+
+fn property(
+    id_short: &str,
+    value: String,
+    value_type: DataTypeDefXsd,
+    unit: Option<String>,
+) -> SubmodelElement {
+    SubmodelElement::new(
+        id_short,
+        ElementKind::Property(Property {
+            value,
+            unit,
+            value_type: Some(value_type),
+        }),
+    )
+}
+
+/// The relative point in time of a record, in seconds
+fn time(value: String) -> SubmodelElement {
+    let mut time = property("Time", value, DataTypeDefXsd::Double, Some("s".to_string()));
+    time.semantic_id = Some(Reference::global(&format!(
+        "{}RelativePointInTime/1/1",
+        SEMANTIC_PREFIX
+    )));
+    time
+}
+
+fn text(id_short: &str, concept: &str, text: String) -> SubmodelElement {
+    let value = vec![LangString {
+        language: "en".to_string(),
+        text,
+    }];
+    element(
+        id_short,
+        concept,
+        ElementKind::MultiLanguageProperty(MultiLanguageProperty { value }),
+    )
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl DigitalTwin {
+    /// The history of `signals` (all recorded ones if empty) as a Time
+    /// Series submodel in AAS JSON
+    pub fn export_time_series(&self, signals: Vec<String>) -> Result<String, TwinError> {
+        let submodel = self.time_series(&signals)?;
+        Ok(serde_json::to_string_pretty(&submodel).unwrap_or_else(|_| "{}".to_string()))
+    }
+
+    /// Add the Time Series submodel of `signals` to the twin, replacing the
+    /// one added before, so that every export carries the history
+    pub fn attach_time_series(&mut self, signals: Vec<String>) -> Result<(), TwinError> {
+        let submodel = self.time_series(&signals)?;
+        self.data
+            .submodels
+            .retain(|s| s.id_short != TIME_SERIES_ID_SHORT);
+        self.data.submodels.push(submodel);
+        Ok(())
+    }
+}
+
+impl DigitalTwin {
+    /// Rust-side `export_time_series`
+    pub fn time_series(&self, signals: &[String]) -> Result<Submodel, TwinError> {
+        let table = self.history_table(signals)?;
+        let id_shorts: Vec<String> = table.keys.iter().map(|key| dtdl_name(key)).collect();
+        let variables = table.keys.iter().zip(&id_shorts).map(|(key, id_short)| {
+            let unit = self.history_unit(key);
+            property(id_short, String::new(), DataTypeDefXsd::Double, unit)
+        });
+
+        let mut schema = vec![time(String::new())];
+        schema.extend(variables);
+        let metadata = collection(
+            "Metadata",
+            "Metadata/1/1",
+            vec![
+                text(
+                    "Name",
+                    "Metadata/Name/1/1",
+                    format!("History of {}", self.data.id),
+                ),
+                text(
+                    "Description",
+                    "Metadata/Description/1/1",
+                    "Simulated and ingested signals of the twin".to_string(),
+                ),
+                collection("Record", "Record/1/1", schema),
+            ],
+        );
+        let record_count = table.rows.len();
+        let records: Vec<SubmodelElement> = table
+            .rows
+            .iter()
+            .enumerate()
+            .map(|(i, (t, values))| {
+                let mut record = vec![time(t.to_string())];
+                record.extend(
+                    id_shorts
+                        .iter()
+                        .zip(values)
+                        .filter_map(|(id_short, value)| {
+                            let value = (*value)?.to_string();
+                            Some(property(id_short, value, DataTypeDefXsd::Double, None))
+                        }),
+                );
+                collection(&format!("Record{}", i + 1), "Record/1/1", record)
+            })
+            .collect();
+        let segment = collection(
+            "InternalSegment",
+            "Segments/InternalSegment/1/1",
+            vec![
+                property(
+                    "RecordCount",
+                    record_count.to_string(),
+                    DataTypeDefXsd::Int,
+                    None,
+                ),
+                property(
+                    "State",
+                    "in progress".to_string(),
+                    DataTypeDefXsd::String,
+                    None,
+                ),
+                collection("Records", "Records/1/1", records),
+            ],
+        );
+
+        Ok(Submodel {
+            id: format!("{}/submodels/{}", self.data.id, TIME_SERIES_ID_SHORT),
+            id_short: TIME_SERIES_ID_SHORT.to_string(),
+            display_name: Vec::new(),
+            description: Vec::new(),
+            administration: None,
+            semantic_id: Some(Reference::global(TIME_SERIES_SEMANTIC_ID)),
+            qualifiers: Vec::new(),
+            extensions: Vec::new(),
+            kind: Default::default(),
+            submodel_elements: vec![
+                metadata,
+                collection("Segments", "Segments/1/1", vec![segment]),
+            ],
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_time_series_submodel_from_history() {
+        let mut twin =
+            DigitalTwin::new(r#"{"id": "M-1", "asset_type": "Motor", "nameplate": []}"#).unwrap();
+        for _ in 0..3 {
+            twin.advance_by(0.5).unwrap();
+        }
+        let signals = vec![
+            "motor.rpm".to_string(),
+            "thermal.winding_temperature".to_string(),
+        ];
+        twin.attach_time_series(signals.clone()).unwrap();
+        twin.attach_time_series(signals).unwrap();
+        assert_eq!(
+            twin.list_submodels()
+                .iter()
+                .filter(|s| *s == TIME_SERIES_ID_SHORT)
+                .count(),
+            1
+        );
+
+        let path = "TimeSeries.Segments.InternalSegment";
+        assert_eq!(
+            twin.get_property_f64(&format!("{}.RecordCount", path))
+                .unwrap(),
+            3.0
+        );
+        let record = format!("{}.Records.Record3", path);
+        assert_eq!(
+            twin.get_property_f64(&format!("{}.Time", record)).unwrap(),
+            1.5
+        );
+        assert_eq!(
+            twin.get_property_f64(&format!("{}.motor_rpm", record))
+                .unwrap(),
+            twin.simulation_values()["motor.rpm"]
+        );
+        let schema = twin
+            .find_element("TimeSeries.Metadata.Record.thermal_winding_temperature")
+            .unwrap();
+        assert_eq!(schema.unit(), Some("degC"));
+
+        let json: serde_json::Value =
+            serde_json::from_str(&twin.export_time_series(Vec::new()).unwrap()).unwrap();
+        assert_eq!(
+            json["semantic_id"]["keys"][0]["value"],
+            TIME_SERIES_SEMANTIC_ID
+        );
+        assert!(twin
+            .export_time_series(vec!["motor.torque_x".to_string()])
+            .is_err_and(|e| e.code() == "NOT_FOUND"));
+    }
+}
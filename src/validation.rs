@@ -0,0 +1,468 @@
+// --- Validation reports ---
+// `validate_aas_json` only says whether a configuration loads. The report
+// walks the document instead and lists every problem found, each with a JSON
+// pointer into the document, a rule id (the AAS constraint id where there is
+// one) and a message, so broken configurations can be fixed in one pass.
+// Spec JSON is checked in the legacy layout and its pointers are translated
+// back; V2.0 documents are checked after migration.
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+#[cfg(feature = "wasm")]
+use tsify::Tsify;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::spec_json::{is_spec_json, snake_to_camel, spec_to_legacy_keys};
+use crate::{
+    is_v2_json, migrate_v2, parse_config, yaml, yaml_to_json, DigitalTwin, ElementKind,
+    SubmodelElement,
+};
+
+/// How serious a validation finding is
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// The twin does not load, or the model violates an AAS constraint
+    Error,
+    /// The twin loads, but part of it is probably not what was meant
+    Warning,
+}
+
+/// One finding of a validation
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+pub struct ValidationIssue {
+    pub severity: Severity,
+    /// "AASd-022" for AAS constraints, otherwise a short name such as "schema"
+    pub rule: String,
+    /// JSON pointer to the offending part of the document ("" for all of it)
+    pub pointer: String,
+    pub message: String,
+}
+
+/// Everything wrong with a configuration
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+pub struct ValidationReport {
+    /// Whether the configuration loads without errors; warnings are allowed
+    pub valid: bool,
+    pub issues: Vec<ValidationIssue>,
+}
+
+/// Validate a configuration and return a `ValidationReport` as JSON
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn validate_aas_json_report(json_str: &str) -> String {
+    serde_json::to_string(&validation_report(json_str)).unwrap_or_else(|_| "{}".to_string())
+}
+
+/// Rust-side `validate_aas_json_report`
+pub fn validation_report(config: &str) -> ValidationReport {
+    let mut checker = Checker::default();
+    checker.check_document(config);
+    if !checker.has_errors() {
+        let loaded = parse_config(config, None)
+            .and_then(|(shell, _)| DigitalTwin::from_shell(shell).map_err(String::from));
+        if let Err(e) = loaded {
+            checker.error("load", String::new(), e);
+        }
+    }
+    ValidationReport {
+        valid: !checker.has_errors(),
+        issues: checker.issues,
+    }
+}
+
+#[derive(Default)]
+struct Checker {
+    issues: Vec<ValidationIssue>,
+    /// Pointers are reported with camelCase keys
+    spec: bool,
+}
+
+impl Checker {
+    fn has_errors(&self) -> bool {
+        self.issues.iter().any(|i| i.severity == Severity::Error)
+    }
+
+    fn error(&mut self, rule: &str, pointer: String, message: impl Into<String>) {
+        self.issue(Severity::Error, rule, pointer, message.into());
+    }
+
+    fn warning(&mut self, rule: &str, pointer: String, message: impl Into<String>) {
+        self.issue(Severity::Warning, rule, pointer, message.into());
+    }
+
+    fn issue(&mut self, severity: Severity, rule: &str, pointer: String, message: String) {
+        let pointer = if self.spec {
+            pointer
+                .split('/')
+                .map(snake_to_camel)
+                .collect::<Vec<_>>()
+                .join("/")
+        } else {
+            pointer
+        };
+        self.issues.push(ValidationIssue {
+            severity,
+            rule: rule.to_string(),
+            pointer,
+            message,
+        });
+    }
+
+    fn check_document(&mut self, config: &str) {
+        let parsed = if yaml::is_yaml(config) {
+            yaml_to_json(config)
+        } else {
+            serde_json::from_str(config).map_err(|e| format!("Invalid AAS JSON: {}", e))
+        };
+        let mut value = match parsed {
+            Ok(value) => value,
+            Err(e) => return self.error("syntax", String::new(), e),
+        };
+        if is_v2_json(&value) {
+            value = match migrate_v2(value) {
+                Ok((migrated, _)) => migrated,
+                Err(e) => return self.error("schema", String::new(), e),
+            };
+            self.warning(
+                "v2-migrated",
+                String::new(),
+                "AAS V2.0 document; pointers refer to its V3.0 migration",
+            );
+        }
+        if is_spec_json(&value) {
+            self.spec = true;
+            spec_to_legacy_keys(&mut value);
+        }
+        let Value::Object(document) = &value else {
+            return self.error("schema", String::new(), "A configuration must be an object");
+        };
+
+        let environment = document.contains_key("asset_administration_shells");
+        if !environment {
+            if !document.get("id").is_some_and(Value::is_string) {
+                self.error("schema", "/id".to_string(), "The shell has no string id");
+            }
+            self.check_elements(document.get("nameplate"), "/nameplate", None);
+        }
+        let Some(submodels) = document.get("submodels") else {
+            return;
+        };
+        let Some(submodels) = submodels.as_array() else {
+            return self.error(
+                "schema",
+                "/submodels".to_string(),
+                "submodels must be an array",
+            );
+        };
+        let mut id_shorts = Vec::new();
+        for (i, submodel) in submodels.iter().enumerate() {
+            let pointer = format!("/submodels/{}", i);
+            let Some(fields) = submodel.as_object() else {
+                self.error("schema", pointer, "A submodel must be an object");
+                continue;
+            };
+            if !fields.get("id").is_some_and(Value::is_string) {
+                self.error(
+                    "schema",
+                    format!("{}/id", pointer),
+                    "The submodel has no string id",
+                );
+            }
+            match fields.get("id_short").and_then(Value::as_str) {
+                Some(id_short) => {
+                    self.check_id_short(id_short, &pointer);
+                    // The twin addresses submodels by idShort
+                    if !environment && id_shorts.contains(&id_short) {
+                        self.error(
+                            "AASd-022",
+                            format!("{}/id_short", pointer),
+                            format!("Duplicate submodel idShort '{}'", id_short),
+                        );
+                    }
+                    id_shorts.push(id_short);
+                }
+                None => self.warning(
+                    "id-short",
+                    pointer.clone(),
+                    "The submodel has no idShort, so its elements cannot be addressed by path",
+                ),
+            }
+            if fields.get("semantic_id").is_none_or(Value::is_null) {
+                self.warning(
+                    "semantic-id",
+                    pointer.clone(),
+                    "The submodel has no semanticId",
+                );
+            }
+            let elements = format!("{}/submodel_elements", pointer);
+            self.check_elements(fields.get("submodel_elements"), &elements, None);
+        }
+    }
+
+    /// Check the elements below `pointer`; `list` is the enclosing
+    /// SubmodelElementList, whose items follow its rules
+    fn check_elements(&mut self, elements: Option<&Value>, pointer: &str, list: Option<&Value>) {
+        let Some(elements) = elements else {
+            return;
+        };
+        let Some(elements) = elements.as_array() else {
+            return self.error("schema", pointer.to_string(), "Elements must be an array");
+        };
+        let mut id_shorts: Vec<&str> = Vec::new();
+        for (i, element) in elements.iter().enumerate() {
+            let pointer = format!("{}/{}", pointer, i);
+            let Some(fields) = element.as_object() else {
+                self.error("schema", pointer, "An element must be an object");
+                continue;
+            };
+            let parsed = match serde_json::from_value::<SubmodelElement>(childless(fields)) {
+                Ok(parsed) => parsed,
+                Err(e) => {
+                    self.error("schema", pointer, e.to_string());
+                    continue;
+                }
+            };
+
+            let id_short = parsed.id_short.as_str();
+            match list {
+                Some(_) if !id_short.is_empty() => self.error(
+                    "AASd-120",
+                    format!("{}/id_short", pointer),
+                    format!(
+                        "List item has idShort '{}'; list items carry none",
+                        id_short
+                    ),
+                ),
+                Some(list) => self.check_list_item(list, &parsed, &pointer),
+                None if id_short.is_empty() => {
+                    self.error("AASd-117", pointer.clone(), "The element has no idShort")
+                }
+                None => {
+                    self.check_id_short(id_short, &pointer);
+                    if id_shorts.contains(&id_short) {
+                        self.error(
+                            "AASd-022",
+                            format!("{}/id_short", pointer),
+                            format!("Duplicate idShort '{}' among siblings", id_short),
+                        );
+                    }
+                    id_shorts.push(fields["id_short"].as_str().unwrap_or_default());
+                }
+            }
+
+            match &parsed.kind {
+                ElementKind::Property(p) => match p.value_type {
+                    Some(value_type) => {
+                        if let Err(e) = value_type.check(&p.value) {
+                            self.error("value-type", format!("{}/value", pointer), e);
+                        }
+                    }
+                    None => self.warning(
+                        "value-type",
+                        pointer.clone(),
+                        "The property has no valueType, so its value is not checked",
+                    ),
+                },
+                ElementKind::SubmodelElementCollection(_) => {
+                    let children = format!("{}/value", pointer);
+                    self.check_elements(fields.get("value"), &children, None);
+                }
+                ElementKind::SubmodelElementList(_) => {
+                    let children = format!("{}/value", pointer);
+                    self.check_elements(fields.get("value"), &children, Some(element));
+                }
+                ElementKind::Entity(_) => {
+                    let children = format!("{}/statements", pointer);
+                    self.check_elements(fields.get("statements"), &children, None);
+                }
+                ElementKind::AnnotatedRelationshipElement(_) => {
+                    let children = format!("{}/annotations", pointer);
+                    self.check_elements(fields.get("annotations"), &children, None);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn check_list_item(&mut self, list: &Value, item: &SubmodelElement, pointer: &str) {
+        let Ok(ElementKind::SubmodelElementList(list)) = serde_json::from_value::<ElementKind>(
+            childless(list.as_object().unwrap_or(&Map::new())),
+        ) else {
+            return;
+        };
+        if !list.type_value_list_element.matches(&item.kind) {
+            self.error(
+                "AASd-108",
+                pointer.to_string(),
+                format!(
+                    "Item is a {}, the list holds {:?}",
+                    item.kind.model_type(),
+                    list.type_value_list_element
+                ),
+            );
+        }
+        if let (Some(expected), Some(actual)) = (list.value_type_list_element, item.value_type()) {
+            if expected != actual {
+                self.error(
+                    "AASd-109",
+                    format!("{}/value_type", pointer),
+                    format!(
+                        "Item has valueType {}, the list holds {}",
+                        actual.as_str(),
+                        expected.as_str()
+                    ),
+                );
+            }
+        }
+        if let (Some(expected), Some(actual)) = (&list.semantic_id_list_element, &item.semantic_id)
+        {
+            if expected != actual {
+                self.error(
+                    "AASd-107",
+                    format!("{}/semantic_id", pointer),
+                    format!(
+                        "Item has semanticId {}, the list holds {}",
+                        actual, expected
+                    ),
+                );
+            }
+        }
+    }
+
+    /// AASd-002: a letter first, then letters, digits, '_' and '-', not
+    /// ending in '-'
+    fn check_id_short(&mut self, id_short: &str, pointer: &str) {
+        let valid = id_short.starts_with(|c: char| c.is_ascii_alphabetic())
+            && id_short
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+            && !id_short.ends_with('-');
+        if !valid {
+            self.error(
+                "AASd-002",
+                format!("{}/id_short", pointer),
+                format!(
+                    "idShort '{}' must start with a letter and contain only letters, digits, '_' and '-'",
+                    id_short
+                ),
+            );
+        }
+    }
+}
+
+/// An element with its child elements left out; they are checked on their own
+fn childless(fields: &Map<String, Value>) -> Value {
+    let mut fields = fields.clone();
+    let container = fields
+        .get("model_type")
+        .and_then(Value::as_str)
+        .is_some_and(|t| matches!(t, "SubmodelElementCollection" | "SubmodelElementList"));
+    if container {
+        fields.insert("value".to_string(), Value::Array(Vec::new()));
+    }
+    for key in ["statements", "annotations"] {
+        if fields.contains_key(key) {
+            fields.insert(key.to_string(), Value::Array(Vec::new()));
+        }
+    }
+    Value::Object(fields)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn issues(config: &str) -> Vec<(Severity, String, String)> {
+        validation_report(config)
+            .issues
+            .into_iter()
+            .map(|i| (i.severity, i.rule, i.pointer))
+            .collect()
+    }
+
+    #[test]
+    fn test_validation_report_lists_every_problem() {
+        let config = r#"{
+            "id": "urn:example:aas:motor",
+            "asset_type": "Siemens 1LE1",
+            "nameplate": [
+                {"id_short": "Voltage", "value": "high", "value_type": "xs:double"},
+                {"id_short": "Voltage", "value": "400", "value_type": "xs:double"},
+                {"id_short": "2ndName", "value": "x", "value_type": "xs:string"},
+                {"value": "orphan", "value_type": "xs:string"},
+                {"id_short": "Markings", "model_type": "SubmodelElementList",
+                 "type_value_list_element": "Property", "value": [
+                    {"id_short": "CE", "value": "CE", "value_type": "xs:string"},
+                    {"model_type": "File", "content_type": "image/png", "value": "ce.png"}
+                ]}
+            ],
+            "submodels": [{
+                "id": "urn:example:sm:td",
+                "id_short": "TechnicalData",
+                "submodel_elements": [{"id_short": "Speed", "value": "1500"}]
+            }]
+        }"#;
+        let report = validation_report(config);
+        assert!(!report.valid);
+        let error =
+            |rule: &str, pointer: &str| (Severity::Error, rule.to_string(), pointer.to_string());
+        let warning =
+            |rule: &str, pointer: &str| (Severity::Warning, rule.to_string(), pointer.to_string());
+        assert_eq!(
+            issues(config),
+            [
+                error("value-type", "/nameplate/0/value"),
+                error("AASd-022", "/nameplate/1/id_short"),
+                error("AASd-002", "/nameplate/2/id_short"),
+                error("AASd-117", "/nameplate/3"),
+                error("AASd-120", "/nameplate/4/value/0/id_short"),
+                error("AASd-108", "/nameplate/4/value/1"),
+                warning("semantic-id", "/submodels/0"),
+                warning("value-type", "/submodels/0/submodel_elements/0"),
+            ]
+        );
+        assert!(report.issues[0].message.contains("'high'"));
+    }
+
+    #[test]
+    fn test_validation_report_of_spec_and_broken_documents() {
+        let spec = DigitalTwin::new(
+            r#"{"id": "urn:x", "asset_type": "Motor",
+                "nameplate": [{"id_short": "Speed", "value": "fast", "value_type": "xs:string"}]}"#,
+        )
+        .unwrap()
+        .to_json(crate::JsonFormat::Spec)
+        .replace("xs:string", "xs:int");
+        assert_eq!(
+            issues(&spec),
+            [(
+                Severity::Error,
+                "value-type".to_string(),
+                "/submodels/0/submodelElements/0/value".to_string()
+            )]
+        );
+
+        let report = validation_report("{\"id\": ");
+        assert_eq!(report.issues[0].rule, "syntax");
+        assert!(!report.valid);
+        assert_eq!(
+            issues(r#"{"nameplate": [{"id_short": "A", "model_type": "Gauge"}]}"#),
+            [
+                (Severity::Error, "schema".to_string(), "/id".to_string()),
+                (
+                    Severity::Error,
+                    "schema".to_string(),
+                    "/nameplate/0".to_string()
+                ),
+            ]
+        );
+        let valid = validation_report(
+            r#"{"id": "urn:x", "asset_type": "Motor", "nameplate": [{"id_short": "n", "value": "1", "value_type": "xs:int"}]}"#,
+        );
+        assert!(valid.valid && valid.issues.is_empty());
+    }
+}
@@ -0,0 +1,290 @@
+// --- Vibration model ---
+// Velocity signal at the drive-end bearing, built from the shaft frequency
+// (motor speed / 60), its second and third harmonic, optional bearing defect
+// frequencies and broadband noise. Every tick synthesizes a block of samples
+// like an accelerometer would deliver, from which RMS and peak are computed.
+
+use std::f64::consts::TAU;
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    unknown_parameter, DigitalTwin, FaultKind, SampleBlock, SimulationModel, SimulationRng,
+    SimulationValues, TwinError, MOTOR_RPM, TIME_INPUT,
+};
+
+/// Name under which new twins carry the vibration model
+pub const VIBRATION_MODEL: &str = "vibration";
+
+/// Outer race defect frequency of a 6205 deep-groove ball bearing, in
+/// multiples of the shaft frequency (BPFO)
+pub const BPFO_ORDER: f64 = 3.585;
+
+/// Inner race defect frequency of a 6205 bearing (BPFI)
+pub const BPFI_ORDER: f64 = 5.415;
+
+/// A spectral line at a multiple of the shaft frequency
+#[derive(Clone, Debug, PartialEq)]
+pub struct BearingDefect {
+    /// Frequency in multiples of the shaft frequency
+    pub order: f64,
+    /// Peak velocity in mm/s
+    pub amplitude: f64,
+}
+
+/// Vibration velocity synthesized from shaft speed and defects
+#[derive(Clone, Debug, PartialEq)]
+pub struct VibrationModel {
+    /// Samples per second
+    pub sample_rate: f64,
+    /// Samples synthesized per tick
+    pub block_size: usize,
+    /// Standard deviation-like amplitude of the broadband noise in mm/s
+    pub noise: f64,
+    pub defects: Vec<BearingDefect>,
+    samples: SampleBlock,
+}
+
+impl Default for VibrationModel {
+    /// 2048 Hz in blocks of 512 samples, no defects
+    fn default() -> VibrationModel {
+        VibrationModel {
+            sample_rate: 2048.0,
+            block_size: 512,
+            noise: 0.05,
+            defects: Vec::new(),
+            samples: SampleBlock::default(),
+        }
+    }
+}
+
+impl VibrationModel {
+    /// Amplitudes (mm/s peak) of the shaft frequency and its harmonics
+    fn harmonics(rpm: f64) -> [(f64, f64); 3] {
+        let base = 0.6 + 1.2e-3 * rpm.abs();
+        [(1.0, base), (2.0, 0.4 * base), (3.0, 0.15 * base)]
+    }
+
+    /// Peak velocity of the defect line at `order`, 0 without one
+    fn defect(&self, order: f64) -> f64 {
+        self.defects
+            .iter()
+            .find(|d| d.order == order)
+            .map_or(0.0, |d| d.amplitude)
+    }
+
+    /// Set the defect line at `order`, removing it for a zero amplitude
+    fn set_defect(&mut self, order: f64, amplitude: f64) {
+        self.defects.retain(|d| d.order != order);
+        if amplitude > 0.0 {
+            self.defects.push(BearingDefect { order, amplitude });
+        }
+    }
+
+    fn outputs(&self, shaft_frequency: f64) -> SimulationValues {
+        let samples = &self.samples.samples;
+        let rms = if samples.is_empty() {
+            0.0
+        } else {
+            (samples.iter().map(|v| v * v).sum::<f64>() / samples.len() as f64).sqrt()
+        };
+        let peak = samples.iter().fold(0.0_f64, |peak, v| peak.max(v.abs()));
+        SimulationValues::from([
+            ("rms".to_string(), rms),
+            ("peak".to_string(), peak),
+            ("shaft_frequency".to_string(), shaft_frequency),
+        ])
+    }
+}
+
+impl SimulationModel for VibrationModel {
+    fn init(&mut self) -> SimulationValues {
+        self.samples = SampleBlock {
+            sample_rate: self.sample_rate,
+            ..SampleBlock::default()
+        };
+        self.outputs(0.0)
+    }
+
+    fn step(
+        &mut self,
+        _dt: f64,
+        inputs: &SimulationValues,
+        rng: &mut SimulationRng,
+    ) -> SimulationValues {
+        let rpm = inputs.get(MOTOR_RPM).copied().unwrap_or(0.0);
+        let shaft_frequency = rpm / 60.0;
+        let time = inputs.get(TIME_INPUT).copied().unwrap_or(0.0);
+        // The block ends at the current simulated time
+        let start_time = time - self.block_size as f64 / self.sample_rate;
+        let mut lines: Vec<(f64, f64)> = Self::harmonics(rpm).to_vec();
+        lines.extend(self.defects.iter().map(|d| (d.order, d.amplitude)));
+        let wear = FaultKind::BearingWear.severity(inputs);
+        if wear > 0.0 {
+            lines.extend([(BPFO_ORDER, 4.0 * wear), (BPFI_ORDER, 2.0 * wear)]);
+        }
+        let samples = (0..self.block_size)
+            .map(|i| {
+                let t = start_time + i as f64 / self.sample_rate;
+                let tonal: f64 = lines
+                    .iter()
+                    .map(|(order, amplitude)| amplitude * (TAU * order * shaft_frequency * t).sin())
+                    .sum();
+                tonal + rng.noise() * self.noise
+            })
+            .collect();
+        self.samples = SampleBlock {
+            sample_rate: self.sample_rate,
+            start_time,
+            samples,
+        };
+        self.outputs(shaft_frequency)
+    }
+
+    fn unit(&self, output: &str) -> Option<&str> {
+        match output {
+            "rms" | "peak" => Some("mm/s"),
+            "shaft_frequency" => Some("Hz"),
+            _ => None,
+        }
+    }
+
+    fn parameters(&self) -> SimulationValues {
+        SimulationValues::from([
+            ("noise".to_string(), self.noise),
+            ("outer_race".to_string(), self.defect(BPFO_ORDER)),
+            ("inner_race".to_string(), self.defect(BPFI_ORDER)),
+        ])
+    }
+
+    fn set_parameter(&mut self, name: &str, value: f64) -> Result<(), String> {
+        match name {
+            "noise" | "outer_race" | "inner_race" if value < 0.0 => Err(format!(
+                "Parameter '{}' must not be negative, got {}",
+                name, value
+            )),
+            "noise" => {
+                self.noise = value;
+                Ok(())
+            }
+            "outer_race" => {
+                self.set_defect(BPFO_ORDER, value);
+                Ok(())
+            }
+            "inner_race" => {
+                self.set_defect(BPFI_ORDER, value);
+                Ok(())
+            }
+            _ => Err(unknown_parameter(name)),
+        }
+    }
+
+    fn samples(&self) -> Option<&SampleBlock> {
+        Some(&self.samples)
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl DigitalTwin {
+    /// Simulate bearing damage: peak velocities in mm/s at the outer and
+    /// inner race defect frequencies, 0 for an intact race. The vibration
+    /// model keeps its other parameters and its state.
+    pub fn set_bearing_defects(
+        &mut self,
+        outer_race: f64,
+        inner_race: f64,
+    ) -> Result<(), TwinError> {
+        if [outer_race, inner_race]
+            .iter()
+            .any(|a| !a.is_finite() || *a < 0.0)
+        {
+            return Err(TwinError::invalid_argument(
+                "Defect amplitudes must be non-negative numbers",
+            ));
+        }
+        for (parameter, amplitude) in [("outer_race", outer_race), ("inner_race", inner_race)] {
+            self.simulation
+                .set_parameter(&format!("{}.{}", VIBRATION_MODEL, parameter), amplitude)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Magnitude of one frequency in a block (single DFT bin)
+    fn magnitude(block: &SampleBlock, frequency: f64) -> f64 {
+        let (re, im) = block
+            .samples
+            .iter()
+            .enumerate()
+            .fold((0.0, 0.0), |(re, im), (i, v)| {
+                let phase = TAU * frequency * i as f64 / block.sample_rate;
+                (re + v * phase.cos(), im + v * phase.sin())
+            });
+        2.0 * (re * re + im * im).sqrt() / block.samples.len() as f64
+    }
+
+    #[test]
+    fn test_vibration_spectrum() {
+        let config = r#"{"id": "M-1", "asset_type": "Pump", "nameplate": []}"#;
+        let mut twin = DigitalTwin::new(config).unwrap();
+        let mut model = VibrationModel::default();
+        model.init();
+        let mut rng = SimulationRng::new(1);
+        // 1440 rpm: 24 Hz shaft frequency, a whole number of periods per block
+        let inputs = SimulationValues::from([(MOTOR_RPM.to_string(), 1440.0)]);
+        let outputs = model.step(1.0, &inputs, &mut rng);
+        let block = model.samples().unwrap();
+        assert_eq!(block.samples.len(), 512);
+        assert_eq!(outputs["shaft_frequency"], 24.0);
+        assert!((magnitude(block, 24.0) - 2.328).abs() < 0.05);
+        assert!((magnitude(block, 48.0) - 0.931).abs() < 0.05);
+        assert!((magnitude(block, 72.0) - 0.349).abs() < 0.05);
+        assert!(magnitude(block, 100.0) < 0.05);
+        assert!(outputs["peak"] > outputs["rms"]);
+
+        twin.advance_by(1.0).unwrap();
+        assert_eq!(twin.samples(VIBRATION_MODEL).unwrap().samples.len(), 512);
+        assert!(twin.samples("motor").is_err());
+    }
+
+    #[test]
+    fn test_bearing_defects_raise_vibration() {
+        let config = r#"{"id": "M-1", "asset_type": "Pump", "nameplate": []}"#;
+        let mut healthy = DigitalTwin::new(config).unwrap();
+        let mut damaged = DigitalTwin::new(config).unwrap();
+        damaged.set_bearing_defects(3.0, 0.0).unwrap();
+        assert!(damaged.set_bearing_defects(-1.0, 0.0).is_err());
+        assert!(damaged.list_simulation_models().contains(VIBRATION_MODEL));
+        for _ in 0..100 {
+            healthy.advance_simulation();
+            damaged.advance_simulation();
+        }
+        // A 3 mm/s line away from the harmonics adds its RMS in quadrature
+        let rms = |twin: &DigitalTwin| twin.simulation_values()["vibration.rms"];
+        let expected = rms(&healthy).hypot(3.0 / std::f64::consts::SQRT_2);
+        assert!((rms(&damaged) - expected).abs() < 0.1);
+    }
+
+    #[test]
+    fn test_bearing_defects_keep_parameters() {
+        let config = r#"{"id": "M-1", "asset_type": "Pump", "nameplate": []}"#;
+        let mut twin = DigitalTwin::new(config).unwrap();
+        twin.set_sim_param("vibration.noise", 1.0).unwrap();
+        twin.set_bearing_defects(3.0, 0.0).unwrap();
+        let params = twin.simulation_parameters();
+        assert_eq!(params["vibration.noise"], 1.0);
+        assert_eq!(params["vibration.outer_race"], 3.0);
+        assert_eq!(params["vibration.inner_race"], 0.0);
+
+        twin.set_bearing_defects(0.0, 2.0).unwrap();
+        let params = twin.simulation_parameters();
+        assert_eq!(params["vibration.outer_race"], 0.0);
+        assert_eq!(params["vibration.inner_race"], 2.0);
+        assert!(twin.set_sim_param("vibration.inner_race", -1.0).is_err());
+    }
+}
@@ -0,0 +1,327 @@
+// --- Wear and remaining useful life ---
+// Damage accumulates from 0 (new) to 1 (end of life). At rated current and
+// the insulation class limit the motor ages at its rated lifetime; the rate
+// grows with the cube of the load and doubles for every 10 K of winding
+// temperature (the ten-degree rule), and some aging goes on while idle. The
+// remaining useful life extrapolates the current rate; `get_rul_estimate`
+// also writes it into the OperationalData submodel so it is part of the AAS.
+
+use serde::Serialize;
+#[cfg(feature = "wasm")]
+use tsify::Tsify;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::thermal::nameplate_number;
+#[cfg(feature = "wasm")]
+use crate::to_js;
+use crate::{
+    join_path, positive_parameter, unknown_parameter, DataTypeDefXsd, DigitalTwin, ElementKind,
+    Property, SimulationModel, SimulationRng, SimulationValues, Submodel, SubmodelElement,
+    ThermalModel, TwinError, MOTOR_MODEL, THERMAL_MODEL,
+};
+
+/// Name under which new twins carry the wear model
+pub const WEAR_MODEL: &str = "wear";
+
+/// Submodel that receives the remaining useful life
+pub const OPERATIONAL_DATA_ID_SHORT: &str = "OperationalData";
+
+/// Lifetime assumed when the nameplate states none, in hours
+pub const DEFAULT_RATED_LIFETIME_HOURS: f64 = 20_000.0;
+
+const SECONDS_PER_HOUR: f64 = 3600.0;
+
+/// Share of the rated aging rate that goes on without load
+const IDLE_AGING: f64 = 0.1;
+
+/// Damage accumulation from load and winding temperature
+#[derive(Clone, Debug, PartialEq)]
+pub struct WearModel {
+    /// Life at rated current and the reference temperature, in seconds
+    pub rated_lifetime: f64,
+    /// Current at rated load in A
+    pub rated_current: f64,
+    /// Winding temperature at which the rated lifetime applies, in degC
+    pub reference_temperature: f64,
+    damage: f64,
+}
+
+impl Default for WearModel {
+    fn default() -> WearModel {
+        let thermal = ThermalModel::default();
+        WearModel {
+            rated_lifetime: DEFAULT_RATED_LIFETIME_HOURS * SECONDS_PER_HOUR,
+            rated_current: thermal.rated_current,
+            reference_temperature: thermal.temperature_limit,
+            damage: 0.0,
+        }
+    }
+}
+
+impl WearModel {
+    /// Take the rated lifetime ("RatedLifetime", in h or years), rated
+    /// current and insulation class from the twin's nameplate where present
+    pub fn from_nameplate(twin: &DigitalTwin) -> WearModel {
+        let thermal = ThermalModel::from_nameplate(twin);
+        let mut model = WearModel {
+            rated_current: thermal.rated_current,
+            reference_temperature: thermal.temperature_limit,
+            ..WearModel::default()
+        };
+        if let Some((life, unit)) =
+            nameplate_number(twin, "RatedLifetime").filter(|(l, _)| *l > 0.0)
+        {
+            let hours = match unit.as_deref() {
+                Some("a") | Some("years") => life * 8760.0,
+                _ => life,
+            };
+            model.rated_lifetime = hours * SECONDS_PER_HOUR;
+        }
+        model
+    }
+
+    /// Damage per second under the given conditions
+    fn rate(&self, current: f64, temperature: f64) -> f64 {
+        let load = current / self.rated_current;
+        let stress = IDLE_AGING + (1.0 - IDLE_AGING) * load.powi(3);
+        let aging = 2f64.powf((temperature - self.reference_temperature) / 10.0);
+        stress * aging / self.rated_lifetime
+    }
+
+    fn outputs(&self, rate: f64) -> SimulationValues {
+        let remaining = (1.0 - self.damage).max(0.0);
+        SimulationValues::from([
+            ("damage".to_string(), self.damage),
+            ("rate".to_string(), rate),
+            ("rul".to_string(), remaining / rate),
+        ])
+    }
+}
+
+impl SimulationModel for WearModel {
+    fn init(&mut self) -> SimulationValues {
+        self.damage = 0.0;
+        let rate = self.rate(0.0, self.reference_temperature);
+        self.outputs(rate)
+    }
+
+    fn step(
+        &mut self,
+        dt: f64,
+        inputs: &SimulationValues,
+        _rng: &mut SimulationRng,
+    ) -> SimulationValues {
+        let input = |key: String| inputs.get(&key).copied();
+        let current = input(format!("{}.current", MOTOR_MODEL)).unwrap_or(0.0);
+        let temperature = input(format!("{}.winding_temperature", THERMAL_MODEL))
+            .unwrap_or(self.reference_temperature);
+        let rate = self.rate(current, temperature);
+        self.damage += rate * dt;
+        self.outputs(rate)
+    }
+
+    fn resume(&mut self, outputs: &SimulationValues) {
+        self.damage = outputs.get("damage").copied().unwrap_or(0.0);
+    }
+
+    fn parameters(&self) -> SimulationValues {
+        SimulationValues::from([
+            ("rated_lifetime".to_string(), self.rated_lifetime),
+            ("rated_current".to_string(), self.rated_current),
+            (
+                "reference_temperature".to_string(),
+                self.reference_temperature,
+            ),
+        ])
+    }
+
+    fn set_parameter(&mut self, name: &str, value: f64) -> Result<(), String> {
+        match name {
+            "rated_lifetime" => self.rated_lifetime = positive_parameter(name, value)?,
+            "rated_current" => self.rated_current = positive_parameter(name, value)?,
+            "reference_temperature" => self.reference_temperature = value,
+            _ => return Err(unknown_parameter(name)),
+        }
+        Ok(())
+    }
+
+    fn unit(&self, output: &str) -> Option<&str> {
+        match output {
+            "rate" => Some("1/s"),
+            "rul" => Some("s"),
+            _ => None,
+        }
+    }
+}
+
+/// Remaining useful life at the current operating point
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "wasm", derive(Tsify))]
+pub struct RulEstimate {
+    /// Accumulated damage, 0 when new and 1 at end of life
+    pub damage: f64,
+    /// Hours left at the current rate of damage
+    pub remaining_hours: f64,
+    /// Path of the property holding the estimate
+    pub path: String,
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl DigitalTwin {
+    /// Estimate the remaining useful life as a `RulEstimate` and store it in
+    /// the OperationalData submodel (RemainingUsefulLife in h, Damage),
+    /// creating the submodel and properties if needed
+    #[cfg(feature = "wasm")]
+    #[wasm_bindgen(unchecked_return_type = "RulEstimate")]
+    pub fn get_rul_estimate(&mut self) -> Result<JsValue, TwinError> {
+        Ok(to_js(&self.rul_estimate()?))
+    }
+}
+
+impl DigitalTwin {
+    /// Rust-side `get_rul_estimate`
+    pub fn rul_estimate(&mut self) -> Result<RulEstimate, TwinError> {
+        let value = |output: &str| {
+            let key = format!("{}.{}", WEAR_MODEL, output);
+            self.simulation_values().get(&key).copied()
+        };
+        let (Some(damage), Some(rul)) = (value("damage"), value("rul")) else {
+            return Err(TwinError::not_found(
+                WEAR_MODEL,
+                format!("No simulation model '{}'", WEAR_MODEL),
+            ));
+        };
+        let remaining_hours = rul / SECONDS_PER_HOUR;
+        let path = self.tracked(|twin| {
+            twin.write_operational_value(
+                "Damage",
+                &format!("{:.6}", damage),
+                DataTypeDefXsd::Double,
+                None,
+            )?;
+            twin.write_operational_value(
+                "RemainingUsefulLife",
+                &format!("{:.1}", remaining_hours),
+                DataTypeDefXsd::Double,
+                Some("h"),
+            )
+        })?;
+        Ok(RulEstimate {
+            damage,
+            remaining_hours,
+            path,
+        })
+    }
+
+    /// Set a property of the OperationalData submodel, adding the submodel
+    /// and property when missing; returns the property path
+    pub(crate) fn write_operational_value(
+        &mut self,
+        id_short: &str,
+        value: &str,
+        value_type: DataTypeDefXsd,
+        unit: Option<&str>,
+    ) -> Result<String, TwinError> {
+        self.operational_data_id();
+        let path = join_path(OPERATIONAL_DATA_ID_SHORT, id_short);
+        match self.find_element_mut(&path) {
+            Ok(element) => match &mut element.kind {
+                ElementKind::Property(p) => p.value = value.to_string(),
+                other => {
+                    return Err(TwinError::type_mismatch(
+                        &path,
+                        format!("'{}' is a {}, not a Property", path, other.model_type()),
+                    ))
+                }
+            },
+            Err(_) => {
+                let property = Property {
+                    value: value.to_string(),
+                    unit: unit.map(str::to_string),
+                    value_type: Some(value_type),
+                };
+                self.insert_element(
+                    OPERATIONAL_DATA_ID_SHORT,
+                    SubmodelElement::new(id_short, ElementKind::Property(property)),
+                )?;
+            }
+        }
+        Ok(path)
+    }
+
+    /// Id of the OperationalData submodel, which is added when missing
+    pub(crate) fn operational_data_id(&mut self) -> String {
+        if let Some(submodel) = self
+            .data
+            .submodels
+            .iter()
+            .find(|s| s.id_short == OPERATIONAL_DATA_ID_SHORT)
+        {
+            return submodel.id.clone();
+        }
+        let id = format!("{}/submodels/{}", self.data.id, OPERATIONAL_DATA_ID_SHORT);
+        self.data.submodels.push(Submodel {
+            id: id.clone(),
+            id_short: OPERATIONAL_DATA_ID_SHORT.to_string(),
+            display_name: Vec::new(),
+            description: Vec::new(),
+            administration: None,
+            semantic_id: None,
+            qualifiers: Vec::new(),
+            extensions: Vec::new(),
+            kind: Default::default(),
+            submodel_elements: Vec::new(),
+        });
+        id
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wear_accumulates_with_load() {
+        let model = WearModel::default();
+        let idle = model.rate(0.0, 25.0);
+        let rated = model.rate(model.rated_current, 155.0);
+        assert!((rated * model.rated_lifetime - 1.0).abs() < 1e-12);
+        assert!(idle < rated / 1000.0);
+        // Ten degrees hotter ages twice as fast
+        assert!((model.rate(model.rated_current, 165.0) / rated - 2.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_rul_estimate_written_to_operational_data() {
+        let mut twin = DigitalTwin::new(
+            r#"{"id": "M-1", "asset_type": "Motor", "nameplate": [
+                {"id_short": "Current", "value": "10", "unit": "A"},
+                {"id_short": "RatedLifetime", "value": "1", "unit": "h"},
+                {"id_short": "ThermalTimeConstant", "value": "1", "unit": "min"}
+            ]}"#,
+        )
+        .unwrap();
+        let fresh = twin.rul_estimate().unwrap();
+        assert_eq!(fresh.damage, 0.0);
+        assert_eq!(fresh.path, "OperationalData.RemainingUsefulLife");
+
+        for _ in 0..300 {
+            twin.advance_simulation();
+        }
+        let worn = twin.rul_estimate().unwrap();
+        assert!(worn.damage > 0.0);
+        assert!(worn.remaining_hours < fresh.remaining_hours);
+        assert_eq!(
+            twin.get_property_display("OperationalData.RemainingUsefulLife"),
+            format!("{:.1} h", worn.remaining_hours)
+        );
+        assert_eq!(
+            twin.get_property_display("OperationalData.Damage"),
+            format!("{:.6} ", worn.damage)
+        );
+
+        twin.detach_simulation_model(WEAR_MODEL).unwrap();
+        assert!(twin.rul_estimate().is_err_and(|e| e.code() == "NOT_FOUND"));
+    }
+}
@@ -0,0 +1,271 @@
+// --- W3C WoT Thing Description ---
+// The twin as a Thing Description 1.1: the top-level elements of every
+// submodel become property affordances (writable for PARAMETER, observable
+// for VARIABLE), operations become actions and basic event elements become
+// events, wherever they are nested. Affordances are named by idShortPath and
+// their forms address HTTP resources below the optional base URL; events
+// with an MQTT broker point at their message topic instead.
+
+use serde_json::{json, Map, Value};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    join_path, select_lang, walk_elements, AssetAdministrationShell, Category, DataTypeDefXsd,
+    DigitalTwin, ElementKind, LangString, SubmodelElement, NAMEPLATE_ID_SHORT,
+};
+
+pub const TD_CONTEXT: &str = "https://www.w3.org/2022/wot/td/v1.1";
+
+/// A Thing Description for a shell; hrefs are relative to `base` if given
+pub fn shell_to_thing_description(shell: &AssetAdministrationShell, base: Option<&str>) -> Value {
+    let mut submodels = vec![(NAMEPLATE_ID_SHORT, &shell.nameplate[..])];
+    for sm in &shell.submodels {
+        submodels.push((sm.id_short.as_str(), &sm.submodel_elements[..]));
+    }
+
+    let mut properties = Map::new();
+    let mut actions = Map::new();
+    let mut events = Map::new();
+    for (id_short, elements) in submodels {
+        for element in elements {
+            if matches!(
+                element.kind,
+                ElementKind::Operation(_)
+                    | ElementKind::BasicEventElement(_)
+                    | ElementKind::Capability(_)
+            ) {
+                continue;
+            }
+            let path = join_path(id_short, &element.id_short);
+            properties.insert(path.clone(), property(element, &path));
+        }
+        let mut paths = Vec::new();
+        walk_elements(id_short, elements, &mut paths);
+        for (path, element) in paths {
+            match &element.kind {
+                ElementKind::Operation(op) => {
+                    let mut action = json!({
+                        "forms": [form(&format!("actions/{}", path), &["invokeaction"])],
+                    });
+                    let inputs = [op.input_variables.as_slice(), &op.inoutput_variables].concat();
+                    let outputs = [op.output_variables.as_slice(), &op.inoutput_variables].concat();
+                    if !inputs.is_empty() {
+                        action["input"] = object_schema(inputs.iter().map(|v| &v.value));
+                    }
+                    if !outputs.is_empty() {
+                        action["output"] = object_schema(outputs.iter().map(|v| &v.value));
+                    }
+                    labels(&mut action, element);
+                    actions.insert(path, action);
+                }
+                ElementKind::BasicEventElement(event) => {
+                    let broker = event.message_broker.as_ref().and_then(|r| r.key_value());
+                    let form = match (broker, &event.message_topic) {
+                        (Some(broker), Some(topic)) if broker.starts_with("mqtt") => json!({
+                            "href": format!("{}/{}", broker.trim_end_matches('/'), topic),
+                            "op": ["subscribeevent"],
+                            "contentType": "application/json",
+                            "mqv:controlPacket": "subscribe",
+                        }),
+                        _ => form(&format!("events/{}", path), &["subscribeevent"]),
+                    };
+                    let mut affordance = json!({
+                        "data": {"type": "object"},
+                        "forms": [form],
+                    });
+                    if let Some(observed) = event.observed.key_value() {
+                        affordance["aas:observed"] = json!(observed);
+                    }
+                    labels(&mut affordance, element);
+                    events.insert(path, affordance);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    let mut td = json!({
+        "@context": [TD_CONTEXT, {"aas": "https://admin-shell.io/aas/3/0/", "mqv": "http://www.example.org/mqtt-binding#"}],
+        "id": shell.id,
+        "@type": "Thing",
+        "title": shell.asset_information.asset_type.as_deref().unwrap_or(&shell.id),
+        "securityDefinitions": {"nosec_sc": {"scheme": "nosec"}},
+        "security": "nosec_sc",
+        "properties": properties,
+        "actions": actions,
+        "events": events,
+    });
+    if let Some(description) = text(&shell.description) {
+        td["description"] = json!(description);
+    }
+    if let Some(base) = base {
+        td["base"] = json!(base);
+    }
+    td
+}
+
+fn form(href: &str, op: &[&str]) -> Value {
+    json!({"href": href, "op": op, "contentType": "application/json"})
+}
+
+/// A property affordance: the element's data schema plus access and forms
+fn property(element: &SubmodelElement, path: &str) -> Value {
+    let mut affordance = schema(element);
+    let mut op = vec!["readproperty"];
+    match element.category {
+        Some(Category::Parameter) => op.push("writeproperty"),
+        Some(Category::Variable) => {
+            op.extend(["observeproperty", "unobserveproperty"]);
+            affordance["observable"] = json!(true);
+        }
+        _ => {}
+    }
+    if element.category != Some(Category::Parameter) {
+        affordance["readOnly"] = json!(true);
+    }
+    affordance["forms"] = json!([form(&format!("properties/{}", path), &op)]);
+    affordance
+}
+
+fn object_schema<'a>(elements: impl Iterator<Item = &'a SubmodelElement>) -> Value {
+    let properties: Map<String, Value> =
+        elements.map(|e| (e.id_short.clone(), schema(e))).collect();
+    json!({"type": "object", "properties": properties})
+}
+
+/// Data schema of an element's value, with its unit and labels
+fn schema(element: &SubmodelElement) -> Value {
+    let number = |t: Option<DataTypeDefXsd>| match t {
+        Some(DataTypeDefXsd::Int) => "integer",
+        _ => "number",
+    };
+    let mut schema = match &element.kind {
+        ElementKind::Property(p) => match p.value_type {
+            Some(t @ (DataTypeDefXsd::Int | DataTypeDefXsd::Double)) => {
+                json!({"type": number(Some(t))})
+            }
+            Some(DataTypeDefXsd::Boolean) => json!({"type": "boolean"}),
+            Some(DataTypeDefXsd::DateTime) => json!({"type": "string", "format": "date-time"}),
+            Some(DataTypeDefXsd::String) | None => json!({"type": "string"}),
+        },
+        ElementKind::MultiLanguageProperty(_) => {
+            json!({"type": "object", "additionalProperties": {"type": "string"}})
+        }
+        ElementKind::Range(r) => {
+            let bound = json!({"type": number(r.value_type)});
+            json!({"type": "object", "properties": {"min": bound, "max": bound}})
+        }
+        ElementKind::File(_) => json!({"type": "string", "format": "uri"}),
+        ElementKind::Blob(b) => json!({
+            "type": "string",
+            "contentEncoding": "base64",
+            "contentMediaType": b.content_type,
+        }),
+        ElementKind::SubmodelElementCollection(c) => object_schema(c.value.iter()),
+        ElementKind::Entity(e) => object_schema(e.statements.iter()),
+        ElementKind::SubmodelElementList(l) => {
+            let mut array = json!({"type": "array"});
+            if let Some(first) = l.value.first() {
+                array["items"] = schema(first);
+            }
+            array
+        }
+        _ => json!({"type": "string"}),
+    };
+    if let Some(unit) = element.unit() {
+        schema["unit"] = json!(unit);
+    }
+    labels(&mut schema, element);
+    schema
+}
+
+/// Add `title` and `description` if the element has them
+fn labels(target: &mut Value, element: &SubmodelElement) {
+    if let Some(title) = text(&element.display_name) {
+        target["title"] = json!(title);
+    }
+    if let Some(description) = text(&element.description) {
+        target["description"] = json!(description);
+    }
+}
+
+fn text(strings: &[LangString]) -> Option<String> {
+    select_lang(strings, "en").map(|s| s.text.clone())
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl DigitalTwin {
+    /// Export the twin as a W3C WoT Thing Description; forms are relative to
+    /// `base_url` if given
+    pub fn get_thing_description(&self, base_url: Option<String>) -> String {
+        serde_json::to_string_pretty(&shell_to_thing_description(&self.data, base_url.as_deref()))
+            .unwrap_or_else(|_| "{}".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_thing_description() {
+        let json = r#"{
+            "id": "urn:example:aas:motor",
+            "asset_type": "Siemens 1LE1",
+            "nameplate": [
+                {"id_short": "Voltage", "value": "400", "unit": "V", "value_type": "xs:double"}
+            ],
+            "submodels": [{
+                "id": "urn:example:sm:op",
+                "id_short": "OperationalData",
+                "submodel_elements": [
+                    {"id_short": "Speed", "value": "1450", "value_type": "xs:int", "category": "VARIABLE"},
+                    {"id_short": "SetPoint", "value": "1500", "value_type": "xs:int", "category": "PARAMETER"},
+                    {"id_short": "Start", "model_type": "Operation", "input_variables": [
+                        {"value": {"id_short": "Rpm", "value": "0", "value_type": "xs:int"}}
+                    ]},
+                    {"id_short": "Overheat", "model_type": "BasicEventElement",
+                     "observed": {"type": "ModelReference", "keys": [{"type": "Submodel", "value": "urn:example:sm:op"}]},
+                     "direction": "output", "state": "on", "message_topic": "motor/overheat",
+                     "message_broker": {"type": "ExternalReference", "keys": [{"type": "GlobalReference", "value": "mqtt://broker.local"}]}}
+                ]
+            }]
+        }"#;
+        let twin = DigitalTwin::new(json).unwrap();
+        let td: Value =
+            serde_json::from_str(&twin.get_thing_description(Some("http://motor.local/".into())))
+                .unwrap();
+
+        assert_eq!(td["@context"][0], TD_CONTEXT);
+        assert_eq!(td["id"], "urn:example:aas:motor");
+        assert_eq!(td["title"], "Siemens 1LE1");
+        assert_eq!(td["base"], "http://motor.local/");
+
+        let voltage = &td["properties"]["Nameplate.Voltage"];
+        assert_eq!(voltage["type"], "number");
+        assert_eq!(voltage["unit"], "V");
+        assert_eq!(voltage["readOnly"], true);
+        assert_eq!(voltage["forms"][0]["href"], "properties/Nameplate.Voltage");
+        assert_eq!(
+            td["properties"]["OperationalData.Speed"]["observable"],
+            true
+        );
+        let set_point = &td["properties"]["OperationalData.SetPoint"];
+        assert_eq!(
+            set_point["forms"][0]["op"],
+            json!(["readproperty", "writeproperty"])
+        );
+        assert!(set_point.get("readOnly").is_none());
+        assert!(td["properties"].get("OperationalData.Start").is_none());
+
+        let start = &td["actions"]["OperationalData.Start"];
+        assert_eq!(start["input"]["properties"]["Rpm"]["type"], "integer");
+        assert_eq!(start["forms"][0]["op"], json!(["invokeaction"]));
+        let overheat = &td["events"]["OperationalData.Overheat"];
+        assert_eq!(
+            overheat["forms"][0]["href"],
+            "mqtt://broker.local/motor/overheat"
+        );
+    }
+}
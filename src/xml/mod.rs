@@ -0,0 +1,45 @@
+// --- AAS XML ---
+// The XML flavor of the AAS 3.0 serialization (namespace below). Element
+// names are the camelCase attribute names of the metamodel, lists wrap their
+// items (`<keys><key>..</key></keys>`) and submodel elements are tagged with
+// their type (`<property>`, `<file>`, ...).
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{select_shell, DigitalTwin, Environment, TwinError};
+
+mod read;
+mod write;
+
+pub use read::environment_from_xml;
+pub use write::environment_to_xml;
+
+/// Namespace of the AAS 3.0 XML schema
+pub const AAS_XML_NAMESPACE: &str = "https://admin-shell.io/aas/3/0";
+
+/// XML element name of a submodel element type ("MultiLanguageProperty" ->
+/// "multiLanguageProperty")
+pub fn xml_tag(model_type: &str) -> String {
+    let mut chars = model_type.chars();
+    match chars.next() {
+        Some(first) => first.to_ascii_lowercase().to_string() + chars.as_str(),
+        None => String::new(),
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl DigitalTwin {
+    /// Hydrate the twin from an AAS 3.0 XML environment (the first shell), e.g.
+    /// as exported by PLC engineering tools
+    pub fn from_xml(xml: &str) -> Result<DigitalTwin, TwinError> {
+        let env = environment_from_xml(xml)
+            .map_err(|e| TwinError::parse(format!("Invalid AAS XML: {}", e)))?;
+        DigitalTwin::from_shell(select_shell(env, None)?)
+    }
+
+    /// Export the twin as an AAS 3.0 XML environment
+    pub fn get_aas_xml(&self) -> String {
+        environment_to_xml(&Environment::from_shell(&self.data))
+    }
+}
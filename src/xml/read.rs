@@ -0,0 +1,552 @@
+// Deserialization from AAS 3.0 XML. The document is first read into a plain
+// element tree, which is then mapped onto the model so that errors can name
+// the offending element and its line. Elements the model does not cover
+// (e.g. supplementalSemanticIds) are skipped.
+
+use quick_xml::escape::resolve_predefined_entity;
+use quick_xml::events::Event;
+use quick_xml::{Reader, XmlVersion};
+use serde::de::DeserializeOwned;
+
+use super::{xml_tag, AAS_XML_NAMESPACE};
+use crate::{
+    AdministrativeInformation, AnnotatedRelationshipElement, AssetInformation, BasicEventElement,
+    Blob, Capability, ConceptDescription, DataSpecificationIec61360, ElementKind,
+    EmbeddedDataSpecification, Entity, Environment, EnvironmentShell, Extension, File, Key,
+    LangString, MultiLanguageProperty, Operation, OperationVariable, Property, Qualifier, Range,
+    Reference, ReferenceElement, RelationshipElement, Resource, SpecificAssetId, Submodel,
+    SubmodelElement, SubmodelElementCollection, SubmodelElementList, UNIT_EXTENSION,
+};
+
+/// Parse an AAS 3.0 XML environment
+pub fn environment_from_xml(xml: &str) -> Result<Environment, String> {
+    let root = parse_tree(xml)?;
+    if root.name != "environment" {
+        return Err(
+            root.error("is not an AAS environment; expected <environment> as the root element")
+        );
+    }
+    if let Some(ns) = root
+        .namespace
+        .as_deref()
+        .filter(|ns| *ns != AAS_XML_NAMESPACE)
+    {
+        return Err(root.error(&format!(
+            "has namespace '{}', expected '{}'",
+            ns, AAS_XML_NAMESPACE
+        )));
+    }
+    Ok(Environment {
+        asset_administration_shells: root.items(
+            "assetAdministrationShells",
+            "assetAdministrationShell",
+            shell,
+        )?,
+        submodels: root.items("submodels", "submodel", submodel)?,
+        concept_descriptions: root.items(
+            "conceptDescriptions",
+            "conceptDescription",
+            concept_description,
+        )?,
+    })
+}
+
+/// An XML element with its text content and child elements
+struct Node {
+    name: String,
+    namespace: Option<String>,
+    text: String,
+    children: Vec<Node>,
+    line: usize,
+}
+
+impl Node {
+    fn error(&self, message: &str) -> String {
+        format!("line {}: <{}> {}", self.line, self.name, message)
+    }
+
+    fn child(&self, name: &str) -> Option<&Node> {
+        self.children.iter().find(|c| c.name == name)
+    }
+
+    fn required(&self, name: &str) -> Result<&Node, String> {
+        self.child(name)
+            .ok_or_else(|| self.error(&format!("is missing required element <{}>", name)))
+    }
+
+    fn text_of(&self, name: &str) -> Option<String> {
+        self.child(name).map(|c| c.text.clone())
+    }
+
+    fn required_text(&self, name: &str) -> Result<String, String> {
+        self.required(name).map(|c| c.text.clone())
+    }
+
+    /// Map the `item` children of the list wrapper `list`; a missing wrapper is an empty list
+    fn items<T>(
+        &self,
+        list: &str,
+        item: &str,
+        map: impl Fn(&Node) -> Result<T, String>,
+    ) -> Result<Vec<T>, String> {
+        let Some(wrapper) = self.child(list) else {
+            return Ok(Vec::new());
+        };
+        wrapper
+            .children
+            .iter()
+            .map(|c| {
+                if c.name == item {
+                    map(c)
+                } else {
+                    Err(c.error(&format!(
+                        "is not allowed in <{}>, expected <{}>",
+                        list, item
+                    )))
+                }
+            })
+            .collect()
+    }
+
+    /// Parse the node's text as an enum using its JSON name
+    fn parse<T: DeserializeOwned>(&self) -> Result<T, String> {
+        let text = self.text.trim();
+        serde_json::from_value(serde_json::Value::String(text.to_string()))
+            .map_err(|e| self.error(&format!("has invalid value '{}': {}", text, e)))
+    }
+
+    fn parse_child<T: DeserializeOwned>(&self, name: &str) -> Result<Option<T>, String> {
+        self.child(name).map(Node::parse).transpose()
+    }
+
+    fn parse_required<T: DeserializeOwned>(&self, name: &str) -> Result<T, String> {
+        self.required(name)?.parse()
+    }
+
+    fn reference(&self, name: &str) -> Result<Option<Reference>, String> {
+        self.child(name).map(reference).transpose()
+    }
+}
+
+fn parse_tree(xml: &str) -> Result<Node, String> {
+    let line_at = |pos: u64| xml[..(pos as usize).min(xml.len())].matches('\n').count() + 1;
+    let mut reader = Reader::from_str(xml);
+    let mut stack: Vec<Node> = Vec::new();
+    loop {
+        let pos = reader.buffer_position();
+        let event = reader.read_event().map_err(|e| {
+            format!(
+                "line {}: malformed XML: {}",
+                line_at(reader.error_position()),
+                e
+            )
+        })?;
+        let empty = matches!(event, Event::Empty(_));
+        match event {
+            Event::Start(e) | Event::Empty(e) => {
+                let mut namespace = None;
+                for attr in e.attributes().flatten() {
+                    if attr.key.as_ref() == "xmlns" {
+                        let value = attr
+                            .normalized_value(XmlVersion::Implicit1_0)
+                            .map_err(|e| format!("line {}: {}", line_at(pos), e))?;
+                        namespace = Some(value.into_owned());
+                    }
+                }
+                let node = Node {
+                    name: e.local_name().as_ref().to_string(),
+                    namespace,
+                    text: String::new(),
+                    children: Vec::new(),
+                    line: line_at(pos),
+                };
+                stack.push(node);
+                if empty {
+                    if let Some(root) = close(&mut stack) {
+                        return Ok(root);
+                    }
+                }
+            }
+            Event::End(_) => {
+                if let Some(root) = close(&mut stack) {
+                    return Ok(root);
+                }
+            }
+            Event::Text(t) => {
+                if let Some(node) = stack.last_mut() {
+                    node.text.push_str(&t.xml_content(XmlVersion::Implicit1_0));
+                }
+            }
+            Event::CData(t) => {
+                if let Some(node) = stack.last_mut() {
+                    node.text.push_str(&t.xml_content(XmlVersion::Implicit1_0));
+                }
+            }
+            Event::GeneralRef(r) => {
+                let resolved = match r.resolve_char_ref() {
+                    Ok(Some(c)) => c.to_string(),
+                    _ => resolve_predefined_entity(&r.xml_content(XmlVersion::Implicit1_0))
+                        .map(str::to_string)
+                        .ok_or_else(|| {
+                            format!("line {}: unknown entity reference", line_at(pos))
+                        })?,
+                };
+                if let Some(node) = stack.last_mut() {
+                    node.text.push_str(&resolved);
+                }
+            }
+            Event::Eof => return Err("XML document ends before the root element is closed".into()),
+            _ => {}
+        }
+    }
+}
+
+/// Pop the innermost open element and attach it to its parent; returns the
+/// root once it is closed
+fn close(stack: &mut Vec<Node>) -> Option<Node> {
+    let node = stack.pop()?;
+    match stack.last_mut() {
+        Some(parent) => {
+            parent.children.push(node);
+            None
+        }
+        None => Some(node),
+    }
+}
+
+fn lang_strings(node: &Node, list: &str) -> Result<Vec<LangString>, String> {
+    let Some(wrapper) = node.child(list) else {
+        return Ok(Vec::new());
+    };
+    wrapper
+        .children
+        .iter()
+        .map(|s| {
+            Ok(LangString {
+                language: s.required_text("language")?,
+                text: s.required_text("text")?,
+            })
+        })
+        .collect()
+}
+
+fn reference(node: &Node) -> Result<Reference, String> {
+    Ok(Reference {
+        reference_type: node.parse_required("type")?,
+        referred_semantic_id: node.reference("referredSemanticId")?.map(Box::new),
+        keys: node.items("keys", "key", |key| {
+            Ok(Key {
+                key_type: key.parse_required("type")?,
+                value: key.required_text("value")?,
+            })
+        })?,
+    })
+}
+
+fn extension(node: &Node) -> Result<Extension, String> {
+    Ok(Extension {
+        name: node.required_text("name")?,
+        value_type: node.parse_child("valueType")?,
+        value: node.text_of("value"),
+        refers_to: node.items("refersTo", "reference", reference)?,
+        semantic_id: node.reference("semanticId")?,
+    })
+}
+
+fn administration(node: &Node) -> Result<Option<AdministrativeInformation>, String> {
+    let Some(admin) = node.child("administration") else {
+        return Ok(None);
+    };
+    Ok(Some(AdministrativeInformation {
+        version: admin.text_of("version"),
+        revision: admin.text_of("revision"),
+        creator: admin.reference("creator")?,
+        template_id: admin.text_of("templateId"),
+    }))
+}
+
+fn qualifier(node: &Node) -> Result<Qualifier, String> {
+    Ok(Qualifier {
+        qualifier_type: node.required_text("type")?,
+        value_type: node.parse_required("valueType")?,
+        value: node.text_of("value"),
+        value_id: node.reference("valueId")?,
+        kind: node.parse_child("kind")?.unwrap_or_default(),
+        semantic_id: node.reference("semanticId")?,
+    })
+}
+
+fn data_specification(node: &Node) -> Result<EmbeddedDataSpecification, String> {
+    let content = node
+        .required("dataSpecificationContent")?
+        .required("dataSpecificationIec61360")?;
+    Ok(EmbeddedDataSpecification {
+        data_specification: reference(node.required("dataSpecification")?)?,
+        data_specification_content: DataSpecificationIec61360 {
+            preferred_name: lang_strings(content, "preferredName")?,
+            short_name: lang_strings(content, "shortName")?,
+            unit: content.text_of("unit"),
+            unit_id: content.reference("unitId")?,
+            symbol: content.text_of("symbol"),
+            data_type: content.parse_child("dataType")?,
+            definition: lang_strings(content, "definition")?,
+            value_format: content.text_of("valueFormat"),
+        },
+    })
+}
+
+fn asset_information(node: &Node) -> Result<AssetInformation, String> {
+    let info = node.required("assetInformation")?;
+    Ok(AssetInformation {
+        asset_kind: info.parse_required("assetKind")?,
+        global_asset_id: info.text_of("globalAssetId"),
+        specific_asset_ids: info.items("specificAssetIds", "specificAssetId", |id| {
+            Ok(SpecificAssetId {
+                name: id.required_text("name")?,
+                value: id.required_text("value")?,
+                external_subject_id: id.reference("externalSubjectId")?,
+                semantic_id: id.reference("semanticId")?,
+            })
+        })?,
+        asset_type: info.text_of("assetType"),
+        default_thumbnail: info
+            .child("defaultThumbnail")
+            .map(|t| {
+                Ok::<_, String>(Resource {
+                    path: t.required_text("path")?,
+                    content_type: t.text_of("contentType"),
+                })
+            })
+            .transpose()?,
+    })
+}
+
+fn shell(node: &Node) -> Result<EnvironmentShell, String> {
+    Ok(EnvironmentShell {
+        id: node.required_text("id")?,
+        id_short: node.text_of("idShort"),
+        display_name: lang_strings(node, "displayName")?,
+        description: lang_strings(node, "description")?,
+        asset_information: asset_information(node)?,
+        administration: administration(node)?,
+        submodels: node.items("submodels", "reference", reference)?,
+        extensions: node.items("extensions", "extension", extension)?,
+    })
+}
+
+fn submodel(node: &Node) -> Result<Submodel, String> {
+    Ok(Submodel {
+        id: node.required_text("id")?,
+        id_short: node.text_of("idShort").unwrap_or_default(),
+        display_name: lang_strings(node, "displayName")?,
+        description: lang_strings(node, "description")?,
+        administration: administration(node)?,
+        semantic_id: node.reference("semanticId")?,
+        qualifiers: node.items("qualifiers", "qualifier", qualifier)?,
+        extensions: node.items("extensions", "extension", extension)?,
+        kind: node.parse_child("kind")?.unwrap_or_default(),
+        submodel_elements: elements(node, "submodelElements")?,
+    })
+}
+
+fn concept_description(node: &Node) -> Result<ConceptDescription, String> {
+    Ok(ConceptDescription {
+        id: node.required_text("id")?,
+        id_short: node.text_of("idShort"),
+        administration: administration(node)?,
+        is_case_of: node.items("isCaseOf", "reference", reference)?,
+        embedded_data_specifications: node.items(
+            "embeddedDataSpecifications",
+            "embeddedDataSpecification",
+            data_specification,
+        )?,
+    })
+}
+
+/// The submodel elements inside the wrapper `list`, each tagged with its type
+fn elements(node: &Node, list: &str) -> Result<Vec<SubmodelElement>, String> {
+    node.child(list)
+        .map(|wrapper| wrapper.children.iter().map(element).collect())
+        .unwrap_or_else(|| Ok(Vec::new()))
+}
+
+fn operation_variables(node: &Node, list: &str) -> Result<Vec<OperationVariable>, String> {
+    node.items(list, "operationVariable", |var| {
+        let value = var.required("value")?;
+        let element = value
+            .children
+            .first()
+            .ok_or_else(|| value.error("must contain a submodel element"))?;
+        Ok(OperationVariable {
+            value: self::element(element)?,
+        })
+    })
+}
+
+fn element(node: &Node) -> Result<SubmodelElement, String> {
+    let mut extensions = node.items("extensions", "extension", extension)?;
+    let unit = extensions
+        .iter()
+        .position(|e| e.name == UNIT_EXTENSION)
+        .and_then(|i| extensions.remove(i).value);
+
+    let kind = match node.name.as_str() {
+        "property" => ElementKind::Property(Property {
+            value: node.text_of("value").unwrap_or_default(),
+            unit,
+            value_type: Some(node.parse_required("valueType")?),
+        }),
+        "multiLanguageProperty" => ElementKind::MultiLanguageProperty(MultiLanguageProperty {
+            value: lang_strings(node, "value")?,
+        }),
+        "range" => ElementKind::Range(Range {
+            value_type: Some(node.parse_required("valueType")?),
+            min: node.text_of("min"),
+            max: node.text_of("max"),
+            unit,
+        }),
+        "file" => ElementKind::File(File {
+            value: node.text_of("value"),
+            content_type: node.text_of("contentType"),
+        }),
+        "blob" => ElementKind::Blob(Blob {
+            value: node.text_of("value"),
+            content_type: node.text_of("contentType"),
+        }),
+        "referenceElement" => ElementKind::ReferenceElement(ReferenceElement {
+            value: node.reference("value")?,
+        }),
+        "relationshipElement" => ElementKind::RelationshipElement(RelationshipElement {
+            first: node.reference("first")?,
+            second: node.reference("second")?,
+        }),
+        "annotatedRelationshipElement" => {
+            ElementKind::AnnotatedRelationshipElement(AnnotatedRelationshipElement {
+                first: node.reference("first")?,
+                second: node.reference("second")?,
+                annotations: elements(node, "annotations")?,
+            })
+        }
+        "entity" => ElementKind::Entity(Entity {
+            entity_type: node.parse_required("entityType")?,
+            global_asset_id: node.text_of("globalAssetId"),
+            statements: elements(node, "statements")?,
+        }),
+        "operation" => ElementKind::Operation(Operation {
+            input_variables: operation_variables(node, "inputVariables")?,
+            output_variables: operation_variables(node, "outputVariables")?,
+            inoutput_variables: operation_variables(node, "inoutputVariables")?,
+        }),
+        "capability" => ElementKind::Capability(Capability {}),
+        "basicEventElement" => ElementKind::BasicEventElement(BasicEventElement {
+            observed: reference(node.required("observed")?)?,
+            direction: node.parse_required("direction")?,
+            state: node.parse_required("state")?,
+            message_topic: node.text_of("messageTopic"),
+            message_broker: node.reference("messageBroker")?,
+            last_update: node.text_of("lastUpdate"),
+            min_interval: node.text_of("minInterval"),
+            max_interval: node.text_of("maxInterval"),
+        }),
+        "submodelElementCollection" => {
+            ElementKind::SubmodelElementCollection(SubmodelElementCollection {
+                value: elements(node, "value")?,
+            })
+        }
+        "submodelElementList" => ElementKind::SubmodelElementList(SubmodelElementList {
+            order_relevant: node
+                .child("orderRelevant")
+                .is_none_or(|n| n.text.trim() == "true"),
+            type_value_list_element: node.parse_required("typeValueListElement")?,
+            semantic_id_list_element: node.reference("semanticIdListElement")?,
+            value_type_list_element: node.parse_child("valueTypeListElement")?,
+            value: elements(node, "value")?,
+        }),
+        _ => {
+            let expected: Vec<String> = ElementKind::MODEL_TYPES
+                .iter()
+                .map(|t| format!("<{}>", xml_tag(t)))
+                .collect();
+            return Err(node.error(&format!(
+                "is not a submodel element; expected one of {}",
+                expected.join(", ")
+            )));
+        }
+    };
+
+    Ok(SubmodelElement {
+        id_short: node.text_of("idShort").unwrap_or_default(),
+        display_name: lang_strings(node, "displayName")?,
+        description: lang_strings(node, "description")?,
+        category: node.parse_child("category")?,
+        semantic_id: node.reference("semanticId")?,
+        qualifiers: node.items("qualifiers", "qualifier", qualifier)?,
+        extensions,
+        embedded_data_specifications: node.items(
+            "embeddedDataSpecifications",
+            "embeddedDataSpecification",
+            data_specification,
+        )?,
+        kind,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DigitalTwin;
+
+    #[test]
+    fn test_xml_round_trip() {
+        let json = r#"{
+            "id": "MOTOR-12345",
+            "asset_type": "Siemens 1LE1",
+            "nameplate": [
+                {"id_short": "Voltage", "value": "400", "unit": "V", "value_type": "xs:double"},
+                {"id_short": "Name", "model_type": "MultiLanguageProperty",
+                 "value": [{"language": "en", "text": "Motor <A&B>"}]},
+                {"id_short": "Markings", "model_type": "SubmodelElementList",
+                 "type_value_list_element": "File",
+                 "value": [{"model_type": "File", "value": "/aasx/ce.png",
+                            "content_type": "image/png"}]}
+            ]
+        }"#;
+        let twin = DigitalTwin::new(json).unwrap();
+        let back = DigitalTwin::from_xml(&twin.get_aas_xml()).unwrap();
+
+        assert_eq!(back.get_property_display("Voltage"), "400 V");
+        assert_eq!(back.get_property_display("Name"), "Motor <A&B>");
+        assert_eq!(
+            back.get_property_display("Nameplate.Markings[0]"),
+            "/aasx/ce.png"
+        );
+        assert_eq!(back.get_asset_type(), "Siemens 1LE1");
+    }
+
+    #[test]
+    fn test_schema_errors_name_the_element() {
+        let missing = r#"<?xml version="1.0"?>
+<environment xmlns="https://admin-shell.io/aas/3/0">
+  <submodels>
+    <submodel>
+      <idShort>TechnicalData</idShort>
+    </submodel>
+  </submodels>
+</environment>"#;
+        assert_eq!(
+            environment_from_xml(missing).unwrap_err(),
+            "line 4: <submodel> is missing required element <id>"
+        );
+
+        let unknown = r#"<environment xmlns="https://admin-shell.io/aas/3/0"><submodels>
+            <submodel><id>sm</id><submodelElements><gizmo/></submodelElements></submodel>
+        </submodels></environment>"#;
+        let err = environment_from_xml(unknown).unwrap_err();
+        assert!(err.starts_with("line 2: <gizmo> is not a submodel element"));
+
+        let v2 = r#"<aasenv xmlns="http://www.admin-shell.io/aas/2/0"/>"#;
+        assert!(environment_from_xml(v2)
+            .unwrap_err()
+            .contains("<environment>"));
+    }
+}
@@ -0,0 +1,413 @@
+// Serialization to AAS 3.0 XML; child elements follow the order of the schema
+
+use quick_xml::escape::escape;
+
+use super::{xml_tag, AAS_XML_NAMESPACE};
+use crate::{
+    enum_name, AdministrativeInformation, AssetInformation, ConceptDescription, DataTypeDefXsd,
+    ElementKind, EmbeddedDataSpecification, Environment, EnvironmentShell, Extension, LangString,
+    OperationVariable, Qualifier, Reference, Submodel, SubmodelElement,
+};
+
+/// Serialize an environment as an indented AAS 3.0 XML document
+pub fn environment_to_xml(env: &Environment) -> String {
+    let mut w = XmlWriter::default();
+    w.out
+        .push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    w.out
+        .push_str(&format!("<environment xmlns=\"{}\">\n", AAS_XML_NAMESPACE));
+    w.depth = 1;
+    w.list(
+        "assetAdministrationShells",
+        &env.asset_administration_shells,
+        shell,
+    );
+    w.list("submodels", &env.submodels, submodel);
+    w.list(
+        "conceptDescriptions",
+        &env.concept_descriptions,
+        concept_description,
+    );
+    w.out.push_str("</environment>\n");
+    w.out
+}
+
+#[derive(Default)]
+struct XmlWriter {
+    out: String,
+    depth: usize,
+}
+
+impl XmlWriter {
+    fn indent(&mut self) {
+        for _ in 0..self.depth {
+            self.out.push_str("  ");
+        }
+    }
+
+    fn open(&mut self, tag: &str) {
+        self.indent();
+        self.out.push_str(&format!("<{}>\n", tag));
+        self.depth += 1;
+    }
+
+    fn close(&mut self, tag: &str) {
+        self.depth -= 1;
+        self.indent();
+        self.out.push_str(&format!("</{}>\n", tag));
+    }
+
+    fn leaf(&mut self, tag: &str, text: &str) {
+        self.indent();
+        self.out
+            .push_str(&format!("<{}>{}</{}>\n", tag, escape(text), tag));
+    }
+
+    fn opt(&mut self, tag: &str, text: Option<&str>) {
+        if let Some(text) = text {
+            self.leaf(tag, text);
+        }
+    }
+
+    /// A wrapper element around `items`, omitted when there are none
+    fn list<T>(&mut self, tag: &str, items: &[T], mut item: impl FnMut(&mut Self, &T)) {
+        if items.is_empty() {
+            return;
+        }
+        self.open(tag);
+        for i in items {
+            item(self, i);
+        }
+        self.close(tag);
+    }
+}
+
+fn lang_strings(w: &mut XmlWriter, tag: &str, item_tag: &str, strings: &[LangString]) {
+    w.list(tag, strings, |w, s| {
+        w.open(item_tag);
+        w.leaf("language", &s.language);
+        w.leaf("text", &s.text);
+        w.close(item_tag);
+    });
+}
+
+fn reference(w: &mut XmlWriter, tag: &str, r: &Reference) {
+    w.open(tag);
+    w.leaf("type", &enum_name(&r.reference_type));
+    if let Some(referred) = &r.referred_semantic_id {
+        reference(w, "referredSemanticId", referred);
+    }
+    w.list("keys", &r.keys, |w, key| {
+        w.open("key");
+        w.leaf("type", &enum_name(&key.key_type));
+        w.leaf("value", &key.value);
+        w.close("key");
+    });
+    w.close(tag);
+}
+
+fn opt_reference(w: &mut XmlWriter, tag: &str, r: Option<&Reference>) {
+    if let Some(r) = r {
+        reference(w, tag, r);
+    }
+}
+
+fn extensions(w: &mut XmlWriter, extensions: &[Extension]) {
+    w.list("extensions", extensions, |w, e| {
+        w.open("extension");
+        opt_reference(w, "semanticId", e.semantic_id.as_ref());
+        w.leaf("name", &e.name);
+        w.opt(
+            "valueType",
+            e.value_type.as_ref().map(DataTypeDefXsd::as_str),
+        );
+        w.opt("value", e.value.as_deref());
+        w.list("refersTo", &e.refers_to, |w, r| {
+            reference(w, "reference", r)
+        });
+        w.close("extension");
+    });
+}
+
+fn administration(w: &mut XmlWriter, admin: Option<&AdministrativeInformation>) {
+    let Some(admin) = admin else { return };
+    w.open("administration");
+    w.opt("version", admin.version.as_deref());
+    w.opt("revision", admin.revision.as_deref());
+    opt_reference(w, "creator", admin.creator.as_ref());
+    w.opt("templateId", admin.template_id.as_deref());
+    w.close("administration");
+}
+
+fn qualifiers(w: &mut XmlWriter, qualifiers: &[Qualifier]) {
+    w.list("qualifiers", qualifiers, |w, q| {
+        w.open("qualifier");
+        opt_reference(w, "semanticId", q.semantic_id.as_ref());
+        w.leaf("kind", &enum_name(&q.kind));
+        w.leaf("type", &q.qualifier_type);
+        w.leaf("valueType", q.value_type.as_str());
+        w.opt("value", q.value.as_deref());
+        opt_reference(w, "valueId", q.value_id.as_ref());
+        w.close("qualifier");
+    });
+}
+
+fn data_specifications(w: &mut XmlWriter, specs: &[EmbeddedDataSpecification]) {
+    w.list("embeddedDataSpecifications", specs, |w, spec| {
+        let content = &spec.data_specification_content;
+        w.open("embeddedDataSpecification");
+        reference(w, "dataSpecification", &spec.data_specification);
+        w.open("dataSpecificationContent");
+        w.open("dataSpecificationIec61360");
+        lang_strings(
+            w,
+            "preferredName",
+            "langStringPreferredNameTypeIec61360",
+            &content.preferred_name,
+        );
+        lang_strings(
+            w,
+            "shortName",
+            "langStringShortNameTypeIec61360",
+            &content.short_name,
+        );
+        w.opt("unit", content.unit.as_deref());
+        opt_reference(w, "unitId", content.unit_id.as_ref());
+        w.opt("symbol", content.symbol.as_deref());
+        w.opt(
+            "dataType",
+            content.data_type.as_ref().map(enum_name).as_deref(),
+        );
+        lang_strings(
+            w,
+            "definition",
+            "langStringDefinitionTypeIec61360",
+            &content.definition,
+        );
+        w.opt("valueFormat", content.value_format.as_deref());
+        w.close("dataSpecificationIec61360");
+        w.close("dataSpecificationContent");
+        w.close("embeddedDataSpecification");
+    });
+}
+
+fn asset_information(w: &mut XmlWriter, info: &AssetInformation) {
+    w.open("assetInformation");
+    w.leaf("assetKind", &enum_name(&info.asset_kind));
+    w.opt("globalAssetId", info.global_asset_id.as_deref());
+    w.list("specificAssetIds", &info.specific_asset_ids, |w, id| {
+        w.open("specificAssetId");
+        opt_reference(w, "semanticId", id.semantic_id.as_ref());
+        w.leaf("name", &id.name);
+        w.leaf("value", &id.value);
+        opt_reference(w, "externalSubjectId", id.external_subject_id.as_ref());
+        w.close("specificAssetId");
+    });
+    w.opt("assetType", info.asset_type.as_deref());
+    if let Some(thumbnail) = &info.default_thumbnail {
+        w.open("defaultThumbnail");
+        w.leaf("path", &thumbnail.path);
+        w.opt("contentType", thumbnail.content_type.as_deref());
+        w.close("defaultThumbnail");
+    }
+    w.close("assetInformation");
+}
+
+fn shell(w: &mut XmlWriter, shell: &EnvironmentShell) {
+    w.open("assetAdministrationShell");
+    extensions(w, &shell.extensions);
+    w.opt("idShort", shell.id_short.as_deref());
+    lang_strings(w, "displayName", "langStringNameType", &shell.display_name);
+    lang_strings(w, "description", "langStringTextType", &shell.description);
+    administration(w, shell.administration.as_ref());
+    w.leaf("id", &shell.id);
+    asset_information(w, &shell.asset_information);
+    w.list("submodels", &shell.submodels, |w, r| {
+        reference(w, "reference", r)
+    });
+    w.close("assetAdministrationShell");
+}
+
+fn submodel(w: &mut XmlWriter, sm: &Submodel) {
+    w.open("submodel");
+    extensions(w, &sm.extensions);
+    w.leaf("idShort", &sm.id_short);
+    lang_strings(w, "displayName", "langStringNameType", &sm.display_name);
+    lang_strings(w, "description", "langStringTextType", &sm.description);
+    administration(w, sm.administration.as_ref());
+    w.leaf("id", &sm.id);
+    w.leaf("kind", &enum_name(&sm.kind));
+    opt_reference(w, "semanticId", sm.semantic_id.as_ref());
+    qualifiers(w, &sm.qualifiers);
+    w.list("submodelElements", &sm.submodel_elements, element);
+    w.close("submodel");
+}
+
+fn concept_description(w: &mut XmlWriter, cd: &ConceptDescription) {
+    w.open("conceptDescription");
+    w.opt("idShort", cd.id_short.as_deref());
+    administration(w, cd.administration.as_ref());
+    w.leaf("id", &cd.id);
+    data_specifications(w, &cd.embedded_data_specifications);
+    w.list("isCaseOf", &cd.is_case_of, |w, r| {
+        reference(w, "reference", r)
+    });
+    w.close("conceptDescription");
+}
+
+fn operation_variables(w: &mut XmlWriter, tag: &str, vars: &[OperationVariable]) {
+    w.list(tag, vars, |w, var| {
+        w.open("operationVariable");
+        w.open("value");
+        element(w, &var.value);
+        w.close("value");
+        w.close("operationVariable");
+    });
+}
+
+fn element(w: &mut XmlWriter, e: &SubmodelElement) {
+    let tag = xml_tag(e.kind.model_type());
+    w.open(&tag);
+
+    extensions(w, &e.spec_extensions());
+    w.opt("category", e.category.as_ref().map(enum_name).as_deref());
+    if !e.id_short.is_empty() {
+        w.leaf("idShort", &e.id_short);
+    }
+    lang_strings(w, "displayName", "langStringNameType", &e.display_name);
+    lang_strings(w, "description", "langStringTextType", &e.description);
+    opt_reference(w, "semanticId", e.semantic_id.as_ref());
+    qualifiers(w, &e.qualifiers);
+    data_specifications(w, &e.embedded_data_specifications);
+
+    match &e.kind {
+        ElementKind::Property(p) => {
+            let value_type = p.value_type.unwrap_or(DataTypeDefXsd::String);
+            w.leaf("valueType", value_type.as_str());
+            if !p.value.is_empty() {
+                w.leaf("value", &p.value);
+            }
+        }
+        ElementKind::MultiLanguageProperty(mlp) => {
+            lang_strings(w, "value", "langStringTextType", &mlp.value);
+        }
+        ElementKind::Range(r) => {
+            let value_type = r.value_type.unwrap_or(DataTypeDefXsd::Double);
+            w.leaf("valueType", value_type.as_str());
+            w.opt("min", r.min.as_deref());
+            w.opt("max", r.max.as_deref());
+        }
+        ElementKind::File(f) => {
+            w.opt("value", f.value.as_deref());
+            let content_type = f.content_type.as_deref();
+            w.leaf(
+                "contentType",
+                content_type.unwrap_or("application/octet-stream"),
+            );
+        }
+        ElementKind::Blob(b) => {
+            w.opt("value", b.value.as_deref());
+            let content_type = b.content_type.as_deref();
+            w.leaf(
+                "contentType",
+                content_type.unwrap_or("application/octet-stream"),
+            );
+        }
+        ElementKind::ReferenceElement(r) => opt_reference(w, "value", r.value.as_ref()),
+        ElementKind::RelationshipElement(r) => {
+            opt_reference(w, "first", r.first.as_ref());
+            opt_reference(w, "second", r.second.as_ref());
+        }
+        ElementKind::AnnotatedRelationshipElement(r) => {
+            opt_reference(w, "first", r.first.as_ref());
+            opt_reference(w, "second", r.second.as_ref());
+            w.list("annotations", &r.annotations, element);
+        }
+        ElementKind::Entity(entity) => {
+            w.list("statements", &entity.statements, element);
+            w.leaf("entityType", &enum_name(&entity.entity_type));
+            w.opt("globalAssetId", entity.global_asset_id.as_deref());
+        }
+        ElementKind::Operation(op) => {
+            operation_variables(w, "inputVariables", &op.input_variables);
+            operation_variables(w, "outputVariables", &op.output_variables);
+            operation_variables(w, "inoutputVariables", &op.inoutput_variables);
+        }
+        ElementKind::Capability(_) => {}
+        ElementKind::BasicEventElement(event) => {
+            reference(w, "observed", &event.observed);
+            w.leaf("direction", &enum_name(&event.direction));
+            w.leaf("state", &enum_name(&event.state));
+            w.opt("messageTopic", event.message_topic.as_deref());
+            opt_reference(w, "messageBroker", event.message_broker.as_ref());
+            w.opt("lastUpdate", event.last_update.as_deref());
+            w.opt("minInterval", event.min_interval.as_deref());
+            w.opt("maxInterval", event.max_interval.as_deref());
+        }
+        ElementKind::SubmodelElementCollection(c) => w.list("value", &c.value, element),
+        ElementKind::SubmodelElementList(l) => {
+            w.leaf(
+                "orderRelevant",
+                if l.order_relevant { "true" } else { "false" },
+            );
+            opt_reference(
+                w,
+                "semanticIdListElement",
+                l.semantic_id_list_element.as_ref(),
+            );
+            w.leaf(
+                "typeValueListElement",
+                &enum_name(&l.type_value_list_element),
+            );
+            w.opt(
+                "valueTypeListElement",
+                l.value_type_list_element
+                    .as_ref()
+                    .map(DataTypeDefXsd::as_str),
+            );
+            w.list("value", &l.value, element);
+        }
+    }
+    w.close(&tag);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::DigitalTwin;
+
+    #[test]
+    fn test_get_aas_xml() {
+        let json = r#"{
+            "id": "MOTOR-12345",
+            "asset_type": "Siemens 1LE1",
+            "nameplate": [
+                {"id_short": "Voltage", "value": "400", "unit": "V", "value_type": "xs:double",
+                 "semantic_id": "0173-1#02-AAE416#004"},
+                {"id_short": "Name", "model_type": "MultiLanguageProperty",
+                 "value": [{"language": "en", "text": "Motor <A&B>"}]}
+            ]
+        }"#;
+        let xml = DigitalTwin::new(json).unwrap().get_aas_xml();
+
+        assert!(xml.contains(r#"<environment xmlns="https://admin-shell.io/aas/3/0">"#));
+        assert!(xml.contains("<assetType>Siemens 1LE1</assetType>"));
+        assert!(xml.contains("<id>MOTOR-12345/submodels/nameplate</id>"));
+        let property = xml.split("<property>").nth(1).unwrap();
+        let property = property.split("</property>").next().unwrap();
+        let voltage: Vec<&str> = property
+            .lines()
+            .map(str::trim)
+            .filter(|l| l.starts_with("<idShort>") || l.starts_with("<valueType>xs:d"))
+            .collect();
+        assert_eq!(
+            voltage,
+            [
+                "<idShort>Voltage</idShort>",
+                "<valueType>xs:double</valueType>"
+            ]
+        );
+        assert!(property.contains("<name>unit</name>"));
+        assert!(xml.contains("<text>Motor &lt;A&amp;B&gt;</text>"));
+        assert!(xml.trim_end().ends_with("</environment>"));
+    }
+}
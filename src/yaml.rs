@@ -0,0 +1,88 @@
+// --- YAML configuration ---
+// Hand-written twin configurations may be YAML (with comments). YAML is read
+// into a JSON tree and then loaded like JSON, so every JSON layout is
+// available in YAML too. Unquoted scalars such as `value: 400` are read as
+// numbers by YAML; where the model expects a string they are converted back.
+
+use serde_json::Value;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::DigitalTwin;
+
+/// Fields holding strings that hand-written YAML tends to leave unquoted
+const STRING_FIELDS: &[&str] = &[
+    "value", "min", "max", "id", "id_short", "version", "revision",
+];
+
+/// Parse YAML into the JSON tree the loaders work on
+pub fn yaml_to_json(yaml: &str) -> Result<Value, String> {
+    let mut value: Value =
+        serde_yaml::from_str(yaml).map_err(|e| format!("Invalid AAS YAML: {}", e))?;
+    stringify_scalars(&mut value);
+    Ok(value)
+}
+
+fn stringify_scalars(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, v) in map.iter_mut() {
+                match v {
+                    Value::Number(n) if STRING_FIELDS.contains(&key.as_str()) => {
+                        *v = Value::String(n.to_string())
+                    }
+                    Value::Bool(b) if STRING_FIELDS.contains(&key.as_str()) => {
+                        *v = Value::String(b.to_string())
+                    }
+                    v => stringify_scalars(v),
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(stringify_scalars),
+        _ => {}
+    }
+}
+
+/// Whether a configuration is YAML rather than JSON (JSON documents start
+/// with an object or array)
+pub fn is_yaml(config: &str) -> bool {
+    !config.trim_start().starts_with(['{', '['])
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl DigitalTwin {
+    /// Export the twin in the legacy layout as YAML
+    pub fn get_aas_yaml(&self) -> String {
+        serde_yaml::to_string(&self.data).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_yaml_config() {
+        let yaml = "\
+# Commissioned 2024-03
+id: MOTOR-12345
+asset_type: Siemens 1LE1
+nameplate:
+  - id_short: Voltage
+    value: 400        # rated
+    unit: V
+    value_type: xs:double
+  - id_short: Certified
+    value: true
+";
+        let twin = DigitalTwin::new(yaml).unwrap();
+        assert_eq!(twin.get_property_display("Voltage"), "400 V");
+        assert_eq!(twin.get_property_display("Certified"), "true ");
+
+        let restored = DigitalTwin::new(&twin.get_aas_yaml()).unwrap();
+        assert_eq!(restored.get_aas_json(), twin.get_aas_json());
+        assert!(yaml_to_json("id: [unclosed")
+            .unwrap_err()
+            .starts_with("Invalid AAS YAML"));
+    }
+}